@@ -0,0 +1,33 @@
+//! Reports non-fatal problems in the built-in phosphor database. Doing it
+//! here rather than in `phosphor_table!` means cargo shows them once, when
+//! the data changes, instead of on every build of the crate.
+//!
+//! The `bands-32` feature reaches the `phosphor-data` build dependency too
+//! (cargo applies `phosphor-data/bands-32` to every kind of dependency), so
+//! the warnings describe the band layout the crate is built with.
+
+use std::path::PathBuf;
+
+use phosphor_data::{DATABASE_PATH_ENV, DEFAULT_DATABASE_PATH};
+
+fn main() {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let db_path = manifest_dir.join(
+        std::env::var_os(DATABASE_PATH_ENV)
+            .filter(|p| !p.is_empty())
+            .map_or_else(|| PathBuf::from(DEFAULT_DATABASE_PATH), PathBuf::from),
+    );
+    println!("cargo::rerun-if-env-changed={DATABASE_PATH_ENV}");
+    // The database and the spectrum CSVs it points at.
+    if let Some(data_dir) = db_path.parent() {
+        println!("cargo::rerun-if-changed={}", data_dir.display());
+    }
+    println!("cargo::rerun-if-changed=build.rs");
+
+    // Load errors are left to the macro, which fails the build with them.
+    if let Ok((_, warnings)) = phosphor_data::load_phosphors_from_file_with_warnings(&db_path) {
+        for warning in warnings {
+            println!("cargo::warning={warning}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! The CIE 1931 2-degree standard observer color matching functions as
+//! runtime data, for code that needs the raw table rather than the
+//! compile-time array `cie-data` expands to.
+//!
+//! Both crates read the same CSV, so the values are identical.
+//!
+//! Source: [CIE 018:2019](https://cie.co.at/datatable/cie-1931-colour-matching-functions-2-degree-observer)
+//! DOI: [10.25039/CIE.DS.xvudnb9b](https://doi.org/10.25039/CIE.DS.xvudnb9b)
+
+use std::sync::OnceLock;
+
+const CIE_1931_CSV: &str = include_str!("../../../data/CIE_xyz_1931_2deg.csv");
+
+/// The CIE 1931 2° observer as `(wavelength_nm, x_bar, y_bar, z_bar)`
+/// tuples in 1 nm steps from 360 to 830 nm. Parsed on first call.
+pub fn cie_1931_2deg() -> &'static [(f32, f64, f64, f64)] {
+    static TABLE: OnceLock<Vec<(f32, f64, f64, f64)>> = OnceLock::new();
+    TABLE.get_or_init(|| parse_table(CIE_1931_CSV))
+}
+
+/// Parse `wavelength,x_bar,y_bar,z_bar` rows. The CSV is embedded and
+/// checked by the tests, so malformed data is a build defect and panics.
+fn parse_table(csv: &str) -> Vec<(f32, f64, f64, f64)> {
+    csv.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            assert_eq!(
+                fields.len(),
+                4,
+                "Expected 4 columns, got {} at record {}",
+                fields.len(),
+                i + 1
+            );
+            let value = |col: usize| -> f64 {
+                fields[col]
+                    .parse()
+                    .unwrap_or_else(|e| panic!("Invalid value '{}': {e}", fields[col]))
+            };
+            (value(0) as f32, value(1), value(2), value(3))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MACRO_TABLE: [(f32, f64, f64, f64); 471] =
+        cie_data::cie_1931_table!("../../data/CIE_xyz_1931_2deg.csv");
+
+    #[test]
+    fn runtime_table_matches_macro_table() {
+        let table = cie_1931_2deg();
+        assert_eq!(table.len(), MACRO_TABLE.len());
+        assert_eq!(table, &MACRO_TABLE[..]);
+    }
+
+    #[test]
+    fn table_spans_visible_range_in_1nm_steps() {
+        let table = cie_1931_2deg();
+        assert_eq!(table.first().unwrap().0, 360.0);
+        assert_eq!(table.last().unwrap().0, 830.0);
+        assert!(table.windows(2).all(|w| w[1].0 - w[0].0 == 1.0));
+    }
+}
@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 use proc_macro::TokenStream;
 use syn::{LitStr, parse_macro_input};
@@ -46,12 +47,26 @@ fn format_decay_term(term: &phosphor_data::DecayTerm) -> String {
     }
 }
 
+fn format_raw_spectrum(points: Option<&[(f32, f32)]>) -> String {
+    match points {
+        Some(points) => {
+            let inner: Vec<String> = points
+                .iter()
+                .map(|(wl, intensity)| format!("({wl}_f32, {intensity}_f32)"))
+                .collect();
+            format!("Some(vec![{}])", inner.join(", "))
+        }
+        None => "None".to_string(),
+    }
+}
+
 fn format_layer(layer: &phosphor_data::PhosphorLayer) -> String {
     let terms: Vec<String> = layer.decay_terms.iter().map(format_decay_term).collect();
     format!(
-        "phosphor_data::PhosphorLayer {{ emission_weights: {}, decay_terms: vec![{}] }}",
+        "phosphor_data::PhosphorLayer {{ emission_weights: {}, decay_terms: vec![{}], raw_spectrum: {} }}",
         format_weights(&layer.emission_weights),
         terms.join(", "),
+        format_raw_spectrum(layer.raw_spectrum.as_deref()),
     )
 }
 
@@ -67,6 +82,7 @@ fn format_phosphor(p: &phosphor_data::PhosphorType) -> String {
         peak_wavelength_nm: {peak}_f32,
         relative_luminance: {lum}_f32,
         relative_writing_speed: {ws}_f32,
+        truncated_terms: false,
     }}"#,
         designation = p.designation,
         description = p.description,
@@ -84,7 +100,16 @@ fn format_phosphor(p: &phosphor_data::PhosphorType) -> String {
 /// of `phosphor_data::PhosphorType` structs with pre-computed emission
 /// weights and fitted decay constants.
 ///
-/// The path is resolved relative to the calling crate's `CARGO_MANIFEST_DIR`.
+/// The path is resolved relative to the calling crate's `CARGO_MANIFEST_DIR`,
+/// and defaults to `phosphor_data::DEFAULT_DATABASE_PATH` when omitted.
+/// Setting the `PHOSPHOR_DB` environment variable at build time replaces
+/// the path (resolved the same way if relative). The macro runs at
+/// compile time, so changing the variable only takes effect once the
+/// calling crate is rebuilt, e.g. after `cargo clean -p phosphor`.
+///
+/// Load errors fail the build. Warnings are not reported here, since a
+/// proc macro can only print them on every expansion; the calling crate's
+/// build script reports them instead.
 ///
 /// The calling crate must depend on `phosphor-data` for the types.
 ///
@@ -94,8 +119,12 @@ fn format_phosphor(p: &phosphor_data::PhosphorType) -> String {
 /// ```
 #[proc_macro]
 pub fn phosphor_table(input: TokenStream) -> TokenStream {
-    let lit = parse_macro_input!(input as LitStr);
-    let path = resolve_path(&lit.value());
+    let literal = if input.is_empty() {
+        phosphor_data::DEFAULT_DATABASE_PATH.to_string()
+    } else {
+        parse_macro_input!(input as LitStr).value()
+    };
+    let path = resolve_path(&literal);
 
     let contents = std::fs::read_to_string(&path)
         .unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
@@ -106,6 +135,16 @@ pub fn phosphor_table(input: TokenStream) -> TokenStream {
 
     let phosphors = phosphor_data::load_phosphors_with_base_path(&contents, Some(base_path))
         .unwrap_or_else(|e| panic!("Failed to parse {}: {e}", path.display()));
+    // Runtime loads degrade to a truncated phosphor; a built-in one has no
+    // excuse, so fail the build instead.
+    if let Some(p) = phosphors.iter().find(|p| p.truncated_terms) {
+        panic!(
+            "{}: {} has more than {} decay terms in a layer",
+            path.display(),
+            p.designation,
+            phosphor_data::MAX_DECAY_TERMS
+        );
+    }
 
     let entries: Vec<String> = phosphors.iter().map(format_phosphor).collect();
     let body = entries.join(",\n    ");
@@ -115,9 +154,13 @@ pub fn phosphor_table(input: TokenStream) -> TokenStream {
         .expect("failed to parse generated phosphor array")
 }
 
-fn resolve_path(relative: &str) -> PathBuf {
+fn resolve_path(literal: &str) -> PathBuf {
     let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
-    let path = PathBuf::from(manifest_dir).join(relative);
+    let path = database_path(
+        Path::new(&manifest_dir),
+        literal,
+        std::env::var_os(phosphor_data::DATABASE_PATH_ENV).as_deref(),
+    );
     assert!(
         path.exists(),
         "Phosphor data file not found at {}",
@@ -125,3 +168,43 @@ fn resolve_path(relative: &str) -> PathBuf {
     );
     path
 }
+
+/// The database file to read: `override_path` if set and non-empty, else
+/// `literal`, joined onto `manifest_dir` when relative.
+fn database_path(manifest_dir: &Path, literal: &str, override_path: Option<&OsStr>) -> PathBuf {
+    match override_path.filter(|p| !p.is_empty()) {
+        Some(path) => manifest_dir.join(path),
+        None => manifest_dir.join(literal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_path_is_used_without_override() {
+        let dir = Path::new("/crate");
+        assert_eq!(
+            database_path(dir, "data/phosphors.toml", None),
+            Path::new("/crate/data/phosphors.toml")
+        );
+        assert_eq!(
+            database_path(dir, "data/phosphors.toml", Some(OsStr::new(""))),
+            Path::new("/crate/data/phosphors.toml")
+        );
+    }
+
+    #[test]
+    fn override_replaces_literal_path() {
+        let dir = Path::new("/crate");
+        assert_eq!(
+            database_path(dir, "data/phosphors.toml", Some(OsStr::new("ci/test.toml"))),
+            Path::new("/crate/ci/test.toml")
+        );
+        assert_eq!(
+            database_path(dir, "data/phosphors.toml", Some(OsStr::new("/abs/db.toml"))),
+            Path::new("/abs/db.toml")
+        );
+    }
+}
@@ -1,10 +1,11 @@
 pub mod spectral;
 
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::path::Path;
 
 use serde::Deserialize;
-use spectral::SPECTRAL_BANDS;
+use spectral::{CsvSpectrumError, SPECTRAL_BANDS};
 
 // --- Public types ---
 
@@ -21,7 +22,44 @@ pub enum DecayTerm {
     },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl DecayTerm {
+    /// Whether the term's parameters describe a decay curve: finite, with
+    /// non-negative amplitude and positive time constants. Anything else
+    /// yields NaN or infinities once evaluated.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            DecayTerm::Exponential { amplitude, tau } => {
+                amplitude.is_finite() && amplitude >= 0.0 && tau.is_finite() && tau > 0.0
+            }
+            DecayTerm::PowerLaw {
+                amplitude,
+                alpha,
+                beta,
+            } => {
+                amplitude.is_finite()
+                    && amplitude >= 0.0
+                    && alpha.is_finite()
+                    && alpha > 0.0
+                    && beta.is_finite()
+            }
+        }
+    }
+
+    /// Intensity contributed by this term `t_secs` after excitation:
+    /// `A·exp(-t/τ)` or `A·(α/(α+t))^β`.
+    pub fn evaluate(&self, t_secs: f32) -> f32 {
+        match *self {
+            DecayTerm::Exponential { amplitude, tau } => amplitude * (-t_secs / tau).exp(),
+            DecayTerm::PowerLaw {
+                amplitude,
+                alpha,
+                beta,
+            } => amplitude * (alpha / (alpha + t_secs)).powf(beta),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PhosphorCategory {
     GeneralPurpose,
     ShortDecay,
@@ -29,10 +67,26 @@ pub enum PhosphorCategory {
     LongDecaySulfide,
 }
 
+impl PhosphorCategory {
+    /// Human-readable name for display.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::GeneralPurpose => "General purpose",
+            Self::ShortDecay => "Short decay",
+            Self::VideoDisplay => "Video display",
+            Self::LongDecaySulfide => "Long-decay sulfide",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct PhosphorLayer {
     pub emission_weights: [f32; SPECTRAL_BANDS],
     pub decay_terms: Vec<DecayTerm>,
+    /// Measured `(wavelength_nm, rel_intensity)` points the weights were
+    /// integrated from, sorted by wavelength. `None` for layers whose
+    /// weights come from a Gaussian `peak_nm`/`fwhm_nm`.
+    pub raw_spectrum: Option<Vec<(f32, f32)>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -46,6 +100,202 @@ pub struct PhosphorType {
     pub peak_wavelength_nm: f32,
     pub relative_luminance: f32,
     pub relative_writing_speed: f32,
+    /// A layer listed more than [`MAX_DECAY_TERMS`] decay terms and only the
+    /// first ones were kept.
+    pub truncated_terms: bool,
+}
+
+/// The built-in database, relative to the `phosphor` crate root. Read by
+/// `phosphor_table!()` and by the crate's build script.
+pub const DEFAULT_DATABASE_PATH: &str = "data/phosphors.toml";
+
+/// Environment variable that replaces [`DEFAULT_DATABASE_PATH`] at build
+/// time.
+pub const DATABASE_PATH_ENV: &str = "PHOSPHOR_DB";
+
+/// Most decay terms a layer can have: the GPU decay pass uploads them as a
+/// fixed-size array of this many.
+pub const MAX_DECAY_TERMS: usize = 8;
+
+/// `relative_luminance` that gets an exposure of 1.0 (P1's rating).
+pub const REFERENCE_LUMINANCE: f32 = 50.0;
+
+/// Range of exposures [`exposure_for_luminance`] returns, matching the
+/// intensity control's range.
+pub const EXPOSURE_RANGE: (f32, f32) = (0.1, 10.0);
+
+/// Starting exposure for a phosphor of the given `relative_luminance`, so
+/// that dim and bright phosphors land at a similar apparent brightness.
+/// Compensates by the square root of the luminance ratio rather than the
+/// full ratio, so dim phosphors still look dimmer, as they would on a real
+/// tube at the same beam current.
+pub fn exposure_for_luminance(relative_luminance: f32) -> f32 {
+    let (min, max) = EXPOSURE_RANGE;
+    if relative_luminance.is_nan() || relative_luminance <= 0.0 {
+        return max;
+    }
+    (REFERENCE_LUMINANCE / relative_luminance)
+        .sqrt()
+        .clamp(min, max)
+}
+
+/// Longest time [`PhosphorType::persistence_time`] searches. Power-law
+/// tails can take far longer to fade; those report as beyond this horizon.
+pub const PERSISTENCE_HORIZON_SECS: f32 = 10.0;
+
+impl PhosphorType {
+    /// Exposure to start at when this phosphor is selected; see
+    /// [`exposure_for_luminance`].
+    pub fn default_exposure(&self) -> f32 {
+        exposure_for_luminance(self.relative_luminance)
+    }
+
+    /// Combined decay curve of the fluorescence layer (the one the simulator
+    /// renders) at `t_secs` after excitation. At `t = 0` this is the sum of
+    /// the term amplitudes.
+    pub fn decay_intensity(&self, t_secs: f32) -> f32 {
+        self.fluorescence
+            .decay_terms
+            .iter()
+            .map(|term| term.evaluate(t_secs))
+            .sum()
+    }
+
+    /// Largest exponential time constant in seconds across both layers, or
+    /// `f32::INFINITY` if either has a power-law tail. Zero with no decay
+    /// terms.
+    pub fn longest_tau(&self) -> f32 {
+        self.fluorescence
+            .decay_terms
+            .iter()
+            .chain(&self.phosphorescence.decay_terms)
+            .map(|term| match term {
+                DecayTerm::Exponential { tau, .. } => *tau,
+                DecayTerm::PowerLaw { .. } => f32::INFINITY,
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Time in seconds for [`Self::decay_intensity`] to fall to `fraction`
+    /// of its initial value (e.g. 0.1 for the 10% persistence). Found by
+    /// bisection over log-time between 1 ns and [`PERSISTENCE_HORIZON_SECS`];
+    /// returns `f32::INFINITY` if the curve is still above `fraction` at the
+    /// horizon.
+    pub fn persistence_time(&self, fraction: f32) -> f32 {
+        let initial = self.decay_intensity(0.0);
+        if initial <= 0.0 {
+            return 0.0;
+        }
+        let target = initial * fraction;
+
+        let (mut lo, mut hi) = (1e-9f64.ln(), (PERSISTENCE_HORIZON_SECS as f64).ln());
+        let above = |log_t: f64| self.decay_intensity(log_t.exp() as f32) > target;
+        if above(hi) {
+            return f32::INFINITY;
+        }
+        if !above(lo) {
+            return lo.exp() as f32;
+        }
+        for _ in 0..64 {
+            let mid = 0.5 * (lo + hi);
+            if above(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        hi.exp() as f32
+    }
+}
+
+// --- Errors ---
+
+/// Why a phosphor database failed to load.
+#[derive(Debug)]
+pub enum PhosphorLoadError {
+    /// The TOML file couldn't be read.
+    Io(std::io::Error),
+    /// The TOML is malformed or doesn't match the phosphor schema.
+    Toml(toml::de::Error),
+    /// A phosphor's `spectrum_csv` couldn't be turned into emission weights.
+    Spectrum {
+        designation: String,
+        source: CsvSpectrumError,
+    },
+    /// A phosphor's fields parse but don't describe a usable phosphor.
+    Validation { designation: String, reason: String },
+}
+
+impl PhosphorLoadError {
+    fn validation(designation: &str, reason: impl Into<String>) -> Self {
+        Self::Validation {
+            designation: designation.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for PhosphorLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PhosphorLoadError::Io(e) => write!(f, "failed to read phosphor file: {e}"),
+            PhosphorLoadError::Toml(e) => write!(f, "invalid phosphor TOML: {e}"),
+            PhosphorLoadError::Spectrum {
+                designation,
+                source,
+            } => write!(f, "{designation}: {source}"),
+            PhosphorLoadError::Validation {
+                designation,
+                reason,
+            } => write!(f, "{designation}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PhosphorLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PhosphorLoadError::Io(e) => Some(e),
+            PhosphorLoadError::Toml(e) => Some(e),
+            PhosphorLoadError::Spectrum { source, .. } => Some(source),
+            PhosphorLoadError::Validation { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PhosphorLoadError {
+    fn from(e: std::io::Error) -> Self {
+        PhosphorLoadError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for PhosphorLoadError {
+    fn from(e: toml::de::Error) -> Self {
+        PhosphorLoadError::Toml(e)
+    }
+}
+
+/// A non-fatal problem with a phosphor definition. The phosphor still
+/// loads; the caller decides how to report it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhosphorLoadWarning {
+    pub designation: String,
+    pub message: String,
+}
+
+impl PhosphorLoadWarning {
+    fn new(designation: &str, message: impl Into<String>) -> Self {
+        Self {
+            designation: designation.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for PhosphorLoadWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.designation, self.message)
+    }
 }
 
 // --- TOML deserialization ---
@@ -76,43 +326,132 @@ struct PhosphorData {
     decay_terms: Vec<DecayTerm>,
     relative_luminance: f32,
     relative_writing_speed: f32,
+    /// Acknowledges an emission too narrow for the spectral bands, so it
+    /// isn't reported as degenerate on every load.
+    #[serde(default)]
+    narrow_emission: bool,
     fluorescence: Option<LayerData>,
     phosphorescence: Option<LayerData>,
 }
 
-fn parse_category(s: &str) -> PhosphorCategory {
+fn parse_category(s: &str, designation: &str) -> Result<PhosphorCategory, PhosphorLoadError> {
     match s {
-        "general_purpose" => PhosphorCategory::GeneralPurpose,
-        "short_decay" => PhosphorCategory::ShortDecay,
-        "video_display" => PhosphorCategory::VideoDisplay,
-        "long_decay_sulfide" => PhosphorCategory::LongDecaySulfide,
-        other => panic!("Unknown phosphor category: {other}"),
+        "general_purpose" => Ok(PhosphorCategory::GeneralPurpose),
+        "short_decay" => Ok(PhosphorCategory::ShortDecay),
+        "video_display" => Ok(PhosphorCategory::VideoDisplay),
+        "long_decay_sulfide" => Ok(PhosphorCategory::LongDecaySulfide),
+        other => Err(PhosphorLoadError::validation(
+            designation,
+            format!("unknown category '{other}'"),
+        )),
     }
 }
 
-fn resolve_emission_weights(
+/// Warn when `weights` are concentrated in too few bands to give a
+/// believable color.
+fn warn_if_degenerate(
+    weights: &[f32; SPECTRAL_BANDS],
+    designation: &str,
+    warnings: &mut Vec<PhosphorLoadWarning>,
+) {
+    let ratio = spectral::participation_ratio(weights);
+    if ratio < spectral::MIN_PARTICIPATION_RATIO {
+        warnings.push(PhosphorLoadWarning::new(
+            designation,
+            format!(
+                "emission spans only {ratio:.2} effective bands (below {}); \
+                 the color will look artificial",
+                spectral::MIN_PARTICIPATION_RATIO
+            ),
+        ));
+    }
+}
+
+/// The first [`MAX_DECAY_TERMS`] of `terms`, and whether any were dropped.
+/// A warning rather than an error, so the rest of a runtime database still
+/// loads.
+fn capped_decay_terms(
+    terms: &[DecayTerm],
+    designation: &str,
+    warnings: &mut Vec<PhosphorLoadWarning>,
+) -> (Vec<DecayTerm>, bool) {
+    if terms.len() <= MAX_DECAY_TERMS {
+        return (terms.to_vec(), false);
+    }
+    warnings.push(PhosphorLoadWarning::new(
+        designation,
+        format!(
+            "{} decay terms exceed the limit of {MAX_DECAY_TERMS}; \
+             keeping the first {MAX_DECAY_TERMS}",
+            terms.len()
+        ),
+    ));
+    (terms[..MAX_DECAY_TERMS].to_vec(), true)
+}
+
+/// Build one emission layer, integrating its weights from `spectrum_csv`
+/// when given and from a Gaussian otherwise.
+fn build_layer(
     peak_nm: f32,
     fwhm_nm: Option<f32>,
     spectrum_csv: Option<&str>,
+    decay_terms: &[DecayTerm],
     base_path: Option<&Path>,
     designation: &str,
-) -> [f32; SPECTRAL_BANDS] {
+    warnings: &mut Vec<PhosphorLoadWarning>,
+) -> Result<PhosphorLayer, PhosphorLoadError> {
     if let Some(csv_rel) = spectrum_csv {
-        let base = base_path.unwrap_or_else(|| {
-            panic!("{designation}: spectrum_csv requires a base path for resolution")
-        });
+        let base = base_path.ok_or_else(|| {
+            PhosphorLoadError::validation(
+                designation,
+                "spectrum_csv requires a base path for resolution",
+            )
+        })?;
         let csv_path = base.join(csv_rel);
-        let csv_text = std::fs::read_to_string(&csv_path).unwrap_or_else(|e| {
-            panic!("{designation}: failed to read {}: {e}", csv_path.display())
-        });
-        spectral::csv_to_emission_weights(&csv_text).unwrap_or_else(|e| {
-            panic!("{designation}: failed to parse {}: {e}", csv_path.display())
+        let csv_text = std::fs::read_to_string(&csv_path).map_err(|e| {
+            PhosphorLoadError::validation(
+                designation,
+                format!("failed to read {}: {e}", csv_path.display()),
+            )
+        })?;
+        let spectrum_error = |source| PhosphorLoadError::Spectrum {
+            designation: designation.to_string(),
+            source,
+        };
+        let points = spectral::parse_spectrum_points(&csv_text).map_err(spectrum_error)?;
+        let (weights, csv_warnings) =
+            spectral::points_to_emission_weights_with_warnings(&points).map_err(spectrum_error)?;
+        warnings.extend(csv_warnings.into_iter().map(|warning| {
+            PhosphorLoadWarning::new(designation, format!("{}: {warning}", csv_path.display()))
+        }));
+        Ok(PhosphorLayer {
+            emission_weights: weights,
+            decay_terms: decay_terms.to_vec(),
+            raw_spectrum: Some(points),
         })
     } else {
-        let fwhm = fwhm_nm.unwrap_or_else(|| {
-            panic!("{designation}: need fwhm_nm or spectrum_csv for emission weights")
-        });
-        spectral::gaussian_emission_weights(peak_nm, fwhm)
+        let fwhm = fwhm_nm.ok_or_else(|| {
+            PhosphorLoadError::validation(
+                designation,
+                "need fwhm_nm or spectrum_csv for emission weights",
+            )
+        })?;
+        if !peak_nm.is_finite() {
+            return Err(PhosphorLoadError::validation(
+                designation,
+                format!("peak_nm must be finite, got {peak_nm}"),
+            ));
+        }
+        let weights = spectral::gaussian_emission_weights(peak_nm, fwhm);
+        debug_assert!(
+            weights.iter().all(|w| w.is_finite()),
+            "{designation}: non-finite emission weights"
+        );
+        Ok(PhosphorLayer {
+            emission_weights: weights,
+            decay_terms: decay_terms.to_vec(),
+            raw_spectrum: None,
+        })
     }
 }
 
@@ -120,14 +459,21 @@ fn build_phosphor(
     designation: &str,
     data: &PhosphorData,
     base_path: Option<&Path>,
-) -> PhosphorType {
+    warnings: &mut Vec<PhosphorLoadWarning>,
+) -> Result<PhosphorType, PhosphorLoadError> {
     let (fluorescence, phosphorescence, is_dual_layer) = if data.dual_layer {
-        let fl = data.fluorescence.as_ref().unwrap_or_else(|| {
-            panic!("{designation}: dual_layer = true but missing [fluorescence]")
-        });
-        let ph = data.phosphorescence.as_ref().unwrap_or_else(|| {
-            panic!("{designation}: dual_layer = true but missing [phosphorescence]")
-        });
+        let fl = data.fluorescence.as_ref().ok_or_else(|| {
+            PhosphorLoadError::validation(
+                designation,
+                "dual_layer = true but missing [fluorescence]",
+            )
+        })?;
+        let ph = data.phosphorescence.as_ref().ok_or_else(|| {
+            PhosphorLoadError::validation(
+                designation,
+                "dual_layer = true but missing [phosphorescence]",
+            )
+        })?;
         let fl_terms = if fl.decay_terms.is_empty() {
             &data.decay_terms
         } else {
@@ -139,53 +485,73 @@ fn build_phosphor(
             &ph.decay_terms
         };
         (
-            PhosphorLayer {
-                emission_weights: resolve_emission_weights(
-                    fl.peak_nm,
-                    fl.fwhm_nm,
-                    fl.spectrum_csv.as_deref(),
-                    base_path,
-                    designation,
-                ),
-                decay_terms: fl_terms.to_vec(),
-            },
-            PhosphorLayer {
-                emission_weights: resolve_emission_weights(
-                    ph.peak_nm,
-                    ph.fwhm_nm,
-                    ph.spectrum_csv.as_deref(),
-                    base_path,
-                    designation,
-                ),
-                decay_terms: ph_terms.to_vec(),
-            },
+            build_layer(
+                fl.peak_nm,
+                fl.fwhm_nm,
+                fl.spectrum_csv.as_deref(),
+                fl_terms,
+                base_path,
+                designation,
+                warnings,
+            )?,
+            build_layer(
+                ph.peak_nm,
+                ph.fwhm_nm,
+                ph.spectrum_csv.as_deref(),
+                ph_terms,
+                base_path,
+                designation,
+                warnings,
+            )?,
             true,
         )
     } else {
-        let layer = PhosphorLayer {
-            emission_weights: resolve_emission_weights(
-                data.peak_nm,
-                data.fwhm_nm,
-                data.spectrum_csv.as_deref(),
-                base_path,
-                designation,
-            ),
-            decay_terms: data.decay_terms.to_vec(),
-        };
+        let layer = build_layer(
+            data.peak_nm,
+            data.fwhm_nm,
+            data.spectrum_csv.as_deref(),
+            &data.decay_terms,
+            base_path,
+            designation,
+            warnings,
+        )?;
         (layer.clone(), layer, false)
     };
 
-    PhosphorType {
+    if !data.narrow_emission {
+        warn_if_degenerate(&fluorescence.emission_weights, designation, warnings);
+        if is_dual_layer {
+            warn_if_degenerate(&phosphorescence.emission_weights, designation, warnings);
+        }
+    }
+
+    let (fl_terms, fl_truncated) =
+        capped_decay_terms(&fluorescence.decay_terms, designation, warnings);
+    // A single-layer phosphor's two layers are the same, so warn once.
+    let (ph_terms, ph_truncated) = if is_dual_layer {
+        capped_decay_terms(&phosphorescence.decay_terms, designation, warnings)
+    } else {
+        (fl_terms.clone(), fl_truncated)
+    };
+
+    Ok(PhosphorType {
         designation: designation.to_string(),
         description: data.description.clone(),
-        category: parse_category(&data.category),
+        category: parse_category(&data.category, designation)?,
         is_dual_layer,
-        fluorescence,
-        phosphorescence,
+        fluorescence: PhosphorLayer {
+            decay_terms: fl_terms,
+            ..fluorescence
+        },
+        phosphorescence: PhosphorLayer {
+            decay_terms: ph_terms,
+            ..phosphorescence
+        },
         peak_wavelength_nm: data.peak_nm,
         relative_luminance: data.relative_luminance,
         relative_writing_speed: data.relative_writing_speed,
-    }
+        truncated_terms: fl_truncated || ph_truncated,
+    })
 }
 
 /// Result of classifying a phosphor's decay terms into tiers.
@@ -240,33 +606,83 @@ pub fn classify_decay_terms(terms: &[DecayTerm], tau_cutoff: f32) -> DecayClassi
     }
 }
 
+/// Number of phosphors in each category present in `db`, in category order.
+/// Categories with no phosphors are omitted.
+pub fn category_counts(db: &[PhosphorType]) -> BTreeMap<PhosphorCategory, usize> {
+    let mut counts = BTreeMap::new();
+    for phosphor in db {
+        *counts.entry(phosphor.category).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Compare phosphors by peak wavelength, blue before red, falling back to
+/// natural designation order for equal peaks.
+pub fn cmp_by_peak(a: &PhosphorType, b: &PhosphorType) -> Ordering {
+    a.peak_wavelength_nm
+        .total_cmp(&b.peak_wavelength_nm)
+        .then_with(|| natord::compare(&a.designation, &b.designation))
+}
+
+/// Sort phosphors from blue to red by peak wavelength. See [`cmp_by_peak`].
+pub fn sort_by_peak(db: &mut [PhosphorType]) {
+    db.sort_by(cmp_by_peak);
+}
+
 /// Parse phosphor definitions from a TOML string, resolving any `spectrum_csv`
 /// paths relative to `base_path`.
+///
+/// Phosphors are returned in natural designation order (P1 < P2 < P10),
+/// not the TOML's order.
+///
+/// Warnings are dropped; use [`load_phosphors_with_warnings`] to see them.
 pub fn load_phosphors_with_base_path(
     toml_str: &str,
     base_path: Option<&Path>,
-) -> Result<Vec<PhosphorType>, toml::de::Error> {
+) -> Result<Vec<PhosphorType>, PhosphorLoadError> {
+    load_phosphors_with_warnings(toml_str, base_path).map(|(phosphors, _)| phosphors)
+}
+
+/// Like [`load_phosphors_with_base_path`], but also returns non-fatal
+/// problems with the definitions, such as a spectrum CSV that stops short of
+/// the band range, a degenerate emission spectrum or truncated decay terms,
+/// for the caller to report.
+pub fn load_phosphors_with_warnings(
+    toml_str: &str,
+    base_path: Option<&Path>,
+) -> Result<(Vec<PhosphorType>, Vec<PhosphorLoadWarning>), PhosphorLoadError> {
     let table: BTreeMap<String, PhosphorData> = toml::from_str(toml_str)?;
-    Ok(table
+    let mut warnings = Vec::new();
+    let mut phosphors = table
         .iter()
-        .map(|(name, data)| build_phosphor(name, data, base_path))
-        .collect())
+        .map(|(name, data)| build_phosphor(name, data, base_path, &mut warnings))
+        .collect::<Result<Vec<_>, _>>()?;
+    phosphors.sort_by(|a, b| natord::compare(&a.designation, &b.designation));
+    Ok((phosphors, warnings))
 }
 
-/// Parse phosphor definitions from a TOML string.
-pub fn load_phosphors(toml_str: &str) -> Result<Vec<PhosphorType>, toml::de::Error> {
+/// Parse phosphor definitions from a TOML string, in natural designation
+/// order.
+pub fn load_phosphors(toml_str: &str) -> Result<Vec<PhosphorType>, PhosphorLoadError> {
     load_phosphors_with_base_path(toml_str, None)
 }
 
-/// Load phosphor definitions from a TOML file on disk.
+/// Load phosphor definitions from a TOML file on disk, in natural
+/// designation order.
 ///
 /// Any `spectrum_csv` paths are resolved relative to the TOML file's parent directory.
-pub fn load_phosphors_from_file(
+pub fn load_phosphors_from_file(path: &Path) -> Result<Vec<PhosphorType>, PhosphorLoadError> {
+    load_phosphors_from_file_with_warnings(path).map(|(phosphors, _)| phosphors)
+}
+
+/// Like [`load_phosphors_from_file`], but also returns non-fatal problems
+/// with the definitions. See [`load_phosphors_with_warnings`].
+pub fn load_phosphors_from_file_with_warnings(
     path: &Path,
-) -> Result<Vec<PhosphorType>, Box<dyn std::error::Error>> {
+) -> Result<(Vec<PhosphorType>, Vec<PhosphorLoadWarning>), PhosphorLoadError> {
     let contents = std::fs::read_to_string(path)?;
     let base = path.parent().unwrap_or(Path::new("."));
-    Ok(load_phosphors_with_base_path(&contents, Some(base))?)
+    load_phosphors_with_warnings(&contents, Some(base))
 }
 
 #[cfg(test)]
@@ -309,6 +725,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn category_counts_groups_by_category() {
+        let toml_str = [
+            ("P1", "general_purpose"),
+            ("P2", "general_purpose"),
+            ("P7", "long_decay_sulfide"),
+        ]
+        .iter()
+        .map(|(name, category)| {
+            format!(
+                r#"
+[{name}]
+description = "Test phosphor."
+category = "{category}"
+peak_nm = 520.0
+fwhm_nm = 40.0
+relative_luminance = 50.0
+relative_writing_speed = 60.0
+"#
+            )
+        })
+        .collect::<String>();
+        let counts = category_counts(&load_phosphors(&toml_str).unwrap());
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            [
+                (PhosphorCategory::GeneralPurpose, 2),
+                (PhosphorCategory::LongDecaySulfide, 1),
+            ]
+        );
+    }
+
     #[test]
     fn parse_explicit_decay_terms_from_toml() {
         let toml_str = r#"
@@ -343,6 +791,85 @@ tau = 0.0151
         }
     }
 
+    #[test]
+    fn excess_decay_terms_are_truncated_and_flagged() {
+        let terms: String = (1..=11)
+            .map(|i| {
+                format!(
+                    "[[P1.decay_terms]]\ntype = \"exponential\"\namplitude = 1.0\ntau = {}\n",
+                    i as f32 * 0.01
+                )
+            })
+            .collect();
+        let toml_str = format!(
+            r#"
+[P1]
+description = "Too many terms."
+category = "general_purpose"
+peak_nm = 520.0
+fwhm_nm = 40.0
+relative_luminance = 50.0
+relative_writing_speed = 60.0
+
+{terms}
+[P2]
+description = "Within the limit."
+category = "general_purpose"
+peak_nm = 520.0
+fwhm_nm = 40.0
+relative_luminance = 50.0
+relative_writing_speed = 60.0
+"#
+        );
+        let (phosphors, warnings) = load_phosphors_with_warnings(&toml_str, None).unwrap();
+        let (p1, p2) = (&phosphors[0], &phosphors[1]);
+        assert!(p1.truncated_terms);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].designation, "P1");
+        assert!(
+            warnings[0].message.contains("11 decay terms"),
+            "{}",
+            warnings[0]
+        );
+        assert_eq!(p1.fluorescence.decay_terms.len(), MAX_DECAY_TERMS);
+        assert_eq!(
+            p1.fluorescence.decay_terms[0],
+            DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 0.01
+            }
+        );
+        assert!(!p2.truncated_terms);
+    }
+
+    #[test]
+    fn narrow_emission_is_reported_as_a_warning() {
+        let toml_str = r#"
+[P15]
+description = "Line emitter."
+category = "short_decay"
+peak_nm = 391.0
+fwhm_nm = 1.0
+relative_luminance = 3.0
+relative_writing_speed = 250.0
+"#;
+        let (phosphors, warnings) = load_phosphors_with_warnings(toml_str, None).unwrap();
+        assert_eq!(phosphors.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].designation, "P15");
+        assert!(
+            warnings[0].message.contains("effective bands"),
+            "{}",
+            warnings[0]
+        );
+
+        let acknowledged =
+            toml_str.replace("fwhm_nm = 1.0", "fwhm_nm = 1.0\nnarrow_emission = true");
+        let (phosphors, warnings) = load_phosphors_with_warnings(&acknowledged, None).unwrap();
+        assert_eq!(phosphors.len(), 1);
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
     #[test]
     fn parse_power_law_term_from_toml() {
         let toml_str = r#"
@@ -489,9 +1016,177 @@ tau = 0.003
         let (lo, hi) = spectral::band_range(peak_band);
         assert!(525.0 >= lo && 525.0 < hi);
 
+        let raw = p.fluorescence.raw_spectrum.as_ref().unwrap();
+        assert_eq!(raw.len(), 5);
+        assert_eq!(raw[2], (525.0, 100.0));
+
         let _ = std::fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn truncated_spectrum_csv_is_reported_as_a_warning() {
+        // Still rising at 600nm when the data stops.
+        let mut csv = String::from("wavelength_nm,rel_intensity\n");
+        for wl in (500..=600).step_by(10) {
+            csv.push_str(&format!("{wl},{}\n", (wl - 500) as f32 / 100.0));
+        }
+        let dir = std::env::temp_dir().join("phosphor_test_truncated_csv");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(dir.join("rising.csv"), csv).unwrap();
+
+        let toml_str = r#"
+[Rising]
+description = "Spectrum cut off mid-rise."
+category = "general_purpose"
+peak_nm = 600.0
+spectrum_csv = "rising.csv"
+relative_luminance = 50.0
+relative_writing_speed = 60.0
+"#;
+        let (phosphors, warnings) = load_phosphors_with_warnings(toml_str, Some(&dir)).unwrap();
+        assert_eq!(phosphors.len(), 1);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].designation, "Rising");
+        assert!(
+            warnings[0].message.contains("rising.csv"),
+            "{}",
+            warnings[0]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn phosphor_with_terms(decay_terms: Vec<DecayTerm>) -> PhosphorType {
+        let layer = PhosphorLayer {
+            emission_weights: spectral::gaussian_emission_weights(525.0, 38.0),
+            decay_terms,
+            raw_spectrum: None,
+        };
+        PhosphorType {
+            designation: "Test".into(),
+            description: String::new(),
+            category: PhosphorCategory::GeneralPurpose,
+            is_dual_layer: false,
+            fluorescence: layer.clone(),
+            phosphorescence: layer,
+            peak_wavelength_nm: 525.0,
+            relative_luminance: 1.0,
+            relative_writing_speed: 1.0,
+            truncated_terms: false,
+        }
+    }
+
+    #[test]
+    fn longest_tau_takes_the_slowest_term() {
+        let p = phosphor_with_terms(vec![
+            DecayTerm::Exponential {
+                amplitude: 6.72,
+                tau: 0.00288,
+            },
+            DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 0.0151,
+            },
+        ]);
+        assert_eq!(p.longest_tau(), 0.0151);
+
+        let p = phosphor_with_terms(vec![DecayTerm::PowerLaw {
+            amplitude: 1.0,
+            alpha: 1e-5,
+            beta: 1.1,
+        }]);
+        assert_eq!(p.longest_tau(), f32::INFINITY);
+        assert_eq!(phosphor_with_terms(Vec::new()).longest_tau(), 0.0);
+    }
+
+    #[test]
+    fn sort_by_peak_puts_blue_first() {
+        let phosphor = |designation: &str, peak_nm: f32| PhosphorType {
+            designation: designation.into(),
+            peak_wavelength_nm: peak_nm,
+            ..phosphor_with_terms(Vec::new())
+        };
+        let mut db = vec![
+            phosphor("P22R", 625.0),
+            phosphor("P11", 460.0),
+            phosphor("P31", 525.0),
+            phosphor("P2", 525.0),
+        ];
+        sort_by_peak(&mut db);
+        let order: Vec<&str> = db.iter().map(|p| p.designation.as_str()).collect();
+        assert_eq!(order, ["P11", "P2", "P31", "P22R"]);
+    }
+
+    #[test]
+    fn decay_intensity_at_zero_is_summed_amplitudes() {
+        let p = phosphor_with_terms(vec![
+            DecayTerm::Exponential {
+                amplitude: 6.72,
+                tau: 0.00288,
+            },
+            DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 1e-6,
+            },
+            DecayTerm::PowerLaw {
+                amplitude: 2.1e-4,
+                alpha: 5.5e-6,
+                beta: 1.1,
+            },
+        ]);
+        let expected = 6.72 + 1.0 + 2.1e-4;
+        assert!((p.decay_intensity(0.0) - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn exponential_decay_intensity_decreases_monotonically() {
+        let p = phosphor_with_terms(vec![
+            DecayTerm::Exponential {
+                amplitude: 2.0,
+                tau: 0.05,
+            },
+            DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 2e-5,
+            },
+        ]);
+        let mut prev = p.decay_intensity(0.0);
+        for i in 1..=100 {
+            let t = 1e-6 * 10f32.powf(i as f32 * 0.07);
+            let v = p.decay_intensity(t);
+            // Strictly decreasing until the tail underflows to zero
+            assert!(
+                v < prev || (v == 0.0 && prev == 0.0),
+                "not decreasing at t = {t}: {v} >= {prev}"
+            );
+            prev = v;
+        }
+    }
+
+    #[test]
+    fn persistence_of_single_exponential_is_tau_ln_fraction() {
+        let tau = 0.003;
+        let p = phosphor_with_terms(vec![DecayTerm::Exponential {
+            amplitude: 1.0,
+            tau,
+        }]);
+        let t10 = p.persistence_time(0.1);
+        let t1 = p.persistence_time(0.01);
+        assert!((t10 - tau * 10f32.ln()).abs() < 1e-3 * t10, "t10 = {t10}");
+        assert!((t1 - tau * 100f32.ln()).abs() < 1e-3 * t1, "t1 = {t1}");
+    }
+
+    #[test]
+    fn slow_power_law_tail_is_beyond_horizon() {
+        let p = phosphor_with_terms(vec![DecayTerm::PowerLaw {
+            amplitude: 1.0,
+            alpha: 1.0,
+            beta: 0.5,
+        }]);
+        // (1 / 11)^0.5 ≈ 0.30 at 10 s, so 10% is never reached in range
+        assert_eq!(p.persistence_time(0.1), f32::INFINITY);
+    }
+
     #[test]
     fn build_phosphor_falls_back_to_gaussian_without_csv() {
         let toml_str = r#"
@@ -509,6 +1204,7 @@ amplitude = 1.0
 tau = 0.003
 "#;
         let phosphors = load_phosphors_with_base_path(toml_str, None).unwrap();
+        assert!(phosphors[0].fluorescence.raw_spectrum.is_none());
         let p = &phosphors[0];
         let expected = spectral::gaussian_emission_weights(525.0, 38.0);
         assert_eq!(p.fluorescence.emission_weights, expected);
@@ -540,4 +1236,203 @@ tau = 0.003
         assert_eq!(class.slow_exp_count, 0);
         assert!(class.has_power_law);
     }
+
+    #[test]
+    fn zero_fwhm_in_toml_gives_finite_weights() {
+        let toml_str = r#"
+[PX]
+description = "Degenerate width."
+category = "general_purpose"
+peak_nm = 525.0
+fwhm_nm = 0.0
+relative_luminance = 1.0
+relative_writing_speed = 1.0
+"#;
+        let p = &load_phosphors(toml_str).unwrap()[0];
+        let weights = &p.fluorescence.emission_weights;
+        assert!(weights.iter().all(|w| w.is_finite()), "{weights:?}");
+        assert_eq!(weights.iter().sum::<f32>(), 1.0);
+    }
+
+    #[test]
+    fn phosphors_load_in_natural_order() {
+        let toml_str: String = ["P10", "P2", "P1"]
+            .iter()
+            .map(|name| {
+                format!(
+                    "[{name}]\ndescription = \"x\"\ncategory = \"general_purpose\"\n\
+                     peak_nm = 525.0\nfwhm_nm = 40.0\n\
+                     relative_luminance = 1.0\nrelative_writing_speed = 1.0\n"
+                )
+            })
+            .collect();
+        let designations: Vec<_> = load_phosphors(&toml_str)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.designation)
+            .collect();
+        assert_eq!(designations, ["P1", "P2", "P10"]);
+    }
+
+    #[test]
+    fn exposure_for_luminance_is_sane_across_the_database_range() {
+        assert_eq!(exposure_for_luminance(REFERENCE_LUMINANCE), 1.0);
+
+        let dimmest = exposure_for_luminance(3.0);
+        let brightest = exposure_for_luminance(100.0);
+        assert!(dimmest > 1.0 && dimmest <= EXPOSURE_RANGE.1, "{dimmest}");
+        assert!(
+            brightest < 1.0 && brightest >= EXPOSURE_RANGE.0,
+            "{brightest}"
+        );
+
+        // Degenerate ratings stay within the control's range.
+        for lum in [0.0, -1.0, f32::NAN, 1e-6, 1e6] {
+            let exposure = exposure_for_luminance(lum);
+            assert!(
+                (EXPOSURE_RANGE.0..=EXPOSURE_RANGE.1).contains(&exposure),
+                "{lum} -> {exposure}"
+            );
+        }
+    }
+
+    #[test]
+    fn missing_file_is_io_error() {
+        let err = load_phosphors_from_file(Path::new("/nonexistent/phosphors.toml")).unwrap_err();
+        assert!(matches!(err, PhosphorLoadError::Io(_)), "{err}");
+    }
+
+    #[test]
+    fn malformed_toml_is_toml_error() {
+        let err = load_phosphors("[P1\ndescription = ").unwrap_err();
+        assert!(matches!(err, PhosphorLoadError::Toml(_)), "{err}");
+
+        // Well-formed TOML missing a required field is a schema error.
+        let err = load_phosphors("[P1]\ndescription = \"x\"\n").unwrap_err();
+        assert!(matches!(err, PhosphorLoadError::Toml(_)), "{err}");
+    }
+
+    #[test]
+    fn unusable_fields_are_validation_errors() {
+        let base = r#"
+description = "Test."
+peak_nm = 525.0
+relative_luminance = 1.0
+relative_writing_speed = 1.0
+"#;
+        for (extra, expected) in [
+            (
+                "category = \"bogus\"\nfwhm_nm = 40.0",
+                "unknown category 'bogus'",
+            ),
+            (
+                "category = \"general_purpose\"",
+                "need fwhm_nm or spectrum_csv",
+            ),
+            (
+                "category = \"general_purpose\"\ndual_layer = true",
+                "missing [fluorescence]",
+            ),
+            (
+                "category = \"general_purpose\"\nspectrum_csv = \"x.csv\"",
+                "requires a base path",
+            ),
+        ] {
+            let toml_str = format!("[PX]{base}{extra}\n");
+            match load_phosphors(&toml_str).unwrap_err() {
+                PhosphorLoadError::Validation {
+                    designation,
+                    reason,
+                } => {
+                    assert_eq!(designation, "PX");
+                    assert!(reason.contains(expected), "{reason}");
+                }
+                other => panic!("expected a validation error, got {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_spectrum_csv_is_spectrum_error() {
+        let dir = std::env::temp_dir().join("phosphor_test_bad_csv");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(
+            dir.join("bad.csv"),
+            "wavelength_nm,rel_intensity\n500,0\n525,lots\n560,0\n",
+        )
+        .unwrap();
+
+        let toml_str = r#"
+[P22B]
+description = "Bad spectrum."
+category = "video_display"
+peak_nm = 525.0
+spectrum_csv = "bad.csv"
+relative_luminance = 1.0
+relative_writing_speed = 1.0
+"#;
+        let err = load_phosphors_with_base_path(toml_str, Some(&dir)).unwrap_err();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(
+            matches!(
+                &err,
+                PhosphorLoadError::Spectrum {
+                    designation,
+                    source: CsvSpectrumError::ParseFloat { line: 3, col: "rel_intensity", .. },
+                } if designation == "P22B"
+            ),
+            "{err:?}"
+        );
+        assert!(
+            err.to_string()
+                .starts_with("P22B: line 3, column 'rel_intensity': "),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_spectrum_csv_names_the_phosphor() {
+        let toml_str = r#"
+[PX]
+description = "Missing spectrum."
+category = "general_purpose"
+peak_nm = 525.0
+spectrum_csv = "does_not_exist.csv"
+relative_luminance = 1.0
+relative_writing_speed = 1.0
+"#;
+        let err =
+            load_phosphors_with_base_path(toml_str, Some(Path::new("/nonexistent"))).unwrap_err();
+        assert!(
+            matches!(&err, PhosphorLoadError::Validation { designation, .. } if designation == "PX"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn decay_term_validity() {
+        assert!(
+            DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 1e-3
+            }
+            .is_valid()
+        );
+        assert!(
+            !DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 0.0
+            }
+            .is_valid()
+        );
+        assert!(
+            !DecayTerm::PowerLaw {
+                amplitude: f32::NAN,
+                alpha: 1e-3,
+                beta: 1.0
+            }
+            .is_valid()
+        );
+    }
 }
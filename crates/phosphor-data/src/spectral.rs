@@ -1,4 +1,15 @@
+/// Number of spectral bands sampled across [`WAVELENGTH_MIN`, `WAVELENGTH_MAX`].
+///
+/// 16 by default; the `bands-32` feature doubles it. Everything sized by
+/// this const follows automatically, but a few GPU-facing structs pack
+/// bands four to a vec4 and so require a multiple of 4: [`PackedBands`], and
+/// `EmissionGroupGpu` and `SpectralResolveParams` in the main crate, whose
+/// uniform sizes change with the band count.
+#[cfg(not(feature = "bands-32"))]
 pub const SPECTRAL_BANDS: usize = 16;
+#[cfg(feature = "bands-32")]
+pub const SPECTRAL_BANDS: usize = 32;
+
 pub const WAVELENGTH_MIN: f32 = 380.0;
 pub const WAVELENGTH_MAX: f32 = 780.0;
 pub const BAND_WIDTH: f32 = (WAVELENGTH_MAX - WAVELENGTH_MIN) / SPECTRAL_BANDS as f32;
@@ -13,11 +24,35 @@ pub const fn band_center(band: usize) -> f32 {
     WAVELENGTH_MIN + (band as f32 + 0.5) * BAND_WIDTH
 }
 
+/// [`band_center`] for every band, for code that indexes bands in a loop.
+pub const SPECTRAL_BAND_CENTERS: [f32; SPECTRAL_BANDS] = {
+    let mut centers = [0.0; SPECTRAL_BANDS];
+    let mut band = 0;
+    while band < SPECTRAL_BANDS {
+        centers[band] = band_center(band);
+        band += 1;
+    }
+    centers
+};
+
+/// [`band_range`] for every band, as `(min_nm, max_nm)`.
+pub const SPECTRAL_BAND_RANGES: [(f32, f32); SPECTRAL_BANDS] = {
+    let mut ranges = [(0.0, 0.0); SPECTRAL_BANDS];
+    let mut band = 0;
+    while band < SPECTRAL_BANDS {
+        ranges[band] = band_range(band);
+        band += 1;
+    }
+    ranges
+};
+
 // --- CSV spectrum parsing ---
 
 #[derive(Debug)]
 pub enum CsvSpectrumError {
     MissingColumn(&'static str),
+    /// A `# units:` directive named a unit other than nm, µm, or Å.
+    UnknownUnit(String),
     ParseFloat {
         line: usize,
         col: &'static str,
@@ -25,6 +60,13 @@ pub enum CsvSpectrumError {
     },
     TooFewPoints,
     ZeroIntensity,
+    /// The data stops short of the band range while still carrying
+    /// significant energy, so the emission beyond `covered` is lost and the
+    /// normalized weights overstate the covered bands. Reported as a warning
+    /// by [`csv_to_emission_weights_with_warnings`].
+    InsufficientCoverage {
+        covered: (f32, f32),
+    },
 }
 
 impl std::fmt::Display for CsvSpectrumError {
@@ -33,6 +75,9 @@ impl std::fmt::Display for CsvSpectrumError {
             CsvSpectrumError::MissingColumn(col) => {
                 write!(f, "missing required column: {col}")
             }
+            CsvSpectrumError::UnknownUnit(unit) => {
+                write!(f, "unknown wavelength unit '{unit}'")
+            }
             CsvSpectrumError::ParseFloat { line, col, err } => {
                 write!(f, "line {line}, column '{col}': {err}")
             }
@@ -42,39 +87,177 @@ impl std::fmt::Display for CsvSpectrumError {
             CsvSpectrumError::ZeroIntensity => {
                 write!(f, "total integrated intensity is zero")
             }
+            CsvSpectrumError::InsufficientCoverage { covered: (lo, hi) } => {
+                write!(
+                    f,
+                    "spectrum covers only {lo}–{hi} nm but is still emitting at an edge \
+                     (bands span {WAVELENGTH_MIN}–{WAVELENGTH_MAX} nm)"
+                )
+            }
         }
     }
 }
 
 impl std::error::Error for CsvSpectrumError {}
 
+/// Intensity at an uncovered edge, relative to the peak, above which the
+/// spectrum is considered truncated rather than tailing off.
+const SIGNIFICANT_EDGE_FRACTION: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WavelengthUnit {
+    Nanometer,
+    Micrometer,
+    Angstrom,
+}
+
+impl WavelengthUnit {
+    fn parse(name: &str) -> Option<Self> {
+        // Full Unicode lowercasing, so "Å" matches "å".
+        match name.trim().to_lowercase().as_str() {
+            "nm" | "nanometer" | "nanometers" => Some(Self::Nanometer),
+            "um" | "µm" | "micrometer" | "micrometers" | "micron" | "microns" => {
+                Some(Self::Micrometer)
+            }
+            "a" | "å" | "angstrom" | "angstroms" => Some(Self::Angstrom),
+            _ => None,
+        }
+    }
+
+    fn to_nm(self, value: f32) -> f32 {
+        match self {
+            Self::Nanometer => value,
+            Self::Micrometer => value * 1000.0,
+            Self::Angstrom => value * 0.1,
+        }
+    }
+}
+
+/// Wavelength column names in order of preference, with their unit. `None`
+/// means the unit comes from a `# units:` directive.
+const WAVELENGTH_COLUMNS: [(&str, Option<WavelengthUnit>); 4] = [
+    ("wavelength_nm", Some(WavelengthUnit::Nanometer)),
+    ("wavelength_um", Some(WavelengthUnit::Micrometer)),
+    ("wavelength_angstrom", Some(WavelengthUnit::Angstrom)),
+    ("wavelength", None),
+];
+
+/// Field delimiters recognized in spectrum files, in tie-break order.
+const DELIMITERS: [char; 3] = [',', ';', '\t'];
+
+/// Pick the delimiter that occurs most often in `header`, preferring comma on
+/// a tie (including a single-column header with none at all).
+fn sniff_delimiter(header: &str) -> char {
+    DELIMITERS
+        .into_iter()
+        .rev()
+        .max_by_key(|&d| header.matches(d).count())
+        .unwrap_or(',')
+}
+
 /// Parse a CSV spectrum (with `wavelength_nm` and `rel_intensity` columns) into
 /// normalized emission weights for [`SPECTRAL_BANDS`] spectral bands.
 ///
 /// The CSV may contain comment lines starting with `#`, extra columns (ignored),
 /// and non-uniform wavelength spacing. Integration uses the trapezoidal rule with
 /// line segments clipped to band boundaries.
+///
+/// Fields may be separated by commas, semicolons, or tabs; the delimiter is
+/// sniffed from the header row. Numbers must use `.` as the decimal separator —
+/// European exports that write `1,5` need converting first, as the comma is not
+/// accepted as a decimal point.
+///
+/// Wavelengths default to nanometers. A `wavelength_um` or `wavelength_angstrom`
+/// column is converted to nm; a bare `wavelength` column takes its unit from a
+/// `# units: nm|micrometer|angstrom` comment before the header.
+///
+/// Coverage problems are not reported; use
+/// [`csv_to_emission_weights_with_warnings`] to check for them.
 pub fn csv_to_emission_weights(csv_text: &str) -> Result<[f32; SPECTRAL_BANDS], CsvSpectrumError> {
+    csv_to_emission_weights_with(csv_text, Normalization::Area)
+}
+
+/// How [`csv_to_emission_weights_with`] scales the integrated band weights.
+///
+/// The emission weights stored on a phosphor and uploaded to the GPU are
+/// assumed to sum to 1, so that a unit of deposited beam energy produces the
+/// same total emission whatever the spectrum's shape. Only [`Self::Area`]
+/// guarantees that; the others are for analysis and comparison.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Weights sum to 1.0. The default, and what the renderer expects.
+    #[default]
+    Area,
+    /// The brightest band is 1.0. Compares spectral shapes independent of
+    /// width; the sum is then greater than 1 for all but single-band spikes.
+    Peak,
+    /// Raw integrals of intensity × nm per band, preserving relative
+    /// brightness between spectra measured on the same scale.
+    None,
+}
+
+/// Like [`csv_to_emission_weights`], with a choice of normalization.
+pub fn csv_to_emission_weights_with(
+    csv_text: &str,
+    normalization: Normalization,
+) -> Result<[f32; SPECTRAL_BANDS], CsvSpectrumError> {
+    parse_spectrum(csv_text, normalization).map(|(weights, _)| weights)
+}
+
+/// Like [`csv_to_emission_weights`], but also returns non-fatal problems
+/// with the data. Currently this is [`CsvSpectrumError::InsufficientCoverage`]
+/// when the data ends inside the band range while still emitting: the
+/// intensity at the uncovered edge is at least 5% of the peak, or the peak
+/// itself is within one band width of that edge.
+pub fn csv_to_emission_weights_with_warnings(
+    csv_text: &str,
+) -> Result<([f32; SPECTRAL_BANDS], Vec<CsvSpectrumError>), CsvSpectrumError> {
+    parse_spectrum(csv_text, Normalization::Area)
+}
+
+fn parse_spectrum(
+    csv_text: &str,
+    normalization: Normalization,
+) -> Result<([f32; SPECTRAL_BANDS], Vec<CsvSpectrumError>), CsvSpectrumError> {
+    let points = parse_spectrum_points(csv_text)?;
+    integrate_points(&points, normalization)
+}
+
+/// Parse a spectrum CSV (same format as [`csv_to_emission_weights`]) into
+/// `(wavelength_nm, rel_intensity)` points, converted to nm, sorted by
+/// wavelength, with negative intensities clamped to zero.
+pub fn parse_spectrum_points(csv_text: &str) -> Result<Vec<(f32, f32)>, CsvSpectrumError> {
     // 1. Iterate all lines, tracking 1-indexed line numbers, skip comments inline
     let mut line_iter = csv_text.lines().enumerate();
 
-    // 2. Parse header row, find column indices (skip comments/empty lines)
+    // 2. Parse header row, find column indices (skip comments/empty lines,
+    //    picking up a `# units:` directive on the way)
+    let mut directive_unit = WavelengthUnit::Nanometer;
     let header = loop {
         match line_iter.next() {
             Some((_idx, line)) => {
                 let trimmed = line.trim();
-                if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                if let Some(comment) = trimmed.strip_prefix('#') {
+                    if let Some(unit) = comment.trim().strip_prefix("units:") {
+                        directive_unit = WavelengthUnit::parse(unit)
+                            .ok_or_else(|| CsvSpectrumError::UnknownUnit(unit.trim().into()))?;
+                    }
+                } else if !trimmed.is_empty() {
                     break line;
                 }
             }
             None => return Err(CsvSpectrumError::TooFewPoints),
         }
     };
-    let columns: Vec<&str> = header.split(',').map(|s| s.trim()).collect();
+    let delimiter = sniff_delimiter(header);
+    let columns: Vec<&str> = header.split(delimiter).map(|s| s.trim()).collect();
 
-    let wl_idx = columns
+    let (wl_idx, wl_col, unit) = WAVELENGTH_COLUMNS
         .iter()
-        .position(|&c| c == "wavelength_nm")
+        .find_map(|&(name, unit)| {
+            let idx = columns.iter().position(|&c| c == name)?;
+            Some((idx, name, unit.unwrap_or(directive_unit)))
+        })
         .ok_or(CsvSpectrumError::MissingColumn("wavelength_nm"))?;
     let int_idx = columns
         .iter()
@@ -89,14 +272,15 @@ pub fn csv_to_emission_weights(csv_text: &str) -> Result<[f32; SPECTRAL_BANDS],
             continue;
         }
         let line_num = line_idx + 1; // 1-indexed
-        let fields: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+        let fields: Vec<&str> = line.split(delimiter).map(|s| s.trim()).collect();
         let wl: f32 = fields.get(wl_idx).unwrap_or(&"").parse().map_err(|err| {
             CsvSpectrumError::ParseFloat {
                 line: line_num,
-                col: "wavelength_nm",
+                col: wl_col,
                 err,
             }
         })?;
+        let wl = unit.to_nm(wl);
         let intensity: f32 = fields
             .get(int_idx)
             .unwrap_or(&"")
@@ -117,12 +301,36 @@ pub fn csv_to_emission_weights(csv_text: &str) -> Result<[f32; SPECTRAL_BANDS],
 
     // 5. Sort by wavelength
     points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(points)
+}
+
+/// [`csv_to_emission_weights_with_warnings`] for points already parsed by
+/// [`parse_spectrum_points`].
+pub fn points_to_emission_weights_with_warnings(
+    points: &[(f32, f32)],
+) -> Result<([f32; SPECTRAL_BANDS], Vec<CsvSpectrumError>), CsvSpectrumError> {
+    integrate_points(points, Normalization::Area)
+}
+
+/// Integrate sorted spectrum points into band weights (steps 6–7 of
+/// [`parse_spectrum`]).
+fn integrate_points(
+    points: &[(f32, f32)],
+    normalization: Normalization,
+) -> Result<([f32; SPECTRAL_BANDS], Vec<CsvSpectrumError>), CsvSpectrumError> {
+    if points.len() < 2 {
+        return Err(CsvSpectrumError::TooFewPoints);
+    }
+    let mut warnings = Vec::new();
+    if let Some(covered) = truncated_coverage(points) {
+        warnings.push(CsvSpectrumError::InsufficientCoverage { covered });
+    }
 
     // 6. For each spectral band, compute trapezoidal integral with clipping
     let mut weights = [0.0f32; SPECTRAL_BANDS];
 
     for (band, weight) in weights.iter_mut().enumerate() {
-        let (band_lo, band_hi) = band_range(band);
+        let (band_lo, band_hi) = SPECTRAL_BAND_RANGES[band];
         let mut integral = 0.0f32;
 
         for i in 0..points.len() - 1 {
@@ -159,24 +367,256 @@ pub fn csv_to_emission_weights(csv_text: &str) -> Result<[f32; SPECTRAL_BANDS],
         *weight = integral;
     }
 
-    // 7. Normalize weights to sum to 1.0
+    // 7. Normalize
     let total: f32 = weights.iter().sum();
     if total <= 0.0 {
         return Err(CsvSpectrumError::ZeroIntensity);
     }
+    let scale = match normalization {
+        Normalization::Area => total,
+        Normalization::Peak => weights.iter().copied().fold(0.0, f32::max),
+        Normalization::None => 1.0,
+    };
     for w in &mut weights {
-        *w /= total;
+        *w /= scale;
+    }
+
+    Ok((weights, warnings))
+}
+
+/// The covered wavelength span of sorted `points`, if the spectrum is cut off
+/// at an edge that falls inside the band range.
+fn truncated_coverage(points: &[(f32, f32)]) -> Option<(f32, f32)> {
+    let (&(lo, lo_intensity), &(hi, hi_intensity)) = (points.first()?, points.last()?);
+    let (peak_nm, peak) = points
+        .iter()
+        .copied()
+        .fold((lo, 0.0f32), |best, p| if p.1 > best.1 { p } else { best });
+    if peak <= 0.0 {
+        return None;
+    }
+
+    let cut_off = |edge_nm: f32, edge_intensity: f32| {
+        edge_intensity >= SIGNIFICANT_EDGE_FRACTION * peak || (peak_nm - edge_nm).abs() < BAND_WIDTH
+    };
+    let low_truncated = lo > WAVELENGTH_MIN && cut_off(lo, lo_intensity);
+    let high_truncated = hi < WAVELENGTH_MAX && cut_off(hi, hi_intensity);
+    (low_truncated || high_truncated).then_some((lo, hi))
+}
+
+/// CIE 1931 2-degree standard observer color matching functions.
+/// 1nm steps, 360-830nm. Each entry: (wavelength_nm, x_bar, y_bar, z_bar).
+///
+/// Source: CIE 018:2019 (DOI: 10.25039/CIE.DS.xvudnb9b)
+const CIE_1931_DATA: [(f32, f64, f64, f64); 471] =
+    cie_data::cie_1931_table!("../../data/CIE_xyz_1931_2deg.csv");
+
+/// CIE 1931 color matching function values (x_bar, y_bar, z_bar),
+/// pre-integrated over each spectral band. Used to convert spectral
+/// energy to XYZ tristimulus values.
+///
+/// Computed at compile time as a [SPECTRAL_BANDS] array of (x, y, z) tuples.
+/// These are the same weights the GPU spectral resolve pass integrates with.
+pub const CIE_INTEGRATION_WEIGHTS: [(f32, f32, f32); SPECTRAL_BANDS] = {
+    let mut weights = [(0.0f32, 0.0f32, 0.0f32); SPECTRAL_BANDS];
+
+    let mut band = 0;
+    while band < SPECTRAL_BANDS {
+        let (band_min, band_max) = band_range(band);
+        let mut sum_x = 0.0f64;
+        let mut sum_y = 0.0f64;
+        let mut sum_z = 0.0f64;
+        let mut count = 0u32;
+
+        let mut i = 0;
+        while i < CIE_1931_DATA.len() {
+            let (wl, x, y, z) = CIE_1931_DATA[i];
+            if wl >= band_min && wl < band_max {
+                sum_x += x;
+                sum_y += y;
+                sum_z += z;
+                count += 1;
+            }
+            i += 1;
+        }
+
+        if count > 0 {
+            let n = count as f64;
+            weights[band] = (
+                (sum_x / n * BAND_WIDTH as f64) as f32,
+                (sum_y / n * BAND_WIDTH as f64) as f32,
+                (sum_z / n * BAND_WIDTH as f64) as f32,
+            );
+        }
+
+        band += 1;
+    }
+
+    weights
+};
+
+/// Integrate per-band emission weights against the CIE 1931 observer,
+/// returning XYZ tristimulus values. Mirrors the GPU spectral resolve pass,
+/// so results are directly comparable with what the simulator displays.
+pub fn spectrum_to_xyz(emission_weights: &[f32; SPECTRAL_BANDS]) -> [f32; 3] {
+    let mut xyz = [0.0f32; 3];
+    for (&w, &(x, y, z)) in emission_weights.iter().zip(&CIE_INTEGRATION_WEIGHTS) {
+        xyz[0] += w * x;
+        xyz[1] += w * y;
+        xyz[2] += w * z;
+    }
+    xyz
+}
+
+/// Convert CIE XYZ to linear sRGB (IEC 61966-2-1 matrix, D65 white).
+///
+/// No gamma and no gamut mapping: out-of-gamut colors come back with
+/// negative components, as in the shader before its gamut map.
+pub fn xyz_to_srgb(xyz: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = xyz;
+    [
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    ]
+}
+
+/// Number of vec4s needed to hold one value per band.
+pub const PACKED_BAND_VEC4S: usize = SPECTRAL_BANDS / 4;
+
+/// Per-band values packed 4 bands per vec4 for GPU uniforms.
+pub type PackedBands = [[f32; 4]; PACKED_BAND_VEC4S];
+
+const _: () = assert!(
+    SPECTRAL_BANDS.is_multiple_of(4),
+    "PackedBands packs 4 bands per vec4; SPECTRAL_BANDS must be a multiple of 4"
+);
+
+/// Pack per-band values into the vec4 layout used by GPU uniforms: band `i`
+/// lands in `[i / 4][i % 4]`.
+pub fn pack_bands(values: &[f32; SPECTRAL_BANDS]) -> PackedBands {
+    let mut packed = [[0.0f32; 4]; PACKED_BAND_VEC4S];
+    for (i, &v) in values.iter().enumerate() {
+        packed[i / 4][i % 4] = v;
+    }
+    packed
+}
+
+/// Pack [`CIE_INTEGRATION_WEIGHTS`] into the vec4 layout used by GPU
+/// uniforms, one [`PackedBands`] each for x, y, and z.
+pub fn pack_cie_weights() -> (PackedBands, PackedBands, PackedBands) {
+    let mut cie_x = [[0.0f32; 4]; PACKED_BAND_VEC4S];
+    let mut cie_y = [[0.0f32; 4]; PACKED_BAND_VEC4S];
+    let mut cie_z = [[0.0f32; 4]; PACKED_BAND_VEC4S];
+
+    for (i, &(x, y, z)) in CIE_INTEGRATION_WEIGHTS.iter().enumerate() {
+        cie_x[i / 4][i % 4] = x;
+        cie_y[i / 4][i % 4] = y;
+        cie_z[i / 4][i % 4] = z;
     }
 
-    Ok(weights)
+    (cie_x, cie_y, cie_z)
+}
+
+/// Color temperature treated as the image's own white: no adaptation at this
+/// setting. The daylight locus at 6500 K is within 0.0002 of D65 in xy.
+pub const NEUTRAL_WHITE_K: f32 = 6500.0;
+
+/// Chromaticity (x, y) of the CIE daylight locus at correlated color
+/// temperature `kelvin`, clamped to the locus' 4000–25000 K validity range.
+pub fn cct_to_xy(kelvin: f32) -> (f32, f32) {
+    let t = kelvin.clamp(4000.0, 25000.0) as f64;
+    let (t2, t3) = (t * t, t * t * t);
+    let x = if t <= 7000.0 {
+        -4.6070e9 / t3 + 2.9678e6 / t2 + 0.09911e3 / t + 0.244063
+    } else {
+        -2.0064e9 / t3 + 1.9018e6 / t2 + 0.24748e3 / t + 0.237040
+    };
+    let y = -3.000 * x * x + 2.870 * x - 0.275;
+    (x as f32, y as f32)
+}
+
+/// Row-major 3×3 matrix.
+pub type Mat3 = [[f32; 3]; 3];
+
+const BRADFORD: Mat3 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+const BRADFORD_INV: Mat3 = [
+    [0.9869929, -0.1470543, 0.1599627],
+    [0.4323053, 0.5183603, 0.0492912],
+    [-0.0085287, 0.0400428, 0.9684867],
+];
+
+const SRGB_TO_XYZ: Mat3 = [
+    [0.4124, 0.3576, 0.1805],
+    [0.2126, 0.7152, 0.0722],
+    [0.0193, 0.1192, 0.9505],
+];
+
+/// Same matrix as [`xyz_to_srgb`] and the spectral resolve shader.
+const XYZ_TO_SRGB: Mat3 = [
+    [3.2406, -1.5372, -0.4986],
+    [-0.9689, 1.8758, 0.0415],
+    [0.0557, -0.2040, 1.0570],
+];
+
+/// Matrix product `a · b`.
+pub fn mat3_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    std::array::from_fn(|r| std::array::from_fn(|c| (0..3).map(|k| a[r][k] * b[k][c]).sum()))
+}
+
+/// Matrix-vector product `m · v`.
+pub fn mat3_apply(m: &Mat3, v: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|r| (0..3).map(|k| m[r][k] * v[k]).sum())
+}
+
+/// XYZ of chromaticity `(x, y)` at unit luminance.
+pub fn xy_to_xyz((x, y): (f32, f32)) -> [f32; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+/// Linear-sRGB color of the daylight locus at `kelvin`, scaled so the
+/// brightest channel is 1. Near-white at [`NEUTRAL_WHITE_K`], amber when
+/// warmer and blue when cooler.
+pub fn cct_to_tint(kelvin: f32) -> [f32; 3] {
+    let rgb = mat3_apply(&XYZ_TO_SRGB, xy_to_xyz(cct_to_xy(kelvin)));
+    let max = rgb.iter().copied().fold(f32::MIN, f32::max);
+    rgb.map(|c| (c / max).max(0.0))
+}
+
+/// Row-major linear-sRGB matrix performing a von Kries (Bradford) chromatic
+/// adaptation from [`NEUTRAL_WHITE_K`] to `target_k` on the daylight locus.
+pub fn white_balance_matrix(target_k: f32) -> Mat3 {
+    let src = mat3_apply(&BRADFORD, xy_to_xyz(cct_to_xy(NEUTRAL_WHITE_K)));
+    let dst = mat3_apply(&BRADFORD, xy_to_xyz(cct_to_xy(target_k)));
+    let mut gain = [[0.0; 3]; 3];
+    for i in 0..3 {
+        gain[i][i] = dst[i] / src[i];
+    }
+    let adapt_xyz = mat3_mul(&BRADFORD_INV, &mat3_mul(&gain, &BRADFORD));
+    mat3_mul(&XYZ_TO_SRGB, &mat3_mul(&adapt_xyz, &SRGB_TO_XYZ))
 }
 
 pub fn gaussian_emission_weights(peak_nm: f32, fwhm_nm: f32) -> [f32; SPECTRAL_BANDS] {
-    let sigma = fwhm_nm / 2.355;
     let mut weights = [0.0f32; SPECTRAL_BANDS];
 
-    for (i, w) in weights.iter_mut().enumerate().take(SPECTRAL_BANDS) {
-        let center = band_center(i);
+    // A zero (or nonsensical) width is the limit of a line spectrum: all the
+    // energy in the band containing the peak. Dividing by a zero sigma would
+    // instead give 0/0 = NaN in that band.
+    if !(fwhm_nm.is_finite() && fwhm_nm > 0.0) {
+        let band =
+            ((peak_nm - WAVELENGTH_MIN) / BAND_WIDTH).clamp(0.0, (SPECTRAL_BANDS - 1) as f32);
+        weights[band as usize] = 1.0;
+        return weights;
+    }
+
+    let sigma = fwhm_nm / 2.355;
+
+    for (w, &center) in weights.iter_mut().zip(&SPECTRAL_BAND_CENTERS) {
         let d = (center - peak_nm) / sigma;
         *w = (-0.5 * d * d).exp();
     }
@@ -191,13 +631,83 @@ pub fn gaussian_emission_weights(peak_nm: f32, fwhm_nm: f32) -> [f32; SPECTRAL_B
     weights
 }
 
+/// Participation ratios below this mean nearly all the emission lands in a
+/// single band, which renders as an artificial-looking pure color.
+pub const MIN_PARTICIPATION_RATIO: f32 = 1.5;
+
+/// Effective number of bands a set of emission weights spreads over:
+/// `(Σw)² / Σw²`, which is `1 / Σw²` for weights summing to 1. Ranges from
+/// 1 (a single band) to the band count (flat). Zero for all-zero weights.
+pub fn participation_ratio(weights: &[f32]) -> f32 {
+    let sum: f32 = weights.iter().sum();
+    let sum_sq: f32 = weights.iter().map(|w| w * w).sum();
+    if sum_sq > 0.0 {
+        sum * sum / sum_sq
+    } else {
+        0.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn participation_ratio_spans_one_to_band_count() {
+        let mut single = [0.0f32; SPECTRAL_BANDS];
+        single[3] = 1.0;
+        assert_eq!(participation_ratio(&single), 1.0);
+
+        let flat = [1.0 / SPECTRAL_BANDS as f32; SPECTRAL_BANDS];
+        let ratio = participation_ratio(&flat);
+        assert!((ratio - SPECTRAL_BANDS as f32).abs() < 1e-3, "{ratio}");
+
+        assert_eq!(participation_ratio(&[0.0; SPECTRAL_BANDS]), 0.0);
+    }
+
+    /// Band index containing wavelength `nm`, for tests that must hold at
+    /// any band count.
+    fn band_of(nm: f32) -> usize {
+        ((nm - WAVELENGTH_MIN) / BAND_WIDTH) as usize
+    }
+
+    fn chromaticity(xyz: [f32; 3]) -> (f32, f32) {
+        let sum = xyz[0] + xyz[1] + xyz[2];
+        (xyz[0] / sum, xyz[1] / sum)
+    }
+
+    #[test]
+    fn flat_spectrum_is_near_equal_energy_white() {
+        let flat = [1.0 / SPECTRAL_BANDS as f32; SPECTRAL_BANDS];
+        let (x, y) = chromaticity(spectrum_to_xyz(&flat));
+        assert!((x - 1.0 / 3.0).abs() < 0.01, "x = {x}");
+        assert!((y - 1.0 / 3.0).abs() < 0.01, "y = {y}");
+    }
+
+    #[test]
+    fn narrow_green_band_is_saturated_green() {
+        let green = gaussian_emission_weights(545.0, 20.0);
+        let (x, y) = chromaticity(spectrum_to_xyz(&green));
+        assert!(x > 0.2 && x < 0.35, "x = {x}");
+        assert!(y > 0.6, "y = {y}");
+
+        // Outside the sRGB gamut: red goes negative, green dominates
+        let [r, g, b] = xyz_to_srgb(spectrum_to_xyz(&green));
+        assert!(g > r && g > b);
+    }
+
+    #[test]
+    fn d65_white_maps_to_equal_srgb() {
+        // D65 white point normalized to Y = 1
+        let [r, g, b] = xyz_to_srgb([0.9505, 1.0, 1.089]);
+        for c in [r, g, b] {
+            assert!((c - 1.0).abs() < 0.01, "{r} {g} {b}");
+        }
+    }
+
     #[test]
     fn csv_to_emission_weights_basic() {
-        // Narrow spike at 525nm — should land in band 5 (505–530nm).
+        // Narrow spike at 525nm — should land in one band (505–530nm at 16 bands).
         // Two points tightly around 525nm with high intensity.
         let csv = "\
 wavelength_nm,rel_intensity
@@ -207,21 +717,117 @@ wavelength_nm,rel_intensity
 ";
         let weights = csv_to_emission_weights(csv).unwrap();
 
-        // Band 5 (505-530nm) should have essentially all the weight
         let sum: f32 = weights.iter().sum();
         assert!(
             (sum - 1.0).abs() < 1e-5,
             "weights should sum to 1.0, got {sum}"
         );
 
-        // Band 5 should dominate
+        // The band containing 525nm should have essentially all the weight
+        let band = band_of(525.0);
         assert!(
-            weights[5] > 0.99,
-            "band 5 should have >99% of weight, got {}",
-            weights[5]
+            weights[band] > 0.99,
+            "band {band} should have >99% of weight, got {}",
+            weights[band]
         );
     }
 
+    #[test]
+    fn semicolon_and_tab_delimited_match_comma() {
+        let comma = "wavelength_nm,rel_intensity\n524.0,1.0\n525.0,1.0\n526.0,1.0\n";
+        let expected = csv_to_emission_weights(comma).unwrap();
+        for delimiter in [";", "\t"] {
+            let csv = comma.replace(',', delimiter);
+            assert_eq!(
+                csv_to_emission_weights(&csv).unwrap(),
+                expected,
+                "delimiter {delimiter:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sniffing_picks_the_most_frequent_delimiter() {
+        assert_eq!(
+            sniff_delimiter("wavelength_nm;rel_intensity;note, with comma"),
+            ';'
+        );
+        assert_eq!(sniff_delimiter("wavelength_nm\trel_intensity"), '\t');
+        assert_eq!(sniff_delimiter("wavelength_nm"), ',');
+    }
+
+    #[test]
+    fn angstrom_and_micrometer_columns_convert_to_nm() {
+        let nm = "wavelength_nm,rel_intensity\n500.0,0.5\n510.0,1.0\n520.0,0.5\n";
+        let angstrom = "wavelength_angstrom,rel_intensity\n5000.0,0.5\n5100.0,1.0\n5200.0,0.5\n";
+        let micrometer = "wavelength_um,rel_intensity\n0.5,0.5\n0.51,1.0\n0.52,0.5\n";
+        let expected = csv_to_emission_weights(nm).unwrap();
+        assert_eq!(csv_to_emission_weights(angstrom).unwrap(), expected);
+        let um = csv_to_emission_weights(micrometer).unwrap();
+        for (a, b) in um.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-5, "{um:?} vs {expected:?}");
+        }
+    }
+
+    #[test]
+    fn units_directive_applies_to_bare_wavelength_column() {
+        let csv =
+            "# units: angstrom\nwavelength,rel_intensity\n5000.0,0.5\n5100.0,1.0\n5200.0,0.5\n";
+        let nm = "wavelength_nm,rel_intensity\n500.0,0.5\n510.0,1.0\n520.0,0.5\n";
+        assert_eq!(
+            csv_to_emission_weights(csv).unwrap(),
+            csv_to_emission_weights(nm).unwrap()
+        );
+        assert!(matches!(
+            csv_to_emission_weights("# units: furlong\nwavelength,rel_intensity\n"),
+            Err(CsvSpectrumError::UnknownUnit(unit)) if unit == "furlong"
+        ));
+    }
+
+    #[test]
+    fn unit_names_match_case_insensitively() {
+        for name in ["Å", "å", "Angstrom", "A"] {
+            assert_eq!(
+                WavelengthUnit::parse(name),
+                Some(WavelengthUnit::Angstrom),
+                "{name}"
+            );
+        }
+        assert_eq!(WavelengthUnit::parse("NM"), Some(WavelengthUnit::Nanometer));
+        assert_eq!(
+            WavelengthUnit::parse("µm"),
+            Some(WavelengthUnit::Micrometer)
+        );
+    }
+
+    #[test]
+    fn peak_normalization_sets_brightest_band_to_one() {
+        let csv = "wavelength_nm,rel_intensity\n500.0,0.5\n510.0,1.0\n520.0,0.5\n";
+        let weights = csv_to_emission_weights_with(csv, Normalization::Peak).unwrap();
+        let max = weights.iter().copied().fold(0.0, f32::max);
+        assert_eq!(max, 1.0);
+        // Same shape as the area-normalized weights.
+        let area = csv_to_emission_weights(csv).unwrap();
+        let ratio = weights[band_of(505.0)] / area[band_of(505.0)];
+        for (p, a) in weights.iter().zip(&area) {
+            assert!((p - a * ratio).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn no_normalization_preserves_intensity_scale() {
+        let csv = "wavelength_nm,rel_intensity\n500.0,0.5\n510.0,1.0\n520.0,0.5\n";
+        let doubled = "wavelength_nm,rel_intensity\n500.0,1.0\n510.0,2.0\n520.0,1.0\n";
+        let single = csv_to_emission_weights_with(csv, Normalization::None).unwrap();
+        let double = csv_to_emission_weights_with(doubled, Normalization::None).unwrap();
+        for (s, d) in single.iter().zip(&double) {
+            assert!((d - 2.0 * s).abs() < 1e-5);
+        }
+        // Raw trapezoid area: 0.75 × 10 + 0.75 × 10 nm.
+        let total: f32 = single.iter().sum();
+        assert!((total - 15.0).abs() < 1e-4, "total was {total}");
+    }
+
     #[test]
     fn csv_to_emission_weights_uniform() {
         // Flat spectrum across 380-780nm — all bands should get roughly equal weight.
@@ -255,11 +861,11 @@ wavelength_nm,wavelength_angstrom,energy_eV,rel_intensity
             (sum - 1.0).abs() < 1e-5,
             "weights should sum to 1.0, got {sum}"
         );
-        // All energy is in the 500-530nm range (bands 4-5)
-        let band_4_5 = weights[4] + weights[5];
+        // All energy is in the 500-520nm range (bands 4-5 at 16 bands)
+        let in_range: f32 = weights[band_of(500.0)..=band_of(520.0)].iter().sum();
         assert!(
-            band_4_5 > 0.99,
-            "bands 4+5 should capture >99% of energy, got {band_4_5}"
+            in_range > 0.99,
+            "bands covering 500-520nm should capture >99% of energy, got {in_range}"
         );
     }
 
@@ -298,4 +904,142 @@ wavelength_nm,something_else
             Err(CsvSpectrumError::ZeroIntensity)
         ));
     }
+
+    #[test]
+    fn band_tables_match_the_functions() {
+        for band in 0..SPECTRAL_BANDS {
+            assert_eq!(
+                SPECTRAL_BAND_CENTERS[band],
+                band_center(band),
+                "band {band}"
+            );
+            assert_eq!(SPECTRAL_BAND_RANGES[band], band_range(band), "band {band}");
+        }
+    }
+
+    #[test]
+    fn zero_fwhm_is_a_line_in_the_peak_band() {
+        for fwhm in [0.0, -5.0, f32::NAN] {
+            let weights = gaussian_emission_weights(525.0, fwhm);
+            assert!(weights.iter().all(|w| w.is_finite()), "fwhm {fwhm}");
+            assert_eq!(weights[band_of(525.0)], 1.0, "fwhm {fwhm}");
+            assert_eq!(weights.iter().sum::<f32>(), 1.0, "fwhm {fwhm}");
+        }
+    }
+
+    #[cfg(feature = "bands-32")]
+    #[test]
+    fn emission_weights_normalize_at_32_bands() {
+        assert_eq!(SPECTRAL_BANDS, 32);
+        let weights = gaussian_emission_weights(540.0, 30.0);
+        let sum: f32 = weights.iter().sum();
+        assert!((sum - 1.0).abs() < 0.001, "sum was {sum}");
+    }
+
+    #[test]
+    fn truncated_csv_warns_about_coverage() {
+        // Still rising at 600nm when the data stops.
+        let mut csv = String::from("wavelength_nm,rel_intensity\n");
+        for wl in (500..=600).step_by(10) {
+            csv.push_str(&format!("{wl},{}\n", (wl - 500) as f32 / 100.0));
+        }
+        let (weights, warnings) = csv_to_emission_weights_with_warnings(&csv).unwrap();
+        assert!(matches!(
+            warnings.as_slice(),
+            [CsvSpectrumError::InsufficientCoverage {
+                covered: (500.0, 600.0)
+            }]
+        ));
+        // The plain API still succeeds with the same weights.
+        assert_eq!(csv_to_emission_weights(&csv).unwrap(), weights);
+    }
+
+    #[test]
+    fn spectrum_tailing_off_inside_range_does_not_warn() {
+        let csv = "\
+wavelength_nm,rel_intensity
+500.0,0.0
+540.0,0.5
+550.0,1.0
+560.0,0.5
+600.0,0.0
+";
+        let (_, warnings) = csv_to_emission_weights_with_warnings(csv).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn pack_bands_fills_vec4s_in_order() {
+        let values: [f32; SPECTRAL_BANDS] = std::array::from_fn(|i| i as f32);
+        let flat: Vec<f32> = pack_bands(&values).into_iter().flatten().collect();
+        assert_eq!(flat, values);
+    }
+
+    #[test]
+    fn packed_cie_weights_preserve_band_order() {
+        let (cie_x, cie_y, cie_z) = pack_cie_weights();
+        let flat = |packed: PackedBands| packed.into_iter().flatten().collect::<Vec<_>>();
+        let (x, y, z) = (flat(cie_x), flat(cie_y), flat(cie_z));
+        for (i, &(wx, wy, wz)) in CIE_INTEGRATION_WEIGHTS.iter().enumerate() {
+            assert_eq!((x[i], y[i], z[i]), (wx, wy, wz), "band {i}");
+        }
+    }
+
+    #[test]
+    fn cct_6500_is_d65() {
+        let (x, y) = cct_to_xy(6500.0);
+        assert!((x - 0.3127).abs() < 1e-3, "x = {x}");
+        assert!((y - 0.3290).abs() < 1e-3, "y = {y}");
+    }
+
+    #[test]
+    fn cct_5000_is_near_d50() {
+        let (x, y) = cct_to_xy(5000.0);
+        assert!((x - 0.3457).abs() < 2e-3, "x = {x}");
+        assert!((y - 0.3585).abs() < 2e-3, "y = {y}");
+    }
+
+    #[test]
+    fn cct_branches_meet_at_7000k() {
+        let (below, _) = cct_to_xy(6999.9);
+        let (above, _) = cct_to_xy(7000.1);
+        assert!((below - above).abs() < 1e-3);
+    }
+
+    #[test]
+    fn tint_is_white_at_neutral_and_amber_when_warm() {
+        let [r, g, b] = cct_to_tint(NEUTRAL_WHITE_K);
+        for c in [r, g, b] {
+            assert!((c - 1.0).abs() < 0.02, "6500 K tint = {:?}", [r, g, b]);
+        }
+        let [r, g, b] = cct_to_tint(4000.0);
+        assert_eq!(r, 1.0);
+        assert!(r > g && g > b, "4000 K tint = {:?}", [r, g, b]);
+    }
+
+    #[test]
+    fn neutral_white_balance_is_identity() {
+        let m = white_balance_matrix(NEUTRAL_WHITE_K);
+        for (r, row) in m.iter().enumerate() {
+            for (c, &v) in row.iter().enumerate() {
+                let expected = if r == c { 1.0 } else { 0.0 };
+                assert!((v - expected).abs() < 2e-3, "m[{r}][{c}] = {v}");
+            }
+        }
+    }
+
+    #[test]
+    fn higher_temperature_makes_white_bluer() {
+        let [r, _, b] = mat3_apply(&white_balance_matrix(9300.0), [1.0, 1.0, 1.0]);
+        assert!(b > r, "r = {r}, b = {b}");
+        let [r, _, b] = mat3_apply(&white_balance_matrix(4000.0), [1.0, 1.0, 1.0]);
+        assert!(r > b, "r = {r}, b = {b}");
+    }
+
+    #[test]
+    fn xyz_to_srgb_matrix_matches_the_function() {
+        for xyz in [[0.9505, 1.0, 1.089], [0.2, 0.5, 0.1], [0.0, 0.0, 1.0]] {
+            assert_eq!(mat3_apply(&XYZ_TO_SRGB, xyz), xyz_to_srgb(xyz));
+        }
+    }
 }
@@ -6,12 +6,15 @@ use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, ControlFlow};
 use winit::window::{Window, WindowId};
 
-use crate::beam::SampleConsumer;
+use crate::beam::{SampleConsumer, deposited_energy};
+use crate::cli::AppConfig;
 use crate::controls_window::ControlsWindow;
 use crate::gpu::GpuState;
+use crate::gpu::composite::ViewportRect;
+use crate::settings::{AppSettings, MonitorRect, WindowGeometry};
 use crate::simulation::SimCommand;
 use crate::simulation_stats::SimStats;
-use crate::ui::{SimFrameInfo, UiState};
+use crate::ui::{BufferInfo, GpuInfo, SimFrameInfo, UiState};
 
 #[derive(Default, PartialEq)]
 enum WindowMode {
@@ -24,6 +27,7 @@ enum GlobalAction {
     Quit,
     ToggleDetach,
     ToggleFullscreen,
+    ToggleHud,
 }
 
 fn check_global_shortcut(event: &WindowEvent, ctx: &egui::Context) -> Option<GlobalAction> {
@@ -49,10 +53,54 @@ fn check_global_shortcut(event: &WindowEvent, ctx: &egui::Context) -> Option<Glo
         winit::keyboard::KeyCode::KeyQ => Some(GlobalAction::Quit),
         winit::keyboard::KeyCode::KeyD => Some(GlobalAction::ToggleDetach),
         winit::keyboard::KeyCode::KeyF => Some(GlobalAction::ToggleFullscreen),
+        winit::keyboard::KeyCode::KeyH => Some(GlobalAction::ToggleHud),
         _ => None,
     }
 }
 
+/// Which of the app's windows have keyboard focus, and whether rendering is
+/// paused because none does.
+#[derive(Debug)]
+struct FocusState {
+    viewport: bool,
+    controls: bool,
+    paused: bool,
+}
+
+impl Default for FocusState {
+    /// The main window is assumed focused until winit says otherwise.
+    fn default() -> Self {
+        Self {
+            viewport: true,
+            controls: false,
+            paused: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum FocusAction {
+    Pause,
+    Resume,
+}
+
+impl FocusState {
+    /// Re-evaluate after focus or the pause option changed. Returns what to
+    /// do when the paused state flips, `None` when it stays the same.
+    fn update(&mut self, pause_when_unfocused: bool) -> Option<FocusAction> {
+        let pause = pause_when_unfocused && !self.viewport && !self.controls;
+        if pause == self.paused {
+            return None;
+        }
+        self.paused = pause;
+        Some(if pause {
+            FocusAction::Pause
+        } else {
+            FocusAction::Resume
+        })
+    }
+}
+
 /// Fallback frame interval when the monitor refresh rate can't be queried.
 const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_micros(16_667); // 60 Hz
 
@@ -66,34 +114,74 @@ pub struct App {
     window: Option<Arc<Window>>,
     frame_interval: Duration,
     next_frame: Instant,
+    focus: FocusState,
     // Simulation thread
     sim_consumer: Option<SampleConsumer>,
     sim_commands: Option<crossbeam_channel::Sender<SimCommand>>,
     sim_handle: Option<std::thread::JoinHandle<()>>,
     sim_stats: Option<Arc<SimStats>>,
     sample_rate: f32,
+    settings: AppSettings,
+    /// Command-line startup options, applied to the UI when it is created.
+    config: AppConfig,
 }
 
-impl Default for App {
-    fn default() -> Self {
+impl App {
+    pub fn new(settings: AppSettings, config: AppConfig) -> Self {
+        let mode = if config.detached.unwrap_or(settings.detached) {
+            WindowMode::Detached
+        } else {
+            WindowMode::Combined
+        };
         Self {
             gpu: None,
             controls: None,
             ui: None,
-            mode: WindowMode::default(),
+            mode,
             window: None,
             frame_interval: DEFAULT_FRAME_INTERVAL,
             next_frame: Instant::now(),
+            focus: FocusState::default(),
             sim_consumer: None,
             sim_commands: None,
             sim_handle: None,
             sim_stats: None,
             sample_rate: 44100.0,
+            settings,
+            config,
+        }
+    }
+
+    /// Record the current window geometry and layout mode and write them to
+    /// the settings file. Geometry is not updated while fullscreen.
+    fn save_settings(&mut self) {
+        if let Some(window) = &self.window
+            && window.fullscreen().is_none()
+            && let Ok(pos) = window.outer_position()
+        {
+            let size = window.inner_size();
+            self.settings.window = Some(WindowGeometry {
+                x: pos.x,
+                y: pos.y,
+                width: size.width,
+                height: size.height,
+            });
+        }
+        self.settings.detached = self.mode == WindowMode::Detached;
+        if let Some(ui) = &self.ui {
+            self.settings.pause_when_unfocused = ui.engineer.pause_when_unfocused;
+            self.settings.timing_palette = ui.engineer.timing_palette;
+            self.settings.panel_tab = ui.tab();
+            // Zero while hidden or detached; keep the last shown width.
+            if ui.panel_width > 0.0 {
+                self.settings.panel_width = Some(ui.panel_width);
+            }
+        }
+        if let Err(e) = self.settings.save() {
+            tracing::warn!("Failed to save settings: {e}");
         }
     }
-}
 
-impl App {
     fn toggle_detach(&mut self, event_loop: &ActiveEventLoop) {
         match self.mode {
             WindowMode::Combined => {
@@ -116,6 +204,13 @@ impl App {
         }
     }
 
+    /// Toggle the layout if the UI's Detach/Attach button was clicked.
+    fn handle_detach_request(&mut self, event_loop: &ActiveEventLoop) {
+        if self.ui.as_mut().is_some_and(|ui| ui.detach_request.take()) {
+            self.toggle_detach(event_loop);
+        }
+    }
+
     fn handle_viewport_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
         // Only pass events to egui in Combined mode (viewport shouldn't
         // consume events for an invisible panel in Detached mode)
@@ -131,6 +226,7 @@ impl App {
 
         match event {
             WindowEvent::CloseRequested => {
+                self.save_settings();
                 if let Some(tx) = self.sim_commands.take() {
                     let _ = tx.send(SimCommand::Shutdown);
                 }
@@ -149,52 +245,63 @@ impl App {
                 }
             }
             WindowEvent::RedrawRequested => {
+                let frame_start = Instant::now();
                 let Some(window) = &self.window else { return };
                 let Some(gpu) = &mut self.gpu else { return };
                 let Some(ui) = &mut self.ui else { return };
 
                 // Phosphor change: rebuild decay/emission/spectral params + buffer
                 if ui.phosphor_changed() {
-                    gpu.switch_phosphor(ui.selected_phosphor());
+                    gpu.switch_phosphor(ui.phosphor_index, ui.selected_phosphor());
+                    ui.apply_default_exposure();
+                }
+                if ui.compare_changed() {
+                    let compare = ui.compare_phosphor.zip(ui.compare_phosphor());
+                    gpu.set_compare_phosphor(compare);
                 }
 
                 // Apply UI state to GPU parameters
                 crate::frame::sync_gpu_params(gpu, ui);
 
                 // Feed accumulation buffer size to UI for display
-                ui.accum_size = Some(gpu.accum.resolution);
+                ui.buffer_info = Some(BufferInfo {
+                    resolution: gpu.accum.resolution,
+                    requested: gpu.requested_resolution,
+                    bytes: gpu.total_buffer_bytes(),
+                });
+
+                // The histogram only runs while the engineer tab is on screen:
+                // always in the detached window, only with the sidebar open
+                // in combined mode.
+                gpu.histogram_enabled = ui.engineer_tab_selected()
+                    && (self.mode == WindowMode::Detached || ui.panel_visible);
+                ui.histogram = gpu.histogram.latest();
 
                 // Drain samples from simulation thread's ring buffer.
-                // Cap at 2x frame interval to prevent catastrophic decay during stalls.
+                // Cap at 2x frame interval of beam time to prevent
+                // catastrophic decay during stalls. Time comes from the
+                // samples' own dt, which holds however many samples the
+                // source or resampler spent on it.
                 let max_dt = self.frame_interval.as_secs_f32() * 2.0;
-                let max_samples = (self.sample_rate * max_dt) as usize;
                 let samples = self
                     .sim_consumer
                     .as_mut()
-                    .map(|c| c.drain_up_to(max_samples))
+                    .map(|c| c.drain_for(max_dt))
                     .unwrap_or_default();
-                let sim_dt = if samples.is_empty() {
-                    0.0
-                } else {
-                    samples.len() as f32 / self.sample_rate
-                };
+                let sim_dt: f32 = samples.iter().map(|s| s.dt).sum();
 
-                // Build per-frame simulation info for the engineer panel
-                let sim_frame_info = SimFrameInfo {
+                // Record per-frame simulation info for the engineer panel
+                ui.sim_frame.record(SimFrameInfo {
                     samples_this_frame: samples.len(),
                     sim_dt,
+                    energy_this_frame: deposited_energy(&samples),
                     buffer_pending: self.sim_consumer.as_ref().map_or(0, |c| c.pending()),
-                };
+                });
 
                 // Run egui frame only in Combined mode
                 let egui_output = if self.mode == WindowMode::Combined {
                     let timings = gpu.profiler.as_ref().map(|p| &p.history);
-                    Some(ui.run(
-                        window,
-                        timings,
-                        self.sim_stats.as_ref(),
-                        Some(&sim_frame_info),
-                    ))
+                    Some(ui.run(window, timings, self.sim_stats.as_ref()))
                 } else {
                     None
                 };
@@ -205,11 +312,12 @@ impl App {
                 } else {
                     0.0
                 };
-                gpu.composite_params.viewport_offset = [sidebar_width, 0.0];
-                gpu.composite_params.viewport_size = [
-                    gpu.surface_config.width as f32 - sidebar_width,
-                    gpu.surface_config.height as f32,
-                ];
+                gpu.composite_viewport = (sidebar_width > 0.0).then_some(ViewportRect {
+                    x: sidebar_width,
+                    y: 0.0,
+                    width: gpu.surface_config.width as f32 - sidebar_width,
+                    height: gpu.surface_config.height as f32,
+                });
 
                 if let Some(tx) = &self.sim_commands {
                     crate::frame::dispatch_sim_commands(
@@ -222,7 +330,9 @@ impl App {
                     );
                 }
 
-                match gpu.render(&samples, sim_dt, egui_output.as_ref()) {
+                let result = gpu.render(&samples, sim_dt, egui_output.as_ref());
+                gpu.record_cpu_frame_time(frame_start.elapsed());
+                match result {
                     Ok(()) => {}
                     Err(wgpu::SurfaceError::Lost) => {
                         let (w, h) = (gpu.surface_config.width, gpu.surface_config.height);
@@ -236,6 +346,7 @@ impl App {
                         tracing::warn!("Surface error: {e:?}");
                     }
                 }
+                self.handle_detach_request(event_loop);
             }
             _ => {}
         }
@@ -293,6 +404,7 @@ impl App {
                         tracing::warn!("Controls surface error: {e:?}");
                     }
                 }
+                self.handle_detach_request(event_loop);
             }
             _ => {}
         }
@@ -301,7 +413,25 @@ impl App {
 
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let attrs = Window::default_attributes().with_title("Phosphor");
+        let mut attrs = Window::default_attributes().with_title("Phosphor");
+        if let Some(geom) = self.settings.window {
+            let monitors: Vec<MonitorRect> = event_loop
+                .available_monitors()
+                .map(|m| MonitorRect {
+                    x: m.position().x,
+                    y: m.position().y,
+                    width: m.size().width,
+                    height: m.size().height,
+                })
+                .collect();
+            if geom.is_on_screen(&monitors) {
+                attrs = attrs
+                    .with_inner_size(winit::dpi::PhysicalSize::new(geom.width, geom.height))
+                    .with_position(winit::dpi::PhysicalPosition::new(geom.x, geom.y));
+            } else {
+                tracing::info!("Saved window position is off-screen, using default");
+            }
+        }
 
         let window: Arc<Window> = match event_loop.create_window(attrs) {
             Ok(w) => Arc::new(w),
@@ -328,14 +458,29 @@ impl ApplicationHandler for App {
         }
 
         let mut gpu = GpuState::new(window.clone());
-        let ui = UiState::new(&window);
-        gpu.switch_phosphor(ui.selected_phosphor());
+        let mut ui = UiState::new(&window);
+        ui.apply_config(&self.config);
+        ui.engineer.pause_when_unfocused = self.settings.pause_when_unfocused;
+        ui.engineer.timing_palette = self.settings.timing_palette;
+        let window_width = window.inner_size().width as f32 / window.scale_factor() as f32;
+        ui.restore_panel(
+            self.settings.panel_tab,
+            self.settings.panel_width,
+            window_width,
+        );
+        ui.gpu_info = Some(GpuInfo::new(
+            &gpu.adapter_info,
+            gpu.hdr_output,
+            gpu.profiler.is_some(),
+        ));
+        gpu.switch_phosphor(ui.phosphor_index, ui.selected_phosphor());
 
         // Spawn simulation thread
         let buffer_capacity = 65536;
         let (producer, consumer) = crate::beam::sample_channel(buffer_capacity);
         let stats = SimStats::new(buffer_capacity as u32);
-        let (handle, cmd_tx) = crate::simulation::spawn_simulation(producer, stats.clone());
+        let (handle, cmd_tx) =
+            crate::simulation::spawn_simulation(producer, stats.clone(), self.config.sim_thread);
 
         // Send initial viewport dimensions
         let size = window.inner_size();
@@ -371,7 +516,10 @@ impl ApplicationHandler for App {
             && let Some(action) = check_global_shortcut(&event, &ui.ctx)
         {
             match action {
-                GlobalAction::Quit => event_loop.exit(),
+                GlobalAction::Quit => {
+                    self.save_settings();
+                    event_loop.exit();
+                }
                 GlobalAction::ToggleDetach => self.toggle_detach(event_loop),
                 GlobalAction::ToggleFullscreen => {
                     if let Some(window) = &self.window {
@@ -383,6 +531,11 @@ impl ApplicationHandler for App {
                         }
                     }
                 }
+                GlobalAction::ToggleHud => {
+                    if let Some(ui) = &mut self.ui {
+                        ui.hud_visible = !ui.hud_visible;
+                    }
+                }
             }
             return;
         }
@@ -394,6 +547,14 @@ impl ApplicationHandler for App {
             .as_ref()
             .is_some_and(|c| c.window.id() == window_id);
 
+        if let WindowEvent::Focused(focused) = event {
+            if is_viewport {
+                self.focus.viewport = focused;
+            } else if is_controls {
+                self.focus.controls = focused;
+            }
+        }
+
         if is_controls {
             self.handle_controls_event(event_loop, event);
         } else if is_viewport {
@@ -402,6 +563,31 @@ impl ApplicationHandler for App {
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // A closed controls window never reports losing focus.
+        if self.controls.is_none() {
+            self.focus.controls = false;
+        }
+        let pause_when_unfocused = self
+            .ui
+            .as_ref()
+            .is_some_and(|ui| ui.engineer.pause_when_unfocused);
+        if let Some(action) = self.focus.update(pause_when_unfocused) {
+            let paused = action == FocusAction::Pause;
+            if paused {
+                tracing::info!("No window has focus, pausing rendering");
+            } else {
+                tracing::info!("Focus regained, resuming rendering");
+            }
+            if let Some(tx) = &self.sim_commands {
+                let _ = tx.send(SimCommand::SetInputPaused(paused));
+            }
+        }
+        if self.focus.paused {
+            // Sleep until the next window event; a focus gain resumes.
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+
         if let Some(window) = &self.window {
             window.request_redraw();
         }
@@ -420,3 +606,38 @@ impl ApplicationHandler for App {
         event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_only_when_no_window_has_focus() {
+        let mut focus = FocusState::default();
+        assert_eq!(focus.update(true), None);
+
+        // Focus moving to the controls window keeps rendering.
+        focus.viewport = false;
+        focus.controls = true;
+        assert_eq!(focus.update(true), None);
+
+        focus.controls = false;
+        assert_eq!(focus.update(true), Some(FocusAction::Pause));
+        assert_eq!(focus.update(true), None);
+
+        focus.viewport = true;
+        assert_eq!(focus.update(true), Some(FocusAction::Resume));
+    }
+
+    #[test]
+    fn turning_the_option_off_resumes() {
+        let mut focus = FocusState {
+            viewport: false,
+            ..FocusState::default()
+        };
+        assert_eq!(focus.update(false), None);
+        assert_eq!(focus.update(true), Some(FocusAction::Pause));
+        assert_eq!(focus.update(false), Some(FocusAction::Resume));
+        assert!(!focus.paused);
+    }
+}
@@ -14,10 +14,102 @@ pub struct AudioSource {
     samples: Vec<(f32, f32)>,
     sample_rate: u32,
     position: usize,
+    /// Level measure the gain normalizes to, if any.
+    normalize: Option<LevelMode>,
+    /// Applied to both channels in `generate`; 1.0 when not normalizing.
+    gain: f32,
+}
+
+/// How [`AudioSource::analyze_level`] measures a file's level.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum LevelMode {
+    /// Largest absolute sample on either channel.
+    #[default]
+    Peak,
+    /// Root mean square over both channels.
+    #[strum(serialize = "RMS")]
+    Rms,
+}
+
+impl LevelMode {
+    /// Level a normalized file should reach: full scale for peak, and a
+    /// full-scale sine's RMS for RMS.
+    fn target(self) -> f32 {
+        match self {
+            Self::Peak => 1.0,
+            Self::Rms => std::f32::consts::FRAC_1_SQRT_2,
+        }
+    }
+}
+
+/// Upper bound on the normalization gain (40 dB), so a near-silent file
+/// doesn't blow its noise floor up to full deflection.
+const MAX_NORMALIZE_GAIN: f32 = 100.0;
+
+/// How audio with more than two channels is folded into the beam's X/Y pair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum Downmix {
+    /// Use the first two channels and drop the rest.
+    #[default]
+    #[strum(serialize = "First two")]
+    StereoFirst2,
+    /// 5.1 fold-down, channels in FL FR C LFE SL SR order:
+    /// `L = (FL + 0.7·C + 0.7·SL) / 2.4`, `R = (FR + 0.7·C + 0.7·SR) / 2.4`.
+    /// Each row is scaled to unit gain so full-scale input can't exceed
+    /// ±1. LFE is dropped.
+    #[strum(serialize = "5.1 matrix")]
+    Matrix51,
+}
+
+/// Gain of the center and surround channels in [`Downmix::Matrix51`],
+/// relative to the front channel.
+const MIX_51_GAIN: f32 = 0.7;
+
+/// Scale on each [`Downmix::Matrix51`] row, so its coefficients sum to 1.
+const MIX_51_NORM: f32 = 1.0 / (1.0 + 2.0 * MIX_51_GAIN);
+
+impl Downmix {
+    /// The downmix to use by default for a file with `channels` channels.
+    pub fn for_channels(channels: usize) -> Self {
+        if channels == 6 {
+            Self::Matrix51
+        } else {
+            Self::StereoFirst2
+        }
+    }
+}
+
+/// De-interleave `interleaved` audio with `channels` channels into
+/// (left, right) pairs. Mono is copied to both sides; beyond two channels
+/// `mix` decides how the rest are folded in. [`Downmix::Matrix51`] needs at
+/// least six channels and falls back to the first two otherwise.
+pub fn downmix(interleaved: &[f32], channels: usize, mix: Downmix) -> Vec<(f32, f32)> {
+    match (channels, mix) {
+        (0, _) => Vec::new(),
+        (1, _) => interleaved.iter().map(|&s| (s, s)).collect(),
+        (6.., Downmix::Matrix51) => interleaved
+            .chunks_exact(channels)
+            .map(|c| {
+                let (fl, fr, center, sl, sr) = (c[0], c[1], c[2], c[4], c[5]);
+                (
+                    MIX_51_NORM * (fl + MIX_51_GAIN * (center + sl)),
+                    MIX_51_NORM * (fr + MIX_51_GAIN * (center + sr)),
+                )
+            })
+            .collect(),
+        (n, _) => interleaved.chunks_exact(n).map(|c| (c[0], c[1])).collect(),
+    }
 }
 
 impl AudioSource {
+    /// Load a file, downmixing as [`Downmix::for_channels`] suggests.
     pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Self::load_with_downmix(path, None)
+    }
+
+    /// Load a file, folding extra channels with `mix`, or with the default
+    /// for its channel count when `None`.
+    pub fn load_with_downmix(path: &Path, mix: Option<Downmix>) -> anyhow::Result<Self> {
         let file = File::open(path)?;
         let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
@@ -75,21 +167,57 @@ impl AudioSource {
             interleaved.extend_from_slice(sample_buf.samples());
         }
 
-        // De-interleave into (left, right) pairs
-        let samples: Vec<(f32, f32)> = match channels {
-            1 => interleaved.iter().map(|&s| (s, s)).collect(),
-            2 => interleaved.chunks_exact(2).map(|c| (c[0], c[1])).collect(),
-            n => {
-                // Take first two channels, skip the rest
-                interleaved.chunks_exact(n).map(|c| (c[0], c[1])).collect()
-            }
-        };
+        let mix = mix.unwrap_or(Downmix::for_channels(channels));
+        let samples = downmix(&interleaved, channels, mix);
+
+        Ok(Self::from_samples(samples, sample_rate))
+    }
 
-        Ok(Self {
+    fn from_samples(samples: Vec<(f32, f32)>, sample_rate: u32) -> Self {
+        Self {
             samples,
             sample_rate,
             position: 0,
-        })
+            normalize: None,
+            gain: 1.0,
+        }
+    }
+
+    /// The file's level across both channels, measured by `mode`.
+    pub fn analyze_level(&self, mode: LevelMode) -> f32 {
+        let values = self.samples.iter().flat_map(|&(l, r)| [l, r]);
+        match mode {
+            LevelMode::Peak => values.fold(0.0, |peak, s| peak.max(s.abs())),
+            LevelMode::Rms => {
+                if self.samples.is_empty() {
+                    return 0.0;
+                }
+                let sum: f64 = values.map(|s| f64::from(s) * f64::from(s)).sum();
+                (sum / (self.samples.len() * 2) as f64).sqrt() as f32
+            }
+        }
+    }
+
+    /// Scale output so the file's level, measured by `mode`, fills the
+    /// deflection range; `None` plays it as recorded. Samples pushed past
+    /// full scale (possible with RMS) are clipped to the screen edge. Only
+    /// rescans the file when `mode` changes.
+    pub fn set_normalization(&mut self, mode: Option<LevelMode>) {
+        if mode == self.normalize {
+            return;
+        }
+        self.normalize = mode;
+        self.gain = match mode {
+            Some(mode) => {
+                let level = self.analyze_level(mode);
+                if level > 0.0 {
+                    (mode.target() / level).min(MAX_NORMALIZE_GAIN)
+                } else {
+                    1.0
+                }
+            }
+            None => 1.0,
+        };
     }
 
     pub fn sample_rate(&self) -> u32 {
@@ -121,11 +249,20 @@ impl BeamSource for AudioSource {
         let remaining = self.samples.len().saturating_sub(self.position);
         let n = count.min(remaining);
 
+        let gain = self.gain;
+        let deflect = |s: f32| {
+            let s = if gain == 1.0 {
+                s
+            } else {
+                (s * gain).clamp(-1.0, 1.0)
+            };
+            (s + 1.0) / 2.0
+        };
         let result = self.samples[self.position..self.position + n]
             .iter()
             .map(|&(l, r)| BeamSample {
-                x: (l + 1.0) / 2.0,
-                y: (r + 1.0) / 2.0,
+                x: deflect(l),
+                y: deflect(r),
                 intensity: 1.0,
                 dt,
             })
@@ -138,6 +275,8 @@ impl BeamSource for AudioSource {
 
 #[cfg(test)]
 mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
     use super::*;
 
     const TEST_BEAM: BeamState = BeamState { spot_radius: 0.001 };
@@ -169,6 +308,83 @@ mod tests {
         buf
     }
 
+    #[test]
+    fn matrix_51_folds_center_and_surrounds_into_each_side() {
+        // FL FR C LFE SL SR, two frames.
+        let interleaved = [
+            0.1, 0.2, 0.5, 0.9, 0.3, 0.4, //
+            -0.1, 0.0, 0.0, 0.9, 0.0, -0.2,
+        ];
+        let pairs = downmix(&interleaved, 6, Downmix::Matrix51);
+        assert_eq!(pairs.len(), 2);
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+        assert!(close(pairs[0].0, (0.1 + 0.7 * 0.5 + 0.7 * 0.3) / 2.4));
+        assert!(close(pairs[0].1, (0.2 + 0.7 * 0.5 + 0.7 * 0.4) / 2.4));
+        assert!(close(pairs[1].0, -0.1 / 2.4));
+        assert!(close(pairs[1].1, -0.7 * 0.2 / 2.4));
+
+        // Full scale on every channel stays within full scale.
+        let full = [1.0, -1.0, 1.0, 1.0, 1.0, -1.0];
+        let pairs = downmix(&full, 6, Downmix::Matrix51);
+        assert!(close(pairs[0].0, 1.0) && close(pairs[0].1, -1.0 / 2.4));
+        assert!(pairs.iter().all(|&(l, r)| l.abs() <= 1.0 && r.abs() <= 1.0));
+
+        // First-two keeps FL/FR only.
+        let pairs = downmix(&interleaved, 6, Downmix::StereoFirst2);
+        assert_eq!(pairs, vec![(0.1, 0.2), (-0.1, 0.0)]);
+    }
+
+    #[test]
+    fn downmix_default_follows_channel_count() {
+        assert_eq!(Downmix::for_channels(2), Downmix::StereoFirst2);
+        assert_eq!(Downmix::for_channels(6), Downmix::Matrix51);
+        assert_eq!(Downmix::for_channels(4), Downmix::StereoFirst2);
+        // Too few channels for 5.1 falls back to the first two.
+        assert_eq!(
+            downmix(&[0.1, 0.2, 0.3, 0.4], 4, Downmix::Matrix51),
+            vec![(0.1, 0.2)]
+        );
+        assert_eq!(downmix(&[0.5], 1, Downmix::Matrix51), vec![(0.5, 0.5)]);
+    }
+
+    #[test]
+    fn peak_normalization_brings_half_scale_sine_to_full_scale() {
+        let sine = (0..1000)
+            .map(|i| {
+                let s = 0.5 * (i as f32 * 0.05).sin();
+                (s, s)
+            })
+            .collect();
+        let mut src = AudioSource::from_samples(sine, 44100);
+        assert!((src.analyze_level(LevelMode::Peak) - 0.5).abs() < 1e-3);
+        assert!((src.analyze_level(LevelMode::Rms) - 0.5 * FRAC_1_SQRT_2).abs() < 1e-2);
+
+        src.set_normalization(Some(LevelMode::Peak));
+        let beams = src.generate(1000, &TEST_BEAM);
+        let peak = beams
+            .iter()
+            .map(|b| (b.x * 2.0 - 1.0).abs())
+            .fold(0.0, f32::max);
+        assert!(peak > 0.99 && peak <= 1.0, "{peak}");
+
+        // Off again plays the file as recorded.
+        src.set_normalization(None);
+        src.seek(0.0);
+        let beams = src.generate(1000, &TEST_BEAM);
+        let peak = beams
+            .iter()
+            .map(|b| (b.x * 2.0 - 1.0).abs())
+            .fold(0.0, f32::max);
+        assert!((peak - 0.5).abs() < 1e-3, "{peak}");
+    }
+
+    #[test]
+    fn silence_is_not_normalized() {
+        let mut src = AudioSource::from_samples(vec![(0.0, 0.0); 10], 44100);
+        src.set_normalization(Some(LevelMode::Rms));
+        assert_eq!(src.gain, 1.0);
+    }
+
     #[test]
     fn audio_source_maps_lr_to_xy() {
         let test_samples = vec![(0.0, 0.0), (1.0, -1.0), (-1.0, 1.0)];
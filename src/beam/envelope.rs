@@ -0,0 +1,124 @@
+use super::BeamSample;
+
+/// Attack and release times of an [`EnvelopeFollower`], in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnvelopeParams {
+    /// Time constant for the envelope rising toward a louder signal.
+    pub attack: f32,
+    /// Time constant for the envelope falling back after a peak: it drops
+    /// to 1/e of the peak this long after the signal goes quiet.
+    pub release: f32,
+}
+
+impl Default for EnvelopeParams {
+    fn default() -> Self {
+        Self {
+            attack: 0.001,
+            release: 0.2,
+        }
+    }
+}
+
+/// Peak-hold envelope for audio-driven display. Tracks the signal's
+/// magnitude with separate one-pole attack and release, and scales each
+/// sample's intensity by it, so a transient leaves a tail that fades over
+/// the release time instead of vanishing after one frame.
+#[derive(Clone, Debug, Default)]
+pub struct EnvelopeFollower {
+    level: f32,
+}
+
+impl EnvelopeFollower {
+    /// Current envelope level: 0 for silence, 1 for a full-scale peak.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Forget any held peak, e.g. when a new file is loaded.
+    pub fn reset(&mut self) {
+        self.level = 0.0;
+    }
+
+    /// Advance the envelope by `dt` seconds toward `magnitude` and return
+    /// the new level.
+    pub fn process(&mut self, magnitude: f32, dt: f32, params: &EnvelopeParams) -> f32 {
+        let time_constant = if magnitude > self.level {
+            params.attack
+        } else {
+            params.release
+        };
+        // A zero time constant gives exp(-inf) = 0: follow immediately.
+        let keep = (-dt / time_constant).exp();
+        self.level = magnitude + (self.level - magnitude) * keep;
+        self.level
+    }
+
+    /// Scale each sample's intensity by the envelope. The magnitude is the
+    /// larger channel's deflection from screen center, where audio maps
+    /// silence, so a full-scale sample reads as 1.
+    pub fn apply(&mut self, samples: &mut [BeamSample], params: &EnvelopeParams) {
+        for s in samples {
+            let magnitude = ((s.x - 0.5).abs().max((s.y - 0.5).abs()) * 2.0).min(1.0);
+            s.intensity *= self.process(magnitude, s.dt, params);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(x: f32, y: f32, dt: f32) -> BeamSample {
+        BeamSample {
+            x,
+            y,
+            intensity: 1.0,
+            dt,
+        }
+    }
+
+    #[test]
+    fn transient_leaves_a_tail_that_decays_over_the_release() {
+        let params = EnvelopeParams {
+            attack: 0.0,
+            release: 0.1,
+        };
+        let dt = 0.001;
+        // One full-scale transient, then a second of silence at center.
+        let mut samples = vec![sample(1.0, 0.5, dt)];
+        samples.extend((0..1000).map(|_| sample(0.5, 0.5, dt)));
+
+        let mut envelope = EnvelopeFollower::default();
+        envelope.apply(&mut samples, &params);
+
+        assert_eq!(samples[0].intensity, 1.0);
+        assert!(
+            samples
+                .windows(2)
+                .skip(1)
+                .all(|w| w[1].intensity < w[0].intensity)
+        );
+        // 1/e of the peak one release time after it.
+        let at_release = samples[100].intensity;
+        assert!((at_release - (-1.0f32).exp()).abs() < 1e-3, "{at_release}");
+        assert!(samples[1000].intensity < 1e-4);
+    }
+
+    #[test]
+    fn attack_smooths_the_rise() {
+        let params = EnvelopeParams {
+            attack: 0.01,
+            release: 0.1,
+        };
+        let mut envelope = EnvelopeFollower::default();
+        let first = envelope.process(1.0, 0.001, &params);
+        assert!(first > 0.0 && first < 0.2, "{first}");
+        for _ in 0..100 {
+            envelope.process(1.0, 0.001, &params);
+        }
+        assert!(envelope.level() > 0.99);
+
+        envelope.reset();
+        assert_eq!(envelope.level(), 0.0);
+    }
+}
@@ -2,18 +2,44 @@ use nom::IResult;
 use nom::Parser;
 use nom::bytes::complete::tag;
 use nom::character::complete::{char, multispace0, space1};
-use nom::number::complete::float;
+use nom::multi::many0;
+use nom::number::complete::{double, float};
 use nom::sequence::preceded;
 
+use super::vector::subdivide_segment;
 use super::{BeamSample, BeamSource, BeamState};
-
-/// Minimum subdivisions per segment.
-const MIN_SUBDIVISIONS: usize = 2;
+use crate::phosphor::spectral::{SPECTRAL_BAND_CENTERS, SPECTRAL_BANDS};
+
+/// Per-band weights on the phosphor's emission. All ones leaves it as is.
+pub type EmissionWeights = [f32; SPECTRAL_BANDS];
+
+/// Emission weights used until a `C` command sets others.
+pub const PHOSPHOR_EMISSION: EmissionWeights = [1.0; SPECTRAL_BANDS];
+
+/// Spread an RGB color over the bands: blue below 490 nm, green up to
+/// 580 nm, red above. Coarse, but enough to tint a trace.
+pub fn rgb_emission(r: f32, g: f32, b: f32) -> EmissionWeights {
+    SPECTRAL_BAND_CENTERS.map(|nm| match nm {
+        nm if nm < 490.0 => b,
+        nm if nm < 580.0 => g,
+        _ => r,
+    })
+}
 
 /// A parsed command from the external protocol.
 pub enum Command {
     /// A single beam sample: `B x y intensity dt`
     Beam(BeamSample),
+    /// A beam sample at an absolute time on the timeline, in seconds:
+    /// `BT x y intensity t`. Its dt is the time since the previous sample.
+    TimedBeam {
+        x: f32,
+        y: f32,
+        intensity: f32,
+        t: f64,
+    },
+    /// Set the timeline position in seconds: `T seconds`
+    Time(f64),
     /// A line segment: `L x0 y0 x1 y1 intensity`
     Segment {
         x0: f32,
@@ -22,6 +48,9 @@ pub enum Command {
         y1: f32,
         intensity: f32,
     },
+    /// Emission color for subsequent samples: `C r g b`, or one weight
+    /// per spectral band. A bare `C` goes back to the phosphor's emission.
+    Color(EmissionWeights),
     /// Frame sync: `F`
     FrameSync,
 }
@@ -44,6 +73,21 @@ fn parse_beam(input: &str) -> IResult<&str, Command> {
     ))
 }
 
+fn sp_double(input: &str) -> IResult<&str, f64> {
+    preceded(space1, double).parse(input)
+}
+
+fn parse_timed_beam(input: &str) -> IResult<&str, Command> {
+    let (rest, (_, x, y, intensity, t)) =
+        (tag("BT"), sp_float, sp_float, sp_float, sp_double).parse(input)?;
+    Ok((rest, Command::TimedBeam { x, y, intensity, t }))
+}
+
+fn parse_time(input: &str) -> IResult<&str, Command> {
+    let (rest, (_, t)) = (char('T'), sp_double).parse(input)?;
+    Ok((rest, Command::Time(t)))
+}
+
 fn parse_segment(input: &str) -> IResult<&str, Command> {
     let (rest, (_, x0, y0, x1, y1, intensity)) =
         (char('L'), sp_float, sp_float, sp_float, sp_float, sp_float).parse(input)?;
@@ -59,6 +103,24 @@ fn parse_segment(input: &str) -> IResult<&str, Command> {
     ))
 }
 
+fn parse_color(input: &str) -> IResult<&str, Vec<f32>> {
+    preceded(char('C'), many0(sp_float)).parse(input)
+}
+
+fn color_command(line: &str, values: &[f32]) -> anyhow::Result<Command> {
+    let weights = match *values {
+        [] => PHOSPHOR_EMISSION,
+        [r, g, b] => rgb_emission(r, g, b),
+        _ => values.try_into().map_err(|_| {
+            anyhow::anyhow!(
+                "color needs 3 (RGB) or {SPECTRAL_BANDS} (spectral) values, got {}: {line}",
+                values.len()
+            )
+        })?,
+    };
+    Ok(Command::Color(weights))
+}
+
 fn parse_frame_sync(input: &str) -> IResult<&str, Command> {
     let (rest, _) = tag("F").parse(input)?;
     Ok((rest, Command::FrameSync))
@@ -68,7 +130,10 @@ fn parse_frame_sync(input: &str) -> IResult<&str, Command> {
 ///
 /// Protocol:
 /// - `B x y intensity dt` — a single beam sample
+/// - `BT x y intensity t` — a beam sample at timeline time `t` seconds
+/// - `T seconds` — move the timeline to an absolute time
 /// - `L x0 y0 x1 y1 intensity` — a line segment
+/// - `C r g b` or `C w0 .. w15` — emission color for subsequent samples
 /// - `F` — frame sync
 /// - `#...` — comment (returns None)
 /// - empty/whitespace — ignored (returns None)
@@ -84,12 +149,21 @@ pub fn parse_line(line: &str) -> anyhow::Result<Option<Command>> {
         .map(|(rest, _)| rest)
         .unwrap_or(trimmed);
 
+    if let Ok((_, cmd)) = parse_timed_beam(input) {
+        return Ok(Some(cmd));
+    }
     if let Ok((_, cmd)) = parse_beam(input) {
         return Ok(Some(cmd));
     }
+    if let Ok((_, cmd)) = parse_time(input) {
+        return Ok(Some(cmd));
+    }
     if let Ok((_, cmd)) = parse_segment(input) {
         return Ok(Some(cmd));
     }
+    if let Ok((_, values)) = parse_color(input) {
+        return color_command(input, &values).map(Some);
+    }
     if let Ok((_, cmd)) = parse_frame_sync(input) {
         return Ok(Some(cmd));
     }
@@ -97,39 +171,27 @@ pub fn parse_line(line: &str) -> anyhow::Result<Option<Command>> {
     anyhow::bail!("unknown command: {trimmed}");
 }
 
-/// Subdivide a segment into beam samples, spaced within one spot radius.
-fn subdivide_segment(
-    x0: f32,
-    y0: f32,
-    x1: f32,
-    y1: f32,
-    intensity: f32,
-    beam_speed: f32,
-    beam: &BeamState,
-) -> Vec<BeamSample> {
-    let dx = x1 - x0;
-    let dy = y1 - y0;
-    let length = (dx * dx + dy * dy).sqrt();
-    let steps = ((length / beam.spot_radius).ceil() as usize).max(MIN_SUBDIVISIONS);
-    let dt = length / (beam_speed * steps as f32);
-
-    (0..steps)
-        .map(|i| {
-            let t = (i as f32 + 0.5) / steps as f32;
-            BeamSample {
-                x: x0 + dx * t,
-                y: y0 + dy * t,
-                intensity,
-                dt,
-            }
-        })
-        .collect()
-}
-
 pub struct ExternalSource {
     pub beam_speed: f32,
     lines: Vec<String>,
     position: usize,
+    /// Timeline position in seconds: set by `T`, advanced by every sample.
+    /// `BT` samples are spaced against it.
+    time: f64,
+    /// Emission set by the last `C` command.
+    emission: EmissionWeights,
+    /// Emission of each sample from the last `generate`, parallel to its
+    /// output. Not yet used by the beam write, which takes the phosphor's
+    /// emission for every sample.
+    sample_emissions: Vec<EmissionWeights>,
+    /// Lines that failed to parse, or were dropped as out of order, so far.
+    parse_errors: usize,
+    /// The most recent parse error, prefixed with its line number.
+    last_error: Option<String>,
+    /// Samples past the last batch's `count`, returned first next time,
+    /// with their emissions.
+    pending: Vec<BeamSample>,
+    pending_emissions: Vec<EmissionWeights>,
 }
 
 impl ExternalSource {
@@ -138,25 +200,85 @@ impl ExternalSource {
             beam_speed,
             lines: Vec::new(),
             position: 0,
+            time: 0.0,
+            emission: PHOSPHOR_EMISSION,
+            sample_emissions: Vec::new(),
+            parse_errors: 0,
+            last_error: None,
+            pending: Vec::new(),
+            pending_emissions: Vec::new(),
         }
     }
 
+    /// Number of lines that failed to parse so far, counting `BT` samples
+    /// dropped for being stamped before the timeline.
+    pub fn parse_errors(&self) -> usize {
+        self.parse_errors
+    }
+
+    /// The most recent parse error, e.g. `line 3: unknown command: X`.
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Emission of each sample from the last batch, in sample order.
+    pub fn sample_emissions(&self) -> &[EmissionWeights] {
+        &self.sample_emissions
+    }
+
+    /// Current timeline position in seconds.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
     /// Feed lines from the external protocol into the source.
     pub fn push_lines(&mut self, lines: impl IntoIterator<Item = String>) {
         self.lines.extend(lines);
     }
 }
 
+/// Returns at most `count` samples, stopping early at a frame sync, so a
+/// large burst of input is spread over several batches instead of
+/// overflowing the sample ring buffer. Lines not yet reached wait in
+/// `lines`; samples of a segment that straddles the limit wait in `pending`.
 impl BeamSource for ExternalSource {
-    fn generate(&mut self, _count: usize, beam: &BeamState) -> Vec<BeamSample> {
-        let mut out = Vec::new();
-
-        while self.position < self.lines.len() {
+    fn generate(&mut self, count: usize, beam: &BeamState) -> Vec<BeamSample> {
+        let carried = self.pending.len().min(count);
+        let mut out: Vec<BeamSample> = self.pending.drain(..carried).collect();
+        self.sample_emissions.clear();
+        self.sample_emissions
+            .extend(self.pending_emissions.drain(..carried));
+
+        while out.len() < count && self.position < self.lines.len() {
             let line = &self.lines[self.position];
             self.position += 1;
 
             match parse_line(line) {
-                Ok(Some(Command::Beam(sample))) => out.push(sample),
+                Ok(Some(Command::Beam(sample))) => {
+                    self.time += f64::from(sample.dt);
+                    out.push(sample);
+                }
+                Ok(Some(Command::TimedBeam { x, y, intensity, t })) => {
+                    // Samples stamped before the timeline are out of order
+                    // and dropped; `T` can rewind it for a replay.
+                    if t >= self.time {
+                        out.push(BeamSample {
+                            x,
+                            y,
+                            intensity,
+                            dt: (t - self.time) as f32,
+                        });
+                        self.time = t;
+                    } else {
+                        self.parse_errors += 1;
+                        self.last_error = Some(format!(
+                            "line {}: BT at {t} s is before the timeline at {} s",
+                            self.position, self.time
+                        ));
+                    }
+                }
+                Ok(Some(Command::Time(t))) => self.time = t,
+                Ok(Some(Command::Color(weights))) => self.emission = weights,
                 Ok(Some(Command::Segment {
                     x0,
                     y0,
@@ -164,21 +286,28 @@ impl BeamSource for ExternalSource {
                     y1,
                     intensity,
                 })) => {
-                    out.extend(subdivide_segment(
-                        x0,
-                        y0,
-                        x1,
-                        y1,
-                        intensity,
-                        self.beam_speed,
-                        beam,
-                    ));
+                    let samples =
+                        subdivide_segment(x0, y0, x1, y1, intensity, self.beam_speed, beam);
+                    self.time += samples.iter().map(|s| f64::from(s.dt)).sum::<f64>();
+                    out.extend(samples);
                 }
                 Ok(Some(Command::FrameSync)) => break,
-                Ok(None) | Err(_) => {}
+                Ok(None) => {}
+                Err(e) => {
+                    // Skip the line but keep a note of it, so a script that
+                    // draws nothing says why.
+                    self.parse_errors += 1;
+                    self.last_error = Some(format!("line {}: {e}", self.position));
+                }
             }
+            // Every sample from this line shares the current emission.
+            self.sample_emissions.resize(out.len(), self.emission);
         }
 
+        if out.len() > count {
+            self.pending = out.split_off(count);
+            self.pending_emissions = self.sample_emissions.split_off(count);
+        }
         out
     }
 }
@@ -203,6 +332,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_time_and_timed_beam() {
+        let cmd = parse_line("T 12.5").unwrap().unwrap();
+        assert!(matches!(cmd, Command::Time(t) if t == 12.5));
+
+        let cmd = parse_line("BT 0.5 0.25 1.0 12.501").unwrap().unwrap();
+        match cmd {
+            Command::TimedBeam { x, y, intensity, t } => {
+                assert_eq!((x, y, intensity), (0.5, 0.25, 1.0));
+                assert_eq!(t, 12.501);
+            }
+            _ => panic!("expected TimedBeam command"),
+        }
+        assert!(parse_line("T").is_err());
+        assert!(parse_line("BT 0.5 0.5 1.0").is_err());
+    }
+
+    #[test]
+    fn timed_beams_are_spaced_by_timestamp_in_order() {
+        let mut src = ExternalSource::new(1.0);
+        src.push_lines(vec![
+            "T 10.0".into(),
+            "BT 0.1 0.1 1.0 10.002".into(),
+            "BT 0.2 0.2 1.0 10.005".into(),
+            // Out of order: dropped and reported.
+            "BT 0.3 0.3 1.0 10.004".into(),
+            // dt-based samples still advance the timeline.
+            "B 0.4 0.4 1.0 0.001".into(),
+            "BT 0.5 0.5 1.0 10.010".into(),
+        ]);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
+        let xs: Vec<f32> = samples.iter().map(|s| s.x).collect();
+        assert_eq!(xs, [0.1, 0.2, 0.4, 0.5]);
+        let dts: Vec<f32> = samples.iter().map(|s| s.dt).collect();
+        for (dt, expected) in dts.iter().zip([0.002, 0.003, 0.001, 0.004]) {
+            assert!((dt - expected).abs() < 1e-6, "{dts:?}");
+        }
+        assert!((src.time() - 10.010).abs() < 1e-9);
+        assert_eq!(src.parse_errors(), 1);
+        assert_eq!(
+            src.last_error(),
+            Some("line 4: BT at 10.004 s is before the timeline at 10.005 s")
+        );
+
+        // T can rewind the timeline for a replay.
+        src.push_lines(vec!["T 0.0".into(), "BT 0.6 0.6 1.0 0.5".into()]);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
+        assert_eq!(samples.len(), 1);
+        assert!((samples[0].dt - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn parse_color_command() {
+        let Some(Command::Color(weights)) = parse_line("C 1.0 0.5 0.0").unwrap() else {
+            panic!("expected Color command");
+        };
+        assert_eq!(weights, rgb_emission(1.0, 0.5, 0.0));
+        assert_eq!(weights[0], 0.0);
+        assert_eq!(weights[SPECTRAL_BANDS - 1], 1.0);
+
+        let bands: Vec<String> = (0..SPECTRAL_BANDS).map(|i| format!("{i}")).collect();
+        let Some(Command::Color(weights)) = parse_line(&format!("C {}", bands.join(" "))).unwrap()
+        else {
+            panic!("expected Color command");
+        };
+        assert_eq!(weights[3], 3.0);
+
+        assert!(matches!(
+            parse_line("C").unwrap(),
+            Some(Command::Color(PHOSPHOR_EMISSION))
+        ));
+        assert!(parse_line("C 1.0 0.5").is_err());
+    }
+
+    #[test]
+    fn color_applies_to_following_samples() {
+        let mut src = ExternalSource::new(1.0);
+        src.push_lines(vec![
+            "B 0.1 0.1 1.0 0.001".into(),
+            "C 0.0 1.0 0.0".into(),
+            "L 0.2 0.2 0.8 0.2 1.0".into(),
+        ]);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
+        let emissions = src.sample_emissions();
+        assert_eq!(emissions.len(), samples.len());
+        assert_eq!(emissions[0], PHOSPHOR_EMISSION);
+        assert!(
+            emissions[1..]
+                .iter()
+                .all(|e| *e == rgb_emission(0.0, 1.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn bad_lines_are_counted_and_reported() {
+        let mut src = ExternalSource::new(1.0);
+        src.push_lines(vec![
+            "B 0.1 0.1 1.0 0.001".into(),
+            "X garbage".into(),
+            "B 0.2 0.2 1.0 0.001".into(),
+        ]);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(src.parse_errors(), 1);
+        assert_eq!(src.last_error(), Some("line 2: unknown command: X garbage"));
+
+        src.push_lines(vec!["C 1.0".into()]);
+        src.generate(usize::MAX, &TEST_BEAM);
+        assert_eq!(src.parse_errors(), 2);
+        assert!(src.last_error().unwrap().starts_with("line 4: color needs"));
+    }
+
     #[test]
     fn parse_comment_returns_none() {
         assert!(parse_line("# this is a comment").unwrap().is_none());
@@ -239,7 +480,7 @@ mod tests {
             "B 0.5 0.75 1.0 0.001".into(),
             "B 0.25 0.25 0.5 0.002".into(),
         ]);
-        let samples = src.generate(0, &TEST_BEAM);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
         assert_eq!(samples.len(), 2);
         assert!((samples[0].x - 0.5).abs() < f32::EPSILON);
         assert!((samples[1].x - 0.25).abs() < f32::EPSILON);
@@ -249,13 +490,52 @@ mod tests {
     fn generate_subdivides_segments() {
         let mut src = ExternalSource::new(1.0);
         src.push_lines(vec!["L 0.0 0.0 1.0 0.0 1.0".into()]);
-        let samples = src.generate(0, &TEST_BEAM);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
         assert!(!samples.is_empty());
         for s in &samples {
             assert!((s.y).abs() < 0.01);
         }
     }
 
+    #[test]
+    fn generate_respects_count_and_resumes() {
+        let mut src = ExternalSource::new(1.0);
+        src.push_lines((0..10_000).map(|i| format!("B {} 0.5 1.0 0.001", i as f32 / 1e4)));
+
+        let samples = src.generate(1000, &TEST_BEAM);
+        assert_eq!(samples.len(), 1000);
+        assert_eq!(src.position, 1000);
+        assert_eq!(samples[999].x, 999.0 / 1e4);
+
+        let mut total = samples.len();
+        for _ in 0..9 {
+            let samples = src.generate(1000, &TEST_BEAM);
+            assert_eq!(samples.len(), 1000);
+            total += samples.len();
+        }
+        assert_eq!(total, 10_000);
+        assert!(src.generate(1000, &TEST_BEAM).is_empty());
+    }
+
+    #[test]
+    fn segment_past_the_limit_carries_over() {
+        let line = || vec!["L 0.0 0.0 1.0 0.0 1.0".to_string()];
+        let mut reference = ExternalSource::new(1.0);
+        reference.push_lines(line());
+        let all = reference.generate(usize::MAX, &TEST_BEAM);
+        assert!(all.len() > 4);
+
+        let mut src = ExternalSource::new(1.0);
+        src.push_lines(line());
+
+        let first = src.generate(3, &TEST_BEAM);
+        assert_eq!(first.len(), 3);
+        assert_eq!(src.sample_emissions().len(), 3);
+        let rest = src.generate(usize::MAX, &TEST_BEAM);
+        assert_eq!(first.len() + rest.len(), all.len());
+        assert_eq!(rest[0].x, all[3].x);
+    }
+
     #[test]
     fn generate_stops_at_frame_sync() {
         let mut src = ExternalSource::new(1.0);
@@ -264,11 +544,11 @@ mod tests {
             "F".into(),
             "B 0.9 0.9 1.0 0.001".into(),
         ]);
-        let first_frame = src.generate(0, &TEST_BEAM);
+        let first_frame = src.generate(usize::MAX, &TEST_BEAM);
         assert_eq!(first_frame.len(), 1);
         assert!((first_frame[0].x - 0.1).abs() < f32::EPSILON);
 
-        let second_frame = src.generate(0, &TEST_BEAM);
+        let second_frame = src.generate(usize::MAX, &TEST_BEAM);
         assert_eq!(second_frame.len(), 1);
         assert!((second_frame[0].x - 0.9).abs() < f32::EPSILON);
     }
@@ -282,7 +562,7 @@ mod tests {
             "B 0.5 0.5 1.0 0.001".into(),
             "   ".into(),
         ]);
-        let samples = src.generate(0, &TEST_BEAM);
+        let samples = src.generate(usize::MAX, &TEST_BEAM);
         assert_eq!(samples.len(), 1);
     }
 }
@@ -1,7 +1,9 @@
 pub mod audio;
+pub mod envelope;
 pub mod external;
 pub mod oscilloscope;
 pub mod resample;
+pub mod spectrum;
 pub mod vector;
 
 /// Current beam physics parameters, shared with input sources that need
@@ -33,6 +35,13 @@ pub struct BeamSample {
     pub dt: f32,
 }
 
+/// Total energy `samples` deposit: the sum of `intensity * dt`. Intensities
+/// leaving the simulation already include the beam energy scale, so this
+/// is what `beam_write` adds to the accumulation buffer.
+pub fn deposited_energy(samples: &[BeamSample]) -> f32 {
+    samples.iter().map(|s| s.intensity * s.dt).sum()
+}
+
 /// Producer half of the sample channel. Lives on the input thread.
 pub struct SampleProducer {
     inner: rtrb::Producer<BeamSample>,
@@ -93,6 +102,32 @@ impl SampleConsumer {
         self.drain_up_to(usize::MAX)
     }
 
+    /// Drain pending samples covering up to `max_dt` seconds of beam time,
+    /// by their summed `dt`. Always takes at least one sample if any are
+    /// pending, so a single long dwell can't stall the buffer.
+    pub fn drain_for(&mut self, max_dt: f32) -> Vec<BeamSample> {
+        let available = self.inner.slots();
+        if available == 0 {
+            return Vec::new();
+        }
+        let chunk = self.inner.read_chunk(available).unwrap();
+        let (first, second) = chunk.as_slices();
+        let mut elapsed = 0.0;
+        let count = first
+            .iter()
+            .chain(second)
+            .take_while(|s| {
+                elapsed += s.dt;
+                elapsed <= max_dt
+            })
+            .count()
+            .max(1);
+        let mut samples = Vec::with_capacity(count);
+        samples.extend(first.iter().chain(second).take(count));
+        chunk.commit(count);
+        samples
+    }
+
     /// Drain up to `max` pending samples using zero-copy read_chunk.
     /// Any samples beyond `max` remain in the buffer for the next call.
     pub fn drain_up_to(&mut self, max: usize) -> Vec<BeamSample> {
@@ -127,6 +162,19 @@ mod tests {
         assert_eq!(bytes.len(), 16); // 4 x f32
     }
 
+    #[test]
+    fn deposited_energy_sums_intensity_times_dt() {
+        let sample = |intensity, dt| BeamSample {
+            x: 0.5,
+            y: 0.5,
+            intensity,
+            dt,
+        };
+        assert_eq!(deposited_energy(&[]), 0.0);
+        let samples = [sample(2.0, 0.5), sample(0.0, 1.0), sample(4.0, 0.25)];
+        assert_eq!(deposited_energy(&samples), 2.0);
+    }
+
     #[test]
     fn channel_push_and_drain() {
         let (mut tx, mut rx) = sample_channel(64);
@@ -254,4 +302,33 @@ mod tests {
         let third = rx.drain_up_to(10);
         assert_eq!(third.len(), 0);
     }
+
+    #[test]
+    fn drain_for_caps_by_beam_time() {
+        let (mut tx, mut rx) = sample_channel(128);
+        // Oversampled: 0.25 ms per sample, so 40 samples make 10 ms.
+        let samples: Vec<BeamSample> = (0..100)
+            .map(|i| BeamSample {
+                x: i as f32 * 0.01,
+                y: 0.5,
+                intensity: 1.0,
+                dt: 0.00025,
+            })
+            .collect();
+        tx.push_bulk(&samples);
+
+        let first = rx.drain_for(0.01);
+        assert_eq!(first.len(), 40);
+        let drained_dt: f32 = first.iter().map(|s| s.dt).sum();
+        assert!((drained_dt - 0.01).abs() < 1e-6, "{drained_dt}");
+        assert_eq!(rx.drain_for(1.0).len(), 60);
+        assert!(rx.drain_for(1.0).is_empty());
+
+        // A single sample longer than the cap still drains.
+        tx.push(BeamSample {
+            dt: 1.0,
+            ..samples[0]
+        });
+        assert_eq!(rx.drain_for(0.01).len(), 1);
+    }
 }
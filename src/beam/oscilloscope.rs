@@ -1,3 +1,5 @@
+use std::f32::consts::TAU;
+
 use super::{BeamSample, BeamSource, BeamState};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
@@ -9,6 +11,37 @@ pub enum Waveform {
     Sawtooth,
 }
 
+/// How the oscilloscope source deflects the beam.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum OscilloscopeMode {
+    /// X and Y channels drive the two axes directly (Lissajous figures).
+    #[default]
+    XY,
+    /// A sawtooth sweep on X with two Y channels chopped onto the beam, so
+    /// both traces appear against a common time base.
+    #[strum(serialize = "Dual trace")]
+    DualTrace,
+}
+
+/// Edge direction that fires the dual-trace sweep trigger.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum TriggerSlope {
+    #[default]
+    Rising,
+    Falling,
+}
+
+/// Signal the dual-trace sweep trigger watches.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum TriggerSource {
+    /// No trigger: the sweep repeats at the X frequency.
+    #[default]
+    #[strum(serialize = "Free run")]
+    FreeRun,
+    Y,
+    Y2,
+}
+
 #[derive(Clone)]
 pub struct ChannelConfig {
     pub waveform: Waveform,
@@ -36,7 +69,7 @@ fn eval_waveform(waveform: &Waveform, p: f32) -> f32 {
         Waveform::Sine => p.sin(),
         Waveform::Triangle => {
             // Normalize phase to [0, 2pi)
-            let t = p.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+            let t = p.rem_euclid(TAU) / TAU;
             if t < 0.25 {
                 4.0 * t
             } else if t < 0.75 {
@@ -53,33 +86,114 @@ fn eval_waveform(waveform: &Waveform, p: f32) -> f32 {
             }
         }
         Waveform::Sawtooth => {
-            let t = p.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+            let t = p.rem_euclid(TAU) / TAU;
             2.0 * t - 1.0
         }
     }
 }
 
-/// Evaluate a channel config at time `t`, returning a screen coordinate in [0, 1].
-fn eval_channel(config: &ChannelConfig, t: f32) -> f32 {
-    let phase = std::f32::consts::TAU * config.frequency * t + config.phase;
-    let deflection = config.amplitude * eval_waveform(&config.waveform, phase) + config.dc_offset;
-    (0.5 + deflection).clamp(0.0, 1.0)
+/// Evaluate a channel config at accumulated phase `phase_accum` (radians,
+/// before the channel's own phase offset), returning a screen coordinate.
+/// With `clamp` the coordinate is pinned to [0, 1]; without it, large
+/// deflections leave the screen and `beam_write` deposits nothing there.
+fn eval_channel(config: &ChannelConfig, phase_accum: f32, clamp: bool) -> f32 {
+    let coord = 0.5 + eval_deflection(config, phase_accum);
+    if clamp { coord.clamp(0.0, 1.0) } else { coord }
+}
+
+/// A channel's deflection from screen center, before clamping.
+fn eval_deflection(config: &ChannelConfig, phase_accum: f32) -> f32 {
+    let phase = phase_accum + config.phase;
+    config.amplitude * eval_waveform(&config.waveform, phase) + config.dc_offset
 }
 
 pub struct OscilloscopeSource {
+    pub mode: OscilloscopeMode,
+    /// In dual-trace mode only this channel's frequency, amplitude, and
+    /// offset are used; the waveform is always a sawtooth sweep starting at
+    /// the left edge.
     pub x_channel: ChannelConfig,
     pub y_channel: ChannelConfig,
+    /// Second Y channel, drawn only in dual-trace mode.
+    pub y2_channel: ChannelConfig,
+    /// Dual-trace chop rate: the beam switches between the Y channels twice
+    /// per period.
+    pub chop_freq: f32,
+    /// Dual-trace trigger. When a Y channel is selected, each sweep runs
+    /// once and then waits, blanked at the left edge, until the source
+    /// crosses `trigger_level` (a deflection from center) on `trigger_slope`.
+    pub trigger_source: TriggerSource,
+    pub trigger_slope: TriggerSlope,
+    pub trigger_level: f32,
     pub sample_rate: f32,
-    t_current: f32,
+    /// Pin coordinates to the screen (the default). When off, the trace can
+    /// run past the edges like a real scope's overdriven deflection.
+    pub clamp_to_screen: bool,
+    /// Blanking gate: a square wave at `blank_freq` Hz that is on for
+    /// `blank_duty` of each period and zeroes the intensity otherwise, for
+    /// chopped displays. A duty of 1.0 never blanks.
+    pub blank_freq: f32,
+    pub blank_duty: f32,
+    /// Per-channel phase in radians, kept in [0, 2π). Accumulating phase
+    /// rather than time keeps the trace continuous when a frequency changes
+    /// between batches.
+    phase_accum_x: f32,
+    phase_accum_y: f32,
+    phase_accum_y2: f32,
+    /// Position in the blanking and chop periods, in cycles in [0, 1).
+    blank_phase: f32,
+    chop_phase: f32,
+    /// Trigger source value at the previous sample, carried across batches
+    /// so edges between two `generate` calls still fire.
+    last_trigger_value: Option<f32>,
+    /// The sweep has finished and is waiting for a trigger edge.
+    sweep_armed: bool,
+    /// Dual trace: the previous sample was drawn from Y2.
+    on_y2: bool,
+    /// Dual trace: the free-running sweep wrapped back to the left edge
+    /// after the previous sample.
+    flyback: bool,
 }
 
 impl OscilloscopeSource {
     pub fn new(x_channel: ChannelConfig, y_channel: ChannelConfig, sample_rate: f32) -> Self {
         Self {
+            mode: OscilloscopeMode::XY,
             x_channel,
             y_channel,
+            y2_channel: ChannelConfig::default(),
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
             sample_rate,
-            t_current: 0.0,
+            clamp_to_screen: true,
+            phase_accum_x: 0.0,
+            phase_accum_y: 0.0,
+            phase_accum_y2: 0.0,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            blank_phase: 0.0,
+            chop_phase: 0.0,
+            last_trigger_value: None,
+            sweep_armed: true,
+            on_y2: false,
+            flyback: false,
+        }
+    }
+}
+
+impl OscilloscopeSource {
+    /// Whether moving from the previous trigger value to `value` crosses the
+    /// trigger level in the selected direction.
+    fn is_trigger_edge(&self, value: f32) -> bool {
+        let Some(prev) = self.last_trigger_value else {
+            return false;
+        };
+        let level = self.trigger_level;
+        match self.trigger_slope {
+            TriggerSlope::Rising => prev < level && value >= level,
+            TriggerSlope::Falling => prev > level && value <= level,
         }
     }
 }
@@ -87,18 +201,76 @@ impl OscilloscopeSource {
 impl BeamSource for OscilloscopeSource {
     fn generate(&mut self, count: usize, _beam: &BeamState) -> Vec<BeamSample> {
         let dt = 1.0 / self.sample_rate;
-        let samples = (0..count)
-            .map(|i| {
-                let t = self.t_current + i as f32 * dt;
-                BeamSample {
-                    x: eval_channel(&self.x_channel, t),
-                    y: eval_channel(&self.y_channel, t),
-                    intensity: 1.0,
-                    dt,
+        let step_x = TAU * self.x_channel.frequency * dt;
+        let step_y = TAU * self.y_channel.frequency * dt;
+        let step_y2 = TAU * self.y2_channel.frequency * dt;
+        let blank_step = self.blank_freq * dt;
+        let chop_step = self.chop_freq * dt;
+        let dual_trace = self.mode == OscilloscopeMode::DualTrace;
+        let triggered = dual_trace && self.trigger_source != TriggerSource::FreeRun;
+        let x_channel = if dual_trace {
+            ChannelConfig {
+                waveform: Waveform::Sawtooth,
+                phase: 0.0,
+                ..self.x_channel.clone()
+            }
+        } else {
+            self.x_channel.clone()
+        };
+
+        let mut samples = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut lit = self.blank_phase < self.blank_duty;
+            if triggered {
+                let value = match self.trigger_source {
+                    TriggerSource::Y2 => eval_deflection(&self.y2_channel, self.phase_accum_y2),
+                    _ => eval_deflection(&self.y_channel, self.phase_accum_y),
+                };
+                if self.sweep_armed && self.is_trigger_edge(value) {
+                    self.sweep_armed = false;
+                    self.phase_accum_x = 0.0;
                 }
-            })
-            .collect();
-        self.t_current += count as f32 * dt;
+                self.last_trigger_value = Some(value);
+                lit &= !self.sweep_armed;
+            }
+
+            // The first sample after a chop switch or a flyback is blanked,
+            // so the jump isn't drawn as a lit segment.
+            let on_y2 = dual_trace && self.chop_phase >= 0.5;
+            if dual_trace && (on_y2 != self.on_y2 || self.flyback) {
+                lit = false;
+            }
+            self.on_y2 = on_y2;
+            self.flyback = false;
+
+            let y = if on_y2 {
+                eval_channel(&self.y2_channel, self.phase_accum_y2, self.clamp_to_screen)
+            } else {
+                eval_channel(&self.y_channel, self.phase_accum_y, self.clamp_to_screen)
+            };
+            samples.push(BeamSample {
+                x: eval_channel(&x_channel, self.phase_accum_x, self.clamp_to_screen),
+                y,
+                intensity: if lit { 1.0 } else { 0.0 },
+                dt,
+            });
+
+            if !(triggered && self.sweep_armed) {
+                let next_x = self.phase_accum_x + step_x;
+                if triggered && next_x >= TAU {
+                    // Sweep finished: hold at the left edge until the next edge.
+                    self.sweep_armed = true;
+                    self.phase_accum_x = 0.0;
+                } else {
+                    self.flyback = dual_trace && next_x >= TAU;
+                    self.phase_accum_x = next_x.rem_euclid(TAU);
+                }
+            }
+            self.phase_accum_y = (self.phase_accum_y + step_y).rem_euclid(TAU);
+            self.phase_accum_y2 = (self.phase_accum_y2 + step_y2).rem_euclid(TAU);
+            self.blank_phase = (self.blank_phase + blank_step).rem_euclid(1.0);
+            self.chop_phase = (self.chop_phase + chop_step).rem_euclid(1.0);
+        }
         samples
     }
 }
@@ -219,4 +391,140 @@ mod tests {
             assert!((s.dt - 1.0 / 44100.0).abs() < 1e-9);
         }
     }
+
+    #[test]
+    fn unclamped_dc_offset_runs_off_screen() {
+        let channel = ChannelConfig {
+            waveform: Waveform::Sine,
+            frequency: 100.0,
+            amplitude: 0.25,
+            phase: 0.0,
+            dc_offset: 1.0,
+        };
+        let mut src = OscilloscopeSource::new(channel.clone(), channel, 44100.0);
+
+        let samples = src.generate(1000, &TEST_BEAM);
+        assert!(samples.iter().all(|s| s.x <= 1.0 && s.y <= 1.0));
+
+        src.clamp_to_screen = false;
+        let samples = src.generate(1000, &TEST_BEAM);
+        assert!(samples.iter().all(|s| s.x > 1.0 && s.y > 1.0));
+    }
+
+    #[test]
+    fn frequency_change_keeps_phase_continuous() {
+        let channel = ChannelConfig {
+            waveform: Waveform::Sine,
+            frequency: 100.0,
+            amplitude: 0.4,
+            phase: 0.0,
+            dc_offset: 0.0,
+        };
+        let sample_rate = 44100.0;
+        let mut src = OscilloscopeSource::new(channel.clone(), channel, sample_rate);
+
+        let first = src.generate(1234, &TEST_BEAM);
+        src.x_channel.frequency = 157.0;
+        let second = src.generate(100, &TEST_BEAM);
+
+        // A sine's largest per-sample change is amplitude * 2π * f * dt.
+        let max_step = 0.4 * TAU * 157.0 / sample_rate;
+        let jump = (second[0].x - first.last().unwrap().x).abs();
+        assert!(jump <= max_step + 1e-4, "jump {jump} > step {max_step}");
+    }
+
+    #[test]
+    fn half_duty_blanks_half_the_samples() {
+        let mut src =
+            OscilloscopeSource::new(ChannelConfig::default(), ChannelConfig::default(), 44100.0);
+        src.blank_freq = 441.0;
+        src.blank_duty = 0.5;
+
+        // One blanking period is 100 samples.
+        let samples = src.generate(100, &TEST_BEAM);
+        let blanked = samples.iter().filter(|s| s.intensity == 0.0).count();
+        assert!((48..=52).contains(&blanked), "{blanked} of 100 blanked");
+        assert!(samples[..40].iter().all(|s| s.intensity == 1.0));
+    }
+
+    #[test]
+    fn dual_trace_alternates_between_y_channels() {
+        let flat = |dc_offset| ChannelConfig {
+            amplitude: 0.0,
+            dc_offset,
+            ..ChannelConfig::default()
+        };
+        let mut src = OscilloscopeSource::new(ChannelConfig::default(), flat(0.2), 44100.0);
+        src.mode = OscilloscopeMode::DualTrace;
+        src.y2_channel = flat(-0.2);
+        // Chopping at half the sample rate switches traces every sample.
+        src.chop_freq = 22050.0;
+
+        let samples = src.generate(100, &TEST_BEAM);
+        for (i, s) in samples.iter().enumerate() {
+            let expected = if i % 2 == 0 { 0.7 } else { 0.3 };
+            assert!((s.y - expected).abs() < 1e-5, "sample {i}: y={}", s.y);
+        }
+        // X is a rising sawtooth sweep.
+        assert!(samples[1].x > samples[0].x);
+    }
+
+    #[test]
+    fn dual_trace_blanks_chop_switches_and_flyback() {
+        let flat = |dc_offset| ChannelConfig {
+            amplitude: 0.0,
+            dc_offset,
+            ..ChannelConfig::default()
+        };
+        let x = ChannelConfig {
+            frequency: 500.0,
+            ..ChannelConfig::default()
+        };
+        let mut src = OscilloscopeSource::new(x, flat(0.2), 44100.0);
+        src.mode = OscilloscopeMode::DualTrace;
+        src.y2_channel = flat(-0.2);
+        src.chop_freq = 1000.0;
+
+        let samples = src.generate(4410, &TEST_BEAM);
+        assert!(samples.iter().any(|s| s.intensity > 0.0));
+        for (i, w) in samples.windows(2).enumerate() {
+            if w[0].intensity > 0.0 && w[1].intensity > 0.0 {
+                // Lit segments stay on one trace and only sweep rightward.
+                assert!((w[1].y - w[0].y).abs() < 1e-5, "chop at {i}");
+                assert!(w[1].x > w[0].x, "flyback at {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn rising_edge_trigger_starts_the_sweep_at_the_crossing() {
+        let x = ChannelConfig::default();
+        // Starts falling, so the first rising pass through 0.1 is about 476.5
+        // samples in.
+        let y = ChannelConfig {
+            frequency: 50.0,
+            phase: std::f32::consts::PI,
+            ..ChannelConfig::default()
+        };
+        let mut src = OscilloscopeSource::new(x, y, 44100.0);
+        src.mode = OscilloscopeMode::DualTrace;
+        src.trigger_source = TriggerSource::Y;
+        src.trigger_slope = TriggerSlope::Rising;
+        src.trigger_level = 0.1;
+        // Stay on Y: a chop switch would blank a sample of the sweep.
+        src.chop_freq = 0.0;
+
+        // Split the batches right at the crossing so the edge spans them.
+        let waiting = src.generate(477, &TEST_BEAM);
+        assert!(waiting.iter().all(|s| s.intensity == 0.0));
+
+        let sweep = src.generate(100, &TEST_BEAM);
+        assert_eq!(sweep[0].intensity, 1.0);
+        assert!(
+            (sweep[0].x - 0.1).abs() < 1e-5,
+            "sweep starts at x={}",
+            sweep[0].x
+        );
+        assert!(sweep.windows(2).all(|w| w[1].x > w[0].x));
+    }
 }
@@ -13,6 +13,9 @@ use super::BeamSample;
 /// in each run is emitted directly (as a line-start anchor for the shader);
 /// subsequent depositions emit when accumulated arc length exceeds the threshold.
 /// Any remaining energy is flushed at the end.
+///
+/// Beam time is conserved too: a merged deposit's `dt` is the sum of its
+/// samples' `dt`s, so the output's total `dt` matches the input's.
 pub fn arc_length_resample(samples: &[BeamSample], threshold: f32) -> Vec<BeamSample> {
     if samples.is_empty() || threshold <= 0.0 {
         return samples.to_vec();
@@ -23,6 +26,7 @@ pub fn arc_length_resample(samples: &[BeamSample], threshold: f32) -> Vec<BeamSa
     let mut prev_x: f32 = 0.0;
     let mut prev_y: f32 = 0.0;
     let mut accum_energy: f32 = 0.0;
+    let mut accum_dt: f32 = 0.0;
     let mut accum_dist: f32 = 0.0;
     let mut in_run = false;
 
@@ -30,16 +34,12 @@ pub fn arc_length_resample(samples: &[BeamSample], threshold: f32) -> Vec<BeamSa
         if sample.intensity <= 0.0 {
             // Flush any pending energy before the blank
             if in_run && accum_energy > 0.0 {
-                output.push(BeamSample {
-                    x: prev_x,
-                    y: prev_y,
-                    intensity: accum_energy,
-                    dt: 1.0,
-                });
+                output.push(merged(prev_x, prev_y, accum_energy, accum_dt));
             }
             // Emit blank as-is (retrace marker)
             output.push(sample);
             accum_energy = 0.0;
+            accum_dt = 0.0;
             accum_dist = 0.0;
             in_run = false;
             continue;
@@ -52,6 +52,7 @@ pub fn arc_length_resample(samples: &[BeamSample], threshold: f32) -> Vec<BeamSa
             prev_x = sample.x;
             prev_y = sample.y;
             accum_energy = 0.0;
+            accum_dt = 0.0;
             accum_dist = 0.0;
             in_run = true;
             continue;
@@ -62,34 +63,37 @@ pub fn arc_length_resample(samples: &[BeamSample], threshold: f32) -> Vec<BeamSa
         let dy = sample.y - prev_y;
         accum_dist += (dx * dx + dy * dy).sqrt();
         accum_energy += sample.intensity * sample.dt;
+        accum_dt += sample.dt;
         prev_x = sample.x;
         prev_y = sample.y;
 
         if accum_dist >= threshold {
-            output.push(BeamSample {
-                x: sample.x,
-                y: sample.y,
-                intensity: accum_energy,
-                dt: 1.0,
-            });
+            output.push(merged(sample.x, sample.y, accum_energy, accum_dt));
             accum_energy = 0.0;
+            accum_dt = 0.0;
             accum_dist = 0.0;
         }
     }
 
     // End-of-frame flush: deposit any remaining accumulated energy
     if in_run && accum_energy > 0.0 {
-        output.push(BeamSample {
-            x: prev_x,
-            y: prev_y,
-            intensity: accum_energy,
-            dt: 1.0,
-        });
+        output.push(merged(prev_x, prev_y, accum_energy, accum_dt));
     }
 
     output
 }
 
+/// A deposit of `energy` spread over `dt` seconds of beam time. Only called
+/// with energy from lit samples, so `dt` is positive.
+fn merged(x: f32, y: f32, energy: f32, dt: f32) -> BeamSample {
+    BeamSample {
+        x,
+        y,
+        intensity: energy / dt,
+        dt,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +156,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn beam_time_conserved() {
+        let mut input: Vec<_> = (0..100)
+            .map(|i| sample(0.5 + i as f32 * 0.001, 0.5, 2.0, 0.001))
+            .collect();
+        input[50].intensity = 0.0;
+        let output = arc_length_resample(&input, 0.01);
+        assert!(output.len() < input.len());
+        let total_input: f32 = input.iter().map(|s| s.dt).sum();
+        let total_output: f32 = output.iter().map(|s| s.dt).sum();
+        assert!(
+            (total_input - total_output).abs() < 1e-5,
+            "input={total_input}, output={total_output}"
+        );
+    }
+
     #[test]
     fn blanks_break_runs() {
         let input = [
@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use super::{BeamSample, BeamSource, BeamState};
+
+/// FFT sizes offered in the UI.
+pub const FFT_SIZES: [usize; 4] = [512, 1024, 2048, 4096];
+pub const DEFAULT_FFT_SIZE: usize = 2048;
+
+/// Magnitudes this far below full scale sit on the baseline.
+const DB_RANGE: f32 = 80.0;
+/// Screen y of the baseline (-80 dBFS) and of full scale (0 dBFS).
+const BASELINE_Y: f32 = 0.9;
+const FULL_SCALE_Y: f32 = 0.1;
+
+/// Window applied to each block before the FFT.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum SpectrumWindow {
+    /// Good general-purpose leakage and resolution.
+    #[default]
+    Hann,
+    /// Lower sidelobes at the cost of a wider peak.
+    Blackman,
+    /// No window: the sharpest peaks for tones that fit the block exactly,
+    /// heavy leakage otherwise.
+    Rectangular,
+}
+
+impl SpectrumWindow {
+    /// The window's value at sample `i` of `n`.
+    fn coefficient(self, i: usize, n: usize) -> f32 {
+        let phase = std::f32::consts::TAU * i as f32 / n as f32;
+        match self {
+            Self::Hann => 0.5 - 0.5 * phase.cos(),
+            Self::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+            Self::Rectangular => 1.0,
+        }
+    }
+}
+
+/// Swept spectrum analyzer over mono audio. The beam sweeps left to right
+/// across the FFT bins, one bin per audio sample, at a height set by each
+/// bin's magnitude in dBFS; a retrace is blanked. The spectrum is
+/// recomputed from the latest block of audio at the start of every sweep,
+/// so it refreshes at `sample_rate / bins` Hz.
+pub struct SpectrumSource {
+    fft_size: usize,
+    window: SpectrumWindow,
+    fft: Arc<dyn Fft<f32>>,
+    /// Window coefficients, pre-scaled so a full-scale sine on a bin
+    /// reads as magnitude 1.
+    coefficients: Vec<f32>,
+    /// The most recent `fft_size` audio samples.
+    history: VecDeque<f32>,
+    /// Screen y of each bin for the current sweep.
+    trace: Vec<f32>,
+    /// Next bin to draw; a full `trace.len()` starts a new sweep.
+    sweep_pos: usize,
+    pub sample_rate: f32,
+}
+
+impl SpectrumSource {
+    pub fn new(fft_size: usize, window: SpectrumWindow, sample_rate: f32) -> Self {
+        let mut source = Self {
+            fft_size: 0,
+            window,
+            fft: FftPlanner::new().plan_fft_forward(1),
+            coefficients: Vec::new(),
+            history: VecDeque::new(),
+            trace: Vec::new(),
+            sweep_pos: 0,
+            sample_rate,
+        };
+        source.set_params(fft_size, window);
+        source
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Number of bins drawn per sweep, from DC to Nyquist.
+    pub fn bins(&self) -> usize {
+        self.fft_size / 2 + 1
+    }
+
+    /// Screen x of FFT bin `bin`.
+    pub fn bin_x(&self, bin: usize) -> f32 {
+        bin as f32 / (self.bins() - 1) as f32
+    }
+
+    /// Change the FFT size or window. Does nothing if both are unchanged,
+    /// so it is cheap to call every frame.
+    pub fn set_params(&mut self, fft_size: usize, window: SpectrumWindow) {
+        let fft_size = fft_size.max(2);
+        if fft_size == self.fft_size && window == self.window {
+            return;
+        }
+        if fft_size != self.fft_size {
+            self.fft = FftPlanner::new().plan_fft_forward(fft_size);
+            self.fft_size = fft_size;
+            self.trace = vec![BASELINE_Y; self.bins()];
+            self.sweep_pos = self.trace.len();
+        }
+        self.window = window;
+        let coefficients: Vec<f32> = (0..fft_size)
+            .map(|i| window.coefficient(i, fft_size))
+            .collect();
+        // A sine of amplitude A lands A·Σw/2 on its bin.
+        let scale = 2.0 / coefficients.iter().sum::<f32>();
+        self.coefficients = coefficients.into_iter().map(|w| w * scale).collect();
+    }
+
+    /// Forget buffered audio, e.g. when a new file is loaded.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.trace.fill(BASELINE_Y);
+        self.sweep_pos = self.trace.len();
+    }
+
+    /// Feed mono audio, newest last.
+    pub fn push_audio(&mut self, mono: &[f32]) {
+        self.history.extend(mono);
+        let excess = self.history.len().saturating_sub(self.fft_size);
+        self.history.drain(..excess);
+    }
+
+    /// Feed samples from the audio source, undoing its mapping of the left
+    /// and right channels to x and y and averaging them to mono.
+    pub fn push_beam_audio(&mut self, samples: &[BeamSample]) {
+        let mono: Vec<f32> = samples.iter().map(|s| s.x + s.y - 1.0).collect();
+        self.push_audio(&mono);
+    }
+
+    /// Recompute the trace from the buffered audio, zero-padded at the
+    /// front until a full block has arrived.
+    fn analyze(&mut self) {
+        let pad = self.fft_size - self.history.len();
+        let mut buffer: Vec<Complex<f32>> = std::iter::repeat_n(0.0, pad)
+            .chain(self.history.iter().copied())
+            .zip(&self.coefficients)
+            .map(|(s, w)| Complex::new(s * w, 0.0))
+            .collect();
+        self.fft.process(&mut buffer);
+        for (y, bin) in self.trace.iter_mut().zip(&buffer) {
+            let db = 20.0 * bin.norm().max(1e-12).log10();
+            let level = (1.0 + db / DB_RANGE).clamp(0.0, 1.0);
+            *y = BASELINE_Y + (FULL_SCALE_Y - BASELINE_Y) * level;
+        }
+    }
+}
+
+impl BeamSource for SpectrumSource {
+    fn generate(&mut self, count: usize, _beam: &BeamState) -> Vec<BeamSample> {
+        let dt = 1.0 / self.sample_rate;
+        let mut samples = Vec::with_capacity(count);
+        for _ in 0..count {
+            let retrace = self.sweep_pos >= self.trace.len();
+            if retrace {
+                self.analyze();
+                self.sweep_pos = 0;
+            }
+            let bin = self.sweep_pos;
+            samples.push(BeamSample {
+                x: self.bin_x(bin),
+                y: self.trace[bin],
+                intensity: if retrace { 0.0 } else { 1.0 },
+                dt,
+            });
+            self.sweep_pos += 1;
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_BEAM: BeamState = BeamState { spot_radius: 0.001 };
+
+    fn tone(bin: usize, fft_size: usize, amplitude: f32) -> Vec<f32> {
+        (0..fft_size)
+            .map(|i| {
+                let phase = std::f32::consts::TAU * (bin * i) as f32 / fft_size as f32;
+                amplitude * phase.sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pure_tone_peaks_at_its_bin() {
+        for window in [SpectrumWindow::Hann, SpectrumWindow::Rectangular] {
+            let mut src = SpectrumSource::new(512, window, 44_100.0);
+            src.push_audio(&tone(32, 512, 1.0));
+            let sweep = src.generate(src.bins(), &TEST_BEAM);
+
+            // Highest lit point (smallest y) of the sweep.
+            let peak = sweep
+                .iter()
+                .filter(|s| s.intensity > 0.0)
+                .min_by(|a, b| a.y.total_cmp(&b.y))
+                .unwrap();
+            assert!(
+                (peak.x - src.bin_x(32)).abs() < 1e-6,
+                "{window}: {}",
+                peak.x
+            );
+            // A full-scale tone reads as 0 dBFS.
+            assert!((peak.y - FULL_SCALE_Y).abs() < 0.01, "{window}: {}", peak.y);
+        }
+    }
+
+    #[test]
+    fn sweep_starts_with_a_blanked_retrace() {
+        let mut src = SpectrumSource::new(512, SpectrumWindow::Hann, 44_100.0);
+        let samples = src.generate(2 * src.bins(), &TEST_BEAM);
+        assert_eq!(samples[0].intensity, 0.0);
+        assert_eq!(samples[src.bins()].intensity, 0.0);
+        assert!(samples.iter().all(|s| s.y == BASELINE_Y));
+        assert!(samples.iter().all(|s| (0.0..=1.0).contains(&s.x)));
+    }
+
+    #[test]
+    fn changing_fft_size_resizes_the_sweep() {
+        let mut src = SpectrumSource::new(512, SpectrumWindow::Hann, 44_100.0);
+        src.push_audio(&vec![0.25; 2048]);
+        assert_eq!(src.history.len(), 512);
+        src.set_params(1024, SpectrumWindow::Blackman);
+        assert_eq!(src.bins(), 513);
+        assert_eq!(src.fft_size(), 1024);
+    }
+}
@@ -9,53 +9,297 @@ pub struct VectorSegment {
     pub intensity: f32,
 }
 
+impl VectorSegment {
+    /// Whether this segment starts where the beam already is.
+    fn starts_at(&self, (x, y): (f32, f32)) -> bool {
+        (self.x0 - x).abs() <= 1e-6 && (self.y0 - y).abs() <= 1e-6
+    }
+
+    /// Beam velocity while drawing this segment, in normalized units per
+    /// second.
+    fn velocity(&self, beam_speed: f32) -> (f32, f32) {
+        let dx = self.x1 - self.x0;
+        let dy = self.y1 - self.y0;
+        let length = (dx * dx + dy * dy).sqrt();
+        if length <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (dx / length * beam_speed, dy / length * beam_speed)
+    }
+}
+
+/// How far past the edges of the unit square a coordinate can sit before
+/// loading warns about it. Anything further is probably in the wrong units.
+pub const COORD_MARGIN: f32 = 0.1;
+
+/// Result of [`check_segment`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentCheck {
+    Ok,
+    /// Finite, but outside `[-COORD_MARGIN, 1 + COORD_MARGIN]`. Drawable,
+    /// though likely off screen.
+    OutOfRange,
+    /// NaN or infinite; the segment can't be drawn.
+    NonFinite,
+}
+
+/// Check a segment's endpoints for values that can't be drawn or are far
+/// off screen.
+pub fn check_segment(segment: &VectorSegment) -> SegmentCheck {
+    let coords = [segment.x0, segment.y0, segment.x1, segment.y1];
+    if coords.iter().any(|c| !c.is_finite()) {
+        SegmentCheck::NonFinite
+    } else if coords
+        .iter()
+        .any(|c| !(-COORD_MARGIN..=1.0 + COORD_MARGIN).contains(c))
+    {
+        SegmentCheck::OutOfRange
+    } else {
+        SegmentCheck::Ok
+    }
+}
+
+/// Newest vector file wrapper version this build reads.
+pub const VECTOR_FILE_VERSION: u32 = 1;
+
+/// A parsed vector JSON file. Either a bare array of segments, or an object
+/// wrapper that can also carry playback settings:
+///
+/// ```json
+/// { "version": 1, "beam_speed": 2.0, "settling_time": 0.0005, "segments": [...] }
+/// ```
+///
+/// Other keys in the wrapper (e.g. `title`, `author`) are ignored.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub struct VectorFile {
+    #[serde(default)]
+    pub version: u32,
+    pub beam_speed: Option<f32>,
+    pub settling_time: Option<f32>,
+    pub segments: Vec<VectorSegment>,
+}
+
+impl VectorFile {
+    /// Parse either form. Text starting with `{` is read as the wrapper,
+    /// so its errors aren't masked by a bare-array fallback. Files with a
+    /// non-finite coordinate are rejected.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let file = if json.trim_start().starts_with('{') {
+            let file: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+            if file.version == 0 || file.version > VECTOR_FILE_VERSION {
+                return Err(format!(
+                    "unsupported vector file version {} (expected 1 to {VECTOR_FILE_VERSION})",
+                    file.version
+                ));
+            }
+            file
+        } else {
+            let segments = serde_json::from_str(json).map_err(|e| e.to_string())?;
+            Self {
+                segments,
+                ..Self::default()
+            }
+        };
+        if let Some(i) = file.first_segment(SegmentCheck::NonFinite) {
+            return Err(format!("segment {i} has a non-finite coordinate"));
+        }
+        Ok(file)
+    }
+
+    /// A warning naming the first segment that reaches well off screen, if
+    /// any does.
+    pub fn range_warning(&self) -> Option<String> {
+        self.first_segment(SegmentCheck::OutOfRange)
+            .map(|i| format!("segment {i} lies far outside the screen"))
+    }
+
+    fn first_segment(&self, check: SegmentCheck) -> Option<usize> {
+        self.segments.iter().position(|s| check_segment(s) == check)
+    }
+}
+
 pub struct VectorSource {
     pub segments: Vec<VectorSegment>,
-    pub beam_speed: f32,    // units per second (normalized coords)
-    pub settling_time: f32, // seconds for retrace between disconnected segments
+    pub beam_speed: f32, // units per second (normalized coords)
+    /// Deflection amplifier settling time in seconds: how long a retrace
+    /// between disconnected segments takes, and how long the beam rings
+    /// around a corner before settling.
+    pub settling_time: f32,
+    /// How far the deflection amplifiers overshoot a corner, from 0.0 (a
+    /// critically damped clean corner) to 1.0 (the full underdamped
+    /// response). 0.0 disables the ringing.
+    pub ringing: f32,
+    /// Beam intensity during retrace. 0.0 blanks it like a real vector
+    /// display; anything higher leaves a faint visible "spider-web" path.
+    pub retrace_intensity: f32,
 }
 
 /// Minimum number of subdivisions per segment (even very short ones get at least this many).
 const MIN_SUBDIVISIONS: usize = 2;
 
+/// Damping ratio of the deflection amplifiers' second-order response.
+/// Underdamped, so the beam overshoots a corner and rings before settling.
+const SETTLING_DAMPING: f32 = 0.35;
+
+/// Samples spent ringing at each corner.
+const SETTLING_SAMPLES: usize = 12;
+
+/// Upper bound on the intensity/speed density multiplier, so a very bright
+/// or very slow stroke can't flood the sample ring buffer.
+const MAX_DENSITY_SCALE: f32 = 4.0;
+
+/// Number of samples to split a segment into. Lit segments are spaced within
+/// one spot radius, tightened further for bright or slow strokes: energy per
+/// unit length goes as `intensity / beam_speed`, and the more a stroke
+/// deposits the sooner sample spacing shows up as banding. Blanked segments
+/// deposit nothing, so they only get `MIN_SUBDIVISIONS`.
+fn subdivision_count(length: f32, intensity: f32, beam_speed: f32, beam: &BeamState) -> usize {
+    if intensity <= 0.0 {
+        return MIN_SUBDIVISIONS;
+    }
+    let density = (intensity / beam_speed).clamp(1.0, MAX_DENSITY_SCALE);
+    ((length * density / beam.spot_radius).ceil() as usize).max(MIN_SUBDIVISIONS)
+}
+
+/// Subdivide a segment into beam samples at the density chosen by
+/// [`subdivision_count`]. The samples' `dt` adds up to the time the beam
+/// takes to cover the segment at `beam_speed`.
+pub fn subdivide_segment(
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    intensity: f32,
+    beam_speed: f32,
+    beam: &BeamState,
+) -> Vec<BeamSample> {
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+    let length = (dx * dx + dy * dy).sqrt();
+    let steps = subdivision_count(length, intensity, beam_speed, beam);
+    let dt = length / (beam_speed * steps as f32);
+
+    (0..steps)
+        .map(|i| {
+            let t = (i as f32 + 0.5) / steps as f32;
+            BeamSample {
+                x: x0 + dx * t,
+                y: y0 + dy * t,
+                intensity,
+                dt,
+            }
+        })
+        .collect()
+}
+
+/// The beam ringing around `corner` after its velocity changes by `dv`
+/// there, modeled as an underdamped second-order system that starts on the
+/// corner with velocity `dv` relative to the new path:
+/// `x(t) = dv / ω_d · e^(−ζ·ω_n·t) · sin(ω_d·t)`. `ω_n` is chosen so the
+/// envelope decays to 2% over `settling_time`, which the samples span.
+/// `ringing` scales the overshoot. Empty when there is no velocity change,
+/// no ringing, or no settling time.
+fn settle(
+    corner: (f32, f32),
+    dv: (f32, f32),
+    ringing: f32,
+    settling_time: f32,
+    intensity: f32,
+) -> Vec<BeamSample> {
+    if ringing <= 0.0 || settling_time <= 0.0 || intensity <= 0.0 || (dv.0 == 0.0 && dv.1 == 0.0) {
+        return Vec::new();
+    }
+    let zeta = SETTLING_DAMPING;
+    let omega_n = 4.0 / (zeta * settling_time);
+    let omega_d = omega_n * (1.0 - zeta * zeta).sqrt();
+    let dt = settling_time / SETTLING_SAMPLES as f32;
+
+    (1..=SETTLING_SAMPLES)
+        .map(|i| {
+            let t = i as f32 * dt;
+            let offset = ringing * (-zeta * omega_n * t).exp() * (omega_d * t).sin() / omega_d;
+            BeamSample {
+                x: corner.0 + dv.0 * offset,
+                y: corner.1 + dv.1 * offset,
+                intensity,
+                dt,
+            }
+        })
+        .collect()
+}
+
+impl VectorSource {
+    /// Samples for the jump from `from` to `to` between disconnected
+    /// segments, taking `settling_time` in total.
+    fn retrace(&self, from: (f32, f32), to: (f32, f32), beam: &BeamState) -> Vec<BeamSample> {
+        if self.retrace_intensity <= 0.0 {
+            // A single blanked sample: the resampler and shader treat it as
+            // a run break, so nothing is drawn along the jump.
+            return vec![BeamSample {
+                x: to.0,
+                y: to.1,
+                intensity: 0.0,
+                dt: self.settling_time,
+            }];
+        }
+        let mut samples = subdivide_segment(
+            from.0,
+            from.1,
+            to.0,
+            to.1,
+            self.retrace_intensity,
+            self.beam_speed,
+            beam,
+        );
+        let dt = self.settling_time / samples.len() as f32;
+        for s in &mut samples {
+            s.dt = dt;
+        }
+        samples
+    }
+}
+
 impl BeamSource for VectorSource {
     fn generate(&mut self, _count: usize, beam: &BeamState) -> Vec<BeamSample> {
         let mut out = Vec::new();
         let mut prev_end: Option<(f32, f32)> = None;
 
-        for seg in &self.segments {
-            // Insert blanked retrace if the beam must jump to a new position
-            if let Some((px, py)) = prev_end {
-                let dx = seg.x0 - px;
-                let dy = seg.y0 - py;
-                if dx.abs() > 1e-6 || dy.abs() > 1e-6 {
-                    out.push(BeamSample {
-                        x: seg.x0,
-                        y: seg.y0,
-                        intensity: 0.0,
-                        dt: self.settling_time,
-                    });
-                }
+        for (i, seg) in self.segments.iter().enumerate() {
+            // Insert a retrace if the beam must jump to a new position
+            if let Some(prev) = prev_end
+                && !seg.starts_at(prev)
+            {
+                out.extend(self.retrace(prev, (seg.x0, seg.y0), beam));
             }
 
-            // Subdivide so consecutive samples are within one spot radius
-            let dx = seg.x1 - seg.x0;
-            let dy = seg.y1 - seg.y0;
-            let length = (dx * dx + dy * dy).sqrt();
-            let steps = ((length / beam.spot_radius).ceil() as usize).max(MIN_SUBDIVISIONS);
-            let dt = length / (self.beam_speed * steps as f32);
-
-            for i in 0..steps {
-                let t = (i as f32 + 0.5) / steps as f32;
-                out.push(BeamSample {
-                    x: seg.x0 + dx * t,
-                    y: seg.y0 + dy * t,
-                    intensity: seg.intensity,
-                    dt,
-                });
-            }
+            out.extend(subdivide_segment(
+                seg.x0,
+                seg.y0,
+                seg.x1,
+                seg.y1,
+                seg.intensity,
+                self.beam_speed,
+                beam,
+            ));
+
+            // Ring at the corner: the beam either carries on into the next
+            // connected segment or stops here before a retrace.
+            let end = (seg.x1, seg.y1);
+            let v_in = seg.velocity(self.beam_speed);
+            let v_out = self
+                .segments
+                .get(i + 1)
+                .filter(|next| next.starts_at(end))
+                .map_or((0.0, 0.0), |next| next.velocity(self.beam_speed));
+            out.extend(settle(
+                end,
+                (v_in.0 - v_out.0, v_in.1 - v_out.1),
+                self.ringing,
+                self.settling_time,
+                seg.intensity,
+            ));
 
-            prev_end = Some((seg.x1, seg.y1));
+            prev_end = Some(end);
         }
 
         out
@@ -68,6 +312,90 @@ mod tests {
 
     const TEST_BEAM: BeamState = BeamState { spot_radius: 0.001 };
 
+    const SEGMENT_JSON: &str = r#"{"x0": 0.0, "y0": 0.0, "x1": 1.0, "y1": 0.5, "intensity": 1.0}"#;
+
+    #[test]
+    fn bare_array_parses_without_settings() {
+        let file = VectorFile::parse(&format!("[{SEGMENT_JSON}]")).unwrap();
+        assert_eq!(file.segments.len(), 1);
+        assert_eq!(file.beam_speed, None);
+        assert_eq!(file.settling_time, None);
+    }
+
+    #[test]
+    fn wrapper_carries_settings_and_ignores_metadata() {
+        let json = format!(
+            r#"{{"version": 1, "title": "Square", "author": "someone",
+                "beam_speed": 2.0, "segments": [{SEGMENT_JSON}, {SEGMENT_JSON}]}}"#
+        );
+        let file = VectorFile::parse(&json).unwrap();
+        assert_eq!(file.segments.len(), 2);
+        assert_eq!(file.segments[0].y1, 0.5);
+        assert_eq!(file.beam_speed, Some(2.0));
+        assert_eq!(file.settling_time, None);
+    }
+
+    #[test]
+    fn wrapper_needs_a_known_version() {
+        assert!(VectorFile::parse(r#"{"segments": []}"#).is_err());
+        assert!(VectorFile::parse(r#"{"version": 2, "segments": []}"#).is_err());
+        assert!(VectorFile::parse(r#"{"version": 1}"#).is_err());
+    }
+
+    fn segment(x0: f32, y0: f32, x1: f32, y1: f32) -> VectorSegment {
+        VectorSegment {
+            x0,
+            y0,
+            x1,
+            y1,
+            intensity: 1.0,
+        }
+    }
+
+    #[test]
+    fn non_finite_coordinates_are_rejected() {
+        assert_eq!(
+            check_segment(&segment(0.0, f32::NAN, 1.0, 1.0)),
+            SegmentCheck::NonFinite
+        );
+        // 1e39 overflows f32 to infinity.
+        let json = format!(
+            r#"[{SEGMENT_JSON}, {{"x0": 1e39, "y0": 0, "x1": 0, "y1": 0, "intensity": 1}}]"#
+        );
+        let err = VectorFile::parse(&json).unwrap_err();
+        assert!(err.contains("segment 1"), "{err}");
+    }
+
+    #[test]
+    fn out_of_range_coordinates_load_with_a_warning() {
+        assert_eq!(
+            check_segment(&segment(0.0, 0.0, 1.0, 1.0)),
+            SegmentCheck::Ok
+        );
+        assert_eq!(
+            check_segment(&segment(-0.05, 0.0, 1.05, 1.0)),
+            SegmentCheck::Ok
+        );
+        assert_eq!(
+            check_segment(&segment(0.0, 0.0, 1.2, 1.0)),
+            SegmentCheck::OutOfRange
+        );
+
+        let json = format!(
+            r#"[{SEGMENT_JSON}, {{"x0": 0, "y0": 0, "x1": 1.2, "y1": 1, "intensity": 1}}]"#
+        );
+        let file = VectorFile::parse(&json).unwrap();
+        assert_eq!(file.segments.len(), 2);
+        let warning = file.range_warning().unwrap();
+        assert!(warning.contains("segment 1"), "{warning}");
+        assert_eq!(
+            VectorFile::parse(&format!("[{SEGMENT_JSON}]"))
+                .unwrap()
+                .range_warning(),
+            None
+        );
+    }
+
     #[test]
     fn single_segment_produces_samples_along_line() {
         let mut src = VectorSource {
@@ -80,6 +408,8 @@ mod tests {
             }],
             beam_speed: 1.0,
             settling_time: 0.001,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
         };
         let samples = src.generate(0, &TEST_BEAM);
         assert!(!samples.is_empty());
@@ -112,6 +442,8 @@ mod tests {
             ],
             beam_speed: 1.0,
             settling_time: 0.001,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
         };
         let samples = src.generate(0, &TEST_BEAM);
         assert!(samples.iter().any(|s| s.intensity == 0.0));
@@ -138,6 +470,8 @@ mod tests {
             ],
             beam_speed: 1.0,
             settling_time: 0.001,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
         };
         let samples = src.generate(0, &TEST_BEAM);
         assert!(samples.iter().all(|s| s.intensity > 0.0));
@@ -155,6 +489,8 @@ mod tests {
             }],
             beam_speed: 1.0,
             settling_time: 0.0,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
         };
         let mut long_src = VectorSource {
             segments: vec![VectorSegment {
@@ -166,7 +502,153 @@ mod tests {
             }],
             beam_speed: 1.0,
             settling_time: 0.0,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
         };
         assert!(long_src.generate(0, &TEST_BEAM).len() > short_src.generate(0, &TEST_BEAM).len());
     }
+
+    fn disconnected_pair(retrace_intensity: f32) -> VectorSource {
+        VectorSource {
+            segments: vec![
+                VectorSegment {
+                    x0: 0.0,
+                    y0: 0.0,
+                    x1: 0.5,
+                    y1: 0.0,
+                    intensity: 1.0,
+                },
+                VectorSegment {
+                    x0: 0.5,
+                    y0: 0.5,
+                    x1: 1.0,
+                    y1: 0.5,
+                    intensity: 1.0,
+                },
+            ],
+            beam_speed: 1.0,
+            settling_time: 0.001,
+            ringing: 0.0,
+            retrace_intensity,
+        }
+    }
+
+    #[test]
+    fn visible_retrace_emits_lit_transition_samples() {
+        let samples = disconnected_pair(0.1).generate(0, &TEST_BEAM);
+        assert!(samples.iter().all(|s| s.intensity > 0.0));
+
+        // Samples on the jump from (0.5, 0) to (0.5, 0.5) at the retrace level
+        let retrace: Vec<_> = samples.iter().filter(|s| s.intensity == 0.1).collect();
+        assert!(retrace.len() > MIN_SUBDIVISIONS);
+        assert!(retrace.iter().all(|s| (s.x - 0.5).abs() < 1e-6));
+        let total: f32 = retrace.iter().map(|s| s.dt).sum();
+        assert!((total - 0.001).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_retrace_intensity_blanks_the_jump() {
+        let samples = disconnected_pair(0.0).generate(0, &TEST_BEAM);
+        let blanks: Vec<_> = samples.iter().filter(|s| s.intensity == 0.0).collect();
+        assert_eq!(blanks.len(), 1);
+        assert_eq!((blanks[0].x, blanks[0].y), (0.5, 0.5));
+    }
+
+    fn corner(ringing: f32) -> VectorSource {
+        VectorSource {
+            segments: vec![
+                VectorSegment {
+                    x0: 0.0,
+                    y0: 0.5,
+                    x1: 0.5,
+                    y1: 0.5,
+                    intensity: 1.0,
+                },
+                VectorSegment {
+                    x0: 0.5,
+                    y0: 0.5,
+                    x1: 0.5,
+                    y1: 1.0,
+                    intensity: 1.0,
+                },
+            ],
+            beam_speed: 10.0,
+            settling_time: 0.005,
+            ringing,
+            retrace_intensity: 0.0,
+        }
+    }
+
+    #[test]
+    fn settling_rings_around_corner_and_converges() {
+        let ringing = corner(1.0).generate(0, &TEST_BEAM);
+        let plain = corner(0.0).generate(0, &TEST_BEAM);
+        // One set of ringing samples at the corner and one at the end
+        assert_eq!(ringing.len(), plain.len() + 2 * SETTLING_SAMPLES);
+
+        // The corner ring follows the first segment's samples
+        let first_len = subdivide_segment(0.0, 0.5, 0.5, 0.5, 1.0, 10.0, &TEST_BEAM).len();
+        let ring = &ringing[first_len..first_len + SETTLING_SAMPLES];
+        let dist = |s: &BeamSample| ((s.x - 0.5).powi(2) + (s.y - 0.5).powi(2)).sqrt();
+        let peak = ring.iter().map(dist).fold(0.0, f32::max);
+        assert!(peak > 1e-4, "corner ring should overshoot, peak {peak}");
+        assert!(
+            peak < 0.01,
+            "overshoot should stay near the corner, peak {peak}"
+        );
+        assert!(dist(ring.last().unwrap()) < 0.05 * peak);
+        // Overshoot carries on past the corner in the incoming direction
+        assert!(ring[0].x > 0.5);
+        let ring_time: f32 = ring.iter().map(|s| s.dt).sum();
+        assert!((ring_time - 0.005).abs() < 1e-6);
+    }
+
+    #[test]
+    fn collinear_continuation_does_not_ring() {
+        let mut src = corner(1.0);
+        src.segments[1] = VectorSegment {
+            x0: 0.5,
+            y0: 0.5,
+            x1: 1.0,
+            y1: 0.5,
+            intensity: 1.0,
+        };
+        let samples = src.generate(0, &TEST_BEAM);
+        let mut plain = corner(0.0);
+        plain.segments[1] = src.segments[1].clone();
+        // Only the final stop rings
+        assert_eq!(
+            samples.len(),
+            plain.generate(0, &TEST_BEAM).len() + SETTLING_SAMPLES
+        );
+    }
+
+    #[test]
+    fn blanked_segment_gets_minimum_subdivisions() {
+        let samples = subdivide_segment(0.0, 0.0, 1.0, 0.0, 0.0, 1.0, &TEST_BEAM);
+        assert_eq!(samples.len(), MIN_SUBDIVISIONS);
+        // The beam still takes the full traversal time.
+        let total: f32 = samples.iter().map(|s| s.dt).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bright_long_segment_gets_many_subdivisions() {
+        let samples = subdivide_segment(0.0, 0.0, 1.0, 0.0, 1.0, 1.0, &TEST_BEAM);
+        assert!(samples.len() >= 100 * MIN_SUBDIVISIONS);
+    }
+
+    #[test]
+    fn brighter_or_slower_strokes_subdivide_more_densely() {
+        let base = subdivision_count(0.5, 1.0, 1.0, &TEST_BEAM);
+        assert!(subdivision_count(0.5, 3.0, 1.0, &TEST_BEAM) > base);
+        assert!(subdivision_count(0.5, 1.0, 0.25, &TEST_BEAM) > base);
+        // Dim or fast strokes never go below one sample per spot radius.
+        assert_eq!(subdivision_count(0.5, 0.2, 5.0, &TEST_BEAM), base);
+        // And the multiplier is capped.
+        assert_eq!(
+            subdivision_count(0.5, 1000.0, 1.0, &TEST_BEAM),
+            subdivision_count(0.5, MAX_DENSITY_SCALE, 1.0, &TEST_BEAM)
+        );
+    }
 }
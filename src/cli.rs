@@ -0,0 +1,208 @@
+use std::path::{Path, PathBuf};
+
+use crate::sim_thread::SimThreadConfig;
+use crate::types::InputMode;
+
+pub const USAGE: &str = "\
+Usage: phosphor [OPTIONS] [FILE]
+
+Arguments:
+  [FILE]                Audio (wav, flac, ogg, mp3), vector (json), or
+                        external script (phos) to open; the input mode is
+                        picked from the extension unless --input is given
+
+Options:
+  --combined            Controls in the main window
+  --detached            Controls in a separate window
+  --phosphor <TYPE>     Initial phosphor, by designation (e.g. P31)
+  --input <MODE>        Initial input: oscilloscope, audio, spectrum, vector,
+                        external
+  --file <PATH>         Same as [FILE]
+  --no-sim-priority     Don't raise the simulation thread's priority
+  --sim-realtime        Run the simulation thread with real-time scheduling
+                        (Unix only, usually needs CAP_SYS_NICE)
+  -h, --help            Print this help
+
+--combined/--detached override the saved layout.";
+
+/// Startup options from the command line. Unset fields keep the saved or
+/// default value.
+#[derive(Debug, Default, PartialEq)]
+pub struct AppConfig {
+    /// Layout override: `Some(true)` for detached, `Some(false)` for combined.
+    pub detached: Option<bool>,
+    /// Phosphor designation to select, matched case-insensitively.
+    pub phosphor: Option<String>,
+    pub input: Option<InputMode>,
+    /// File for the input source to load on the first frame.
+    pub file: Option<PathBuf>,
+    pub sim_thread: SimThreadConfig,
+    pub help: bool,
+}
+
+fn parse_input_mode(s: &str) -> Result<InputMode, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "oscilloscope" | "osc" => Ok(InputMode::Oscilloscope),
+        "audio" => Ok(InputMode::Audio),
+        "spectrum" => Ok(InputMode::Spectrum),
+        "vector" => Ok(InputMode::Vector),
+        "external" => Ok(InputMode::External),
+        _ => Err(format!(
+            "unknown input mode '{s}' (expected oscilloscope, audio, spectrum, vector, or external)"
+        )),
+    }
+}
+
+/// The input mode that opens `path`, judged by its extension.
+pub fn input_mode_for_path(path: &Path) -> Option<InputMode> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "wav" | "flac" | "ogg" | "mp3" => Some(InputMode::Audio),
+        "json" => Some(InputMode::Vector),
+        "phos" => Some(InputMode::External),
+        _ => None,
+    }
+}
+
+/// Parse arguments, not including the program name.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<AppConfig, String> {
+    let mut config = AppConfig::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+        match arg.as_str() {
+            "--combined" => config.detached = Some(false),
+            "--detached" => config.detached = Some(true),
+            "--phosphor" => config.phosphor = Some(value("--phosphor")?),
+            "--input" => config.input = Some(parse_input_mode(&value("--input")?)?),
+            "--file" => set_file(&mut config, value("--file")?)?,
+            "--no-sim-priority" => config.sim_thread.elevate_priority = false,
+            "--sim-realtime" => config.sim_thread.realtime = true,
+            "-h" | "--help" => config.help = true,
+            _ if !arg.starts_with('-') => set_file(&mut config, arg)?,
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    if let Some(file) = &config.file
+        && config.input.is_none()
+    {
+        config.input = input_mode_for_path(file);
+        if config.input.is_none() {
+            tracing::warn!(
+                "Don't know how to open {}, starting in oscilloscope mode",
+                file.display()
+            );
+            config.file = None;
+        }
+    }
+    if config.file.is_some() && config.input == Some(InputMode::Oscilloscope) {
+        return Err("oscilloscope input doesn't take a file".to_string());
+    }
+    Ok(config)
+}
+
+fn set_file(config: &mut AppConfig, path: String) -> Result<(), String> {
+    if config.file.is_some() {
+        return Err("only one file can be opened".to_string());
+    }
+    config.file = Some(PathBuf::from(path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<AppConfig, String> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_change_nothing() {
+        assert_eq!(parse(&[]), Ok(AppConfig::default()));
+    }
+
+    #[test]
+    fn flags_map_to_config() {
+        let config = parse(&[
+            "--combined",
+            "--phosphor",
+            "P31",
+            "--input",
+            "Audio",
+            "--file",
+            "x.wav",
+        ])
+        .unwrap();
+        assert_eq!(
+            config,
+            AppConfig {
+                detached: Some(false),
+                phosphor: Some("P31".to_string()),
+                input: Some(InputMode::Audio),
+                file: Some(PathBuf::from("x.wav")),
+                sim_thread: SimThreadConfig::default(),
+                help: false,
+            }
+        );
+        assert_eq!(parse(&["--detached"]).unwrap().detached, Some(true));
+        assert!(parse(&["--help"]).unwrap().help);
+
+        let config = parse(&["--no-sim-priority", "--sim-realtime"]).unwrap();
+        assert_eq!(
+            config.sim_thread,
+            SimThreadConfig {
+                elevate_priority: false,
+                realtime: true,
+            }
+        );
+    }
+
+    #[test]
+    fn bad_arguments_are_errors() {
+        assert!(parse(&["--phosphor"]).is_err());
+        assert!(parse(&["--input", "radar"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+        assert!(parse(&["--input", "oscilloscope", "--file", "x.wav"]).is_err());
+        assert!(parse(&["a.wav", "b.wav"]).is_err());
+    }
+
+    #[test]
+    fn extension_picks_input_mode() {
+        for (path, mode) in [
+            ("song.wav", Some(InputMode::Audio)),
+            ("song.FLAC", Some(InputMode::Audio)),
+            ("song.ogg", Some(InputMode::Audio)),
+            ("song.mp3", Some(InputMode::Audio)),
+            ("scene.json", Some(InputMode::Vector)),
+            // No loaders for these vector formats yet.
+            ("laser.ild", None),
+            ("logo.svg", None),
+            ("session.phos", Some(InputMode::External)),
+            ("notes.txt", None),
+            ("no_extension", None),
+        ] {
+            assert_eq!(input_mode_for_path(Path::new(path)), mode, "{path}");
+        }
+    }
+
+    #[test]
+    fn positional_file_sets_input_from_extension() {
+        let config = parse(&["session.wav"]).unwrap();
+        assert_eq!(config.input, Some(InputMode::Audio));
+        assert_eq!(config.file, Some(PathBuf::from("session.wav")));
+
+        let config = parse(&["--file", "scene.json"]).unwrap();
+        assert_eq!(config.input, Some(InputMode::Vector));
+
+        // An explicit --input wins over the extension.
+        let config = parse(&["--input", "vector", "points.txt"]).unwrap();
+        assert_eq!(config.input, Some(InputMode::Vector));
+    }
+
+    #[test]
+    fn unknown_extension_starts_in_oscilloscope_mode() {
+        assert_eq!(parse(&["notes.txt"]), Ok(AppConfig::default()));
+    }
+}
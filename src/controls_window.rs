@@ -99,13 +99,7 @@ impl ControlsWindow {
         sim_stats: Option<&Arc<SimStats>>,
     ) -> Result<(), wgpu::SurfaceError> {
         let timings = gpu.profiler.as_ref().map(|p| &p.history);
-        let egui_output = ui.run_detached(
-            &self.window,
-            &mut self.egui_winit,
-            timings,
-            sim_stats,
-            None, // sim_frame only available during viewport redraw
-        );
+        let egui_output = ui.run_detached(&self.window, &mut self.egui_winit, timings, sim_stats);
 
         render_egui_to_surface(
             &gpu.device,
@@ -1,4 +1,5 @@
 use crate::beam::SampleConsumer;
+use crate::beam::envelope::EnvelopeParams;
 use crate::gpu::GpuState;
 use crate::simulation::SimCommand;
 use crate::types::Resolution;
@@ -20,6 +21,9 @@ pub fn sync_gpu_params(gpu: &mut GpuState, ui: &UiState) {
     gpu.faceplate_scatter_params.threshold = eng.scatter_threshold;
     gpu.faceplate_scatter_params.sigma = eng.scatter_sigma * scale;
     gpu.faceplate_scatter_params.intensity = eng.scatter_intensity;
+    gpu.faceplate_scatter_params.levels = eng.scatter_levels;
+    gpu.faceplate_scatter_params.temporal = eng.scatter_temporal;
+    gpu.faceplate_scatter_params.persistence = eng.scatter_persistence;
 
     // Composite / display
     gpu.composite_params.exposure = ui.intensity;
@@ -28,6 +32,37 @@ pub fn sync_gpu_params(gpu: &mut GpuState, ui: &UiState) {
     gpu.composite_params.glass_tint = eng.glass_tint;
     gpu.composite_params.curvature = eng.curvature;
     gpu.composite_params.edge_falloff = eng.edge_falloff;
+    gpu.composite_params.grid_divisions = if eng.grid_enabled {
+        eng.grid_divisions
+    } else {
+        0
+    };
+    gpu.composite_params.grid_intensity = eng.grid_intensity;
+    gpu.composite_params.grid_color = eng.grid_color;
+    if gpu.composite_params.white_balance_k != eng.white_balance_k {
+        gpu.composite_params.set_white_balance(eng.white_balance_k);
+    }
+    gpu.spectral_resolve_params
+        .set_debug_mode(eng.spectral_debug);
+    if let Some(compare) = &mut gpu.compare {
+        compare
+            .spectral_resolve_params
+            .set_debug_mode(eng.spectral_debug);
+    }
+    gpu.composite_params.split_x = if gpu.compare.is_some() {
+        eng.compare_split
+    } else {
+        1.0
+    };
+
+    // Storage format first, since it bounds the ceiling. Both apply to the
+    // compare buffers too.
+    gpu.set_accum_format(eng.accum_format);
+    let ceiling = eng.accum_format.clamp_ceiling(eng.accum_ceiling);
+    gpu.decay_params.ceiling = ceiling;
+    if let Some(compare) = &mut gpu.compare {
+        compare.decay_params.ceiling = ceiling;
+    }
 
     // Accumulation buffer resize if resolution scale changed
     let target = Resolution::new(
@@ -36,7 +71,7 @@ pub fn sync_gpu_params(gpu: &mut GpuState, ui: &UiState) {
             .round()
             .max(1.0) as u32,
     );
-    if target != gpu.accum.resolution {
+    if target != gpu.requested_resolution {
         gpu.resize_buffers(target);
     }
 }
@@ -53,6 +88,12 @@ pub fn dispatch_sim_commands(
     let _ = tx.send(SimCommand::SetInputMode(ui.input_mode));
     let _ = tx.send(SimCommand::SetOscilloscopeParams(ui.oscilloscope.clone()));
     let _ = tx.send(SimCommand::SetFocus(ui.focus));
+    let _ = tx.send(SimCommand::SetResampleFactor(ui.engineer.resample_factor));
+    let _ = tx.send(SimCommand::SetResampleEnabled(ui.engineer.resample_enabled));
+    let _ = tx.send(SimCommand::SetBatchBounds {
+        min_ms: ui.engineer.batch_min_ms,
+        max_ms: ui.engineer.batch_max_ms,
+    });
     let _ = tx.send(SimCommand::SetViewport {
         width: gpu.surface_config.width as f32 - sidebar_width,
         height: gpu.surface_config.height as f32,
@@ -63,6 +104,20 @@ pub fn dispatch_sim_commands(
     let _ = tx.send(SimCommand::SetAudioPlaying(ui.audio_ui.playing));
     let _ = tx.send(SimCommand::SetAudioLooping(ui.audio_ui.looping));
     let _ = tx.send(SimCommand::SetAudioSpeed(ui.audio_ui.speed));
+    let _ = tx.send(SimCommand::SetSpectrumParams {
+        fft_size: ui.audio_ui.fft_size,
+        window: ui.audio_ui.fft_window,
+    });
+    let _ = tx.send(SimCommand::SetAudioNormalize(
+        ui.audio_ui.normalize.then_some(ui.audio_ui.normalize_mode),
+    ));
+    let _ = tx.send(SimCommand::SetAudioDownmix(ui.audio_ui.downmix));
+    let _ = tx.send(SimCommand::SetAudioEnvelope(ui.audio_ui.peak_hold.then(
+        || EnvelopeParams {
+            attack: ui.audio_ui.attack_ms / 1000.0,
+            release: ui.audio_ui.release_ms / 1000.0,
+        },
+    )));
     if let Some(path) = ui.audio_ui.pending_file.take() {
         ui.audio_ui.file_path = Some(path.clone());
         ui.audio_ui.has_file = true;
@@ -71,9 +126,16 @@ pub fn dispatch_sim_commands(
 
     // Vector controls
     if let Some(path) = ui.vector_ui.pending_file.take() {
+        ui.vector_ui.preview_file(&path);
         ui.vector_ui.file_path = Some(path.clone());
         let _ = tx.send(SimCommand::LoadVectorFile(path));
     }
+    let _ = tx.send(SimCommand::SetVectorParams {
+        beam_speed: ui.vector_ui.beam_speed,
+        settling_time: ui.vector_ui.settling_time,
+        ringing: ui.vector_ui.ringing,
+        retrace_intensity: ui.vector_ui.retrace_intensity,
+    });
 
     // Sample rate change — recreate the ring buffer
     if ui.oscilloscope.sample_rate != *sample_rate {
@@ -1,4 +1,8 @@
 use bytemuck::{Pod, Zeroable};
+#[cfg(test)]
+use half::f16;
+use strum::{Display, EnumIter};
+use wgpu::util::DeviceExt;
 
 use crate::types::Resolution;
 
@@ -19,61 +23,190 @@ pub fn accum_layer_count(slow_exp_count: usize, has_power_law: bool, has_instant
     layers as u32
 }
 
+/// How the accumulation buffer stores each value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+pub enum AccumFormat {
+    /// One f32 per value.
+    #[default]
+    F32,
+    /// Two f16 values packed per u32, halving VRAM. Only about three
+    /// significant digits: faint deposits onto a bright texel are lost,
+    /// values are capped at [`F16_ACCUM_CEILING`], and power-law afterglow
+    /// is cut off once its elapsed time can no longer advance (tens of
+    /// seconds at typical frame rates).
+    #[strum(serialize = "F16 (packed)")]
+    F16,
+}
+
+/// Largest energy ceiling usable with [`AccumFormat::F16`], leaving room
+/// below the f16 maximum (65504) for deposits landing before the decay
+/// pass soft-limits them.
+pub const F16_ACCUM_CEILING: f32 = 60_000.0;
+
+impl AccumFormat {
+    /// Clamp a requested energy ceiling to what this format can hold.
+    pub fn clamp_ceiling(self, ceiling: f32) -> f32 {
+        match self {
+            Self::F32 => ceiling,
+            Self::F16 => ceiling.min(F16_ACCUM_CEILING),
+        }
+    }
+}
+
+/// Pack two values into one u32 as f16s, `lo` in the low half, rounding to
+/// nearest. Matches WGSL's `pack2x16float`, which the shaders use for
+/// [`AccumFormat::F16`]; the CPU versions here are test references.
+#[cfg(test)]
+pub fn pack_f16_pair(lo: f32, hi: f32) -> u32 {
+    u32::from(f16::from_f32(lo).to_bits()) | (u32::from(f16::from_f32(hi).to_bits()) << 16)
+}
+
+/// Inverse of [`pack_f16_pair`], matching WGSL's `unpack2x16float`.
+#[cfg(test)]
+pub fn unpack_f16_pair(word: u32) -> [f32; 2] {
+    [
+        f16::from_bits(word as u16).to_f32(),
+        f16::from_bits((word >> 16) as u16).to_f32(),
+    ]
+}
+
+/// f16 bits of `val` rounded toward zero, as the decay shader stores
+/// decayed values. Rounding to nearest would let a per-frame decay smaller
+/// than half an f16 step round back up, freezing long-lived texels.
+#[cfg(test)]
+pub fn f16_bits_toward_zero(val: f32) -> u16 {
+    let mut bits = f16::from_f32(val).to_bits();
+    if f16::from_bits(bits).to_f32().abs() > val.abs() && bits & 0x7fff != 0 {
+        bits -= 1;
+    }
+    bits
+}
+
 /// Dimensions uniform passed to shaders that access the flat accumulation buffer.
-/// Indexing: `layer * (width * height) + y * width + x`
+/// Indexing: `layer * (width * height) + y * width + x`. With
+/// `packed_f16` set, element `i` is half `i & 1` of u32 `i / 2`.
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct AccumDims {
     pub width: u32,
     pub height: u32,
     pub layers: u32,
-    pub _pad: u32,
+    /// 1 for [`AccumFormat::F16`], 0 for [`AccumFormat::F32`].
+    pub packed_f16: u32,
+}
+
+/// Size in bytes of a flat accumulation buffer: one u32 per pixel per layer,
+/// or one per two for packed f16.
+pub fn accum_byte_size(resolution: Resolution, layers: u32, format: AccumFormat) -> u64 {
+    let values = (resolution.width as u64) * (resolution.height as u64) * (layers as u64);
+    match format {
+        AccumFormat::F32 => values * 4,
+        AccumFormat::F16 => values.div_ceil(2) * 4,
+    }
+}
+
+/// Largest resolution with the same aspect ratio as `resolution` whose
+/// accumulation buffer fits in `max_bytes`. Returns `resolution` unchanged
+/// when it already fits.
+pub fn clamp_resolution_to_limit(
+    resolution: Resolution,
+    layers: u32,
+    format: AccumFormat,
+    max_bytes: u64,
+) -> Resolution {
+    let layers = layers.max(1);
+    let size = accum_byte_size(resolution, layers, format);
+    if size <= max_bytes {
+        return resolution;
+    }
+    let scale = (max_bytes as f64 / size as f64).sqrt();
+    let mut width = ((resolution.width as f64 * scale).floor() as u32).max(1);
+    let mut height = ((resolution.height as f64 * scale).floor() as u32).max(1);
+    // Floating-point rounding can leave us a pixel over; shave the longer side.
+    while accum_byte_size(Resolution::new(width, height), layers, format) > max_bytes
+        && (width > 1 || height > 1)
+    {
+        if width >= height {
+            width -= 1;
+        } else {
+            height -= 1;
+        }
+    }
+    Resolution::new(width, height)
 }
 
 pub struct AccumulationBuffer {
     pub buffer: wgpu::Buffer,
+    /// `AccumDims` uniform for this buffer. Dimensions are fixed for the
+    /// buffer's lifetime, so it is written once at creation.
+    pub dims_buffer: wgpu::Buffer,
     pub resolution: Resolution,
     pub layers: u32,
+    pub format: AccumFormat,
 }
 
 impl AccumulationBuffer {
-    pub fn new(device: &wgpu::Device, resolution: Resolution, layers: u32) -> Self {
-        let Resolution { width, height } = resolution;
-        let size = (width as u64) * (height as u64) * (layers as u64) * 4;
+    pub fn new(
+        device: &wgpu::Device,
+        resolution: Resolution,
+        layers: u32,
+        format: AccumFormat,
+    ) -> Self {
+        let size = accum_byte_size(resolution, layers, format);
 
         let buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("accumulation"),
             size,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            // COPY_SRC lets render tests read the stored values back.
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
             mapped_at_creation: false,
         });
 
         tracing::info!(
-            "Accumulation buffer: {layers} layers, {resolution}, {:.1} MB VRAM",
+            "Accumulation buffer: {layers} layers, {resolution}, {format}, {:.1} MB VRAM",
             size as f64 / (1024.0 * 1024.0)
         );
 
+        let dims = Self::dims_for(resolution, layers, format);
+        let dims_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("accumulation_dims"),
+            contents: bytemuck::bytes_of(&dims),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
         Self {
             buffer,
+            dims_buffer,
             resolution,
             layers,
+            format,
         }
     }
 
-    pub fn dims(&self) -> AccumDims {
+    pub fn byte_size(&self) -> u64 {
+        accum_byte_size(self.resolution, self.layers, self.format)
+    }
+
+    fn dims_for(resolution: Resolution, layers: u32, format: AccumFormat) -> AccumDims {
         AccumDims {
-            width: self.resolution.width,
-            height: self.resolution.height,
-            layers: self.layers,
-            _pad: 0,
+            width: resolution.width,
+            height: resolution.height,
+            layers,
+            packed_f16: (format == AccumFormat::F16) as u32,
         }
     }
 
+    pub fn dims(&self) -> AccumDims {
+        Self::dims_for(self.resolution, self.layers, self.format)
+    }
+
     pub fn resize(&mut self, device: &wgpu::Device, resolution: Resolution) {
         if resolution == self.resolution {
             return;
         }
-        *self = Self::new(device, resolution, self.layers);
+        *self = Self::new(device, resolution, self.layers, self.format);
     }
 }
 
@@ -120,10 +253,16 @@ impl HdrBuffer {
         }
         *self = Self::new(device, resolution);
     }
+
+    /// Rgba32Float: 16 bytes per pixel.
+    pub fn byte_size(&self) -> u64 {
+        (self.resolution.width as u64) * (self.resolution.height as u64) * 16
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::AccumFormat::F32;
     use super::*;
 
     #[test]
@@ -138,9 +277,96 @@ mod tests {
         assert_eq!(accum_layer_count(0, true, true), 3);
     }
 
+    #[test]
+    fn accum_byte_size_counts_every_layer() {
+        // 1080p with 3 layers of u32
+        assert_eq!(
+            accum_byte_size(Resolution::new(1920, 1080), 3, F32),
+            1920 * 1080 * 3 * 4
+        );
+        // Large enough to overflow u32 arithmetic
+        assert_eq!(
+            accum_byte_size(Resolution::new(7680, 4320), 8, F32),
+            7680 * 4320 * 8 * 4
+        );
+    }
+
+    #[test]
+    fn resolution_within_limit_is_unchanged() {
+        let res = Resolution::new(1920, 1080);
+        assert_eq!(
+            clamp_resolution_to_limit(res, 3, F32, accum_byte_size(res, 3, F32)),
+            res
+        );
+    }
+
+    #[test]
+    fn resolution_over_limit_is_clamped_preserving_aspect() {
+        // 4K window at 4x scale with 6 layers: ~3.2 GB, limit 1 GiB
+        let res = Resolution::new(15360, 8640);
+        let limit = 1 << 30;
+        let clamped = clamp_resolution_to_limit(res, 6, F32, limit);
+        assert!(accum_byte_size(clamped, 6, F32) <= limit);
+        assert!(clamped.width < res.width && clamped.height < res.height);
+        let aspect = clamped.width as f64 / clamped.height as f64;
+        assert!((aspect - 16.0 / 9.0).abs() < 0.01, "aspect {aspect}");
+        // Close to the limit, not wildly under it
+        assert!(accum_byte_size(clamped, 6, F32) > limit * 99 / 100);
+    }
+
+    #[test]
+    fn tiny_limit_never_collapses_to_zero() {
+        let clamped = clamp_resolution_to_limit(Resolution::new(800, 600), 2, F32, 0);
+        assert_eq!(clamped, Resolution::new(1, 1));
+    }
+
     #[test]
     fn p15_layer_count() {
         // P15: 0 slow exp, no power law, 1 instant (1 scalar) = 1
         assert_eq!(accum_layer_count(0, false, true), 1);
     }
+
+    #[test]
+    fn packed_f16_halves_the_buffer() {
+        let res = Resolution::new(1920, 1080);
+        assert_eq!(
+            accum_byte_size(res, 3, AccumFormat::F16),
+            accum_byte_size(res, 3, F32) / 2
+        );
+        // An odd value count still needs the whole last word.
+        assert_eq!(
+            accum_byte_size(Resolution::new(3, 1), 1, AccumFormat::F16),
+            8
+        );
+    }
+
+    #[test]
+    fn f16_pairs_round_trip_in_lane_order() {
+        let word = pack_f16_pair(1.5, -0.25);
+        assert_eq!(word & 0xffff, 0x3e00);
+        assert_eq!(unpack_f16_pair(word), [1.5, -0.25]);
+        // Values between f16 steps round to nearest.
+        let [x, _] = unpack_f16_pair(pack_f16_pair(1000.3, 0.0));
+        assert_eq!(x, 1000.5);
+    }
+
+    #[test]
+    fn decayed_f16_values_always_move_toward_zero() {
+        // 0.01% decay is under half an f16 step at 1000: nearest rounding
+        // would store 1000 again, toward-zero stores the step below.
+        let decayed = 1000.0 * 0.9999;
+        assert_eq!(f16::from_f32(decayed).to_f32(), 1000.0);
+        let bits = f16_bits_toward_zero(decayed);
+        assert_eq!(f16::from_bits(bits).to_f32(), 999.5);
+        // Exact values and zero are untouched.
+        assert_eq!(f16_bits_toward_zero(0.5), f16::from_f32(0.5).to_bits());
+        assert_eq!(f16_bits_toward_zero(0.0), 0);
+    }
+
+    #[test]
+    fn f16_ceiling_fits_in_half_precision() {
+        assert!(F16_ACCUM_CEILING < f16::MAX.to_f32());
+        assert_eq!(AccumFormat::F16.clamp_ceiling(1e6), F16_ACCUM_CEILING);
+        assert_eq!(AccumFormat::F32.clamp_ceiling(1e6), 1e6);
+    }
 }
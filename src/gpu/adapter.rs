@@ -0,0 +1,240 @@
+/// Timestamp features the profiler needs; it only runs with both.
+pub const TIMESTAMP_FEATURES: wgpu::Features =
+    wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+/// One way of asking for an adapter; see [`ADAPTER_TIERS`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdapterTier {
+    pub name: &'static str,
+    pub power_preference: wgpu::PowerPreference,
+    pub force_fallback_adapter: bool,
+}
+
+/// Adapter requests in order of preference. The discrete GPU can be missing
+/// on hybrid-graphics laptops and headless machines, so fall back to the
+/// integrated one and finally to a software adapter.
+pub const ADAPTER_TIERS: [AdapterTier; 3] = [
+    AdapterTier {
+        name: "high-performance",
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        force_fallback_adapter: false,
+    },
+    AdapterTier {
+        name: "low-power",
+        power_preference: wgpu::PowerPreference::LowPower,
+        force_fallback_adapter: false,
+    },
+    AdapterTier {
+        name: "fallback (software)",
+        power_preference: wgpu::PowerPreference::None,
+        force_fallback_adapter: true,
+    },
+];
+
+/// Try each of [`ADAPTER_TIERS`] with `request` until one yields an adapter,
+/// logging which tier it came from.
+pub fn first_adapter<A>(mut request: impl FnMut(&AdapterTier) -> Option<A>) -> Option<A> {
+    for tier in &ADAPTER_TIERS {
+        if let Some(adapter) = request(tier) {
+            tracing::info!("Using a {} GPU adapter", tier.name);
+            return Some(adapter);
+        }
+        tracing::warn!("No {} GPU adapter available", tier.name);
+    }
+    None
+}
+
+/// Request an adapter through the [`ADAPTER_TIERS`] cascade, able to present
+/// to `surface` if given (`None` for headless rendering). Tiers whose adapter
+/// can't run the renderer (see [`renderer_shortfall`]) are skipped.
+pub fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface<'_>>,
+) -> Option<wgpu::Adapter> {
+    first_adapter(|tier| {
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: tier.power_preference,
+            compatible_surface: surface,
+            force_fallback_adapter: tier.force_fallback_adapter,
+        }))
+        .inspect_err(|e| tracing::debug!("{} adapter request failed: {e}", tier.name))
+        .ok()?;
+        let shortfall = renderer_shortfall(
+            &adapter.get_downlevel_capabilities(),
+            &adapter.limits(),
+            |format| adapter.get_texture_format_features(format),
+        );
+        if let Some(reason) = shortfall {
+            tracing::warn!(
+                "Skipping {} adapter {}: {reason}",
+                tier.name,
+                adapter.get_info().name
+            );
+            return None;
+        }
+        Some(adapter)
+    })
+}
+
+/// Float formats the renderer draws into and then samples: the Rgba32Float
+/// HDR buffer and the Rgba16Float faceplate scatter textures.
+const RENDER_TARGET_FORMATS: [wgpu::TextureFormat; 2] = [
+    wgpu::TextureFormat::Rgba32Float,
+    wgpu::TextureFormat::Rgba16Float,
+];
+
+/// Device limits to request from an adapter whose own limits are
+/// `available`: the WebGPU defaults the pipelines are written against, with
+/// the storage buffer limits taken from the adapter. The accumulation buffer
+/// is clamped to whatever those allow.
+pub fn required_limits(available: &wgpu::Limits) -> wgpu::Limits {
+    wgpu::Limits {
+        max_storage_buffer_binding_size: available.max_storage_buffer_binding_size,
+        max_buffer_size: available.max_buffer_size,
+        ..wgpu::Limits::default()
+    }
+}
+
+/// Why an adapter with these capabilities can't run the renderer, or `None`
+/// if it can. Software and downlevel adapters may lack compute shaders,
+/// renderable float formats, or the [`required_limits`] (such as the
+/// workgroup storage the beam write tile needs), and device setup would
+/// panic on them.
+pub fn renderer_shortfall(
+    downlevel: &wgpu::DownlevelCapabilities,
+    limits: &wgpu::Limits,
+    format_features: impl Fn(wgpu::TextureFormat) -> wgpu::TextureFormatFeatures,
+) -> Option<String> {
+    if !downlevel
+        .flags
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+    {
+        return Some("no compute shader support".to_string());
+    }
+    let mut below_limit = None;
+    required_limits(limits).check_limits_with_fail_fn(limits, true, |name, needed, allowed| {
+        below_limit = Some(format!("{name} is {allowed}, the renderer needs {needed}"));
+    });
+    if below_limit.is_some() {
+        return below_limit;
+    }
+    let needed = wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING;
+    RENDER_TARGET_FORMATS.into_iter().find_map(|format| {
+        (!format_features(format).allowed_usages.contains(needed))
+            .then(|| format!("can't render to and sample {format:?} textures"))
+    })
+}
+
+/// Device features to request out of those `available` on the adapter. None
+/// are required: without `FLOAT32_FILTERABLE` the Rgba32Float HDR buffer is
+/// sampled unfiltered, and without timestamp queries the profiler is off.
+pub fn select_features(available: wgpu::Features) -> wgpu::Features {
+    let mut features = available & wgpu::Features::FLOAT32_FILTERABLE;
+    if available.contains(TIMESTAMP_FEATURES) {
+        features |= TIMESTAMP_FEATURES;
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapter_cascade_stops_at_the_first_tier_that_answers() {
+        let mut tried = Vec::new();
+        let adapter = first_adapter(|tier| {
+            tried.push(tier.name);
+            tier.force_fallback_adapter.then_some(tier.name)
+        });
+        assert_eq!(adapter, Some(ADAPTER_TIERS[2].name));
+        assert_eq!(tried, ADAPTER_TIERS.map(|tier| tier.name));
+
+        let mut tried = 0;
+        let adapter = first_adapter(|tier| {
+            tried += 1;
+            (tier.power_preference == wgpu::PowerPreference::HighPerformance).then_some(())
+        });
+        assert_eq!((adapter, tried), (Some(()), 1));
+
+        assert_eq!(first_adapter(|_| None::<()>), None);
+    }
+
+    #[test]
+    fn adapters_without_renderable_float_targets_are_rejected() {
+        let renderable = |_| wgpu::TextureFormatFeatures {
+            allowed_usages: wgpu::TextureUsages::all(),
+            flags: wgpu::TextureFormatFeatureFlags::empty(),
+        };
+        let full = wgpu::DownlevelCapabilities::default();
+        let limits = wgpu::Limits::default();
+        assert_eq!(renderer_shortfall(&full, &limits, renderable), None);
+
+        // A downlevel adapter that can only sample Rgba32Float.
+        let sample_only_f32 = |format| wgpu::TextureFormatFeatures {
+            allowed_usages: if format == wgpu::TextureFormat::Rgba32Float {
+                wgpu::TextureUsages::TEXTURE_BINDING
+            } else {
+                wgpu::TextureUsages::all()
+            },
+            flags: wgpu::TextureFormatFeatureFlags::empty(),
+        };
+        let reason = renderer_shortfall(&full, &limits, sample_only_f32).unwrap();
+        assert!(reason.contains("Rgba32Float"), "{reason}");
+
+        let no_compute = wgpu::DownlevelCapabilities {
+            flags: wgpu::DownlevelFlags::all() - wgpu::DownlevelFlags::COMPUTE_SHADERS,
+            ..Default::default()
+        };
+        assert!(renderer_shortfall(&no_compute, &limits, renderable).is_some());
+    }
+
+    #[test]
+    fn adapters_below_the_required_limits_are_rejected() {
+        let renderable = |_| wgpu::TextureFormatFeatures {
+            allowed_usages: wgpu::TextureUsages::all(),
+            flags: wgpu::TextureFormatFeatureFlags::empty(),
+        };
+        let full = wgpu::DownlevelCapabilities::default();
+
+        // The beam write tile needs the default 16 KiB of workgroup storage.
+        let small_workgroups = wgpu::Limits {
+            max_compute_workgroup_storage_size: 8192,
+            ..Default::default()
+        };
+        let reason = renderer_shortfall(&full, &small_workgroups, renderable).unwrap();
+        assert!(
+            reason.contains("max_compute_workgroup_storage_size"),
+            "{reason}"
+        );
+
+        // Small storage buffers only lower the resolution clamp.
+        let small_buffers = wgpu::Limits {
+            max_storage_buffer_binding_size: 16 << 20,
+            max_buffer_size: 16 << 20,
+            ..Default::default()
+        };
+        assert_eq!(renderer_shortfall(&full, &small_buffers, renderable), None);
+    }
+
+    #[test]
+    fn feature_selection_keeps_only_what_the_renderer_uses() {
+        use wgpu::Features as F;
+
+        let all = F::FLOAT32_FILTERABLE | TIMESTAMP_FEATURES | F::SHADER_F64;
+        assert_eq!(
+            select_features(all),
+            F::FLOAT32_FILTERABLE | TIMESTAMP_FEATURES
+        );
+
+        // Nothing is required, so an adapter without any of them still works.
+        assert_eq!(select_features(F::empty()), F::empty());
+        assert_eq!(select_features(TIMESTAMP_FEATURES), TIMESTAMP_FEATURES);
+
+        // Half the timestamp pair is useless to the profiler.
+        assert_eq!(
+            select_features(F::FLOAT32_FILTERABLE | F::TIMESTAMP_QUERY),
+            F::FLOAT32_FILTERABLE
+        );
+    }
+}
@@ -1,10 +1,138 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt;
 
 use super::SPECTRAL_CONSTANTS;
 use super::accumulation::AccumulationBuffer;
+use super::uniform::UniformBuffer;
 use crate::beam::BeamSample;
 
+/// Beam samples processed per compute workgroup, and invocations per
+/// workgroup: the workgroup takes its samples in turn, its invocations
+/// sharing each sample's footprint. Passed to the shader as the
+/// `WORKGROUP_SIZE` override constant.
+pub const BEAM_WRITE_WORKGROUP_SIZE: u32 = 64;
+
+/// Number of workgroups needed to cover `sample_count` samples. The last
+/// workgroup may be partially filled; the shader bounds-checks against
+/// `BeamParams::sample_count`.
+pub fn workgroup_count(sample_count: usize) -> u32 {
+    (sample_count as u32).div_ceil(BEAM_WRITE_WORKGROUP_SIZE)
+}
+
+/// Side of the workgroup-shared deposit tile, in accumulation pixels.
+/// Matches `TILE_SIZE` in `beam_write.wgsl`; 64² f32s is 16 KiB, the
+/// WebGPU default limit on workgroup storage.
+pub const BEAM_TILE_SIZE: u32 = 64;
+
+/// Where one workgroup's deposits go: summed in a shared tile with its
+/// top-left at `origin` when every footprint fits in one, otherwise
+/// straight into the accumulation buffer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct WorkgroupBin {
+    pub origin_x: i32,
+    pub origin_y: i32,
+    pub tiled: u32,
+    _pad: u32,
+}
+
+impl WorkgroupBin {
+    fn tiled(origin_x: i32, origin_y: i32) -> Self {
+        Self {
+            origin_x,
+            origin_y,
+            tiled: 1,
+            _pad: 0,
+        }
+    }
+}
+
+/// Decide for each workgroup of `samples` whether its deposits fit in one
+/// shared tile. A workgroup's footprint is the bounding box of every lit
+/// sample and the point its segment starts from, grown by the beam radius
+/// and clipped to the buffer exactly as the shader does.
+pub fn bin_workgroups(samples: &[BeamSample], params: &BeamParams) -> Vec<WorkgroupBin> {
+    let (width, height) = (params.width as f32, params.height as f32);
+    let radius = (4.0 * params.sigma_core.max(params.sigma_halo)).ceil();
+    let prev = BeamSample {
+        x: params.prev_x,
+        y: params.prev_y,
+        intensity: params.prev_intensity,
+        dt: 0.0,
+    };
+    let size = BEAM_WRITE_WORKGROUP_SIZE as usize;
+
+    (0..samples.len().div_ceil(size))
+        .map(|group| {
+            let start = group * size;
+            let end = (start + size).min(samples.len());
+            let mut min = [f32::INFINITY; 2];
+            let mut max = [f32::NEG_INFINITY; 2];
+            for i in start..end {
+                let sample = samples[i];
+                if sample.intensity <= 0.0 {
+                    continue;
+                }
+                let from = if i == 0 { prev } else { samples[i - 1] };
+                let points = [Some(sample), (from.intensity > 0.0).then_some(from)];
+                for point in points.into_iter().flatten() {
+                    let (x, y) = (point.x * width, point.y * height);
+                    min = [min[0].min(x), min[1].min(y)];
+                    max = [max[0].max(x), max[1].max(y)];
+                }
+            }
+            if min[0] > max[0] {
+                // Nothing lit: no deposits to share.
+                return WorkgroupBin::default();
+            }
+
+            let x_min = ((min[0] - radius).floor() as i32).max(0);
+            let x_max = ((max[0] + radius).ceil() as i32).min(params.width as i32 - 1);
+            let y_min = ((min[1] - radius).floor() as i32).max(0);
+            let y_max = ((max[1] + radius).ceil() as i32).min(params.height as i32 - 1);
+            let tile = BEAM_TILE_SIZE as i32;
+            let fits = |lo: i32, hi: i32| hi >= lo && hi - lo < tile;
+            if fits(x_min, x_max) && fits(y_min, y_max) {
+                WorkgroupBin::tiled(x_min, y_min)
+            } else {
+                WorkgroupBin::default()
+            }
+        })
+        .collect()
+}
+
+fn create_bin_buffer(device: &wgpu::Device, sample_capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("beam_workgroup_bins"),
+        size: (workgroup_count(sample_capacity) as usize * std::mem::size_of::<WorkgroupBin>())
+            as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Initial capacity of the persistent sample buffer, in samples.
+const INITIAL_SAMPLE_CAPACITY: usize = 4096;
+
+/// Capacity (in samples) the sample buffer should have to hold `needed`
+/// samples. Never shrinks; when `needed` exceeds `current`, doubles until it
+/// fits so a steadily growing batch size only reallocates a handful of times.
+pub fn grow_capacity(current: usize, needed: usize) -> usize {
+    let mut capacity = current.max(1);
+    while capacity < needed {
+        capacity *= 2;
+    }
+    capacity
+}
+
+fn create_sample_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("beam_samples"),
+        size: (capacity * std::mem::size_of::<BeamSample>()) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct BeamParams {
@@ -14,8 +142,16 @@ pub struct BeamParams {
     pub sample_count: u32,
     pub width: u32,
     pub height: u32,
+    /// Last sample of the previous batch, in normalized screen coords, so the
+    /// batch's first sample draws a segment from it instead of a point splat.
+    /// `prev_intensity <= 0` means there is none (first frame, idle beam, or
+    /// a blanked retrace).
+    pub prev_x: f32,
+    pub prev_y: f32,
+    pub prev_intensity: f32,
     _pad0: u32,
     _pad1: u32,
+    _pad2: u32,
 }
 
 impl BeamParams {
@@ -33,8 +169,12 @@ impl BeamParams {
             sample_count: 0,
             width,
             height,
+            prev_x: 0.0,
+            prev_y: 0.0,
+            prev_intensity: 0.0,
             _pad0: 0,
             _pad1: 0,
+            _pad2: 0,
         }
     }
 
@@ -42,6 +182,15 @@ impl BeamParams {
         self.sample_count = count;
         self
     }
+
+    /// Link this batch to the last sample of the previous one. Pass
+    /// `BeamSample::default()` (zero intensity) to start unlinked.
+    pub fn with_previous(mut self, prev: BeamSample) -> Self {
+        self.prev_x = prev.x;
+        self.prev_y = prev.y;
+        self.prev_intensity = prev.intensity;
+        self
+    }
 }
 
 #[repr(C)]
@@ -53,10 +202,28 @@ pub struct EmissionParams {
     /// energy of the fast decay channels, deposited as a one-frame scalar.
     pub instant_energy_total: f32,
     pub has_instant: u32,
+    /// Power-law alpha (seconds) of the first power-law term, used to evaluate
+    /// a texel's current emission when deciding whether a deposit re-excites it.
+    pub power_law_alpha: f32,
+    /// Power-law beta of the first power-law term.
+    pub power_law_beta: f32,
+    /// A deposit re-excites a power-law texel (restarting its elapsed-time
+    /// clock) when its energy is at least this fraction of the texel's
+    /// current emission. See `beam_write.wgsl` for the re-excitation model.
+    pub reexcite_fraction: f32,
+    pub _pad: u32,
 }
 
+/// Default [`EmissionParams::reexcite_fraction`]: a deposit must add at least
+/// 10% of a texel's current power-law emission to restart its decay.
+pub const REEXCITE_FRACTION: f32 = 0.1;
+
 impl EmissionParams {
     pub fn from_phosphor(terms: &[phosphor_data::DecayTerm], tau_cutoff: f32) -> Self {
+        debug_assert!(
+            terms.iter().all(phosphor_data::DecayTerm::is_valid),
+            "invalid decay terms: {terms:?}"
+        );
         let class = phosphor_data::classify_decay_terms(terms, tau_cutoff);
 
         // Total integrated energy of tier-1 terms: ∫₀^∞ A·exp(-t/τ) dt = A·τ
@@ -70,11 +237,23 @@ impl EmissionParams {
             })
             .sum();
 
+        let (power_law_alpha, power_law_beta) = terms
+            .iter()
+            .find_map(|t| match t {
+                phosphor_data::DecayTerm::PowerLaw { alpha, beta, .. } => Some((*alpha, *beta)),
+                _ => None,
+            })
+            .unwrap_or((0.0, 0.0));
+
         Self {
             slow_exp_count: class.slow_exp_count as u32,
             has_power_law: if class.has_power_law { 1 } else { 0 },
             instant_energy_total: instant_total,
             has_instant: if class.instant_exp_count > 0 { 1 } else { 0 },
+            power_law_alpha,
+            power_law_beta,
+            reexcite_fraction: REEXCITE_FRACTION,
+            _pad: 0,
         }
     }
 }
@@ -83,6 +262,14 @@ pub struct BeamWritePipeline {
     pipeline: wgpu::ComputePipeline,
     params_bind_group_layout: wgpu::BindGroupLayout,
     texture_bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: UniformBuffer<BeamParams>,
+    /// Persistent sample storage, sized to the largest batch seen so far.
+    /// Only the first `BeamParams::sample_count` entries are valid each frame;
+    /// the shader ignores the stale tail.
+    sample_buffer: wgpu::Buffer,
+    /// One [`WorkgroupBin`] per workgroup, sized with `sample_buffer`.
+    bin_buffer: wgpu::Buffer,
+    sample_capacity: usize,
 }
 
 impl BeamWritePipeline {
@@ -129,6 +316,17 @@ impl BeamWritePipeline {
                         },
                         count: None,
                     },
+                    // workgroup bins storage buffer
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
             });
 
@@ -167,81 +365,100 @@ impl BeamWritePipeline {
             push_constant_ranges: &[],
         });
 
+        let constants = [
+            SPECTRAL_CONSTANTS,
+            &[("WORKGROUP_SIZE", BEAM_WRITE_WORKGROUP_SIZE as f64)],
+        ]
+        .concat();
         let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("beam_write"),
             layout: Some(&pipeline_layout),
             module: &shader,
             entry_point: Some("main"),
             compilation_options: wgpu::PipelineCompilationOptions {
-                constants: SPECTRAL_CONSTANTS,
+                constants: &constants,
                 ..Default::default()
             },
             cache: None,
         });
 
+        let params_buffer = UniformBuffer::new(device, "beam_params");
+
         Self {
             pipeline,
             params_bind_group_layout,
             texture_bind_group_layout,
+            params_buffer,
+            sample_buffer: create_sample_buffer(device, INITIAL_SAMPLE_CAPACITY),
+            bin_buffer: create_bin_buffer(device, INITIAL_SAMPLE_CAPACITY),
+            sample_capacity: INITIAL_SAMPLE_CAPACITY,
+        }
+    }
+
+    /// Upload this frame's samples into the persistent sample buffer, growing
+    /// it first if the batch doesn't fit, along with their workgroup bins
+    /// for the frame's `params`.
+    pub fn write_samples(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        samples: &[BeamSample],
+        params: &BeamParams,
+    ) {
+        let capacity = grow_capacity(self.sample_capacity, samples.len());
+        if capacity != self.sample_capacity {
+            self.sample_buffer = create_sample_buffer(device, capacity);
+            self.bin_buffer = create_bin_buffer(device, capacity);
+            self.sample_capacity = capacity;
+        }
+        if !samples.is_empty() {
+            queue.write_buffer(&self.sample_buffer, 0, bytemuck::cast_slice(samples));
+            let bins = bin_workgroups(samples, params);
+            queue.write_buffer(&self.bin_buffer, 0, bytemuck::cast_slice(&bins));
         }
     }
 
+    /// Upload this frame's beam params. Shared by every dispatch in the frame
+    /// (the primary and compare phosphors see the same beam).
+    pub fn write_params(&self, queue: &wgpu::Queue, params: &BeamParams) {
+        self.params_buffer.write(queue, params);
+    }
+
     pub fn dispatch(
         &self,
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
-        samples: &[BeamSample],
-        params: &BeamParams,
-        emission: &EmissionParams,
+        sample_count: usize,
+        emission: &UniformBuffer<EmissionParams>,
         accum: &AccumulationBuffer,
     ) {
-        if samples.is_empty() {
+        if sample_count == 0 {
             return;
         }
 
-        let sample_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("beam_samples"),
-            contents: bytemuck::cast_slice(samples),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-
-        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("beam_params"),
-            contents: bytemuck::bytes_of(params),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
-        let emission_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("emission_params"),
-            contents: bytemuck::bytes_of(emission),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
         let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("beam_write_params"),
             layout: &self.params_bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: sample_buffer.as_entire_binding(),
+                    resource: self.sample_buffer.as_entire_binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: params_buffer.as_entire_binding(),
+                    resource: self.params_buffer.binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 2,
-                    resource: emission_buffer.as_entire_binding(),
+                    resource: emission.binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.bin_buffer.as_entire_binding(),
                 },
             ],
         });
 
-        let dims_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("beam_write_accum_dims"),
-            contents: bytemuck::bytes_of(&accum.dims()),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
         let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("beam_write_accum"),
             layout: &self.texture_bind_group_layout,
@@ -252,7 +469,7 @@ impl BeamWritePipeline {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: dims_buffer.as_entire_binding(),
+                    resource: accum.dims_buffer.as_entire_binding(),
                 },
             ],
         });
@@ -265,7 +482,162 @@ impl BeamWritePipeline {
         pass.set_pipeline(&self.pipeline);
         pass.set_bind_group(0, &params_bind_group, &[]);
         pass.set_bind_group(1, &texture_bind_group, &[]);
-        // One workgroup per sample
-        pass.dispatch_workgroups(samples.len() as u32, 1, 1);
+        pass.dispatch_workgroups(workgroup_count(sample_count), 1, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use phosphor_data::DecayTerm;
+
+    use super::*;
+
+    #[test]
+    fn workgroup_count_covers_all_samples() {
+        assert_eq!(workgroup_count(0), 0);
+        assert_eq!(workgroup_count(1), 1);
+        assert_eq!(workgroup_count(63), 1);
+        assert_eq!(workgroup_count(64), 1);
+        assert_eq!(workgroup_count(65), 2);
+        assert_eq!(workgroup_count(1470), 23);
+        for n in [1usize, 100, 4097, 65_537] {
+            let groups = workgroup_count(n) as usize;
+            let size = BEAM_WRITE_WORKGROUP_SIZE as usize;
+            assert!(groups * size >= n, "{n} samples not covered");
+            assert!((groups - 1) * size < n, "{n} samples over-dispatched");
+        }
+    }
+
+    #[test]
+    fn grow_capacity_doubles_only_when_needed() {
+        assert_eq!(grow_capacity(4096, 0), 4096);
+        assert_eq!(grow_capacity(4096, 4096), 4096);
+        assert_eq!(grow_capacity(4096, 4097), 8192);
+        assert_eq!(grow_capacity(4096, 20_000), 32_768);
+        assert_eq!(grow_capacity(0, 3), 4);
+        // Never shrinks after a large batch.
+        assert_eq!(grow_capacity(32_768, 10), 32_768);
+    }
+
+    #[test]
+    fn beam_params_match_wgsl_layout() {
+        // 9 scalars, padded to a 16-byte multiple for the uniform buffer
+        assert_eq!(std::mem::size_of::<BeamParams>(), 48);
+    }
+
+    #[test]
+    fn with_previous_carries_last_sample() {
+        let prev = BeamSample {
+            x: 0.25,
+            y: 0.75,
+            intensity: 2.0,
+            dt: 1.0,
+        };
+        let params = BeamParams::new(1.5, 6.0, 0.03, 64, 64).with_previous(prev);
+        assert_eq!((params.prev_x, params.prev_y), (0.25, 0.75));
+        assert_eq!(params.prev_intensity, 2.0);
+
+        let unlinked = params.with_previous(BeamSample::default());
+        assert_eq!(unlinked.prev_intensity, 0.0);
+    }
+
+    #[test]
+    fn emission_params_match_wgsl_layout() {
+        // 8 scalars, padded to a 16-byte multiple for the uniform buffer
+        assert_eq!(std::mem::size_of::<EmissionParams>(), 32);
+    }
+
+    #[test]
+    fn power_law_params_drive_reexcitation() {
+        let terms = [
+            DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 1e-6,
+            },
+            DecayTerm::PowerLaw {
+                amplitude: 0.5,
+                alpha: 2e-4,
+                beta: 1.1,
+            },
+        ];
+        let params = EmissionParams::from_phosphor(&terms, 1e-4);
+        assert_eq!(params.has_power_law, 1);
+        assert_eq!(params.power_law_alpha, 2e-4);
+        assert_eq!(params.power_law_beta, 1.1);
+        assert_eq!(params.reexcite_fraction, REEXCITE_FRACTION);
+    }
+
+    #[test]
+    fn no_power_law_leaves_params_zeroed() {
+        let terms = [DecayTerm::Exponential {
+            amplitude: 1.0,
+            tau: 0.01,
+        }];
+        let params = EmissionParams::from_phosphor(&terms, 1e-4);
+        assert_eq!(params.has_power_law, 0);
+        assert_eq!(params.power_law_alpha, 0.0);
+        assert_eq!(params.power_law_beta, 0.0);
+    }
+
+    fn lit(x: f32, y: f32) -> BeamSample {
+        BeamSample {
+            x,
+            y,
+            intensity: 1.0,
+            dt: 1.0,
+        }
+    }
+
+    #[test]
+    fn workgroup_bins_tile_only_compact_footprints() {
+        // 256² buffer, radius ceil(4 * 6) = 24 px.
+        let params = BeamParams::new(1.5, 6.0, 0.03, 256, 256);
+        let size = BEAM_WRITE_WORKGROUP_SIZE as usize;
+
+        // A parked beam fits, with the tile at its footprint's corner.
+        let parked = vec![lit(0.5, 0.5); size];
+        assert_eq!(
+            bin_workgroups(&parked, &params),
+            vec![WorkgroupBin::tiled(104, 104)]
+        );
+
+        // A full-width sweep doesn't; a trailing partial group still gets
+        // its own bin.
+        let mut sweep: Vec<_> = (0..size)
+            .map(|i| lit(i as f32 / size as f32, 0.5))
+            .collect();
+        sweep.push(lit(0.99, 0.5));
+        let bins = bin_workgroups(&sweep, &params);
+        assert_eq!(bins.len(), 2);
+        assert_eq!(bins[0].tiled, 0);
+        assert_eq!(bins[1].tiled, 1);
+
+        // Blanked groups have nothing to tile.
+        let blank = vec![BeamSample::default(); size];
+        assert_eq!(
+            bin_workgroups(&blank, &params),
+            vec![WorkgroupBin::default()]
+        );
+    }
+
+    #[test]
+    fn workgroup_bins_include_the_segment_start() {
+        let params = BeamParams::new(1.5, 6.0, 0.03, 256, 256);
+        let group = vec![lit(0.5, 0.5); BEAM_WRITE_WORKGROUP_SIZE as usize];
+        // The first sample draws from the previous batch's last point,
+        // which drags the footprint out of one tile.
+        let far = params.with_previous(lit(0.0, 0.0));
+        assert_eq!(bin_workgroups(&group, &far)[0].tiled, 0);
+        // A blanked previous sample is a point splat and adds nothing.
+        let blanked = params.with_previous(BeamSample {
+            intensity: 0.0,
+            ..lit(0.0, 0.0)
+        });
+        assert_eq!(bin_workgroups(&group, &blanked)[0].tiled, 1);
+    }
+
+    #[test]
+    fn workgroup_bin_matches_wgsl_layout() {
+        assert_eq!(std::mem::size_of::<WorkgroupBin>(), 16);
     }
 }
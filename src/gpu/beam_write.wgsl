@@ -1,8 +1,12 @@
 // Beam Write Compute Shader
 //
-// For each BeamSample, deposits energy into the spectral accumulation buffer
-// by analytically integrating the Gaussian beam profile along the line segment
-// from the previous sample to the current one. This produces smooth continuous
+// One workgroup per WORKGROUP_SIZE consecutive BeamSamples. The workgroup
+// walks its samples in order, and for each one its invocations stride
+// together over the footprint's pixels, so a long segment costs every
+// invocation only a share of its bounding box. Each sample deposits energy
+// into the spectral accumulation buffer by analytically integrating the
+// Gaussian beam profile along the line segment from the previous sample to
+// the current one. This produces smooth continuous
 // traces even at coarse sample rates, avoiding the "beaded necklace" artifact
 // of per-point splatting.
 //
@@ -10,9 +14,29 @@
 // (first sample in a frame, or after a blanked retrace).
 //
 // Uses atomic CAS-loop float addition to correctly accumulate overlapping
-// spots that write to the same pixel from concurrent workgroups.
+// spots that write to the same pixel from concurrent workgroups. With packed
+// f16 storage the loop swaps the whole word, rewriting only its half.
+//
+// Workgroup tiling: consecutive samples of a slow or parked beam pile onto
+// the same pixels, and their global CAS loops serialize. When the CPU finds
+// that a workgroup's footprints all fit in one TILE_SIZE² tile (see
+// `bin_workgroups`), tier-1 and tier-2 deposits are summed in workgroup
+// memory first and flushed with one global add per touched texel. Power-law
+// deposits stay per-sample, since re-excitation depends on each deposit's
+// size.
+//
+// Power-law re-excitation: a tier-3 texel stores a peak energy and the time
+// since it was last excited. A deposit at least `reexcite_fraction` of the
+// texel's current emission restarts the clock, folding the remaining
+// (decayed) emission into the new peak so brightness stays continuous.
+// Weaker deposits (halo tails, faint retraces) join the existing decay curve
+// without resetting it, so an old trace isn't rejuvenated by stray energy.
 
 override SPECTRAL_BANDS: u32 = 16u;
+// Samples per workgroup; set from BEAM_WRITE_WORKGROUP_SIZE on the Rust side.
+override WORKGROUP_SIZE: u32 = 64u;
+// Side of the shared tile; matches BEAM_TILE_SIZE on the Rust side.
+const TILE_SIZE: u32 = 64u;
 
 struct BeamSample {
     x: f32,
@@ -28,8 +52,12 @@ struct BeamParams {
     sample_count: u32,
     width: u32,
     height: u32,
+    prev_x: f32,
+    prev_y: f32,
+    prev_intensity: f32,
     _pad0: u32,
     _pad1: u32,
+    _pad2: u32,
 }
 
 struct EmissionParams {
@@ -37,19 +65,36 @@ struct EmissionParams {
     has_power_law: u32,
     instant_energy_total: f32,
     has_instant: u32,
+    power_law_alpha: f32,
+    power_law_beta: f32,
+    reexcite_fraction: f32,
+    _pad: u32,
 }
 
 struct AccumDims {
     width: u32,
     height: u32,
     layers: u32,
-    _pad: u32,
+    packed_f16: u32, // 1: element i is half (i & 1) of word i / 2
 }
 
 @group(0) @binding(0) var<storage, read> samples: array<BeamSample>;
 @group(0) @binding(1) var<uniform> params: BeamParams;
 @group(0) @binding(2) var<uniform> emission: EmissionParams;
 
+struct WorkgroupBin {
+    origin_x: i32,
+    origin_y: i32,
+    tiled: u32,
+    _pad: u32,
+}
+
+@group(0) @binding(3) var<storage, read> bins: array<WorkgroupBin>;
+
+// Per-texel base energy for the workgroup's tile, as f32 bits. Workgroup
+// memory starts zeroed.
+var<workgroup> tile: array<atomic<u32>, TILE_SIZE * TILE_SIZE>;
+
 @group(1) @binding(0) var<storage, read_write> accum: array<atomic<u32>>;
 @group(1) @binding(1) var<uniform> accum_dims: AccumDims;
 
@@ -57,16 +102,102 @@ fn accum_index(x: i32, y: i32, layer: u32) -> u32 {
     return layer * (accum_dims.width * accum_dims.height) + u32(y) * accum_dims.width + u32(x);
 }
 
+// Largest finite f16; packed values are clamped to it rather than
+// overflowing to infinity.
+const F16_MAX: f32 = 65504.0;
+
+fn load_accum(idx: u32) -> f32 {
+    if accum_dims.packed_f16 == 1u {
+        return unpack2x16float(atomicLoad(&accum[idx >> 1u]))[idx & 1u];
+    }
+    return bitcast<f32>(atomicLoad(&accum[idx]));
+}
+
+// Atomically replace element idx with old * scale + delta (or with delta
+// alone when `replace` is set), returning the old value.
+fn atomic_update(idx: u32, scale: f32, delta: f32, replace: bool) -> f32 {
+    let packed = accum_dims.packed_f16 == 1u;
+    let word = select(idx, idx >> 1u, packed);
+    let lane = idx & 1u;
+    var prev: f32;
+    loop {
+        let old = atomicLoad(&accum[word]);
+        var new_word: u32;
+        if packed {
+            var pair = unpack2x16float(old);
+            prev = pair[lane];
+            pair[lane] = select(min(prev * scale + delta, F16_MAX), delta, replace);
+            new_word = pack2x16float(pair);
+        } else {
+            prev = bitcast<f32>(old);
+            new_word = bitcast<u32>(select(prev * scale + delta, delta, replace));
+        }
+        let result = atomicCompareExchangeWeak(&accum[word], old, new_word);
+        if result.exchanged { break; }
+    }
+    return prev;
+}
+
 fn atomic_add_f32(idx: u32, delta: f32) {
     if delta == 0.0 { return; }
+    atomic_update(idx, 1.0, delta, false);
+}
+
+// old * scale + delta, atomically. Used to fold decayed power-law energy
+// into a new peak on re-excitation.
+fn atomic_scale_add_f32(idx: u32, scale: f32, delta: f32) {
+    atomic_update(idx, scale, delta, false);
+}
+
+fn atomic_exchange_f32(idx: u32, val: f32) -> f32 {
+    return atomic_update(idx, 0.0, val, true);
+}
+
+fn tile_add_f32(i: u32, delta: f32) {
     loop {
-        let old = atomicLoad(&accum[idx]);
+        let old = atomicLoad(&tile[i]);
         let new_val = bitcast<u32>(bitcast<f32>(old) + delta);
-        let result = atomicCompareExchangeWeak(&accum[idx], old, new_val);
+        let result = atomicCompareExchangeWeak(&tile[i], old, new_val);
         if result.exchanged { break; }
     }
 }
 
+// Tier 2 and tier 1 deposits of `base_energy` straight into the
+// accumulation buffer.
+fn deposit_global(px_x: i32, px_y: i32, base_energy: f32) {
+    // Tier 2: deposit scalar energy per slow exponential term
+    for (var term = 0u; term < emission.slow_exp_count; term++) {
+        atomic_add_f32(accum_index(px_x, px_y, term), base_energy);
+    }
+
+    // Tier 1: deposit scalar instantaneous emission (one-frame layer).
+    // Energy = base * sum(A*tau) for fast exponentials.
+    if emission.has_instant == 1u {
+        let inst_layer = emission.slow_exp_count
+            + select(0u, 2u, emission.has_power_law == 1u);
+        let inst_energy = base_energy * emission.instant_energy_total;
+        atomic_add_f32(accum_index(px_x, px_y, inst_layer), inst_energy);
+    }
+}
+
+// Tier 2 and tier 1 deposits, through the tile when the workgroup has one.
+fn deposit(px_x: i32, px_y: i32, base_energy: f32, bin: WorkgroupBin) {
+    let tx = px_x - bin.origin_x;
+    let ty = px_y - bin.origin_y;
+    let size = i32(TILE_SIZE);
+    if bin.tiled == 1u && tx >= 0 && tx < size && ty >= 0 && ty < size {
+        tile_add_f32(u32(ty) * TILE_SIZE + u32(tx), base_energy);
+    } else {
+        deposit_global(px_x, px_y, base_energy);
+    }
+}
+
+// Power-law decay factor (alpha/(t+alpha))^beta after elapsed time t.
+fn power_law_factor(elapsed: f32) -> f32 {
+    let alpha = emission.power_law_alpha;
+    return pow(alpha / (elapsed + alpha), emission.power_law_beta);
+}
+
 // --- Profile functions ---
 
 // Abramowitz & Stegun 7.1.26, |ε| ≤ 2.5×10⁻⁵
@@ -113,17 +244,38 @@ fn spot_profile(r_sq: f32) -> f32 {
 
 // --- Main ---
 
-@compute @workgroup_size(16, 16, 1)
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
 fn main(
-    @builtin(global_invocation_id) global_id: vec3<u32>,
-    @builtin(workgroup_id) wg_id: vec3<u32>,
-    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
 ) {
-    let sample_idx = wg_id.x;
-    if sample_idx >= params.sample_count {
-        return;
+    let bin = bins[workgroup_id.x];
+
+    // The last workgroup may extend past the batch
+    let first = workgroup_id.x * WORKGROUP_SIZE;
+    let end = min(first + WORKGROUP_SIZE, params.sample_count);
+    for (var sample_idx = first; sample_idx < end; sample_idx++) {
+        write_sample(sample_idx, local_index, bin);
     }
 
+    workgroupBarrier();
+
+    // Flush the tile: one global deposit per texel the workgroup touched
+    if bin.tiled == 1u {
+        for (var i = local_index; i < TILE_SIZE * TILE_SIZE; i += WORKGROUP_SIZE) {
+            let energy = bitcast<f32>(atomicLoad(&tile[i]));
+            if energy != 0.0 {
+                let px_x = bin.origin_x + i32(i % TILE_SIZE);
+                let px_y = bin.origin_y + i32(i / TILE_SIZE);
+                deposit_global(px_x, px_y, energy);
+            }
+        }
+    }
+}
+
+// Deposit one sample's footprint, shared across the workgroup: invocation
+// `lane` takes every WORKGROUP_SIZE-th pixel of the bounding box.
+fn write_sample(sample_idx: u32, lane: u32, bin: WorkgroupBin) {
     let sample = samples[sample_idx];
     if sample.intensity <= 0.0 {
         return;
@@ -133,17 +285,19 @@ fn main(
     let bx = sample.x * f32(params.width);
     let by = sample.y * f32(params.height);
 
-    // Previous beam position — forms a line segment for integration.
-    // Falls back to current position (point splat) for the first sample
-    // or after a blanked retrace.
+    // Previous beam position — forms a line segment for integration. The
+    // first sample of the batch links to the last sample of the previous
+    // batch, carried in the params. Falls back to current position (point
+    // splat) when there is none or after a blanked retrace.
+    var prev = BeamSample(params.prev_x, params.prev_y, params.prev_intensity, 0.0);
+    if sample_idx > 0u {
+        prev = samples[sample_idx - 1u];
+    }
     var ax = bx;
     var ay = by;
-    if sample_idx > 0u {
-        let prev = samples[sample_idx - 1u];
-        if prev.intensity > 0.0 {
-            ax = prev.x * f32(params.width);
-            ay = prev.y * f32(params.height);
-        }
+    if prev.intensity > 0.0 {
+        ax = prev.x * f32(params.width);
+        ay = prev.y * f32(params.height);
     }
 
     // Segment geometry
@@ -166,86 +320,81 @@ fn main(
     let radius = ceil(4.0 * sigma_max);
     let radius_sq = radius * radius;
 
-    // Bounding box: segment AABB expanded by gaussian radius.
-    // For point splats (seg_len ≈ 0), this reduces to a square around the beam.
-    let center_x = (ax + bx) * 0.5;
-    let center_y = (ay + by) * 0.5;
-    let extent_x = i32(ceil(abs(seg_dx) * 0.5 + radius));
-    let extent_y = i32(ceil(abs(seg_dy) * 0.5 + radius));
-
-    let tile_size = 16;
-    let steps_x = i32(ceil(f32(2 * extent_x + 1) / f32(tile_size)));
-    let steps_y = i32(ceil(f32(2 * extent_y + 1) / f32(tile_size)));
-
-    for (var ty = 0; ty < steps_y; ty++) {
-        for (var tx = 0; tx < steps_x; tx++) {
-            let px_x = i32(center_x) + i32(local_id.x) - extent_x + tx * tile_size;
-            let px_y = i32(center_y) + i32(local_id.y) - extent_y + ty * tile_size;
-
-            if px_x < 0 || px_x >= i32(params.width) || px_y < 0 || px_y >= i32(params.height) {
+    // Bounding box: segment AABB expanded by gaussian radius, clipped to
+    // the buffer. For point splats (seg_len ≈ 0), this reduces to a square
+    // around the beam.
+    let x_min = max(i32(floor(min(ax, bx) - radius)), 0);
+    let x_max = min(i32(ceil(max(ax, bx) + radius)), i32(params.width) - 1);
+    let y_min = max(i32(floor(min(ay, by) - radius)), 0);
+    let y_max = min(i32(ceil(max(ay, by) + radius)), i32(params.height) - 1);
+
+    let box_w = x_max - x_min + 1;
+    let box_h = y_max - y_min + 1;
+    if box_w <= 0 || box_h <= 0 {
+        return;
+    }
+    let box_pixels = u32(box_w * box_h);
+
+    for (var i = lane; i < box_pixels; i += WORKGROUP_SIZE) {
+        let px_x = x_min + i32(i % u32(box_w));
+        let px_y = y_min + i32(i / u32(box_w));
+        let px_cx = f32(px_x) + 0.5;
+        let px_cy = f32(px_y) + 0.5;
+
+        var profile_val: f32;
+
+        if use_line {
+            // Vector from segment start (A) to pixel center
+            let vx = px_cx - ax;
+            let vy = px_cy - ay;
+
+            // Parallel projection along segment direction
+            let t_par = vx * dir_x + vy * dir_y;
+
+            // Perpendicular distance squared
+            let perp_x = vx - t_par * dir_x;
+            let perp_y = vy - t_par * dir_y;
+            let d_perp_sq = perp_x * perp_x + perp_y * perp_y;
+
+            // Early-out: distance from pixel to nearest point on segment
+            let ct = clamp(t_par, 0.0, seg_len);
+            let near_x = px_cx - (ax + ct * dir_x);
+            let near_y = px_cy - (ay + ct * dir_y);
+            if near_x * near_x + near_y * near_y > radius_sq {
                 continue;
             }
 
-            let px_cx = f32(px_x) + 0.5;
-            let px_cy = f32(px_y) + 0.5;
-
-            var profile_val: f32;
-
-            if use_line {
-                // Vector from segment start (A) to pixel center
-                let vx = px_cx - ax;
-                let vy = px_cy - ay;
-
-                // Parallel projection along segment direction
-                let t_par = vx * dir_x + vy * dir_y;
-
-                // Perpendicular distance squared
-                let perp_x = vx - t_par * dir_x;
-                let perp_y = vy - t_par * dir_y;
-                let d_perp_sq = perp_x * perp_x + perp_y * perp_y;
-
-                // Early-out: distance from pixel to nearest point on segment
-                let ct = clamp(t_par, 0.0, seg_len);
-                let near_x = px_cx - (ax + ct * dir_x);
-                let near_y = px_cy - (ay + ct * dir_y);
-                if near_x * near_x + near_y * near_y > radius_sq {
-                    continue;
-                }
-
-                profile_val = line_profile(d_perp_sq, t_par, seg_len);
-            } else {
-                // Point splat fallback
-                let dx = px_cx - bx;
-                let dy = px_cy - by;
-                let r_sq = dx * dx + dy * dy;
-                if r_sq > radius_sq {
-                    continue;
-                }
-                profile_val = spot_profile(r_sq);
-            }
-
-            let base_energy = sample.intensity * profile_val * sample.dt;
-
-            // Tier 2: deposit scalar energy per slow exponential term
-            for (var term = 0u; term < emission.slow_exp_count; term++) {
-                atomic_add_f32(accum_index(px_x, px_y, term), base_energy);
-            }
-
-            // Tier 3: deposit scalar peak energy into power-law layer, reset elapsed time
-            if emission.has_power_law == 1u {
-                let pl_peak_layer = emission.slow_exp_count;
-                atomic_add_f32(accum_index(px_x, px_y, pl_peak_layer), base_energy);
-                let time_layer = pl_peak_layer + 1u;
-                accum[accum_index(px_x, px_y, time_layer)] = bitcast<u32>(0.0);
+            profile_val = line_profile(d_perp_sq, t_par, seg_len);
+        } else {
+            // Point splat fallback
+            let dx = px_cx - bx;
+            let dy = px_cy - by;
+            let r_sq = dx * dx + dy * dy;
+            if r_sq > radius_sq {
+                continue;
             }
+            profile_val = spot_profile(r_sq);
+        }
 
-            // Tier 1: deposit scalar instantaneous emission (one-frame layer).
-            // Energy = base * sum(A*tau) for fast exponentials.
-            if emission.has_instant == 1u {
-                let inst_layer = emission.slow_exp_count
-                    + select(0u, 2u, emission.has_power_law == 1u);
-                let inst_energy = base_energy * emission.instant_energy_total;
-                atomic_add_f32(accum_index(px_x, px_y, inst_layer), inst_energy);
+        let base_energy = sample.intensity * profile_val * sample.dt;
+
+        deposit(px_x, px_y, base_energy, bin);
+
+        // Tier 3: deposit scalar peak energy into power-law layer,
+        // re-exciting the texel if the deposit is strong enough
+        if emission.has_power_law == 1u {
+            let peak_idx = accum_index(px_x, px_y, emission.slow_exp_count);
+            let time_idx = accum_index(px_x, px_y, emission.slow_exp_count + 1u);
+            let peak = load_accum(peak_idx);
+            let elapsed = load_accum(time_idx);
+            let current = peak * power_law_factor(elapsed);
+            if base_energy >= emission.reexcite_fraction * current {
+                // Only the first writer this frame sees the old elapsed
+                // time; later writers get 0 and fold with a factor of 1.
+                let prev_elapsed = atomic_exchange_f32(time_idx, 0.0);
+                atomic_scale_add_f32(peak_idx, power_law_factor(prev_elapsed), base_energy);
+            } else {
+                atomic_add_f32(peak_idx, base_energy);
             }
         }
     }
@@ -0,0 +1,74 @@
+use crate::types::Resolution;
+
+use super::accumulation::{AccumFormat, AccumulationBuffer, HdrBuffer};
+use super::beam_write::EmissionParams;
+use super::decay::DecayParams;
+use super::faceplate_scatter::FaceplateScatterTextures;
+use super::phosphor_params::PhosphorParams;
+use super::spectral_resolve::SpectralResolveParams;
+use super::uniform::UniformBuffer;
+
+/// Second phosphor's GPU state for split-screen comparison.
+///
+/// Holds its own accumulation buffer, HDR target, scatter textures, and
+/// decay/emission/spectral params. The beam write samples are shared with
+/// the primary phosphor, so both halves show the same input.
+///
+/// Memory cost: while enabled this roughly doubles per-frame VRAM — one more
+/// accumulation buffer (`layers × w × h × 4` bytes for the compared phosphor),
+/// one more Rgba32Float HDR target (`w × h × 16` bytes), and one more pair of
+/// half-res Rgba16Float scatter textures (`w × h × 4` bytes total). At 1080p
+/// with a 3-layer phosphor that is about 65 MB.
+pub struct CompareSet {
+    pub accum: AccumulationBuffer,
+    pub hdr: HdrBuffer,
+    pub faceplate_scatter_textures: FaceplateScatterTextures,
+    pub decay_params: DecayParams,
+    pub emission_params: EmissionParams,
+    pub spectral_resolve_params: SpectralResolveParams,
+    pub decay_uniform: UniformBuffer<DecayParams>,
+    pub emission_uniform: UniformBuffer<EmissionParams>,
+    pub spectral_resolve_uniform: UniformBuffer<SpectralResolveParams>,
+}
+
+impl CompareSet {
+    pub fn new(
+        device: &wgpu::Device,
+        resolution: Resolution,
+        format: AccumFormat,
+        params: &PhosphorParams,
+    ) -> Self {
+        Self {
+            accum: AccumulationBuffer::new(device, resolution, params.layers.max(1), format),
+            hdr: HdrBuffer::new(device, resolution),
+            faceplate_scatter_textures: FaceplateScatterTextures::new(device, resolution),
+            decay_params: params.decay,
+            emission_params: params.emission,
+            spectral_resolve_params: params.spectral_resolve,
+            decay_uniform: UniformBuffer::new(device, "compare_decay_params"),
+            emission_uniform: UniformBuffer::new(device, "compare_emission_params"),
+            spectral_resolve_uniform: UniformBuffer::new(device, "compare_spectral_resolve_params"),
+        }
+    }
+
+    /// Upload this frame's params. The compare phosphor needs its own uniform
+    /// buffers since the primary's are overwritten with different values in
+    /// the same frame.
+    pub fn write_uniforms(&self, queue: &wgpu::Queue, dt: f32) {
+        self.decay_uniform
+            .write(queue, &self.decay_params.with_dt(dt));
+        self.emission_uniform.write(queue, &self.emission_params);
+        self.spectral_resolve_uniform
+            .write(queue, &self.spectral_resolve_params);
+    }
+
+    pub fn byte_size(&self) -> u64 {
+        self.accum.byte_size() + self.hdr.byte_size() + self.faceplate_scatter_textures.byte_size()
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, resolution: Resolution) {
+        self.accum.resize(device, resolution);
+        self.hdr.resize(device, resolution);
+        self.faceplate_scatter_textures.resize(device, resolution);
+    }
+}
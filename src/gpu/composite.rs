@@ -1,8 +1,8 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt;
 
 use super::accumulation::HdrBuffer;
 use super::faceplate_scatter::FaceplateScatterTextures;
+use super::uniform::UniformBuffer;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[repr(u32)]
@@ -25,10 +25,84 @@ pub struct CompositeParams {
     pub curvature: f32,
     pub glass_tint: [f32; 3],
     pub edge_falloff: f32,
+    /// Size and top-left corner of the composited image in target pixels.
+    /// Overwritten by [`CompositePipeline::render`] from its viewport rect.
     pub viewport_size: [f32; 2],
     pub viewport_offset: [f32; 2],
+    /// Horizontal split position in screen UV for phosphor comparison.
+    /// Pixels left of it show the primary phosphor, pixels right of it the
+    /// compared one. 1.0 disables the split.
+    pub split_x: f32,
+    /// Target white color temperature in kelvin; see [`Self::set_white_balance`].
+    pub white_balance_k: f32,
+    /// Graticule divisions along each axis; 0 hides the grid.
+    pub grid_divisions: u32,
+    /// Opacity of the graticule lines over the image (0–1).
+    pub grid_intensity: f32,
+    /// Bradford adaptation from the neutral white to `white_balance_k`, in
+    /// linear sRGB. Column-major `mat3x3` with each column padded to a vec4.
+    white_balance: [[f32; 4]; 3],
+    /// Linear RGB color of the graticule lines.
+    pub grid_color: [f32; 3],
+    pub _pad: f32,
 }
 
+/// Distance in display UV from `uv` to the nearest graticule line along one
+/// axis, with lines at every multiple of `1 / divisions` (both edges
+/// included). Infinite when `divisions` is 0. Mirrors `grid_coverage` in
+/// `composite.wgsl`.
+pub fn grid_line_distance(uv: f32, divisions: u32) -> f32 {
+    if divisions == 0 {
+        return f32::INFINITY;
+    }
+    let n = divisions as f32;
+    let cell = uv * n;
+    (cell - cell.round()).abs() / n
+}
+
+/// A region of the render target, in pixels from its top-left corner, that
+/// the composite draws the display into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewportRect {
+    /// The whole of a `width`×`height` target.
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: width as f32,
+            height: height as f32,
+        }
+    }
+
+    /// Display UV of the target pixel at `pixel`: (0, 0) at the rect's
+    /// top-left corner, (1, 1) at its bottom-right. Mirrors `fs_main`.
+    pub fn uv_at(&self, pixel: [f32; 2]) -> [f32; 2] {
+        [
+            (pixel[0] - self.x) / self.width,
+            (pixel[1] - self.y) / self.height,
+        ]
+    }
+
+    /// The rect in whole pixels, clipped to a `target_width`×`target_height`
+    /// target, as `(x, y, width, height)`. `None` if nothing is left.
+    pub fn scissor(&self, target_width: u32, target_height: u32) -> Option<(u32, u32, u32, u32)> {
+        let x0 = self.x.max(0.0).floor() as u32;
+        let y0 = self.y.max(0.0).floor() as u32;
+        let x1 = ((self.x + self.width).ceil().max(0.0) as u32).min(target_width);
+        let y1 = ((self.y + self.height).ceil().max(0.0) as u32).min(target_height);
+        (x1 > x0 && y1 > y0).then(|| (x0, y0, x1 - x0, y1 - y0))
+    }
+}
+
+pub use phosphor_data::spectral::{NEUTRAL_WHITE_K, cct_to_tint, white_balance_matrix};
+
 impl CompositeParams {
     pub fn new(exposure: f32, mode: TonemapMode) -> Self {
         Self {
@@ -40,9 +114,29 @@ impl CompositeParams {
             edge_falloff: 0.0,
             viewport_size: [1.0, 1.0],
             viewport_offset: [0.0; 2],
+            split_x: 1.0,
+            white_balance_k: NEUTRAL_WHITE_K,
+            grid_divisions: 0,
+            grid_intensity: 0.0,
+            white_balance: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+            ],
+            grid_color: [1.0; 3],
+            _pad: 0.0,
         }
     }
 
+    /// Set the target white color temperature and rebuild the adaptation
+    /// matrix the composite applies before tonemapping.
+    pub fn set_white_balance(&mut self, kelvin: f32) {
+        self.white_balance_k = kelvin;
+        let m = white_balance_matrix(kelvin);
+        // Row-major → column-major for WGSL
+        self.white_balance = std::array::from_fn(|c| [m[0][c], m[1][c], m[2][c], 0.0]);
+    }
+
     pub fn set_mode(&mut self, mode: TonemapMode) {
         self.tonemap_mode = mode as u32;
     }
@@ -59,14 +153,29 @@ impl CompositeParams {
 
 pub struct CompositePipeline {
     pipeline: wgpu::RenderPipeline,
-    params_bind_group_layout: wgpu::BindGroupLayout,
+    params_buffer: UniformBuffer<CompositeParams>,
+    params_bind_group: wgpu::BindGroup,
     hdr_bind_group_layout: wgpu::BindGroupLayout,
     faceplate_scatter_bind_group_layout: wgpu::BindGroupLayout,
     linear_sampler: wgpu::Sampler,
+    /// Sampler for the Rgba32Float HDR buffers: linear when the device has
+    /// `FLOAT32_FILTERABLE`, nearest otherwise.
+    hdr_sampler: wgpu::Sampler,
+    /// 1×1 black texture bound in place of the scatter result when the
+    /// scatter passes are skipped.
+    black_scatter_view: wgpu::TextureView,
 }
 
 impl CompositePipeline {
-    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+    /// `hdr_filterable` says whether the device can filter the Rgba32Float
+    /// HDR buffer (`FLOAT32_FILTERABLE`); without it the HDR image is
+    /// sampled nearest-neighbor. The scatter textures are Rgba16Float and
+    /// always filtered.
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        hdr_filterable: bool,
+    ) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("composite"),
             source: wgpu::ShaderSource::Wgsl(include_str!("composite.wgsl").into()),
@@ -87,33 +196,42 @@ impl CompositePipeline {
                 }],
             });
 
-        let texture_and_sampler_entries = |label| {
+        // Each group binds the primary texture, a sampler, and the compare
+        // texture (the primary again when compare mode is off).
+        let texture_entry = |binding, filterable| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let texture_and_sampler_entries = |label, filterable| {
+            let sampler_type = if filterable {
+                wgpu::SamplerBindingType::Filtering
+            } else {
+                wgpu::SamplerBindingType::NonFiltering
+            };
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some(label),
                 entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
+                    texture_entry(0, filterable),
                     wgpu::BindGroupLayoutEntry {
                         binding: 1,
                         visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        ty: wgpu::BindingType::Sampler(sampler_type),
                         count: None,
                     },
+                    texture_entry(2, filterable),
                 ],
             })
         };
 
-        let hdr_bind_group_layout = texture_and_sampler_entries("composite_hdr");
+        let hdr_bind_group_layout = texture_and_sampler_entries("composite_hdr", hdr_filterable);
         let faceplate_scatter_bind_group_layout =
-            texture_and_sampler_entries("composite_faceplate_scatter");
+            texture_and_sampler_entries("composite_faceplate_scatter", true);
 
         let linear_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("composite_linear"),
@@ -123,6 +241,16 @@ impl CompositePipeline {
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             ..Default::default()
         });
+        let hdr_sampler = if hdr_filterable {
+            linear_sampler.clone()
+        } else {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("composite_hdr_nearest"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                ..Default::default()
+            })
+        };
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("composite"),
@@ -163,38 +291,77 @@ impl CompositePipeline {
             cache: None,
         });
 
+        // Textures are zero-initialized, so this reads as black without an upload.
+        let black_scatter_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("composite_black_scatter"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba16Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let params_buffer = UniformBuffer::new(device, "composite_params");
+        let params_bind_group =
+            params_buffer.bind_group(device, &params_bind_group_layout, "composite_params");
+
         Self {
             pipeline,
-            params_bind_group_layout,
+            params_buffer,
+            params_bind_group,
             hdr_bind_group_layout,
             faceplate_scatter_bind_group_layout,
             linear_sampler,
+            hdr_sampler,
+            black_scatter_view,
         }
     }
 
+    /// Composite the HDR image and scatter onto `target`. Pass `None` for
+    /// `faceplate_scatter` when the scatter passes were skipped this frame;
+    /// both halves then sample a black fallback instead.
+    ///
+    /// `viewport` is the rect of `target` to draw into. `None` fills the
+    /// whole target and clears it first; an explicit rect leaves the pixels
+    /// outside it untouched, so several composites can share one target.
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &self,
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         encoder: &mut wgpu::CommandEncoder,
         target: &wgpu::TextureView,
+        viewport: Option<ViewportRect>,
         params: &CompositeParams,
         hdr: &HdrBuffer,
-        faceplate_scatter: &FaceplateScatterTextures,
+        faceplate_scatter: Option<&FaceplateScatterTextures>,
+        compare: Option<(&HdrBuffer, &FaceplateScatterTextures)>,
     ) {
-        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("composite_params"),
-            contents: bytemuck::bytes_of(params),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
+        let compare_hdr = compare.map_or(hdr, |(compare_hdr, _)| compare_hdr);
+        let (scatter_view, compare_scatter_view) = match faceplate_scatter {
+            Some(primary) => (
+                primary.output(),
+                compare.map_or(primary.output(), |(_, scatter)| scatter.output()),
+            ),
+            None => (&self.black_scatter_view, &self.black_scatter_view),
+        };
 
-        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("composite_params"),
-            layout: &self.params_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: params_buffer.as_entire_binding(),
-            }],
-        });
+        let size = target.texture().size();
+        let rect = viewport.unwrap_or(ViewportRect::full(size.width, size.height));
+        let params = CompositeParams {
+            viewport_offset: [rect.x, rect.y],
+            viewport_size: [rect.width, rect.height],
+            ..*params
+        };
+        self.params_buffer.write(queue, &params);
 
         let hdr_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("composite_hdr"),
@@ -206,24 +373,31 @@ impl CompositePipeline {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.linear_sampler),
+                    resource: wgpu::BindingResource::Sampler(&self.hdr_sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&compare_hdr.view),
                 },
             ],
         });
 
-        // Faceplate scatter result is in view_a after the blur passes
         let faceplate_scatter_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("composite_faceplate_scatter"),
             layout: &self.faceplate_scatter_bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&faceplate_scatter.view_a),
+                    resource: wgpu::BindingResource::TextureView(scatter_view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&self.linear_sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(compare_scatter_view),
+                },
             ],
         });
 
@@ -233,7 +407,10 @@ impl CompositePipeline {
                 view: target,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    load: match viewport {
+                        None => wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        Some(_) => wgpu::LoadOp::Load,
+                    },
                     store: wgpu::StoreOp::Store,
                 },
                 depth_slice: None,
@@ -242,10 +419,72 @@ impl CompositePipeline {
             ..Default::default()
         });
 
+        // The viewport only bounds the full-screen triangle; UVs come from
+        // the unclipped rect in the params, so a rect hanging off the target
+        // is cropped rather than squeezed.
+        let Some((x, y, width, height)) = rect.scissor(size.width, size.height) else {
+            return;
+        };
+        pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+        pass.set_scissor_rect(x, y, width, height);
         pass.set_pipeline(&self.pipeline);
-        pass.set_bind_group(0, &params_bind_group, &[]);
+        pass.set_bind_group(0, &self.params_bind_group, &[]);
         pass.set_bind_group(1, &hdr_bind_group, &[]);
         pass.set_bind_group(2, &faceplate_scatter_bind_group, &[]);
         pass.draw(0..3, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tonemap_mode_round_trips_through_params() {
+        for mode in [
+            TonemapMode::Reinhard,
+            TonemapMode::Aces,
+            TonemapMode::Clamp,
+            TonemapMode::None,
+        ] {
+            let mut params = CompositeParams::new(1.0, TonemapMode::default());
+            params.set_mode(mode);
+            assert_eq!(params.mode(), mode);
+        }
+    }
+
+    #[test]
+    fn grid_lines_fall_on_division_boundaries() {
+        for k in 0..=10 {
+            assert!(grid_line_distance(k as f32 / 10.0, 10) < 1e-6, "line {k}");
+        }
+        // Halfway between lines is as far as a point can be from one.
+        assert!((grid_line_distance(0.35, 10) - 0.05).abs() < 1e-6);
+        assert!((grid_line_distance(0.375, 4) - 0.125).abs() < 1e-6);
+        assert_eq!(grid_line_distance(0.5, 0), f32::INFINITY);
+    }
+
+    #[test]
+    fn sub_rect_maps_its_corners_to_the_unit_square() {
+        let rect = ViewportRect {
+            x: 300.0,
+            y: 50.0,
+            width: 200.0,
+            height: 100.0,
+        };
+        assert_eq!(rect.uv_at([300.0, 50.0]), [0.0, 0.0]);
+        assert_eq!(rect.uv_at([500.0, 150.0]), [1.0, 1.0]);
+        assert_eq!(rect.uv_at([400.0, 75.0]), [0.5, 0.25]);
+        assert_eq!(rect.scissor(1920, 1080), Some((300, 50, 200, 100)));
+
+        // Hanging off the target: clipped scissor, unchanged UVs.
+        let off_edge = ViewportRect { x: -50.0, ..rect };
+        assert_eq!(off_edge.scissor(100, 1080), Some((0, 50, 100, 100)));
+        assert_eq!(off_edge.uv_at([0.0, 50.0]), [0.25, 0.0]);
+        assert_eq!(ViewportRect { x: 2000.0, ..rect }.scissor(1920, 1080), None);
+
+        let full = ViewportRect::full(1920, 1080);
+        assert_eq!(full.uv_at([960.0, 540.0]), [0.5, 0.5]);
+        assert_eq!(full.scissor(1920, 1080), Some((0, 0, 1920, 1080)));
+    }
+}
@@ -22,6 +22,19 @@ struct CompositeParams {
     edge_falloff: f32,
     viewport_size: vec2<f32>,
     viewport_offset: vec2<f32>,
+    // Compare-mode split in screen UV: left = primary, right = compare.
+    // 1.0 when compare mode is off.
+    split_x: f32,
+    // Target white in kelvin (CPU-side reference; the matrix below is what
+    // the shader applies).
+    white_balance_k: f32,
+    // Graticule divisions per axis (0 = no grid) and line opacity.
+    grid_divisions: u32,
+    grid_intensity: f32,
+    // Bradford chromatic adaptation in linear sRGB, computed on the CPU from
+    // the daylight-locus chromaticity of white_balance_k.
+    white_balance: mat3x3<f32>,
+    grid_color: vec3<f32>,
 }
 
 @group(0) @binding(0) var<uniform> params: CompositeParams;
@@ -30,10 +43,13 @@ struct CompositeParams {
 // Alpha channel carries CIE Y luminance for luminance-based tonemapping.
 @group(1) @binding(0) var hdr_texture: texture_2d<f32>;
 @group(1) @binding(1) var hdr_sampler: sampler;
+// Second phosphor's HDR texture (compare mode); aliases hdr_texture when off.
+@group(1) @binding(2) var compare_hdr_texture: texture_2d<f32>;
 
 // Faceplate scatter (blurred bright areas) at half resolution.
 @group(2) @binding(0) var faceplate_scatter_texture: texture_2d<f32>;
 @group(2) @binding(1) var scatter_sampler: sampler;
+@group(2) @binding(2) var compare_scatter_texture: texture_2d<f32>;
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -100,6 +116,20 @@ fn barrel_distort(uv: vec2<f32>, k: f32) -> vec2<f32> {
     return centered * scale + vec2<f32>(0.5);
 }
 
+// Coverage (0-1) of the graticule at display UV `uv`: lines about a pixel
+// wide at every multiple of 1 / grid_divisions, antialiased over half a
+// pixel. Taken in distorted UV so the grid bends with the faceplate.
+fn grid_coverage(uv: vec2<f32>) -> f32 {
+    if params.grid_divisions == 0u {
+        return 0.0;
+    }
+    let n = f32(params.grid_divisions);
+    let cell = uv * n;
+    let dist_px = abs(cell - round(cell)) / n * params.viewport_size;
+    let line = 1.0 - smoothstep(vec2<f32>(0.5), vec2<f32>(1.0), dist_px);
+    return max(line.x, line.y);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let pixel = in.position.xy - params.viewport_offset;
@@ -113,16 +143,30 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         return vec4<f32>(0.0, 0.0, 0.0, 1.0);
     }
 
-    // Bilinear-filtered sampling — smooth under curvature distortion
-    let hdr = textureSample(hdr_texture, hdr_sampler, distorted_uv);
+    // Bilinear-filtered sampling — smooth under curvature distortion.
+    // Both phosphors are sampled unconditionally (textureSample requires
+    // uniform control flow) and selected by the compare split.
+    let use_compare = distorted_uv.x >= params.split_x;
+    let hdr = select(
+        textureSample(hdr_texture, hdr_sampler, distorted_uv),
+        textureSample(compare_hdr_texture, hdr_sampler, distorted_uv),
+        use_compare,
+    );
 
     var rgb = hdr.rgb;
     let Y = hdr.a; // CIE Y luminance from spectral resolve
 
     // Faceplate scatter — half-res texture, hardware bilinear upscale
-    let scatter = textureSample(faceplate_scatter_texture, scatter_sampler, distorted_uv).rgb;
+    let scatter = select(
+        textureSample(faceplate_scatter_texture, scatter_sampler, distorted_uv).rgb,
+        textureSample(compare_scatter_texture, scatter_sampler, distorted_uv).rgb,
+        use_compare,
+    );
     rgb += scatter * params.faceplate_scatter_intensity;
 
+    // White balance — von Kries (Bradford) adaptation to the target white
+    rgb = params.white_balance * rgb;
+
     // Glass faceplate tint — multiplicative color filter
     rgb *= params.glass_tint;
 
@@ -139,6 +183,14 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     // Tonemapping (mode selected via uniform)
     rgb = apply_tonemap(rgb, luminance, params.tonemap_mode);
 
+    // Graticule drawn over the image, bending with the curvature
+    rgb = mix(rgb, params.grid_color, params.grid_intensity * grid_coverage(distorted_uv));
+
+    // Compare-mode divider: a thin gray line at the split
+    if params.split_x < 1.0 && abs(distorted_uv.x - params.split_x) * params.viewport_size.x < 1.0 {
+        rgb = vec3<f32>(0.25);
+    }
+
     // Output linear RGB — the sRGB render target applies gamma encoding
     return vec4<f32>(rgb, 1.0);
 }
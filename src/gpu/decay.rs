@@ -1,10 +1,16 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt;
 
 use super::SPECTRAL_CONSTANTS;
 use super::accumulation::AccumulationBuffer;
+use super::uniform::UniformBuffer;
 
-pub const MAX_DECAY_TERMS: usize = 8;
+pub use phosphor_data::MAX_DECAY_TERMS;
+
+/// Default cap on a texel's stored energy per layer. The decay shader
+/// soft-limits values above half the ceiling so a beam parked on one spot
+/// for a long session can't grow the buffer until f32 loses precision.
+/// Normal use stays far below the knee.
+pub const DEFAULT_ACCUM_CEILING: f32 = 1e6;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -26,11 +32,18 @@ pub struct DecayParams {
     pub slow_exp_count: u32,
     pub has_power_law: u32,
     pub has_instant: u32,
-    pub _pad: u32,
+    /// Soft limit on stored energy per layer; see [`DEFAULT_ACCUM_CEILING`].
+    pub ceiling: f32,
 }
 
 impl DecayParams {
+    /// Pack `terms` for the GPU. Callers filter out invalid terms first
+    /// (see `PhosphorParams`); a NaN here would poison every pixel.
     pub fn from_terms(terms: &[phosphor_data::DecayTerm], tau_cutoff: f32) -> Self {
+        debug_assert!(
+            terms.iter().all(phosphor_data::DecayTerm::is_valid),
+            "invalid decay terms: {terms:?}"
+        );
         let mut gpu_terms = [DecayTermGpu::zeroed(); MAX_DECAY_TERMS];
         let class = phosphor_data::classify_decay_terms(terms, tau_cutoff);
 
@@ -75,7 +88,7 @@ impl DecayParams {
             slow_exp_count: class.slow_exp_count as u32,
             has_power_law: if class.has_power_law { 1 } else { 0 },
             has_instant: if class.instant_exp_count > 0 { 1 } else { 0 },
-            _pad: 0,
+            ceiling: DEFAULT_ACCUM_CEILING,
         }
     }
 
@@ -171,29 +184,11 @@ impl DecayPipeline {
         &self,
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
-        params: &DecayParams,
+        params: &UniformBuffer<DecayParams>,
         accum: &AccumulationBuffer,
     ) {
-        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("decay_params"),
-            contents: bytemuck::bytes_of(params),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
-        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("decay_params"),
-            layout: &self.params_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: params_buffer.as_entire_binding(),
-            }],
-        });
-
-        let dims_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("decay_accum_dims"),
-            contents: bytemuck::bytes_of(&accum.dims()),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
+        let params_bind_group =
+            params.bind_group(device, &self.params_bind_group_layout, "decay_params");
 
         let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("decay_accum"),
@@ -205,7 +200,7 @@ impl DecayPipeline {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: dims_buffer.as_entire_binding(),
+                    resource: accum.dims_buffer.as_entire_binding(),
                 },
             ],
         });
@@ -224,3 +219,41 @@ impl DecayPipeline {
         pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phosphor::phosphor_database;
+    use crate::simulation::BEAM_ENERGY_SCALE;
+
+    #[test]
+    fn ceiling_packs_after_the_tier_flags() {
+        let params = DecayParams::from_terms(&[], 1e-4);
+        assert_eq!(params.ceiling, DEFAULT_ACCUM_CEILING);
+        let bytes = bytemuck::bytes_of(&params);
+        let offset = std::mem::offset_of!(DecayParams, ceiling);
+        assert_eq!(offset, bytes.len() - 4);
+        assert_eq!(&bytes[offset..], &DEFAULT_ACCUM_CEILING.to_ne_bytes()[..]);
+    }
+
+    #[test]
+    fn default_ceiling_clears_a_parked_beam() {
+        // A beam held on one texel settles where deposition balances decay:
+        // energy per second times tau, with headroom for the halo.
+        let max_tau = phosphor_database()
+            .iter()
+            .flat_map(|p| [&p.fluorescence, &p.phosphorescence])
+            .flat_map(|layer| &layer.decay_terms)
+            .filter_map(|term| match term {
+                phosphor_data::DecayTerm::Exponential { tau, .. } => Some(*tau),
+                _ => None,
+            })
+            .fold(0.0_f32, f32::max);
+        let steady_state = 2.0 * BEAM_ENERGY_SCALE * max_tau;
+        let knee = DEFAULT_ACCUM_CEILING / 2.0;
+        assert!(
+            steady_state < knee,
+            "parked beam reaches {steady_state}, limiter starts at {knee}"
+        );
+    }
+}
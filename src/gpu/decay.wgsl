@@ -23,31 +23,88 @@ struct DecayParams {
     slow_exp_count: u32,
     has_power_law: u32,
     has_instant: u32,
-    _pad: u32,
+    ceiling: f32,
 }
 
 struct AccumDims {
     width: u32,
     height: u32,
     layers: u32,
-    _pad: u32,
+    packed_f16: u32, // 1: element i is half (i & 1) of word i / 2
 }
 
 @group(0) @binding(0) var<uniform> params: DecayParams;
 
-@group(1) @binding(0) var<storage, read_write> accum: array<u32>;
+// Atomic only so packed f16 halves can be written without clobbering the
+// neighboring texel that shares the word; each invocation owns its texels.
+@group(1) @binding(0) var<storage, read_write> accum: array<atomic<u32>>;
 @group(1) @binding(1) var<uniform> accum_dims: AccumDims;
 
 fn accum_index(x: i32, y: i32, layer: u32) -> u32 {
     return layer * (accum_dims.width * accum_dims.height) + u32(y) * accum_dims.width + u32(x);
 }
 
+const F16_MAX: f32 = 65504.0;
+
 fn load_accum(x: i32, y: i32, layer: u32) -> f32 {
-    return bitcast<f32>(accum[accum_index(x, y, layer)]);
+    let idx = accum_index(x, y, layer);
+    if accum_dims.packed_f16 == 1u {
+        return unpack2x16float(atomicLoad(&accum[idx >> 1u]))[idx & 1u];
+    }
+    return bitcast<f32>(atomicLoad(&accum[idx]));
+}
+
+// f16 bits of a non-negative value, rounded to nearest.
+fn f16_bits(val: f32) -> u32 {
+    return pack2x16float(vec2<f32>(min(val, F16_MAX), 0.0)) & 0xffffu;
+}
+
+// f16 bits rounded toward zero, for decayed values: rounding to nearest
+// would let a decay smaller than half an f16 step round back up, so a
+// long-lived texel would never fade.
+fn f16_bits_toward_zero(val: f32) -> u32 {
+    var bits = f16_bits(val);
+    if unpack2x16float(bits).x > val && bits != 0u {
+        bits -= 1u;
+    }
+    return bits;
+}
+
+fn store_packed(idx: u32, bits: u32) {
+    let shift = 16u * (idx & 1u);
+    atomicAnd(&accum[idx >> 1u], ~(0xffffu << shift));
+    atomicOr(&accum[idx >> 1u], bits << shift);
 }
 
 fn store_accum(x: i32, y: i32, layer: u32, val: f32) {
-    accum[accum_index(x, y, layer)] = bitcast<u32>(val);
+    let idx = accum_index(x, y, layer);
+    if accum_dims.packed_f16 == 1u {
+        store_packed(idx, f16_bits(val));
+    } else {
+        atomicStore(&accum[idx], bitcast<u32>(val));
+    }
+}
+
+// Store a value that decayed from its previous contents.
+fn store_decayed(x: i32, y: i32, layer: u32, val: f32) {
+    let idx = accum_index(x, y, layer);
+    if accum_dims.packed_f16 == 1u {
+        store_packed(idx, f16_bits_toward_zero(val));
+    } else {
+        atomicStore(&accum[idx], bitcast<u32>(val));
+    }
+}
+
+// Identity below half the ceiling, then a tanh knee that approaches the
+// ceiling asymptotically. Keeps a parked beam from growing a texel without
+// bound over a long session.
+fn soft_limit(val: f32) -> f32 {
+    let knee = 0.5 * params.ceiling;
+    if val <= knee {
+        return val;
+    }
+    let span = params.ceiling - knee;
+    return knee + span * tanh((val - knee) / span);
 }
 
 @compute @workgroup_size(16, 16, 1)
@@ -65,8 +122,8 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         let tau = params.terms[term].param1;
         let factor = exp(-params.dt / tau);
         let val = load_accum(coord.x, coord.y, term);
-        let decayed = val * factor;
-        store_accum(coord.x, coord.y, term,
+        let decayed = soft_limit(val * factor);
+        store_decayed(coord.x, coord.y, term,
             select(decayed, 0.0, decayed < threshold));
     }
 
@@ -75,12 +132,25 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         let pl_peak_layer = params.slow_exp_count;
         let time_layer = pl_peak_layer + 1u;
 
-        var elapsed = load_accum(coord.x, coord.y, time_layer);
-        elapsed += params.dt;
-        store_accum(coord.x, coord.y, time_layer, elapsed);
+        let prev_elapsed = load_accum(coord.x, coord.y, time_layer);
+        store_accum(coord.x, coord.y, time_layer, prev_elapsed + params.dt);
+        let elapsed = load_accum(coord.x, coord.y, time_layer);
 
         // Threshold dead texels to save compute
-        let peak = load_accum(coord.x, coord.y, pl_peak_layer);
+        var peak = load_accum(coord.x, coord.y, pl_peak_layer);
+        if peak > 0.5 * params.ceiling {
+            peak = soft_limit(peak);
+            store_decayed(coord.x, coord.y, pl_peak_layer, peak);
+        }
+        // Packed f16 time stops advancing once dt is under half a step
+        // (tens of seconds in); treat the afterglow as spent rather than
+        // hold it at that brightness forever. A zero dt (a paused or idle
+        // frame) is not a stall, and f32 time never stalls.
+        if accum_dims.packed_f16 == 1u && params.dt > 0.0
+            && peak > 0.0 && elapsed == prev_elapsed {
+            store_accum(coord.x, coord.y, pl_peak_layer, 0.0);
+            peak = 0.0;
+        }
         if peak > 0.0 {
             // Find the power-law term (first one with type_flag == 1.0)
             for (var i = 0u; i < params.term_count; i++) {
@@ -1,11 +1,21 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt;
 
 use super::accumulation::HdrBuffer;
+use super::uniform::UniformBuffer;
 use crate::types::Resolution;
 
-/// Half-resolution texture pair for ping-pong faceplate_scatter blur.
-pub struct FaceplateScatterTextures {
+/// Maximum number of levels in the scatter downsample chain.
+pub const MAX_SCATTER_LEVELS: u32 = 4;
+
+/// Resolution of scatter level `level` for a full-resolution buffer `full`.
+/// Level 0 is half resolution; each further level halves again.
+pub fn level_resolution(full: Resolution, level: u32) -> Resolution {
+    let shift = level + 1;
+    Resolution::new((full.width >> shift).max(1), (full.height >> shift).max(1))
+}
+
+/// Texture pair for ping-pong blur at one level of the scatter chain.
+pub struct ScatterLevel {
     #[allow(dead_code)] // kept alive for view_a
     pub tex_a: wgpu::Texture,
     pub view_a: wgpu::TextureView,
@@ -15,30 +25,31 @@ pub struct FaceplateScatterTextures {
     pub resolution: Resolution,
 }
 
-impl FaceplateScatterTextures {
-    pub fn new(device: &wgpu::Device, full: Resolution) -> Self {
-        let resolution = Resolution::new((full.width / 2).max(1), (full.height / 2).max(1));
-
-        let create = |label| {
-            device.create_texture(&wgpu::TextureDescriptor {
-                label: Some(label),
-                size: wgpu::Extent3d {
-                    width: resolution.width,
-                    height: resolution.height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba16Float,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            })
-        };
+fn create_scatter_texture(
+    device: &wgpu::Device,
+    resolution: Resolution,
+    label: &str,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: resolution.width,
+            height: resolution.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba16Float,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
 
-        let tex_a = create("faceplate_scatter_a");
-        let tex_b = create("faceplate_scatter_b");
+impl ScatterLevel {
+    fn new(device: &wgpu::Device, resolution: Resolution) -> Self {
+        let tex_a = create_scatter_texture(device, resolution, "faceplate_scatter_a");
+        let tex_b = create_scatter_texture(device, resolution, "faceplate_scatter_b");
         let view_a = tex_a.create_view(&wgpu::TextureViewDescriptor::default());
         let view_b = tex_b.create_view(&wgpu::TextureViewDescriptor::default());
 
@@ -50,13 +61,109 @@ impl FaceplateScatterTextures {
             resolution,
         }
     }
+}
+
+/// Downsample chain of ping-pong texture pairs for the faceplate scatter.
+///
+/// Level 0 is half resolution and holds the frame's result in `view_a`.
+/// Memory cost: level 0 is two Rgba16Float textures at `w/2 × h/2`
+/// (`w × h × 4` bytes total); each deeper level is a quarter of the one
+/// above, so the full chain stays under 4/3 of level 0. Temporal scatter
+/// adds one more level-0-sized texture.
+pub struct FaceplateScatterTextures {
+    pub levels: Vec<ScatterLevel>,
+    /// Persistent level-0-sized history for temporal scatter, allocated
+    /// only while it is enabled.
+    history: Option<(wgpu::Texture, wgpu::TextureView)>,
+    full: Resolution,
+}
+
+impl FaceplateScatterTextures {
+    pub fn new(device: &wgpu::Device, full: Resolution) -> Self {
+        Self::with_levels(device, full, 1)
+    }
+
+    fn with_levels(device: &wgpu::Device, full: Resolution, levels: u32) -> Self {
+        let levels = (0..levels.clamp(1, MAX_SCATTER_LEVELS))
+            .map(|level| ScatterLevel::new(device, level_resolution(full, level)))
+            .collect();
+        Self {
+            levels,
+            history: None,
+            full,
+        }
+    }
 
+    fn create_history(
+        device: &wgpu::Device,
+        full: Resolution,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let tex = create_scatter_texture(
+            device,
+            level_resolution(full, 0),
+            "faceplate_scatter_history",
+        );
+        let view = tex.create_view(&wgpu::TextureViewDescriptor::default());
+        (tex, view)
+    }
+
+    /// Total size of every level's texture pair plus the temporal history
+    /// (Rgba16Float: 8 bytes per texel).
+    pub fn byte_size(&self) -> u64 {
+        let level0 = self.levels[0].resolution;
+        let history = if self.history.is_some() {
+            8 * (level0.width as u64) * (level0.height as u64)
+        } else {
+            0
+        };
+        history
+            + self
+                .levels
+                .iter()
+                .map(|level| {
+                    2 * 8 * (level.resolution.width as u64) * (level.resolution.height as u64)
+                })
+                .sum::<u64>()
+    }
+
+    /// The blurred scatter result sampled by the composite: the temporal
+    /// history when enabled, otherwise this frame's level 0.
+    pub fn output(&self) -> &wgpu::TextureView {
+        match &self.history {
+            Some((_, view)) => view,
+            None => &self.levels[0].view_a,
+        }
+    }
+
+    /// Reallocate for a new buffer size. The temporal history is recreated
+    /// too, so it restarts from black rather than stretching a stale glow.
     pub fn resize(&mut self, device: &wgpu::Device, full: Resolution) {
-        let half = Resolution::new((full.width / 2).max(1), (full.height / 2).max(1));
-        if half == self.resolution {
+        if level_resolution(full, 0) == self.levels[0].resolution {
             return;
         }
-        *self = Self::new(device, full);
+        let temporal = self.history.is_some();
+        *self = Self::with_levels(device, full, self.levels.len() as u32);
+        self.set_temporal(device, temporal);
+    }
+
+    /// Reallocate the chain if `levels` differs from the current depth.
+    pub fn set_levels(&mut self, device: &wgpu::Device, levels: u32) {
+        let levels = levels.clamp(1, MAX_SCATTER_LEVELS);
+        if levels as usize != self.levels.len() {
+            let temporal = self.history.is_some();
+            *self = Self::with_levels(device, self.full, levels);
+            self.set_temporal(device, temporal);
+        }
+    }
+
+    /// Allocate or free the temporal history. A new history starts black
+    /// (wgpu zero-initializes textures).
+    pub fn set_temporal(&mut self, device: &wgpu::Device, temporal: bool) {
+        match (temporal, self.history.is_some()) {
+            (true, false) => self.history = Some(Self::create_history(device, self.full)),
+            (false, true) => self.history = None,
+            _ => {}
+        }
     }
 }
 
@@ -69,6 +176,17 @@ pub struct DownsampleParams {
     _pad2: f32,
 }
 
+impl DownsampleParams {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            _pad0: 0.0,
+            _pad1: 0.0,
+            _pad2: 0.0,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct BlurParams {
@@ -77,14 +195,64 @@ pub struct BlurParams {
     _pad: f32,
 }
 
+impl BlurParams {
+    pub fn new(direction: [f32; 2], sigma: f32) -> Self {
+        Self {
+            direction,
+            sigma,
+            _pad: 0.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct TemporalParams {
+    pub persistence: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+}
+
+impl TemporalParams {
+    pub fn new(persistence: f32) -> Self {
+        Self {
+            persistence,
+            _pad0: 0.0,
+            _pad1: 0.0,
+            _pad2: 0.0,
+        }
+    }
+}
+
 /// User-facing faceplate_scatter parameters.
 pub struct FaceplateScatterParams {
     /// Luminance threshold below which pixels don't scatter.
+    ///
+    /// Compared against CIE Y, not per-channel RGB: spectral resolve writes
+    /// the ȳ-weighted band sum into the HDR alpha channel and the downsample
+    /// thresholds on that, so a deep-blue phosphor scatters exactly as
+    /// readily as a green one of the same perceived brightness. No separate
+    /// Rec. 709 coefficients are involved.
     pub threshold: f32,
     /// Blur sigma in texels at half resolution.
     pub sigma: f32,
     /// Intensity of the faceplate_scatter effect added to the image.
     pub intensity: f32,
+    /// Depth of the downsample chain (1..=`MAX_SCATTER_LEVELS`). Each extra
+    /// level blurs at half the previous resolution and is added back on the
+    /// way up, widening the glow; 1 is a single half-res blur.
+    pub levels: u32,
+    /// Keep the scatter result across frames instead of clearing it: each
+    /// frame's scatter is added to the previous result scaled by
+    /// `persistence`, for a sustained glow. The history is freed when off or
+    /// while the passes are skipped (see [`Self::should_run`]), and cleared
+    /// whenever the buffers are resized.
+    pub temporal: bool,
+    /// Fraction of the previous frame's scatter kept each frame (0..1).
+    /// A steady image settles at `1 / (1 - persistence)` times its
+    /// per-frame scatter.
+    pub persistence: f32,
 }
 
 impl Default for FaceplateScatterParams {
@@ -93,20 +261,56 @@ impl Default for FaceplateScatterParams {
             threshold: 0.5,
             sigma: 4.0,
             intensity: 0.15,
+            levels: 1,
+            temporal: false,
+            persistence: 0.5,
         }
     }
 }
 
+impl FaceplateScatterParams {
+    /// Whether the scatter passes contribute anything. At zero intensity the
+    /// composite adds nothing from them, so they can be skipped outright.
+    pub fn should_run(&self) -> bool {
+        self.intensity > 0.0
+    }
+}
+
 pub struct FaceplateScatterPipeline {
     downsample_pipeline: wgpu::RenderPipeline,
+    /// Level 0's downsample, which reads the Rgba32Float HDR buffer and so
+    /// has its own texture layout and sampler (see `new`).
+    hdr_downsample_pipeline: wgpu::RenderPipeline,
+    hdr_texture_layout: wgpu::BindGroupLayout,
+    hdr_sampler: wgpu::Sampler,
     blur_pipeline: wgpu::RenderPipeline,
-    params_layout: wgpu::BindGroupLayout,
+    upsample_pipeline: wgpu::RenderPipeline,
+    temporal_pipeline: wgpu::RenderPipeline,
     texture_layout: wgpu::BindGroupLayout,
     linear_sampler: wgpu::Sampler,
+    // One uniform per pass: the H and V blurs run in the same frame with
+    // different directions, so they can't share a buffer.
+    downsample_params: UniformBuffer<DownsampleParams>,
+    blur_h_params: UniformBuffer<BlurParams>,
+    blur_v_params: UniformBuffer<BlurParams>,
+    temporal_params: UniformBuffer<TemporalParams>,
+    temporal_params_bg: wgpu::BindGroup,
+    downsample_params_bg: wgpu::BindGroup,
+    // Deeper levels downsample the already-thresholded result, so they use a
+    // fixed zero threshold. Written once at creation.
+    #[allow(dead_code)] // kept alive for chain_downsample_params_bg
+    chain_downsample_params: UniformBuffer<DownsampleParams>,
+    chain_downsample_params_bg: wgpu::BindGroup,
+    blur_h_params_bg: wgpu::BindGroup,
+    blur_v_params_bg: wgpu::BindGroup,
 }
 
 impl FaceplateScatterPipeline {
-    pub fn new(device: &wgpu::Device) -> Self {
+    /// `hdr_filterable` says whether the device can filter the Rgba32Float
+    /// HDR buffer (`FLOAT32_FILTERABLE`). Without it, level 0 downsamples
+    /// with point sampling: one texel per 2×2 block instead of their
+    /// average. The deeper Rgba16Float passes are always filtered.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, hdr_filterable: bool) -> Self {
         let faceplate_scatter_format = wgpu::TextureFormat::Rgba16Float;
 
         // Shared bind group layouts — both passes use the same pattern:
@@ -125,27 +329,37 @@ impl FaceplateScatterPipeline {
             }],
         });
 
-        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("faceplate_scatter_texture"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
+        let texture_layout_for = |label, filterable| {
+            let sampler_type = if filterable {
+                wgpu::SamplerBindingType::Filtering
+            } else {
+                wgpu::SamplerBindingType::NonFiltering
+            };
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(label),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
                     },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-        });
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(sampler_type),
+                        count: None,
+                    },
+                ],
+            })
+        };
+        let texture_layout = texture_layout_for("faceplate_scatter_texture", true);
+        let hdr_texture_layout =
+            texture_layout_for("faceplate_scatter_hdr_texture", hdr_filterable);
 
         let linear_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("faceplate_scatter_linear"),
@@ -155,12 +369,27 @@ impl FaceplateScatterPipeline {
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             ..Default::default()
         });
+        let hdr_sampler = if hdr_filterable {
+            linear_sampler.clone()
+        } else {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("faceplate_scatter_hdr_nearest"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                ..Default::default()
+            })
+        };
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("faceplate_scatter"),
             bind_group_layouts: &[&params_layout, &texture_layout],
             push_constant_ranges: &[],
         });
+        let hdr_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("faceplate_scatter_hdr"),
+            bind_group_layouts: &[&params_layout, &hdr_texture_layout],
+            push_constant_ranges: &[],
+        });
 
         // Downsample pipeline
         let downsample_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -170,11 +399,52 @@ impl FaceplateScatterPipeline {
             ),
         });
 
-        let downsample_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("faceplate_scatter_downsample"),
+        let downsample_pipeline_for = |label, layout| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &downsample_shader,
+                    entry_point: Some("vs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &downsample_shader,
+                    entry_point: Some("fs_main"),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: faceplate_scatter_format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let downsample_pipeline =
+            downsample_pipeline_for("faceplate_scatter_downsample", &pipeline_layout);
+        let hdr_downsample_pipeline =
+            downsample_pipeline_for("faceplate_scatter_hdr_downsample", &hdr_pipeline_layout);
+
+        // Blur pipeline (same layout, different shader)
+        let blur_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("faceplate_scatter_blur"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("faceplate_scatter_blur.wgsl").into()),
+        });
+
+        let blur_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("faceplate_scatter_blur"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &downsample_shader,
+                module: &blur_shader,
                 entry_point: Some("vs_main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
                 buffers: &[],
@@ -186,7 +456,7 @@ impl FaceplateScatterPipeline {
             depth_stencil: None,
             multisample: wgpu::MultisampleState::default(),
             fragment: Some(wgpu::FragmentState {
-                module: &downsample_shader,
+                module: &blur_shader,
                 entry_point: Some("fs_main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
                 targets: &[Some(wgpu::ColorTargetState {
@@ -199,17 +469,73 @@ impl FaceplateScatterPipeline {
             cache: None,
         });
 
-        // Blur pipeline (same layout, different shader)
-        let blur_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("faceplate_scatter_blur"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("faceplate_scatter_blur.wgsl").into()),
+        // Upsample pipeline: additively blends a deeper level into the one
+        // above it. Only needs the texture group.
+        let upsample_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("faceplate_scatter_upsample"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("faceplate_scatter_upsample.wgsl").into(),
+            ),
         });
 
-        let blur_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("faceplate_scatter_blur"),
+        let upsample_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("faceplate_scatter_upsample"),
+            bind_group_layouts: &[&texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        let upsample_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("faceplate_scatter_upsample"),
+            layout: Some(&upsample_layout),
+            vertex: wgpu::VertexState {
+                module: &upsample_shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &upsample_shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: faceplate_scatter_format,
+                    blend: Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        // Temporal pipeline: folds the frame's result into the history,
+        // src * One + dst * SrcAlpha with alpha = persistence.
+        let temporal_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("faceplate_scatter_temporal"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("faceplate_scatter_temporal.wgsl").into(),
+            ),
+        });
+
+        let temporal_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("faceplate_scatter_temporal"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &blur_shader,
+                module: &temporal_shader,
                 entry_point: Some("vs_main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
                 buffers: &[],
@@ -221,12 +547,19 @@ impl FaceplateScatterPipeline {
             depth_stencil: None,
             multisample: wgpu::MultisampleState::default(),
             fragment: Some(wgpu::FragmentState {
-                module: &blur_shader,
+                module: &temporal_shader,
                 entry_point: Some("fs_main"),
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: faceplate_scatter_format,
-                    blend: None,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::One,
+                            dst_factor: wgpu::BlendFactor::SrcAlpha,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent::REPLACE,
+                    }),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -234,87 +567,187 @@ impl FaceplateScatterPipeline {
             cache: None,
         });
 
+        let downsample_params = UniformBuffer::new(device, "faceplate_scatter_downsample_params");
+        let blur_h_params = UniformBuffer::new(device, "faceplate_scatter_blur_h_params");
+        let blur_v_params = UniformBuffer::new(device, "faceplate_scatter_blur_v_params");
+        let downsample_params_bg = downsample_params.bind_group(
+            device,
+            &params_layout,
+            "faceplate_scatter_downsample_params",
+        );
+        let chain_downsample_params =
+            UniformBuffer::new(device, "faceplate_scatter_chain_downsample_params");
+        chain_downsample_params.write(queue, &DownsampleParams::new(0.0));
+        let chain_downsample_params_bg = chain_downsample_params.bind_group(
+            device,
+            &params_layout,
+            "faceplate_scatter_chain_downsample_params",
+        );
+        let temporal_params = UniformBuffer::new(device, "faceplate_scatter_temporal_params");
+        let temporal_params_bg =
+            temporal_params.bind_group(device, &params_layout, "faceplate_scatter_temporal_params");
+        let blur_h_params_bg =
+            blur_h_params.bind_group(device, &params_layout, "faceplate_scatter_blur_h_params");
+        let blur_v_params_bg =
+            blur_v_params.bind_group(device, &params_layout, "faceplate_scatter_blur_v_params");
+
         Self {
             downsample_pipeline,
+            hdr_downsample_pipeline,
+            hdr_texture_layout,
+            hdr_sampler,
             blur_pipeline,
-            params_layout,
+            upsample_pipeline,
+            temporal_pipeline,
             texture_layout,
             linear_sampler,
+            downsample_params,
+            blur_h_params,
+            blur_v_params,
+            temporal_params,
+            temporal_params_bg,
+            downsample_params_bg,
+            chain_downsample_params,
+            chain_downsample_params_bg,
+            blur_h_params_bg,
+            blur_v_params_bg,
         }
     }
 
-    /// Run all faceplate_scatter passes: downsample HDR → blur H → blur V.
-    /// Result ends up in `textures.view_a`.
+    /// Run all faceplate_scatter passes. For each level: downsample (the HDR
+    /// buffer with threshold for level 0, the level above otherwise) → blur
+    /// H → blur V. Then walk back up, additively upsampling each level into
+    /// the one above. With temporal scatter, level 0 is then blended into
+    /// the history. Result ends up in `textures.output()`.
+    ///
+    /// `textures` must already have `params.levels` levels and a history
+    /// matching `params.temporal` (see [`FaceplateScatterTextures::set_levels`]
+    /// and [`FaceplateScatterTextures::set_temporal`]).
     pub fn render(
         &self,
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         encoder: &mut wgpu::CommandEncoder,
         hdr: &HdrBuffer,
         textures: &FaceplateScatterTextures,
         params: &FaceplateScatterParams,
     ) {
-        // Pass 1: Downsample HDR → faceplate_scatter_a
-        self.render_downsample(
-            device,
-            encoder,
-            &hdr.view,
-            &textures.view_a,
-            params.threshold,
+        self.downsample_params
+            .write(queue, &DownsampleParams::new(params.threshold));
+        self.blur_h_params
+            .write(queue, &BlurParams::new([1.0, 0.0], params.sigma));
+        self.blur_v_params
+            .write(queue, &BlurParams::new([0.0, 1.0], params.sigma));
+        self.temporal_params.write(
+            queue,
+            &TemporalParams::new(params.persistence.clamp(0.0, 0.99)),
         );
 
-        // Pass 2: Blur horizontal faceplate_scatter_a → faceplate_scatter_b
-        self.render_blur(
-            device,
-            encoder,
-            &textures.view_a,
-            &textures.view_b,
-            [1.0, 0.0],
-            params.sigma,
-        );
+        let mut src = &hdr.view;
+        let mut params_bg = &self.downsample_params_bg;
+        for (i, level) in textures.levels.iter().enumerate() {
+            // Downsample previous level (or HDR) → a
+            self.render_downsample(device, encoder, src, i == 0, &level.view_a, params_bg);
+
+            // Blur horizontal a → b, then vertical b → a
+            self.render_blur(
+                device,
+                encoder,
+                &level.view_a,
+                &level.view_b,
+                &self.blur_h_params_bg,
+            );
+            self.render_blur(
+                device,
+                encoder,
+                &level.view_b,
+                &level.view_a,
+                &self.blur_v_params_bg,
+            );
+
+            src = &level.view_a;
+            params_bg = &self.chain_downsample_params_bg;
+        }
 
-        // Pass 3: Blur vertical faceplate_scatter_b → faceplate_scatter_a
-        self.render_blur(
-            device,
-            encoder,
-            &textures.view_b,
-            &textures.view_a,
-            [0.0, 1.0],
-            params.sigma,
-        );
+        // Upsample-and-combine from the deepest level back to level 0
+        for pair in textures.levels.windows(2).rev() {
+            self.render_upsample(device, encoder, &pair[1].view_a, &pair[0].view_a);
+        }
+
+        if let Some((_, history)) = &textures.history {
+            self.render_temporal(device, encoder, &textures.levels[0].view_a, history);
+        }
     }
 
-    fn render_downsample(
+    fn render_temporal(
         &self,
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
         src: &wgpu::TextureView,
-        dst: &wgpu::TextureView,
-        threshold: f32,
+        history: &wgpu::TextureView,
     ) {
-        let uniforms = DownsampleParams {
-            threshold,
-            _pad0: 0.0,
-            _pad1: 0.0,
-            _pad2: 0.0,
-        };
-        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("faceplate_scatter_downsample_params"),
-            contents: bytemuck::bytes_of(&uniforms),
-            usage: wgpu::BufferUsages::UNIFORM,
+        let texture_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("faceplate_scatter_temporal_texture"),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.linear_sampler),
+                },
+            ],
         });
 
-        let params_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("faceplate_scatter_downsample_params"),
-            layout: &self.params_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: params_buffer.as_entire_binding(),
-            }],
+        // Load, not clear: the previous frames' scatter is the point.
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("faceplate_scatter_temporal"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: history,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            ..Default::default()
         });
 
+        pass.set_pipeline(&self.temporal_pipeline);
+        pass.set_bind_group(0, &self.temporal_params_bg, &[]);
+        pass.set_bind_group(1, &texture_bg, &[]);
+        pass.draw(0..3, 0..1);
+    }
+
+    fn render_downsample(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &wgpu::TextureView,
+        src_is_hdr: bool,
+        dst: &wgpu::TextureView,
+        params_bg: &wgpu::BindGroup,
+    ) {
+        let (pipeline, layout, sampler) = if src_is_hdr {
+            (
+                &self.hdr_downsample_pipeline,
+                &self.hdr_texture_layout,
+                &self.hdr_sampler,
+            )
+        } else {
+            (
+                &self.downsample_pipeline,
+                &self.texture_layout,
+                &self.linear_sampler,
+            )
+        };
         let texture_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("faceplate_scatter_downsample_texture"),
-            layout: &self.texture_layout,
+            layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -322,7 +755,7 @@ impl FaceplateScatterPipeline {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.linear_sampler),
+                    resource: wgpu::BindingResource::Sampler(sampler),
                 },
             ],
         });
@@ -342,8 +775,8 @@ impl FaceplateScatterPipeline {
             ..Default::default()
         });
 
-        pass.set_pipeline(&self.downsample_pipeline);
-        pass.set_bind_group(0, &params_bg, &[]);
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, params_bg, &[]);
         pass.set_bind_group(1, &texture_bg, &[]);
         pass.draw(0..3, 0..1);
     }
@@ -354,29 +787,8 @@ impl FaceplateScatterPipeline {
         encoder: &mut wgpu::CommandEncoder,
         src: &wgpu::TextureView,
         dst: &wgpu::TextureView,
-        direction: [f32; 2],
-        sigma: f32,
+        params_bg: &wgpu::BindGroup,
     ) {
-        let uniforms = BlurParams {
-            direction,
-            sigma,
-            _pad: 0.0,
-        };
-        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("faceplate_scatter_blur_params"),
-            contents: bytemuck::bytes_of(&uniforms),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
-        let params_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("faceplate_scatter_blur_params"),
-            layout: &self.params_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: params_buffer.as_entire_binding(),
-            }],
-        });
-
         let texture_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("faceplate_scatter_blur_texture"),
             layout: &self.texture_layout,
@@ -408,8 +820,83 @@ impl FaceplateScatterPipeline {
         });
 
         pass.set_pipeline(&self.blur_pipeline);
-        pass.set_bind_group(0, &params_bg, &[]);
+        pass.set_bind_group(0, params_bg, &[]);
         pass.set_bind_group(1, &texture_bg, &[]);
         pass.draw(0..3, 0..1);
     }
+
+    fn render_upsample(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src: &wgpu::TextureView,
+        dst: &wgpu::TextureView,
+    ) {
+        let texture_bg = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("faceplate_scatter_upsample_texture"),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(src),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.linear_sampler),
+                },
+            ],
+        });
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("faceplate_scatter_upsample"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: dst,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            ..Default::default()
+        });
+
+        pass.set_pipeline(&self.upsample_pipeline);
+        pass.set_bind_group(0, &texture_bg, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_intensity(intensity: f32) -> FaceplateScatterParams {
+        FaceplateScatterParams {
+            intensity,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn level_resolutions_halve_down_the_chain() {
+        let full = Resolution::new(1920, 1080);
+        assert_eq!(level_resolution(full, 0), Resolution::new(960, 540));
+        assert_eq!(level_resolution(full, 1), Resolution::new(480, 270));
+        assert_eq!(level_resolution(full, 2), Resolution::new(240, 135));
+        // Never collapses to zero for tiny buffers
+        assert_eq!(
+            level_resolution(Resolution::new(4, 2), 3),
+            Resolution::new(1, 1)
+        );
+    }
+
+    #[test]
+    fn scatter_runs_only_with_positive_intensity() {
+        assert!(FaceplateScatterParams::default().should_run());
+        assert!(with_intensity(1e-4).should_run());
+        assert!(!with_intensity(0.0).should_run());
+        assert!(!with_intensity(-0.5).should_run());
+    }
 }
@@ -36,7 +36,10 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let avg = textureSample(hdr_texture, hdr_sampler, uv);
 
     let rgb = avg.rgb;
-    let luminance = avg.a; // CIE Y from spectral resolve
+    // Threshold on perceived luminance, not per-channel color: alpha is CIE Y
+    // (ȳ-weighted band sum) from spectral resolve. The full color passes
+    // through, scaled by the fraction of Y above threshold.
+    let luminance = avg.a;
 
     // Soft threshold: extract only the bright portion
     if luminance <= 0.0 {
@@ -45,5 +48,7 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let contribution = max(luminance - params.threshold, 0.0);
     let scale = contribution / luminance;
 
-    return vec4<f32>(rgb * scale, 0.0);
+    // Alpha carries the scaled luminance so deeper levels of the chain can
+    // re-run this shader (with threshold 0) as a plain 2x downsample.
+    return vec4<f32>(rgb * scale, contribution);
 }
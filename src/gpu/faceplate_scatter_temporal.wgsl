@@ -0,0 +1,38 @@
+// Faceplate Scatter Temporal Shader
+//
+// Folds this frame's scatter result into a persistent history texture for
+// a sustained glow. The history target is loaded, not cleared, and blended
+// with src * One + dst * SrcAlpha, so writing `persistence` to alpha makes
+// history = current + history * persistence.
+
+struct TemporalParams {
+    persistence: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: TemporalParams;
+@group(1) @binding(0) var src_texture: texture_2d<f32>;
+@group(1) @binding(1) var src_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32(vi & 1u) * 4.0 - 1.0;
+    let y = f32((vi >> 1u) & 1u) * 4.0 - 1.0;
+    out.position = vec4<f32>(x, y, 0.0, 1.0);
+    out.uv = vec2<f32>(x * 0.5 + 0.5, 0.5 - y * 0.5);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let current = textureSample(src_texture, src_sampler, in.uv).rgb;
+    return vec4<f32>(current, params.persistence);
+}
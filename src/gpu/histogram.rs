@@ -0,0 +1,293 @@
+use bytemuck::{Pod, Zeroable};
+
+use super::accumulation::HdrBuffer;
+use super::readback::Readback;
+use super::uniform::UniformBuffer;
+
+/// Number of histogram bins. Must match `BINS` in `histogram.wgsl`.
+pub const HISTOGRAM_BINS: usize = 64;
+/// Lower edge of the histogram in stops (log2 of exposed luminance).
+pub const HISTOGRAM_MIN_EV: f32 = -12.0;
+/// Upper edge of the histogram in stops.
+pub const HISTOGRAM_MAX_EV: f32 = 4.0;
+/// Exposed luminance of 1.0 is display white: anything above it is clipped
+/// by the clamp tonemapper and compressed by the others.
+pub const CLIP_EV: f32 = 0.0;
+
+const HISTOGRAM_BYTES: u64 = (HISTOGRAM_BINS * std::mem::size_of::<u32>()) as u64;
+
+/// Bin index for an exposed luminance, mirroring the shader's bucketing.
+/// Values outside the range land in the end bins; non-positive (unlit) and
+/// NaN values are not counted.
+pub fn luminance_bin(luminance: f32) -> Option<usize> {
+    if luminance.is_nan() || luminance <= 0.0 {
+        return None;
+    }
+    let t = (luminance.log2() - HISTOGRAM_MIN_EV) / (HISTOGRAM_MAX_EV - HISTOGRAM_MIN_EV);
+    let bin = (t * HISTOGRAM_BINS as f32).clamp(0.0, (HISTOGRAM_BINS - 1) as f32);
+    Some(bin as usize)
+}
+
+/// Lower edge of `bin` in stops.
+pub fn bin_ev(bin: usize) -> f32 {
+    HISTOGRAM_MIN_EV + (HISTOGRAM_MAX_EV - HISTOGRAM_MIN_EV) * bin as f32 / HISTOGRAM_BINS as f32
+}
+
+/// Pixel counts per log-luminance bin, as read back from the GPU.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LuminanceHistogram {
+    pub counts: [u32; HISTOGRAM_BINS],
+}
+
+impl LuminanceHistogram {
+    /// Number of lit pixels counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|&c| c as u64).sum()
+    }
+
+    /// Fraction of lit pixels at or above the clip point.
+    pub fn clipped_fraction(&self) -> f32 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        let clipped: u64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|&(bin, _)| bin_ev(bin) >= CLIP_EV)
+            .map(|(_, &c)| c as u64)
+            .sum();
+        clipped as f32 / total as f32
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct HistogramParams {
+    pub exposure: f32,
+    pub min_ev: f32,
+    pub max_ev: f32,
+    pub _pad: f32,
+}
+
+impl HistogramParams {
+    pub fn new(exposure: f32) -> Self {
+        Self {
+            exposure,
+            min_ev: HISTOGRAM_MIN_EV,
+            max_ev: HISTOGRAM_MAX_EV,
+            _pad: 0.0,
+        }
+    }
+}
+
+/// Builds a histogram of the HDR buffer's exposed luminance and reads it
+/// back without stalling. Only the most recent completed result is kept.
+pub struct HistogramPipeline {
+    pipeline: wgpu::ComputePipeline,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    params: UniformBuffer<HistogramParams>,
+    params_bind_group: wgpu::BindGroup,
+    bins_buffer: wgpu::Buffer,
+    readback: Readback<()>,
+    latest: Option<LuminanceHistogram>,
+}
+
+impl HistogramPipeline {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("histogram"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("histogram.wgsl").into()),
+        });
+
+        let params_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("histogram_params"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("histogram_textures"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("histogram"),
+            bind_group_layouts: &[&params_bind_group_layout, &texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("histogram"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let params = UniformBuffer::new(device, "histogram_params");
+        let params_bind_group =
+            params.bind_group(device, &params_bind_group_layout, "histogram_params");
+
+        let bins_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("histogram_bins"),
+            size: HISTOGRAM_BYTES,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            pipeline,
+            texture_bind_group_layout,
+            params,
+            params_bind_group,
+            bins_buffer,
+            readback: Readback::new(device, "histogram_read", HISTOGRAM_BYTES),
+            latest: None,
+        }
+    }
+
+    /// Most recent histogram the GPU has finished, if any.
+    pub fn latest(&self) -> Option<LuminanceHistogram> {
+        self.latest
+    }
+
+    /// Collect any finished readbacks without blocking. Call once per frame
+    /// before recording a new dispatch.
+    pub fn read_back(&mut self, device: &wgpu::Device) {
+        let latest = &mut self.latest;
+        self.readback.poll(device, |bytes, ()| {
+            let mut counts = [0u32; HISTOGRAM_BINS];
+            counts.copy_from_slice(bytemuck::cast_slice(bytes));
+            *latest = Some(LuminanceHistogram { counts });
+        });
+    }
+
+    /// Bin `hdr` at the given exposure and copy the result out for reading
+    /// on a later frame. Skipped if both read buffers are still in flight.
+    pub fn dispatch(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        hdr: &HdrBuffer,
+        exposure: f32,
+    ) {
+        let Some(read_buffer) = self.readback.claim(()) else {
+            return;
+        };
+
+        self.params.write(queue, &HistogramParams::new(exposure));
+
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("histogram_textures"),
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&hdr.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.bins_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        encoder.clear_buffer(&self.bins_buffer, 0, None);
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("histogram"),
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.params_bind_group, &[]);
+            pass.set_bind_group(1, &texture_bind_group, &[]);
+            pass.dispatch_workgroups(
+                hdr.resolution.width.div_ceil(16),
+                hdr.resolution.height.div_ceil(16),
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&self.bins_buffer, 0, read_buffer, 0, HISTOGRAM_BYTES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlit_and_nan_pixels_are_skipped() {
+        assert_eq!(luminance_bin(0.0), None);
+        assert_eq!(luminance_bin(-1.0), None);
+        assert_eq!(luminance_bin(f32::NAN), None);
+    }
+
+    #[test]
+    fn out_of_range_luminance_lands_in_end_bins() {
+        assert_eq!(luminance_bin(1e-9), Some(0));
+        assert_eq!(luminance_bin(1e6), Some(HISTOGRAM_BINS - 1));
+        assert_eq!(luminance_bin(f32::INFINITY), Some(HISTOGRAM_BINS - 1));
+    }
+
+    #[test]
+    fn bins_are_quarter_stops_with_clip_on_an_edge() {
+        let clip_bin = luminance_bin(1.0).unwrap();
+        assert_eq!(bin_ev(clip_bin), CLIP_EV);
+        // Just under white is one bin down; a quarter stop over is one up.
+        assert_eq!(luminance_bin(0.99), Some(clip_bin - 1));
+        assert_eq!(luminance_bin(2f32.powf(0.25)), Some(clip_bin + 1));
+        assert_eq!(luminance_bin(2f32.powi(-12)), Some(0));
+    }
+
+    #[test]
+    fn clipped_fraction_counts_bins_from_white_up() {
+        let mut counts = [0u32; HISTOGRAM_BINS];
+        counts[luminance_bin(0.5).unwrap()] = 3;
+        counts[luminance_bin(1.0).unwrap()] = 1;
+        assert_eq!(LuminanceHistogram { counts }.clipped_fraction(), 0.25);
+        assert_eq!(
+            LuminanceHistogram {
+                counts: [0; HISTOGRAM_BINS]
+            }
+            .clipped_fraction(),
+            0.0
+        );
+    }
+}
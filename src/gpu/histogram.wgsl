@@ -0,0 +1,56 @@
+// Luminance Histogram Compute Shader
+//
+// Bins the exposed CIE Y luminance of every HDR pixel (alpha channel of the
+// spectral resolve output) on a log2 scale, for the engineer panel's
+// exposure meter. Each workgroup accumulates into shared memory first so
+// the global atomics see one add per bin per workgroup instead of one per
+// pixel. Pixels at zero luminance are skipped: the unlit background would
+// otherwise swamp every other bin.
+
+// Must match HISTOGRAM_BINS in histogram.rs.
+const BINS: u32 = 64u;
+
+struct HistogramParams {
+    exposure: f32,
+    min_ev: f32,
+    max_ev: f32,
+    _pad: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: HistogramParams;
+
+@group(1) @binding(0) var hdr_texture: texture_2d<f32>;
+@group(1) @binding(1) var<storage, read_write> bins: array<atomic<u32>, BINS>;
+
+var<workgroup> local_bins: array<atomic<u32>, BINS>;
+
+@compute @workgroup_size(16, 16)
+fn main(
+    @builtin(global_invocation_id) gid: vec3<u32>,
+    @builtin(local_invocation_index) lid: u32,
+) {
+    if lid < BINS {
+        atomicStore(&local_bins[lid], 0u);
+    }
+    workgroupBarrier();
+
+    let dims = textureDimensions(hdr_texture);
+    if gid.x < dims.x && gid.y < dims.y {
+        let luminance = textureLoad(hdr_texture, vec2<i32>(gid.xy), 0).a * params.exposure;
+        if luminance > 0.0 {
+            // Out-of-range values land in the end bins, so clipped pixels
+            // are still counted.
+            let t = (log2(luminance) - params.min_ev) / (params.max_ev - params.min_ev);
+            let bin = u32(clamp(t * f32(BINS), 0.0, f32(BINS - 1u)));
+            atomicAdd(&local_bins[bin], 1u);
+        }
+    }
+    workgroupBarrier();
+
+    if lid < BINS {
+        let count = atomicLoad(&local_bins[lid]);
+        if count > 0u {
+            atomicAdd(&bins[lid], count);
+        }
+    }
+}
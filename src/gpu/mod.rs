@@ -1,12 +1,21 @@
 pub mod accumulation;
+pub mod adapter;
 pub mod beam_write;
+pub mod compare;
 pub mod composite;
 pub mod decay;
 pub mod faceplate_scatter;
+pub mod histogram;
+pub mod phosphor_params;
 pub mod profiler;
+pub mod readback;
+#[cfg(test)]
+mod render_tests;
 pub mod spectral_resolve;
+pub mod uniform;
 
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::phosphor::spectral::SPECTRAL_BANDS;
 
@@ -24,33 +33,52 @@ use crate::ui::EguiRenderOutput;
 
 const SPECTRAL_CONSTANTS: &[(&str, f64)] = &[("SPECTRAL_BANDS", SPECTRAL_BANDS as f64)];
 
-use self::accumulation::{AccumulationBuffer, HdrBuffer};
+use self::accumulation::{AccumFormat, AccumulationBuffer, HdrBuffer};
+use self::adapter::{TIMESTAMP_FEATURES, request_adapter, required_limits, select_features};
 use self::beam_write::{BeamParams, BeamWritePipeline, EmissionParams};
-use self::composite::{CompositeParams, CompositePipeline, TonemapMode};
+use self::compare::CompareSet;
+use self::composite::{CompositeParams, CompositePipeline, TonemapMode, ViewportRect};
 use self::decay::{DecayParams, DecayPipeline};
 use self::faceplate_scatter::{
     FaceplateScatterParams, FaceplateScatterPipeline, FaceplateScatterTextures,
 };
+use self::histogram::HistogramPipeline;
+use self::phosphor_params::PhosphorParamsCache;
 use self::profiler::{GpuProfiler, GpuQuery};
 use self::spectral_resolve::{SpectralResolveParams, SpectralResolvePipeline};
+use self::uniform::UniformBuffer;
 
 pub struct GpuState {
     // Drop order matters: GPU resources that reference the surface/device must
     // drop before the surface, which must drop before the device/instance.
     pub egui_renderer: egui_wgpu::Renderer,
     pub profiler: Option<GpuProfiler>,
+    pub histogram: HistogramPipeline,
+    /// Build the luminance histogram each frame. Off unless the engineer
+    /// panel is showing it.
+    pub histogram_enabled: bool,
+    /// Second phosphor for split-screen comparison; `None` when disabled.
+    pub compare: Option<CompareSet>,
     pub composite: CompositePipeline,
     pub composite_params: CompositeParams,
+    /// Where on the surface to composite the display; `None` fills it.
+    pub composite_viewport: Option<ViewportRect>,
     pub faceplate_scatter: FaceplateScatterPipeline,
     pub faceplate_scatter_textures: FaceplateScatterTextures,
     pub faceplate_scatter_params: FaceplateScatterParams,
     pub spectral_resolve: SpectralResolvePipeline,
     pub spectral_resolve_params: SpectralResolveParams,
+    spectral_resolve_uniform: UniformBuffer<SpectralResolveParams>,
     pub decay: DecayPipeline,
     pub decay_params: DecayParams,
+    decay_uniform: UniformBuffer<DecayParams>,
     pub beam_write: BeamWritePipeline,
     pub beam_params: BeamParams,
     pub emission_params: EmissionParams,
+    emission_uniform: UniformBuffer<EmissionParams>,
+    /// Last sample of the previous frame's batch, linked to the first sample
+    /// of the next so traces don't break at frame boundaries.
+    last_beam_sample: BeamSample,
     pub hdr: HdrBuffer,
     pub accum: AccumulationBuffer,
     pub surface: wgpu::Surface<'static>,
@@ -58,9 +86,24 @@ pub struct GpuState {
     pub queue: wgpu::Queue,
     pub device: wgpu::Device,
     pub adapter: wgpu::Adapter,
+    /// Adapter name, backend, and driver, captured at creation.
+    pub adapter_info: wgpu::AdapterInfo,
     pub instance: wgpu::Instance,
     /// Whether the swapchain surface supports HDR output.
     pub hdr_output: bool,
+    /// Largest storage buffer the device accepts, in bytes. The accumulation
+    /// resolution is clamped so the buffer stays within it.
+    max_storage_bytes: u64,
+    /// Buffer resolution asked for by the window size and scale, before
+    /// clamping to `max_storage_bytes`.
+    pub requested_resolution: Resolution,
+    phosphor_params: PhosphorParamsCache,
+    /// Time the last `render` spent blocked acquiring the swapchain texture
+    /// (the vsync wait), which is excluded from CPU frame time.
+    acquire_wait: Duration,
+    /// CPU time of the previous frame in microseconds, handed to the profiler
+    /// so it lands in the same history slot as that frame's GPU timings.
+    last_cpu_frame_us: f32,
 }
 
 impl GpuState {
@@ -72,34 +115,42 @@ impl GpuState {
 
         let surface = instance.create_surface(window.clone()).unwrap();
 
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        }))
-        .expect("no suitable GPU adapter found");
+        let adapter = request_adapter(&instance, Some(&surface)).unwrap_or_else(|| {
+            panic!(
+                "no GPU adapter can present to this window and run the renderer \
+                 (the log says why each was skipped); phosphor needs Vulkan, Metal, \
+                 or DirectX 12 with compute shaders and renderable float textures"
+            )
+        });
 
-        tracing::info!("GPU adapter: {}", adapter.get_info().name);
+        let adapter_info = adapter.get_info();
+        let adapter_name = adapter_info.name.clone();
+        tracing::info!(
+            "GPU adapter: {adapter_name} ({}, driver {} {})",
+            adapter_info.backend,
+            adapter_info.driver,
+            adapter_info.driver_info
+        );
 
-        let mut features = wgpu::Features::FLOAT32_FILTERABLE;
-        let has_timestamps = GpuProfiler::supports_timestamps(&adapter);
-        if has_timestamps {
-            features |= wgpu::Features::TIMESTAMP_QUERY;
-            features |= wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS;
+        let features = select_features(adapter.features());
+        let has_timestamps = features.contains(TIMESTAMP_FEATURES);
+        let hdr_filterable = features.contains(wgpu::Features::FLOAT32_FILTERABLE);
+        if !hdr_filterable {
+            tracing::warn!(
+                "{adapter_name} lacks FLOAT32_FILTERABLE; the HDR buffer will be sampled without filtering"
+            );
         }
 
         let adapter_limits = adapter.limits();
+        let max_storage_bytes = (adapter_limits.max_storage_buffer_binding_size as u64)
+            .min(adapter_limits.max_buffer_size);
         let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
             label: Some("phosphor"),
             required_features: features,
-            required_limits: wgpu::Limits {
-                max_storage_buffer_binding_size: adapter_limits.max_storage_buffer_binding_size,
-                max_buffer_size: adapter_limits.max_buffer_size,
-                ..wgpu::Limits::default()
-            },
+            required_limits: required_limits(&adapter_limits),
             ..Default::default()
         }))
-        .expect("failed to create GPU device");
+        .unwrap_or_else(|e| panic!("couldn't create a GPU device on {adapter_name}: {e}"));
 
         let profiler = if has_timestamps {
             Some(GpuProfiler::new(&device, &queue))
@@ -143,11 +194,24 @@ impl GpuState {
         };
         surface.configure(&device, &surface_config);
 
-        let buffer_res = Resolution::new(surface_config.width, surface_config.height);
+        let requested_res = Resolution::new(surface_config.width, surface_config.height);
+        let buffer_res = accumulation::clamp_resolution_to_limit(
+            requested_res,
+            1,
+            AccumFormat::default(),
+            max_storage_bytes,
+        );
+        if buffer_res != requested_res {
+            tracing::warn!(
+                "Accumulation buffer at {requested_res} exceeds the device limit of {} MB; \
+                 clamping to {buffer_res}",
+                max_storage_bytes / (1024 * 1024)
+            );
+        }
 
         // Minimal defaults — overwritten by switch_phosphor() immediately after
         // construction, which loads the real phosphor from the database.
-        let accum = AccumulationBuffer::new(&device, buffer_res, 1);
+        let accum = AccumulationBuffer::new(&device, buffer_res, 1, AccumFormat::default());
 
         let beam_write = BeamWritePipeline::new(&device);
         let beam_params = BeamParams::new(
@@ -161,17 +225,20 @@ impl GpuState {
         let decay = DecayPipeline::new(&device);
         let decay_params = DecayParams::from_terms(&[], TAU_CUTOFF);
         let emission_params = EmissionParams::from_phosphor(&[], TAU_CUTOFF);
+        let decay_uniform = UniformBuffer::new(&device, "decay_params");
+        let emission_uniform = UniformBuffer::new(&device, "emission_params");
 
         let hdr = HdrBuffer::new(&device, buffer_res);
 
         let spectral_resolve = SpectralResolvePipeline::new(&device);
         let spectral_resolve_params = SpectralResolveParams::new();
+        let spectral_resolve_uniform = UniformBuffer::new(&device, "spectral_resolve_params");
 
-        let faceplate_scatter = FaceplateScatterPipeline::new(&device);
+        let faceplate_scatter = FaceplateScatterPipeline::new(&device, &queue, hdr_filterable);
         let faceplate_scatter_textures = FaceplateScatterTextures::new(&device, buffer_res);
         let faceplate_scatter_params = FaceplateScatterParams::default();
 
-        let composite = CompositePipeline::new(&device, format);
+        let composite = CompositePipeline::new(&device, format, hdr_filterable);
         let tonemap_mode = if hdr_output {
             TonemapMode::None
         } else {
@@ -179,14 +246,20 @@ impl GpuState {
         };
         let composite_params = CompositeParams::new(1.0, tonemap_mode);
 
+        let histogram = HistogramPipeline::new(&device);
+
         let egui_renderer = egui_wgpu::Renderer::new(&device, format, Default::default());
 
         Self {
             instance,
             adapter,
+            adapter_info,
             device,
             queue,
             profiler,
+            histogram,
+            histogram_enabled: false,
+            compare: None,
             surface,
             surface_config,
             accum,
@@ -194,17 +267,27 @@ impl GpuState {
             beam_write,
             beam_params,
             emission_params,
+            emission_uniform,
+            last_beam_sample: BeamSample::default(),
             decay,
             decay_params,
+            decay_uniform,
             spectral_resolve,
             spectral_resolve_params,
+            spectral_resolve_uniform,
             faceplate_scatter,
             faceplate_scatter_textures,
             faceplate_scatter_params,
             composite,
             composite_params,
+            composite_viewport: None,
             egui_renderer,
             hdr_output,
+            max_storage_bytes,
+            requested_resolution: requested_res,
+            phosphor_params: PhosphorParamsCache::default(),
+            acquire_wait: Duration::ZERO,
+            last_cpu_frame_us: 0.0,
         }
     }
 
@@ -221,30 +304,130 @@ impl GpuState {
 
     /// Resize the internal accumulation, HDR, and scatter buffers without
     /// touching the swapchain surface. Used when the buffer scale changes.
-    pub fn resize_buffers(&mut self, resolution: Resolution) {
+    ///
+    /// The resolution actually allocated may be smaller than requested if
+    /// the accumulation buffer would exceed the device's storage buffer
+    /// limit; see [`Self::resolution_clamped`].
+    pub fn resize_buffers(&mut self, requested: Resolution) {
+        self.requested_resolution = requested;
+        let layers = self.deepest_layers();
+        let resolution = self.clamped_resolution(layers, self.accum.format);
+        if resolution != requested && resolution != self.accum.resolution {
+            tracing::warn!(
+                "Accumulation buffer at {requested} × {layers} layers exceeds the device \
+                 limit of {} MB; clamping to {resolution}",
+                self.max_storage_bytes / (1024 * 1024)
+            );
+        }
+
         self.accum.resize(&self.device, resolution);
         self.hdr.resize(&self.device, resolution);
         self.faceplate_scatter_textures
             .resize(&self.device, resolution);
         self.beam_params.width = resolution.width;
         self.beam_params.height = resolution.height;
+        if let Some(compare) = &mut self.compare {
+            compare.resize(&self.device, resolution);
+        }
     }
 
-    /// Reconfigure GPU state for a new phosphor type. Rebuilds decay params,
-    /// emission params, spectral resolve params, and reallocates the
-    /// accumulation buffer if the layer count changed.
-    pub fn switch_phosphor(&mut self, phosphor: &PhosphorType) {
-        let terms = &phosphor.fluorescence.decay_terms;
-        let class = phosphor_data::classify_decay_terms(terms, TAU_CUTOFF);
-        let layers = accumulation::accum_layer_count(
-            class.slow_exp_count,
-            class.has_power_law,
-            class.instant_exp_count > 0,
-        );
+    /// Layer count of the deeper of the primary and compare accumulation
+    /// buffers, which sets the resolution clamp for both.
+    fn deepest_layers(&self) -> u32 {
+        self.compare
+            .as_ref()
+            .map_or(self.accum.layers, |c| c.accum.layers.max(self.accum.layers))
+    }
+
+    /// The requested resolution, shrunk so a `layers`-deep accumulation
+    /// buffer in `format` fits the device's storage buffer limit.
+    fn clamped_resolution(&self, layers: u32, format: AccumFormat) -> Resolution {
+        accumulation::clamp_resolution_to_limit(
+            self.requested_resolution,
+            layers,
+            format,
+            self.max_storage_bytes,
+        )
+    }
+
+    /// Whether the buffer resolution was reduced to fit the device limit.
+    pub fn resolution_clamped(&self) -> bool {
+        self.accum.resolution != self.requested_resolution
+    }
+
+    /// VRAM used by the resolution-dependent buffers: accumulation, HDR, and
+    /// scatter textures, plus the compare set when enabled.
+    pub fn total_buffer_bytes(&self) -> u64 {
+        self.accum.byte_size()
+            + self.hdr.byte_size()
+            + self.faceplate_scatter_textures.byte_size()
+            + self.compare.as_ref().map_or(0, CompareSet::byte_size)
+    }
+
+    /// Enable split-screen comparison against `phosphor`, or disable it with
+    /// `None`. Allocates (or frees) a second set of accumulation, HDR, and
+    /// scatter buffers — see [`CompareSet`] for the memory cost.
+    /// `index` identifies the phosphor in the UI's list, for caching.
+    pub fn set_compare_phosphor(&mut self, phosphor: Option<(usize, &PhosphorType)>) {
+        // Free the old set before allocating its replacement.
+        self.compare = None;
+        if let Some((index, p)) = phosphor {
+            let params = self.phosphor_params.get(index, p);
+            // The compared phosphor may have more layers and need a lower
+            // clamp, so size it for the deeper of the two from the start.
+            let layers = params.layers.max(1).max(self.accum.layers);
+            let resolution = self.clamped_resolution(layers, self.accum.format);
+            self.compare = Some(CompareSet::new(
+                &self.device,
+                resolution,
+                self.accum.format,
+                &params,
+            ));
+        }
+        // Bring the primary buffers to the same clamp.
+        self.resize_buffers(self.requested_resolution);
+    }
+
+    /// Switch the accumulation storage format, reallocating (and so
+    /// clearing) the primary and compare accumulation buffers. The smaller
+    /// f16 buffer may also lift a resolution clamp.
+    pub fn set_accum_format(&mut self, format: AccumFormat) {
+        if format == self.accum.format {
+            return;
+        }
+        // Clamp for the new texel size before allocating, since switching to
+        // a wider format can push the old resolution past the limit.
+        let layers = self.deepest_layers();
+        let resolution = self.clamped_resolution(layers, format);
+        self.accum = AccumulationBuffer::new(&self.device, resolution, self.accum.layers, format);
+        if let Some(compare) = &mut self.compare {
+            compare.accum =
+                AccumulationBuffer::new(&self.device, resolution, compare.accum.layers, format);
+        }
+        // Bring the HDR and scatter textures to the same resolution.
+        self.resize_buffers(self.requested_resolution);
+    }
+
+    /// Reconfigure GPU state for a new phosphor type. Loads decay, emission,
+    /// and spectral resolve params (cached per `index` into the UI's phosphor
+    /// list), and reallocates the accumulation buffer if the layer count
+    /// changed.
+    pub fn switch_phosphor(&mut self, index: usize, phosphor: &PhosphorType) {
+        let params = self.phosphor_params.get(index, phosphor);
+        let layers = params.layers;
 
         if layers != self.accum.layers {
+            let resolution = accumulation::clamp_resolution_to_limit(
+                self.requested_resolution,
+                layers,
+                self.accum.format,
+                self.max_storage_bytes,
+            );
             self.accum =
-                AccumulationBuffer::new(&self.device, self.accum.resolution, layers.max(1));
+                AccumulationBuffer::new(&self.device, resolution, layers.max(1), self.accum.format);
+            // Bring the other buffers in line if the new layer count moved
+            // the clamp.
+            self.resize_buffers(self.requested_resolution);
         } else {
             // Zero the buffer even if same size — old phosphor's data is invalid
             self.queue.write_buffer(
@@ -254,13 +437,19 @@ impl GpuState {
             );
         }
 
-        self.decay_params = DecayParams::from_terms(terms, TAU_CUTOFF);
-        self.emission_params = EmissionParams::from_phosphor(terms, TAU_CUTOFF);
-        self.spectral_resolve_params.update_from_phosphor(
-            &phosphor.fluorescence.emission_weights,
-            terms,
-            TAU_CUTOFF,
-        );
+        self.decay_params = params.decay;
+        self.emission_params = params.emission;
+        self.spectral_resolve_params = params.spectral_resolve;
+    }
+
+    /// Record the wall-clock time of the frame that just finished, measured
+    /// from the start of event handling to after `render`. The swapchain
+    /// acquire wait is subtracted so a vsync-limited frame doesn't read as
+    /// CPU-bound.
+    pub fn record_cpu_frame_time(&mut self, elapsed: Duration) {
+        let busy = elapsed.saturating_sub(self.acquire_wait);
+        self.last_cpu_frame_us = busy.as_secs_f32() * 1e6;
+        self.acquire_wait = Duration::ZERO;
     }
 
     pub fn render(
@@ -269,14 +458,19 @@ impl GpuState {
         dt: f32,
         egui: Option<&EguiRenderOutput>,
     ) -> Result<(), wgpu::SurfaceError> {
+        let acquire_start = Instant::now();
         let output = self.surface.get_current_texture()?;
+        self.acquire_wait = acquire_start.elapsed();
         let view = output
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
         let beam_sample_count = samples.len() as u32;
         if let Some(profiler) = &mut self.profiler {
-            profiler.read_back(&self.device, beam_sample_count);
+            profiler.read_back(&self.device, beam_sample_count, self.last_cpu_frame_us);
+        }
+        if self.histogram_enabled {
+            self.histogram.read_back(&self.device);
         }
 
         let mut encoder = self
@@ -290,17 +484,45 @@ impl GpuState {
             profiler.timestamp(&mut encoder, GpuQuery::FrameStart);
         }
 
+        // Upload this frame's params into the persistent uniform buffers.
+        // The beam params are shared by both phosphors in compare mode.
+        let beam_params = self
+            .beam_params
+            .with_sample_count(samples.len() as u32)
+            .with_previous(self.last_beam_sample);
+        // An empty frame means the beam is idle; don't link across it.
+        self.last_beam_sample = samples.last().copied().unwrap_or_default();
+        self.beam_write.write_params(&self.queue, &beam_params);
+        self.beam_write
+            .write_samples(&self.device, &self.queue, samples, &beam_params);
+        self.emission_uniform
+            .write(&self.queue, &self.emission_params);
+        self.spectral_resolve_uniform
+            .write(&self.queue, &self.spectral_resolve_params);
+        self.decay_uniform
+            .write(&self.queue, &self.decay_params.with_dt(dt));
+        if let Some(compare) = &self.compare {
+            compare.write_uniforms(&self.queue, dt);
+        }
+
         // Beam write pass
         if !samples.is_empty() {
-            let params = self.beam_params.with_sample_count(samples.len() as u32);
             self.beam_write.dispatch(
                 &self.device,
                 &mut encoder,
-                samples,
-                &params,
-                &self.emission_params,
+                samples.len(),
+                &self.emission_uniform,
                 &self.accum,
             );
+            if let Some(compare) = &self.compare {
+                self.beam_write.dispatch(
+                    &self.device,
+                    &mut encoder,
+                    samples.len(),
+                    &compare.emission_uniform,
+                    &compare.accum,
+                );
+            }
         }
         if let Some(profiler) = &self.profiler {
             profiler.timestamp(&mut encoder, GpuQuery::AfterBeamWrite);
@@ -313,49 +535,125 @@ impl GpuState {
             &self.device,
             &mut encoder,
             &self.hdr,
-            &self.spectral_resolve_params,
+            &self.spectral_resolve_uniform,
             &self.accum,
         );
+        if let Some(compare) = &self.compare {
+            self.spectral_resolve.render(
+                &self.device,
+                &mut encoder,
+                &compare.hdr,
+                &compare.spectral_resolve_uniform,
+                &compare.accum,
+            );
+        }
         if let Some(profiler) = &self.profiler {
             profiler.timestamp(&mut encoder, GpuQuery::AfterSpectralResolve);
         }
 
         // Decay pass: runs after spectral resolve so that tier-1 instant
         // layers are read before being cleared for the next frame.
-        let decay_params = self.decay_params.with_dt(dt);
         self.decay
-            .dispatch(&self.device, &mut encoder, &decay_params, &self.accum);
+            .dispatch(&self.device, &mut encoder, &self.decay_uniform, &self.accum);
+        if let Some(compare) = &self.compare {
+            self.decay.dispatch(
+                &self.device,
+                &mut encoder,
+                &compare.decay_uniform,
+                &compare.accum,
+            );
+        }
         if let Some(profiler) = &self.profiler {
             profiler.timestamp(&mut encoder, GpuQuery::AfterDecay);
         }
 
-        // FaceplateScatter passes: downsample HDR → blur H → blur V
-        self.faceplate_scatter.render(
-            &self.device,
-            &mut encoder,
-            &self.hdr,
-            &self.faceplate_scatter_textures,
-            &self.faceplate_scatter_params,
-        );
+        // FaceplateScatter passes: downsample HDR → blur H → blur V.
+        // Skipped entirely at zero intensity; the composite then samples a
+        // black fallback and the profiler segment reads ~0.
+        let run_scatter = self.faceplate_scatter_params.should_run();
+        if run_scatter {
+            let levels = self.faceplate_scatter_params.levels;
+            let temporal = self.faceplate_scatter_params.temporal;
+            self.faceplate_scatter_textures
+                .set_levels(&self.device, levels);
+            self.faceplate_scatter_textures
+                .set_temporal(&self.device, temporal);
+            if let Some(compare) = &mut self.compare {
+                compare
+                    .faceplate_scatter_textures
+                    .set_levels(&self.device, levels);
+                compare
+                    .faceplate_scatter_textures
+                    .set_temporal(&self.device, temporal);
+            }
+            self.faceplate_scatter.render(
+                &self.device,
+                &self.queue,
+                &mut encoder,
+                &self.hdr,
+                &self.faceplate_scatter_textures,
+                &self.faceplate_scatter_params,
+            );
+            if let Some(compare) = &self.compare {
+                self.faceplate_scatter.render(
+                    &self.device,
+                    &self.queue,
+                    &mut encoder,
+                    &compare.hdr,
+                    &compare.faceplate_scatter_textures,
+                    &self.faceplate_scatter_params,
+                );
+            }
+        } else {
+            // Free the temporal history while skipped, so raising the
+            // intensity again starts from black instead of a stale glow.
+            self.faceplate_scatter_textures
+                .set_temporal(&self.device, false);
+            if let Some(compare) = &mut self.compare {
+                compare
+                    .faceplate_scatter_textures
+                    .set_temporal(&self.device, false);
+            }
+        }
         if let Some(profiler) = &self.profiler {
             profiler.timestamp(&mut encoder, GpuQuery::AfterFaceplateScatter);
         }
 
-        // Composite pass: HDR + faceplate_scatter → display
+        // Composite pass: HDR + faceplate_scatter → display. In compare mode
+        // the right side of the split samples the second phosphor's buffers.
+        let compare = self
+            .compare
+            .as_ref()
+            .map(|c| (&c.hdr, &c.faceplate_scatter_textures));
         self.composite.render(
             &self.device,
+            &self.queue,
             &mut encoder,
             &view,
+            self.composite_viewport,
             &self.composite_params,
             &self.hdr,
-            &self.faceplate_scatter_textures,
+            run_scatter.then_some(&self.faceplate_scatter_textures),
+            compare,
         );
-        if let Some(profiler) = &self.profiler {
+        if let Some(profiler) = &mut self.profiler {
             profiler.timestamp(&mut encoder, GpuQuery::AfterComposite);
             // Resolve all queries into the buffer for reading next frame
             profiler.resolve(&mut encoder);
         }
 
+        // Luminance histogram of the primary phosphor, outside the timed
+        // region since it only runs while the engineer panel is open.
+        if self.histogram_enabled {
+            self.histogram.dispatch(
+                &self.device,
+                &self.queue,
+                &mut encoder,
+                &self.hdr,
+                self.composite_params.exposure,
+            );
+        }
+
         // egui overlay pass
         if let Some(egui) = egui {
             for (id, delta) in &egui.textures_delta.set {
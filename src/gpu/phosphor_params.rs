@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use crate::phosphor::PhosphorType;
+use crate::phosphor::spectral::SPECTRAL_BANDS;
+
+use super::TAU_CUTOFF;
+use super::accumulation;
+use super::beam_write::EmissionParams;
+use super::decay::DecayParams;
+use super::spectral_resolve::SpectralResolveParams;
+
+/// Everything the GPU pipelines derive from a phosphor's definition: the
+/// accumulation layer count and the decay, emission, and spectral resolve
+/// uniforms. A pure function of the phosphor, so safe to cache.
+#[derive(Clone, Copy)]
+pub struct PhosphorParams {
+    pub layers: u32,
+    pub decay: DecayParams,
+    pub emission: EmissionParams,
+    pub spectral_resolve: SpectralResolveParams,
+}
+
+/// Decay terms safe to upload: invalid ones (non-finite, or a zero time
+/// constant) would turn the whole accumulation buffer NaN, so they are
+/// dropped with a warning rather than blanking the screen.
+fn valid_decay_terms(phosphor: &PhosphorType) -> Vec<phosphor_data::DecayTerm> {
+    let (valid, invalid): (Vec<_>, Vec<_>) = phosphor
+        .fluorescence
+        .decay_terms
+        .iter()
+        .partition(|term| term.is_valid());
+    for term in invalid {
+        tracing::warn!(
+            "{}: ignoring invalid decay term {term:?}",
+            phosphor.designation
+        );
+    }
+    valid
+}
+
+/// Emission weights safe to upload, with non-finite entries zeroed.
+fn finite_emission_weights(phosphor: &PhosphorType) -> [f32; SPECTRAL_BANDS] {
+    let mut weights = phosphor.fluorescence.emission_weights;
+    if weights.iter().any(|w| !w.is_finite()) {
+        tracing::warn!(
+            "{}: non-finite emission weights {weights:?}; zeroing them",
+            phosphor.designation
+        );
+        for w in &mut weights {
+            if !w.is_finite() {
+                *w = 0.0;
+            }
+        }
+    }
+    weights
+}
+
+impl PhosphorParams {
+    pub fn from_phosphor(phosphor: &PhosphorType) -> Self {
+        let terms = &valid_decay_terms(phosphor);
+        let class = phosphor_data::classify_decay_terms(terms, TAU_CUTOFF);
+        let layers = accumulation::accum_layer_count(
+            class.slow_exp_count,
+            class.has_power_law,
+            class.instant_exp_count > 0,
+        );
+
+        let mut spectral_resolve = SpectralResolveParams::new();
+        spectral_resolve.update_from_phosphor(
+            &finite_emission_weights(phosphor),
+            terms,
+            TAU_CUTOFF,
+        );
+
+        Self {
+            layers,
+            decay: DecayParams::from_terms(terms, TAU_CUTOFF),
+            emission: EmissionParams::from_phosphor(terms, TAU_CUTOFF),
+            spectral_resolve,
+        }
+    }
+}
+
+/// `PhosphorParams` memoized by index into the UI's phosphor list, so
+/// switching back and forth between phosphors doesn't rebuild them.
+#[derive(Default)]
+pub struct PhosphorParamsCache {
+    entries: HashMap<usize, PhosphorParams>,
+}
+
+impl PhosphorParamsCache {
+    pub fn get(&mut self, index: usize, phosphor: &PhosphorType) -> PhosphorParams {
+        *self
+            .entries
+            .entry(index)
+            .or_insert_with(|| PhosphorParams::from_phosphor(phosphor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phosphor::phosphor_database;
+
+    fn bytes(params: &PhosphorParams) -> Vec<u8> {
+        [
+            bytemuck::bytes_of(&params.decay),
+            bytemuck::bytes_of(&params.emission),
+            bytemuck::bytes_of(&params.spectral_resolve),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn params_are_deterministic_per_phosphor() {
+        for phosphor in &phosphor_database() {
+            let a = PhosphorParams::from_phosphor(phosphor);
+            let b = PhosphorParams::from_phosphor(phosphor);
+            assert_eq!(a.layers, b.layers, "{}", phosphor.designation);
+            assert_eq!(bytes(&a), bytes(&b), "{}", phosphor.designation);
+        }
+    }
+
+    #[test]
+    fn invalid_terms_and_weights_are_dropped() {
+        let mut phosphor = phosphor_database()[0].clone();
+        phosphor.fluorescence.decay_terms = vec![
+            phosphor_data::DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 1e-3,
+            },
+            phosphor_data::DecayTerm::Exponential {
+                amplitude: 1.0,
+                tau: 0.0,
+            },
+            phosphor_data::DecayTerm::Exponential {
+                amplitude: f32::NAN,
+                tau: 1e-2,
+            },
+        ];
+        phosphor.fluorescence.emission_weights[0] = f32::NAN;
+
+        let params = PhosphorParams::from_phosphor(&phosphor);
+        assert_eq!(params.decay.term_count, 1);
+        let weights = params.spectral_resolve.groups[0].weights;
+        assert!(weights.iter().flatten().all(|w| w.is_finite()));
+        assert!(params.emission.instant_energy_total.is_finite());
+    }
+
+    #[test]
+    fn cache_returns_the_same_params_as_a_fresh_build() {
+        let db = phosphor_database();
+        let mut cache = PhosphorParamsCache::default();
+        for (i, phosphor) in db.iter().enumerate() {
+            let fresh = PhosphorParams::from_phosphor(phosphor);
+            assert_eq!(bytes(&cache.get(i, phosphor)), bytes(&fresh));
+            // Second lookup hits the cache
+            assert_eq!(bytes(&cache.get(i, phosphor)), bytes(&fresh));
+        }
+    }
+}
@@ -37,18 +37,22 @@ pub const SEGMENT_NAMES: &[&str] = {
 use std::cell::UnsafeCell;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+use super::readback::Readback;
+
 pub const HISTORY_CAP: usize = 512; // power of 2
 const CAP: usize = HISTORY_CAP;
 const CAP_MASK: usize = CAP - 1;
 
-/// Number of f32 tracks: one per segment + one for totals.
-const F32_TRACKS: usize = NUM_SEGMENTS + 1;
+/// Number of f32 tracks: one per segment, one for GPU totals, one for CPU
+/// frame time.
+const F32_TRACKS: usize = NUM_SEGMENTS + 2;
 const TOTAL_TRACK: usize = NUM_SEGMENTS;
+const CPU_TRACK: usize = NUM_SEGMENTS + 1;
 
 /// Single-producer lock-free ring buffer of per-frame GPU timings in SoA layout.
 ///
 /// A single flat allocation holds all tracks contiguously:
-///   `[seg0 × CAP | seg1 × CAP | ... | total × CAP]`
+///   `[seg0 × CAP | seg1 × CAP | ... | total × CAP | cpu × CAP]`
 ///
 /// Each track's CAP-element region shares a common atomic write cursor (`tail`),
 /// so iterating one track across all frames is a contiguous memory read
@@ -90,8 +94,9 @@ impl TimingHistory {
         }
     }
 
-    /// Push a new frame of timing data. **Single writer only.**
-    pub fn push(&self, segments: [f32; NUM_SEGMENTS], total: f32, beam_samples: u32) {
+    /// Push a new frame of timing data, all times in microseconds.
+    /// **Single writer only.**
+    pub fn push(&self, segments: [f32; NUM_SEGMENTS], total: f32, cpu: f32, beam_samples: u32) {
         let idx = self.tail.load(Ordering::Relaxed) & CAP_MASK;
 
         // Safety: single writer guaranteed by &mut GpuProfiler in call chain.
@@ -100,6 +105,7 @@ impl TimingHistory {
             buf[i * CAP + idx] = val;
         }
         buf[TOTAL_TRACK * CAP + idx] = total;
+        buf[CPU_TRACK * CAP + idx] = cpu;
 
         let beam_buf = unsafe { &mut *self.beam_buf.get() };
         beam_buf[idx] = beam_samples;
@@ -138,6 +144,16 @@ impl TimingHistory {
             .map(|(i, &v)| [i as f64, v as f64])
     }
 
+    /// Iterate `[frame_index, microseconds]` of GPU frame totals.
+    pub fn total_iter(&self) -> impl Iterator<Item = [f64; 2]> + '_ {
+        self.segment_iter(TOTAL_TRACK)
+    }
+
+    /// Iterate `[frame_index, microseconds]` of CPU frame times.
+    pub fn cpu_iter(&self) -> impl Iterator<Item = [f64; 2]> + '_ {
+        self.segment_iter(CPU_TRACK)
+    }
+
     /// Average the last `n` values of a track (clamped to `len`).
     fn track_avg(&self, track: usize, n: usize) -> f32 {
         let (a, b) = self.track_slices(track);
@@ -168,6 +184,11 @@ impl TimingHistory {
         self.track_avg(TOTAL_TRACK, n)
     }
 
+    /// Average CPU frame time over the last `n` frames.
+    pub fn avg_cpu(&self, n: usize) -> f32 {
+        self.track_avg(CPU_TRACK, n)
+    }
+
     /// Average per-segment values over the last `n` frames.
     pub fn avg_segments(&self, n: usize) -> Option<[(&'static str, f32); NUM_SEGMENTS]> {
         if self.len() == 0 {
@@ -219,22 +240,28 @@ impl TimingHistory {
 
 // ------------------ GpuProfiler ------------------
 
+/// Per-frame data recorded alongside a frame's timestamps.
+#[derive(Debug, Clone, Copy)]
+struct FrameTag {
+    beam_samples: u32,
+    /// CPU frame time in microseconds. Only known after the frame ends, so
+    /// filled in at the start of the next one.
+    cpu_us: f32,
+}
+
 pub struct GpuProfiler {
     query_set: wgpu::QuerySet,
     resolve_buffer: wgpu::Buffer,
-    read_buffer: wgpu::Buffer,
+    /// Copies of resolved timestamps, tagged with the frame's CPU-side data.
+    readback: Readback<FrameTag>,
+    /// Beam sample count passed to the latest `read_back`, recorded with the
+    /// frame whose timestamps `resolve` copies out.
+    frame_beam_samples: u32,
     timestamp_period: f32,
-    has_data: bool,
     pub history: TimingHistory,
 }
 
 impl GpuProfiler {
-    pub fn supports_timestamps(adapter: &wgpu::Adapter) -> bool {
-        adapter.features().contains(
-            wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS,
-        )
-    }
-
     pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
         let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
             label: Some("gpu_profiler"),
@@ -249,21 +276,16 @@ impl GpuProfiler {
             mapped_at_creation: false,
         });
 
-        let read_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("profiler_read"),
-            size: RESULT_SIZE,
-            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
-            mapped_at_creation: false,
-        });
+        let readback = Readback::new(device, "profiler_read", RESULT_SIZE);
 
         let timestamp_period = queue.get_timestamp_period();
 
         Self {
             query_set,
             resolve_buffer,
-            read_buffer,
+            readback,
+            frame_beam_samples: 0,
             timestamp_period,
-            has_data: false,
             history: TimingHistory::new(),
         }
     }
@@ -272,43 +294,69 @@ impl GpuProfiler {
         encoder.write_timestamp(&self.query_set, query as u32);
     }
 
-    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+    /// Resolve this frame's queries and copy them into a free read buffer.
+    /// Skipped (the frame goes untimed) if both read buffers are in flight.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let tag = FrameTag {
+            beam_samples: self.frame_beam_samples,
+            cpu_us: 0.0,
+        };
+        let Some(read_buffer) = self.readback.claim(tag) else {
+            return;
+        };
         encoder.resolve_query_set(&self.query_set, 0..QUERY_COUNT, &self.resolve_buffer, 0);
-        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.read_buffer, 0, RESULT_SIZE);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, read_buffer, 0, RESULT_SIZE);
     }
 
-    pub fn read_back(&mut self, device: &wgpu::Device, beam_samples: u32) {
-        if !self.has_data {
-            self.has_data = true;
-            return;
-        }
-
-        self.read_buffer
-            .slice(..)
-            .map_async(wgpu::MapMode::Read, |_| {});
-        let _ = device.poll(wgpu::PollType::wait_indefinitely());
-
-        {
-            let view = self.read_buffer.slice(..).get_mapped_range();
-            let ts: &[u64] = cast_slice(&view);
-
-            if ts.len() >= QUERY_COUNT as usize {
-                let to_us = |a: usize, b: usize| {
-                    ts[b].wrapping_sub(ts[a]) as f32 * self.timestamp_period / 1000.0
-                };
-
-                let mut segments = [0.0f32; NUM_SEGMENTS];
-                let mut prev = 0;
-                for (seg_idx, _variant) in GpuQuery::iter().enumerate().skip(1) {
-                    segments[seg_idx - 1] = to_us(prev, seg_idx);
-                    prev = seg_idx;
-                }
-
-                let total = to_us(0, QUERY_COUNT as usize - 1);
-                self.history.push(segments, total, beam_samples);
+    /// Called at the start of each frame with this frame's beam sample count
+    /// and the previous frame's CPU time. Requests maps for buffers copied
+    /// last frame and pushes any finished ones into `history`. Never blocks:
+    /// results typically land one or two frames after they were recorded.
+    pub fn read_back(&mut self, device: &wgpu::Device, beam_samples: u32, prev_cpu_us: f32) {
+        self.frame_beam_samples = beam_samples;
+        self.readback
+            .update_pending_tag(|tag| tag.cpu_us = prev_cpu_us);
+
+        let timestamp_period = self.timestamp_period;
+        let history = &self.history;
+        self.readback.poll(device, |bytes, tag| {
+            let ts: &[u64] = cast_slice(bytes);
+            if ts.len() < QUERY_COUNT as usize {
+                return;
             }
-        }
+            let to_us =
+                |a: usize, b: usize| ts[b].wrapping_sub(ts[a]) as f32 * timestamp_period / 1000.0;
+
+            let mut segments = [0.0f32; NUM_SEGMENTS];
+            let mut prev = 0;
+            for (seg_idx, _variant) in GpuQuery::iter().enumerate().skip(1) {
+                segments[seg_idx - 1] = to_us(prev, seg_idx);
+                prev = seg_idx;
+            }
+
+            let total = to_us(0, QUERY_COUNT as usize - 1);
+            history.push(segments, total, tag.cpu_us, tag.beam_samples);
+        });
+    }
+}
 
-        self.read_buffer.unmap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_records_cpu_time_in_its_own_track() {
+        let history = TimingHistory::new();
+        history.push([1.0; NUM_SEGMENTS], 5.0, 2500.0, 100);
+        history.push([2.0; NUM_SEGMENTS], 10.0, 3500.0, 200);
+
+        assert_eq!(history.len(), 2);
+        let cpu: Vec<_> = history.cpu_iter().collect();
+        assert_eq!(cpu, vec![[0.0, 2500.0], [1.0, 3500.0]]);
+        let total: Vec<_> = history.total_iter().collect();
+        assert_eq!(total, vec![[0.0, 5.0], [1.0, 10.0]]);
+        assert_eq!(history.avg_cpu(2), 3000.0);
+        assert_eq!(history.latest_total(), 10.0);
+        assert_eq!(history.latest_beam_samples(), 200);
     }
 }
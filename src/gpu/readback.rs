@@ -0,0 +1,259 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Number of read buffers. With two, the GPU can copy this frame's results
+/// into one buffer while the previous frame's buffer is still being mapped.
+pub const READ_BUFFERS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotState<T> {
+    /// Available for the next frame's copy.
+    Free,
+    /// Copy recorded into a frame's encoder; not yet submitted to map.
+    Copied { frame: u64, tag: T },
+    /// `map_async` issued; waiting for the callback.
+    Mapping { frame: u64, tag: T },
+}
+
+/// Bookkeeping for the read buffers, kept separate from the wgpu objects so
+/// the frame-to-frame transitions can be tested without a device. `T` is a
+/// per-frame tag recorded with the copy and handed back with its results.
+///
+/// Per frame: `start_mapping` requests maps for buffers copied last frame,
+/// the caller consumes any that have finished via `finish`, then `claim`
+/// picks a free buffer for this frame's copy. Nothing ever waits on the GPU;
+/// if both buffers are still busy the frame's results are dropped.
+#[derive(Debug)]
+struct ReadbackRing<T> {
+    slots: [SlotState<T>; READ_BUFFERS],
+    next_frame: u64,
+}
+
+impl<T: Copy> ReadbackRing<T> {
+    fn new() -> Self {
+        Self {
+            slots: [SlotState::Free; READ_BUFFERS],
+            next_frame: 0,
+        }
+    }
+
+    /// Claim a free buffer for this frame's copy, or `None` if every buffer
+    /// is still in flight.
+    fn claim(&mut self, tag: T) -> Option<usize> {
+        let idx = self
+            .slots
+            .iter()
+            .position(|s| matches!(s, SlotState::Free))?;
+        self.slots[idx] = SlotState::Copied {
+            frame: self.next_frame,
+            tag,
+        };
+        self.next_frame += 1;
+        Some(idx)
+    }
+
+    /// Tags of buffers copied but not yet mapping, i.e. last frame's.
+    fn copied_tags_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| match slot {
+            SlotState::Copied { tag, .. } => Some(tag),
+            _ => None,
+        })
+    }
+
+    /// Move every copied (and by now submitted) buffer to `Mapping`,
+    /// returning the indices whose maps should be requested.
+    fn start_mapping(&mut self) -> Vec<usize> {
+        let mut started = Vec::new();
+        for (idx, slot) in self.slots.iter_mut().enumerate() {
+            if let SlotState::Copied { frame, tag } = *slot {
+                *slot = SlotState::Mapping { frame, tag };
+                started.push(idx);
+            }
+        }
+        started
+    }
+
+    /// Indices of buffers waiting on a map, oldest frame first.
+    fn mapping(&self) -> Vec<usize> {
+        let mut pending: Vec<(u64, usize)> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, slot)| match *slot {
+                SlotState::Mapping { frame, .. } => Some((frame, idx)),
+                _ => None,
+            })
+            .collect();
+        pending.sort_unstable();
+        pending.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    /// Release a mapped buffer, returning the tag recorded with its frame.
+    fn finish(&mut self, idx: usize) -> T {
+        let SlotState::Mapping { tag, .. } = self.slots[idx] else {
+            panic!("finish on a buffer that is not mapping");
+        };
+        self.slots[idx] = SlotState::Free;
+        tag
+    }
+}
+
+const MAP_PENDING: u8 = 0;
+const MAP_OK: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+struct ReadBuffer {
+    buffer: wgpu::Buffer,
+    /// Set from the `map_async` callback; polled without blocking.
+    map_status: Arc<AtomicU8>,
+}
+
+/// Double-buffered, non-blocking GPU → CPU readback of a fixed-size buffer.
+///
+/// Each frame the owner calls [`Self::claim`] to get a buffer to copy into,
+/// and [`Self::poll`] (before recording the next frame) to map last frame's
+/// copies and consume any that have landed. Results typically arrive one or
+/// two frames after they were recorded.
+pub struct Readback<T> {
+    buffers: [ReadBuffer; READ_BUFFERS],
+    ring: ReadbackRing<T>,
+}
+
+impl<T: Copy> Readback<T> {
+    pub fn new(device: &wgpu::Device, label: &str, size: u64) -> Self {
+        let buffers = std::array::from_fn(|_| ReadBuffer {
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            map_status: Arc::new(AtomicU8::new(MAP_PENDING)),
+        });
+        Self {
+            buffers,
+            ring: ReadbackRing::new(),
+        }
+    }
+
+    /// A free buffer for this frame's copy, tagged with `tag`, or `None` if
+    /// both are still in flight and the frame should be skipped.
+    pub fn claim(&mut self, tag: T) -> Option<&wgpu::Buffer> {
+        let idx = self.ring.claim(tag)?;
+        Some(&self.buffers[idx].buffer)
+    }
+
+    /// Amend the tag of last frame's copy, for per-frame data only known once
+    /// the frame has finished. Call before [`Self::poll`].
+    pub fn update_pending_tag(&mut self, mut update: impl FnMut(&mut T)) {
+        self.ring.copied_tags_mut().for_each(&mut update);
+    }
+
+    /// Request maps for buffers copied last frame, then pass the contents of
+    /// every finished one to `consume`, oldest first. Never blocks.
+    pub fn poll(&mut self, device: &wgpu::Device, mut consume: impl FnMut(&[u8], T)) {
+        for idx in self.ring.start_mapping() {
+            let status = self.buffers[idx].map_status.clone();
+            status.store(MAP_PENDING, Ordering::Relaxed);
+            self.buffers[idx]
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let value = if result.is_ok() { MAP_OK } else { MAP_FAILED };
+                    status.store(value, Ordering::Release);
+                });
+        }
+
+        // Drive callbacks without waiting.
+        let _ = device.poll(wgpu::PollType::Poll);
+
+        for idx in self.ring.mapping() {
+            match self.buffers[idx].map_status.load(Ordering::Acquire) {
+                MAP_OK => {
+                    let tag = self.ring.finish(idx);
+                    let buffer = &self.buffers[idx].buffer;
+                    {
+                        let view = buffer.slice(..).get_mapped_range();
+                        consume(&view, tag);
+                    }
+                    buffer.unmap();
+                }
+                MAP_FAILED => {
+                    self.ring.finish(idx);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_are_consumed_the_frame_after_submit() {
+        let mut ring = ReadbackRing::new();
+
+        // Frame 0: nothing in flight yet; copy into a free buffer.
+        assert!(ring.start_mapping().is_empty());
+        let a = ring.claim(10).unwrap();
+
+        // Frame 1: frame 0's buffer starts mapping; frame 1 uses the other.
+        assert_eq!(ring.start_mapping(), vec![a]);
+        let b = ring.claim(20).unwrap();
+        assert_ne!(a, b);
+
+        // Frame 2: frame 0's map has completed.
+        assert_eq!(ring.start_mapping(), vec![b]);
+        assert_eq!(ring.mapping(), vec![a, b]);
+        assert_eq!(ring.finish(a), 10);
+        assert_eq!(ring.claim(30), Some(a));
+    }
+
+    #[test]
+    fn frames_are_dropped_rather_than_waiting() {
+        let mut ring = ReadbackRing::new();
+        ring.claim(1).unwrap();
+        ring.start_mapping();
+        ring.claim(2).unwrap();
+        ring.start_mapping();
+
+        // Both buffers are still mapping: this frame's results are dropped.
+        assert_eq!(ring.claim(3), None);
+
+        // Once the older map lands, its buffer is reusable.
+        let oldest = ring.mapping()[0];
+        assert_eq!(ring.finish(oldest), 1);
+        assert_eq!(ring.claim(4), Some(oldest));
+    }
+
+    #[test]
+    fn pending_tag_updates_only_touch_unmapped_copies() {
+        let mut ring = ReadbackRing::new();
+        let first = ring.claim(1).unwrap();
+        ring.start_mapping();
+        let second = ring.claim(2).unwrap();
+        ring.copied_tags_mut().for_each(|tag| *tag += 10);
+        ring.start_mapping();
+
+        assert_eq!(ring.finish(first), 1);
+        assert_eq!(ring.finish(second), 12);
+    }
+
+    #[test]
+    fn mapping_order_follows_frame_order() {
+        let mut ring = ReadbackRing::new();
+        let first = ring.claim(1).unwrap();
+        ring.start_mapping();
+        let second = ring.claim(2).unwrap();
+        ring.start_mapping();
+        ring.finish(first);
+        let third = ring.claim(3).unwrap();
+        ring.start_mapping();
+
+        // `third` reuses the first buffer's index but is newer than `second`.
+        assert_eq!(third, first);
+        assert_eq!(ring.mapping(), vec![second, third]);
+    }
+}
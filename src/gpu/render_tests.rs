@@ -0,0 +1,331 @@
+//! End-to-end render tests: feed known beam samples through the real
+//! pipelines on a headless device and check pixels of the composited frame.
+//!
+//! These need a GPU adapter, so they only run with `--cfg gpu_tests`:
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg gpu_tests" cargo test render_tests
+//! ```
+//!
+//! [`GpuState`](super::GpuState) is tied to a window surface, so the harness
+//! builds the same pipelines itself and runs them in the same order as
+//! `GpuState::render`, minus the faceplate scatter, histogram, and egui
+//! passes.
+
+use crate::beam::BeamSample;
+use crate::phosphor::{PhosphorType, phosphor_database};
+use crate::types::Resolution;
+
+use super::accumulation::{AccumFormat, AccumulationBuffer, HdrBuffer, unpack_f16_pair};
+use super::adapter::{request_adapter, required_limits, select_features};
+use super::beam_write::{BeamParams, BeamWritePipeline, EmissionParams};
+use super::composite::{CompositeParams, CompositePipeline, TonemapMode};
+use super::decay::{DecayParams, DecayPipeline};
+use super::phosphor_params::PhosphorParams;
+use super::spectral_resolve::{SpectralResolveParams, SpectralResolvePipeline};
+use super::uniform::UniformBuffer;
+
+/// 64 RGBA8 pixels is 256 bytes, so rows need no padding for
+/// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+const SIZE: u32 = 64;
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// A headless device with the render pipelines for one phosphor, drawing
+/// into an offscreen `SIZE`×`SIZE` target.
+struct Harness {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    accum: AccumulationBuffer,
+    hdr: HdrBuffer,
+    beam_write: BeamWritePipeline,
+    decay: DecayPipeline,
+    spectral_resolve: SpectralResolvePipeline,
+    composite: CompositePipeline,
+    params: PhosphorParams,
+    emission_uniform: UniformBuffer<EmissionParams>,
+    decay_uniform: UniformBuffer<DecayParams>,
+    spectral_resolve_uniform: UniformBuffer<SpectralResolveParams>,
+    target: wgpu::Texture,
+}
+
+impl Harness {
+    fn new(phosphor: &PhosphorType) -> Self {
+        Self::with_format(phosphor, AccumFormat::F32)
+    }
+
+    fn with_format(phosphor: &PhosphorType, format: AccumFormat) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = request_adapter(&instance, None)
+            .expect("gpu_tests needs a GPU adapter that can run the renderer");
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("render_tests"),
+            required_features: select_features(adapter.features()),
+            required_limits: required_limits(&adapter.limits()),
+            ..Default::default()
+        }))
+        .expect("failed to create GPU device");
+
+        let resolution = Resolution::new(SIZE, SIZE);
+        let params = PhosphorParams::from_phosphor(phosphor);
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("render_tests_target"),
+            size: wgpu::Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        Self {
+            accum: AccumulationBuffer::new(&device, resolution, params.layers.max(1), format),
+            hdr: HdrBuffer::new(&device, resolution),
+            beam_write: BeamWritePipeline::new(&device),
+            decay: DecayPipeline::new(&device),
+            spectral_resolve: SpectralResolvePipeline::new(&device),
+            composite: CompositePipeline::new(
+                &device,
+                TARGET_FORMAT,
+                adapter
+                    .features()
+                    .contains(wgpu::Features::FLOAT32_FILTERABLE),
+            ),
+            params,
+            emission_uniform: UniformBuffer::new(&device, "emission_params"),
+            decay_uniform: UniformBuffer::new(&device, "decay_params"),
+            spectral_resolve_uniform: UniformBuffer::new(&device, "spectral_resolve_params"),
+            target,
+            device,
+            queue,
+        }
+    }
+
+    /// Render one frame of `samples` over `dt` seconds and read back the
+    /// composited target as row-major RGBA8 pixels.
+    fn render_frame(&mut self, samples: &[BeamSample], dt: f32) -> Vec<[u8; 4]> {
+        let beam_params =
+            BeamParams::new(1.5, 6.0, 0.03, SIZE, SIZE).with_sample_count(samples.len() as u32);
+        let composite_params = CompositeParams::new(1.0, TonemapMode::default());
+
+        self.beam_write.write_params(&self.queue, &beam_params);
+        self.beam_write
+            .write_samples(&self.device, &self.queue, samples, &beam_params);
+        self.emission_uniform
+            .write(&self.queue, &self.params.emission);
+        self.decay_uniform
+            .write(&self.queue, &self.params.decay.with_dt(dt));
+        self.spectral_resolve_uniform
+            .write(&self.queue, &self.params.spectral_resolve);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("render_tests_frame"),
+            });
+        self.beam_write.dispatch(
+            &self.device,
+            &mut encoder,
+            samples.len(),
+            &self.emission_uniform,
+            &self.accum,
+        );
+        self.spectral_resolve.render(
+            &self.device,
+            &mut encoder,
+            &self.hdr,
+            &self.spectral_resolve_uniform,
+            &self.accum,
+        );
+        self.decay
+            .dispatch(&self.device, &mut encoder, &self.decay_uniform, &self.accum);
+        let view = self
+            .target
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.composite.render(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &view,
+            None,
+            &composite_params,
+            &self.hdr,
+            None,
+            None,
+        );
+
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("render_tests_readback"),
+            size: (SIZE * SIZE * 4) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            self.target.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(SIZE * 4),
+                    rows_per_image: Some(SIZE),
+                },
+            },
+            self.target.size(),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| {
+            result.expect("failed to map render_tests readback");
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .expect("device lost during render_tests readback");
+        let pixels = bytemuck::cast_slice::<u8, [u8; 4]>(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        pixels
+    }
+
+    /// Read back the accumulation buffer as one value per texel per layer,
+    /// unpacking [`AccumFormat::F16`] with the CPU reference.
+    fn read_accum(&self) -> Vec<f32> {
+        let size = self.accum.byte_size();
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("render_tests_accum_readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("render_tests_accum_readback"),
+            });
+        encoder.copy_buffer_to_buffer(&self.accum.buffer, 0, &readback, 0, size);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| {
+            result.expect("failed to map render_tests accumulation readback");
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .expect("device lost during render_tests readback");
+        let words = bytemuck::cast_slice::<u8, u32>(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        match self.accum.format {
+            AccumFormat::F32 => words.into_iter().map(f32::from_bits).collect(),
+            AccumFormat::F16 => words.into_iter().flat_map(unpack_f16_pair).collect(),
+        }
+    }
+}
+
+fn pixel(pixels: &[[u8; 4]], x: u32, y: u32) -> [u8; 4] {
+    pixels[(y * SIZE + x) as usize]
+}
+
+fn p1() -> PhosphorType {
+    phosphor_database()
+        .into_iter()
+        .find(|p| p.designation == "P1")
+        .expect("P1 is in the built-in database")
+}
+
+#[test]
+#[cfg_attr(not(gpu_tests), ignore = "needs a GPU; run with --cfg gpu_tests")]
+fn single_sample_lights_center_green_and_leaves_corner_black() {
+    let mut harness = Harness::new(&p1());
+    // A beam dwelling for a millisecond at the simulation's energy scale.
+    let sample = BeamSample {
+        x: 0.5,
+        y: 0.5,
+        intensity: 5000.0,
+        dt: 1e-3,
+    };
+    let pixels = harness.render_frame(&[sample], 1.0 / 60.0);
+
+    let [r, g, b, _] = pixel(&pixels, SIZE / 2, SIZE / 2);
+    assert!(g > 64, "center should be bright, got {:?}", [r, g, b]);
+    assert!(
+        g > r && g > b,
+        "P1 should render green, got {:?}",
+        [r, g, b]
+    );
+
+    let [r, g, b, _] = pixel(&pixels, 0, 0);
+    assert!(
+        r.max(g).max(b) < 4,
+        "corner should be black, got {:?}",
+        [r, g, b]
+    );
+}
+
+#[test]
+#[cfg_attr(not(gpu_tests), ignore = "needs a GPU; run with --cfg gpu_tests")]
+fn power_law_afterglow_survives_a_zero_dt_frame() {
+    let p31 = phosphor_database()
+        .into_iter()
+        .find(|p| p.designation == "P31")
+        .expect("P31 is in the built-in database");
+    assert!(
+        p31.fluorescence
+            .decay_terms
+            .iter()
+            .any(|t| matches!(t, phosphor_data::DecayTerm::PowerLaw { .. }))
+    );
+    let mut harness = Harness::new(&p31);
+    let sample = BeamSample {
+        x: 0.5,
+        y: 0.5,
+        intensity: 5000.0,
+        dt: 1e-3,
+    };
+    harness.render_frame(&[sample], 1.0 / 60.0);
+
+    // Paused frames drain no samples and advance no time: the afterglow
+    // holds instead of vanishing.
+    let before = harness.render_frame(&[], 0.0);
+    let after = harness.render_frame(&[], 0.0);
+    let [_, g_before, _, _] = pixel(&before, SIZE / 2, SIZE / 2);
+    let [_, g_after, _, _] = pixel(&after, SIZE / 2, SIZE / 2);
+    assert!(g_before > 0, "afterglow should be visible");
+    assert!(
+        g_after.saturating_add(1) >= g_before,
+        "afterglow dropped from {g_before} to {g_after} on a zero-dt frame"
+    );
+}
+
+#[test]
+#[cfg_attr(not(gpu_tests), ignore = "needs a GPU; run with --cfg gpu_tests")]
+fn packed_f16_accumulation_matches_f32() {
+    let sample = BeamSample {
+        x: 0.5,
+        y: 0.5,
+        intensity: 5000.0,
+        dt: 1e-3,
+    };
+    let render = |format| {
+        let mut harness = Harness::with_format(&p1(), format);
+        harness.render_frame(&[sample], 1.0 / 60.0);
+        harness.read_accum()
+    };
+    let full = render(AccumFormat::F32);
+    let packed = render(AccumFormat::F16);
+    assert_eq!(packed.len(), full.len());
+
+    // Each value lands in its own lane and matches to f16 precision: about
+    // one step from the deposit's rounding and one from the decay pass's,
+    // plus the subnormal step for the footprint's faint edges.
+    let center = (SIZE / 2 * SIZE + SIZE / 2) as usize;
+    assert!(full[center] > 0.0, "the sample should deposit energy");
+    for (i, (&f, &h)) in full.iter().zip(&packed).enumerate() {
+        assert!(
+            (f - h).abs() <= f * 2e-3 + 1e-6,
+            "value {i}: f16 {h} vs f32 {f}"
+        );
+    }
+}
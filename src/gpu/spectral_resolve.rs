@@ -1,18 +1,38 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt;
+use strum::{Display, EnumIter};
 
-use crate::phosphor::spectral::CIE_INTEGRATION_WEIGHTS;
+use crate::phosphor::spectral::{
+    PACKED_BAND_VEC4S, PackedBands, SPECTRAL_BANDS, pack_bands, pack_cie_weights,
+};
 
 use super::SPECTRAL_CONSTANTS;
 use super::accumulation::{AccumulationBuffer, HdrBuffer};
+use super::uniform::UniformBuffer;
+
+/// The shader's declaration of the packed vec4 count. Uniform struct arrays
+/// can't be sized by an override constant, so this line is patched to match
+/// [`PACKED_BAND_VEC4S`] when the shader is built.
+const VEC4S_DECL: &str = "const SPECTRAL_VEC4S: u32 = 4u;";
+
+fn shader_source() -> String {
+    let source = include_str!("spectral_resolve.wgsl");
+    assert!(
+        source.contains(VEC4S_DECL),
+        "spectral_resolve.wgsl no longer declares `{VEC4S_DECL}`"
+    );
+    source.replace(
+        VEC4S_DECL,
+        &format!("const SPECTRAL_VEC4S: u32 = {PACKED_BAND_VEC4S}u;"),
+    )
+}
 
 /// GPU-side emission group: a set of decay terms sharing an emission spectrum.
 /// Single-layer phosphors have 1 group; dual-layer phosphors have 2.
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct EmissionGroupGpu {
-    /// Spectral emission weights packed as 4 vec4s (4 bands per vec4).
-    pub weights: [[f32; 4]; 4],
+    /// Spectral emission weights packed 4 bands per vec4.
+    pub weights: PackedBands,
     /// First tier-2 layer index in the accumulation buffer.
     pub slow_exp_start: u32,
     /// Number of tier-2 (slow exponential) terms in this group.
@@ -32,37 +52,44 @@ pub struct EmissionGroupGpu {
 
 pub const MAX_EMISSION_GROUPS: usize = 2;
 
+/// What the spectral resolve pass writes to the HDR buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumIter)]
+#[repr(u32)]
+pub enum SpectralDebugMode {
+    /// The CIE-integrated color.
+    #[default]
+    Off = 0,
+    /// A rainbow hue for the band carrying the most energy, violet for the
+    /// shortest wavelength through red for the longest, at the pixel's
+    /// luminance.
+    #[strum(serialize = "Dominant band")]
+    DominantBand = 1,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct SpectralResolveParams {
-    /// CIE x_bar weights packed as 4 vec4s (4 bands per vec4).
-    cie_x: [[f32; 4]; 4],
+    /// CIE x_bar weights packed 4 bands per vec4.
+    cie_x: PackedBands,
     /// CIE y_bar weights.
-    cie_y: [[f32; 4]; 4],
+    cie_y: PackedBands,
     /// CIE z_bar weights.
-    cie_z: [[f32; 4]; 4],
+    cie_z: PackedBands,
     /// Number of active emission groups (1 or 2).
     pub group_count: u32,
     /// Power-law alpha parameter (shared across groups).
     pub power_law_alpha: f32,
     /// Power-law beta parameter (shared across groups).
     pub power_law_beta: f32,
-    pub _pad: u32,
+    /// A [`SpectralDebugMode`]; see [`Self::set_debug_mode`].
+    debug_mode: u32,
     /// Emission groups (up to 2: fluorescence + phosphorescence).
     pub groups: [EmissionGroupGpu; MAX_EMISSION_GROUPS],
 }
 
 impl SpectralResolveParams {
     pub fn new() -> Self {
-        let mut cie_x = [[0.0f32; 4]; 4];
-        let mut cie_y = [[0.0f32; 4]; 4];
-        let mut cie_z = [[0.0f32; 4]; 4];
-
-        for (i, &(x, y, z)) in CIE_INTEGRATION_WEIGHTS.iter().enumerate() {
-            cie_x[i / 4][i % 4] = x;
-            cie_y[i / 4][i % 4] = y;
-            cie_z[i / 4][i % 4] = z;
-        }
+        let (cie_x, cie_y, cie_z) = pack_cie_weights();
 
         Self {
             cie_x,
@@ -71,26 +98,33 @@ impl SpectralResolveParams {
             group_count: 0,
             power_law_alpha: 0.0,
             power_law_beta: 0.0,
-            _pad: 0,
+            debug_mode: SpectralDebugMode::Off as u32,
             groups: [EmissionGroupGpu::zeroed(); MAX_EMISSION_GROUPS],
         }
     }
 
+    pub fn set_debug_mode(&mut self, mode: SpectralDebugMode) {
+        self.debug_mode = mode as u32;
+    }
+
+    pub fn debug_mode(&self) -> SpectralDebugMode {
+        match self.debug_mode {
+            1 => SpectralDebugMode::DominantBand,
+            _ => SpectralDebugMode::Off,
+        }
+    }
+
     /// Reconfigure for a new phosphor. Builds emission group(s) from the
     /// phosphor's layer(s) and decay term classification.
     pub fn update_from_phosphor(
         &mut self,
-        emission_weights: &[f32; 16],
+        emission_weights: &[f32; SPECTRAL_BANDS],
         terms: &[phosphor_data::DecayTerm],
         tau_cutoff: f32,
     ) {
         let class = phosphor_data::classify_decay_terms(terms, tau_cutoff);
 
-        // Pack emission weights into 4×vec4
-        let mut packed_weights = [[0.0f32; 4]; 4];
-        for (i, &w) in emission_weights.iter().enumerate() {
-            packed_weights[i / 4][i % 4] = w;
-        }
+        let packed_weights = pack_bands(emission_weights);
 
         // Compute layer indices for a single emission group.
         // Layout: [slow_exp × 1] [power_law_peak, elapsed_time]? [instant]?
@@ -154,7 +188,7 @@ impl SpectralResolvePipeline {
     pub fn new(device: &wgpu::Device) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("spectral_resolve"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("spectral_resolve.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(shader_source().into()),
         });
 
         let params_bind_group_layout =
@@ -254,29 +288,14 @@ impl SpectralResolvePipeline {
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
         hdr: &HdrBuffer,
-        params: &SpectralResolveParams,
+        params: &UniformBuffer<SpectralResolveParams>,
         accum: &AccumulationBuffer,
     ) {
-        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("spectral_resolve_params"),
-            contents: bytemuck::bytes_of(params),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
-
-        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("spectral_resolve_params"),
-            layout: &self.params_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: params_buffer.as_entire_binding(),
-            }],
-        });
-
-        let dims_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("spectral_resolve_accum_dims"),
-            contents: bytemuck::bytes_of(&accum.dims()),
-            usage: wgpu::BufferUsages::UNIFORM,
-        });
+        let params_bind_group = params.bind_group(
+            device,
+            &self.params_bind_group_layout,
+            "spectral_resolve_params",
+        );
 
         let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("spectral_resolve_accum"),
@@ -288,7 +307,7 @@ impl SpectralResolvePipeline {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: dims_buffer.as_entire_binding(),
+                    resource: accum.dims_buffer.as_entire_binding(),
                 },
             ],
         });
@@ -314,3 +333,41 @@ impl SpectralResolvePipeline {
         pass.draw(0..3, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shader_vec4_count_follows_band_count() {
+        let source = shader_source();
+        let expected = format!("const SPECTRAL_VEC4S: u32 = {PACKED_BAND_VEC4S}u;");
+        assert!(source.contains(&expected));
+        assert_eq!(PACKED_BAND_VEC4S * 4, SPECTRAL_BANDS);
+    }
+
+    #[test]
+    fn debug_mode_takes_the_header_pad_slot() {
+        use std::mem::{offset_of, size_of};
+
+        let packed = size_of::<PackedBands>();
+        // The fourth u32 of the header, right before the group array.
+        assert_eq!(
+            offset_of!(SpectralResolveParams, debug_mode),
+            3 * packed + 12
+        );
+        assert_eq!(offset_of!(SpectralResolveParams, groups), 3 * packed + 16);
+        assert_eq!(
+            size_of::<SpectralResolveParams>(),
+            3 * packed + 16 + MAX_EMISSION_GROUPS * size_of::<EmissionGroupGpu>()
+        );
+
+        let mut params = SpectralResolveParams::new();
+        assert_eq!(params.debug_mode(), SpectralDebugMode::Off);
+        params.set_debug_mode(SpectralDebugMode::DominantBand);
+        assert_eq!(params.debug_mode(), SpectralDebugMode::DominantBand);
+        let bytes = bytemuck::bytes_of(&params);
+        let word = &bytes[3 * packed + 12..3 * packed + 16];
+        assert_eq!(u32::from_ne_bytes(word.try_into().unwrap()), 1);
+    }
+}
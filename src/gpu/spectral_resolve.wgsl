@@ -6,9 +6,12 @@
 // sRGB with gamut mapping.
 
 override SPECTRAL_BANDS: u32 = 16u;
+// SPECTRAL_BANDS / 4. Patched from Rust to match the build's band count,
+// since uniform array sizes can't depend on an override.
+const SPECTRAL_VEC4S: u32 = 4u;
 
 struct EmissionGroupGpu {
-    weights: array<vec4<f32>, 4>,
+    weights: array<vec4<f32>, SPECTRAL_VEC4S>,
     slow_exp_start: u32,
     slow_exp_count: u32,
     has_power_law: u32,
@@ -20,13 +23,13 @@ struct EmissionGroupGpu {
 }
 
 struct SpectralResolveParams {
-    cie_x: array<vec4<f32>, 4>,
-    cie_y: array<vec4<f32>, 4>,
-    cie_z: array<vec4<f32>, 4>,
+    cie_x: array<vec4<f32>, SPECTRAL_VEC4S>,
+    cie_y: array<vec4<f32>, SPECTRAL_VEC4S>,
+    cie_z: array<vec4<f32>, SPECTRAL_VEC4S>,
     group_count: u32,
     power_law_alpha: f32,
     power_law_beta: f32,
-    _pad: u32,
+    debug_mode: u32, // 1: false-color dominant band instead of CIE color
     groups: array<EmissionGroupGpu, 2>,
 }
 
@@ -34,7 +37,7 @@ struct AccumDims {
     width: u32,
     height: u32,
     layers: u32,
-    _pad: u32,
+    packed_f16: u32, // 1: element i is half (i & 1) of word i / 2
 }
 
 @group(0) @binding(0) var<uniform> params: SpectralResolveParams;
@@ -47,7 +50,11 @@ fn accum_index(x: i32, y: i32, layer: u32) -> u32 {
 }
 
 fn load_accum(x: i32, y: i32, layer: u32) -> f32 {
-    return bitcast<f32>(accum[accum_index(x, y, layer)]);
+    let idx = accum_index(x, y, layer);
+    if accum_dims.packed_f16 == 1u {
+        return unpack2x16float(accum[idx >> 1u])[idx & 1u];
+    }
+    return bitcast<f32>(accum[idx]);
 }
 
 struct VertexOutput {
@@ -92,13 +99,22 @@ fn gamut_map(rgb: vec3<f32>, luminance: f32) -> vec3<f32> {
     return mix(vec3<f32>(luminance), rgb, t);
 }
 
+// Rainbow hue for a band index: violet for band 0 through red for the last.
+fn band_hue(band: u32) -> vec3<f32> {
+    let t = f32(band) / f32(max(SPECTRAL_BANDS - 1u, 1u));
+    let h = (1.0 - t) * 0.75 * 6.0;
+    return clamp(vec3<f32>(
+        abs(h - 3.0) - 1.0,
+        2.0 - abs(h - 2.0),
+        2.0 - abs(h - 4.0),
+    ), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let coord = vec2<i32>(in.position.xy);
 
-    var X = 0.0;
-    var Y = 0.0;
-    var Z = 0.0;
+    var group_energies = array<f32, 2>(0.0, 0.0);
 
     for (var g = 0u; g < params.group_count; g++) {
         let group = params.groups[g];
@@ -127,14 +143,35 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
             group_energy += load_accum(coord.x, coord.y, group.instant_layer);
         }
 
-        // Distribute scalar energy across spectral bands using shared emission
-        // weights, then integrate against CIE color matching functions.
-        for (var band = 0u; band < SPECTRAL_BANDS; band++) {
-            let spectral_energy = group_energy * get_group_weight(g, band);
-            X += spectral_energy * get_cie_weight(0u, band);
-            Y += spectral_energy * get_cie_weight(1u, band);
-            Z += spectral_energy * get_cie_weight(2u, band);
+        group_energies[g] = group_energy;
+    }
+
+    // Distribute each group's scalar energy across spectral bands using its
+    // emission weights, then integrate against CIE color matching functions.
+    var X = 0.0;
+    var Y = 0.0;
+    var Z = 0.0;
+    var dominant_band = 0u;
+    var dominant_energy = 0.0;
+    for (var band = 0u; band < SPECTRAL_BANDS; band++) {
+        var spectral_energy = 0.0;
+        for (var g = 0u; g < params.group_count; g++) {
+            spectral_energy += group_energies[g] * get_group_weight(g, band);
+        }
+        X += spectral_energy * get_cie_weight(0u, band);
+        Y += spectral_energy * get_cie_weight(1u, band);
+        Z += spectral_energy * get_cie_weight(2u, band);
+        if spectral_energy > dominant_energy {
+            dominant_energy = spectral_energy;
+            dominant_band = band;
+        }
+    }
+
+    if params.debug_mode == 1u {
+        if dominant_energy <= 0.0 {
+            return vec4<f32>(0.0);
         }
+        return vec4<f32>(band_hue(dominant_band) * Y, Y);
     }
 
     // XYZ -> linear sRGB (IEC 61966-2-1)
@@ -0,0 +1,98 @@
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+
+/// A persistent uniform buffer holding one `T`. Created once and updated in
+/// place with `queue.write_buffer`, rather than reallocated every frame.
+///
+/// Writes are staged on the queue and land before the next submit, so a
+/// buffer can only hold one value per frame. Passes that need different
+/// values in the same frame (e.g. the primary and compare phosphors) each
+/// need their own `UniformBuffer`.
+pub struct UniformBuffer<T> {
+    buffer: wgpu::Buffer,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> UniformBuffer<T> {
+    pub fn new(device: &wgpu::Device, label: &str) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: std::mem::size_of::<T>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn write(&self, queue: &wgpu::Queue, value: &T) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(value));
+    }
+
+    pub fn binding(&self) -> wgpu::BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+
+    /// Create a bind group with this buffer at binding 0, for layouts whose
+    /// only entry is the params uniform. The bind group stays valid for the
+    /// lifetime of the buffer.
+    pub fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        label: &str,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(label),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: self.binding(),
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use crate::gpu::accumulation::AccumDims;
+    use crate::gpu::beam_write::{BeamParams, EmissionParams};
+    use crate::gpu::composite::CompositeParams;
+    use crate::gpu::decay::DecayParams;
+    use crate::gpu::faceplate_scatter::{BlurParams, DownsampleParams, TemporalParams};
+    use crate::gpu::histogram::HistogramParams;
+    use crate::gpu::spectral_resolve::SpectralResolveParams;
+    use crate::phosphor::spectral::PACKED_BAND_VEC4S;
+
+    fn assert_uniform_layout<T: bytemuck::Pod>(expected: usize) {
+        assert_eq!(size_of::<T>(), expected, "{}", std::any::type_name::<T>());
+        assert_eq!(
+            size_of::<T>() % 16,
+            0,
+            "{} must be a multiple of 16 bytes for uniform buffers",
+            std::any::type_name::<T>()
+        );
+    }
+
+    #[test]
+    fn param_structs_match_wgsl_sizes() {
+        assert_uniform_layout::<AccumDims>(16);
+        assert_uniform_layout::<BeamParams>(48);
+        assert_uniform_layout::<EmissionParams>(32);
+        assert_uniform_layout::<DecayParams>(160);
+        // Three packed CIE arrays, a 16-byte header, and two emission groups
+        // (packed weights + 32 bytes of layer indices): 400 at 16 bands.
+        let packed = PACKED_BAND_VEC4S * 16;
+        assert_uniform_layout::<SpectralResolveParams>(3 * packed + 16 + 2 * (packed + 32));
+        assert_uniform_layout::<DownsampleParams>(16);
+        assert_uniform_layout::<BlurParams>(16);
+        assert_uniform_layout::<TemporalParams>(16);
+        assert_uniform_layout::<CompositeParams>(128);
+        assert_uniform_layout::<HistogramParams>(16);
+    }
+}
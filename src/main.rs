@@ -2,11 +2,14 @@
 
 mod app;
 mod beam;
+mod cli;
 mod controls_window;
 mod frame;
 mod gpu;
 mod phosphor;
 mod presets;
+mod settings;
+mod sim_thread;
 mod simulation;
 mod simulation_stats;
 mod types;
@@ -22,8 +25,20 @@ fn main() -> anyhow::Result<()> {
         .with_writer(non_blocking)
         .init();
 
+    let config = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(config) if config.help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("phosphor: {e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let event_loop = winit::event_loop::EventLoop::new().expect("failed to create event loop");
-    let mut app = app::App::default();
+    let mut app = app::App::new(settings::AppSettings::load(), config);
     event_loop.run_app(&mut app).expect("event loop error");
 
     Ok(())
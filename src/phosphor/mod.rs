@@ -2,19 +2,43 @@ pub mod spectral;
 
 pub use phosphor_data::PhosphorType;
 
-/// Built-in phosphor database, baked at compile time from data/phosphors.toml.
+/// Built-in phosphor database, baked at compile time from data/phosphors.toml
+/// (`phosphor_data::DEFAULT_DATABASE_PATH`).
 /// Returned sorted by designation in natural order (P1 < P2 < P10).
 pub fn phosphor_database() -> Vec<PhosphorType> {
-    let mut db = phosphor_data_macro::phosphor_table!("data/phosphors.toml").to_vec();
-    db.sort_by(|a, b| natord::compare(&a.designation, &b.designation));
+    let mut db = phosphor_data_macro::phosphor_table!().to_vec();
+    sort_phosphors(&mut db);
     db
 }
 
-/// Load additional phosphors from a TOML file on disk.
+/// Sort phosphors by designation in natural order (P1 < P2 < P10). The sort
+/// is stable. Designations are expected to be unique — UI selection is
+/// tracked by index, so duplicates make the selector ambiguous; debug builds
+/// warn when they are found. Call again after appending runtime-loaded
+/// phosphors to a database.
+pub fn sort_phosphors(db: &mut [PhosphorType]) {
+    db.sort_by(|a, b| natord::compare(&a.designation, &b.designation));
+
+    if cfg!(debug_assertions) {
+        for pair in db.windows(2) {
+            if pair[0].designation == pair[1].designation {
+                tracing::warn!("Duplicate phosphor designation: {}", pair[0].designation);
+            }
+        }
+    }
+}
+
+/// Load additional phosphors from a TOML file on disk, sorted in natural
+/// designation order. Non-fatal problems with the definitions are logged.
 pub fn load_phosphors(
     path: &std::path::Path,
-) -> anyhow::Result<Vec<PhosphorType>, Box<dyn std::error::Error>> {
-    phosphor_data::load_phosphors_from_file(path)
+) -> Result<Vec<PhosphorType>, phosphor_data::PhosphorLoadError> {
+    let (mut phosphors, warnings) = phosphor_data::load_phosphors_from_file_with_warnings(path)?;
+    for warning in warnings {
+        tracing::warn!("{}: {warning}", path.display());
+    }
+    sort_phosphors(&mut phosphors);
+    Ok(phosphors)
 }
 
 #[cfg(test)]
@@ -29,6 +53,70 @@ mod tests {
         assert!(db.iter().any(|p| p.designation == "P31"));
     }
 
+    #[test]
+    fn csv_sourced_phosphor_carries_raw_spectrum() {
+        let db = phosphor_database();
+        let p1 = db.iter().find(|p| p.designation == "P1").unwrap();
+        let expected = phosphor_data::spectral::parse_spectrum_points(include_str!(
+            "../../data/spectra/p1_willemite.csv"
+        ))
+        .unwrap();
+        assert_eq!(p1.fluorescence.raw_spectrum.as_ref(), Some(&expected));
+
+        let gaussian = db
+            .iter()
+            .find(|p| p.fluorescence.raw_spectrum.is_none())
+            .expect("some built-in phosphor uses a Gaussian spectrum");
+        assert!(gaussian.phosphorescence.raw_spectrum.is_none());
+    }
+
+    #[test]
+    fn category_counts_cover_the_whole_database() {
+        let db = phosphor_database();
+        let counts = phosphor_data::category_counts(&db);
+        assert_eq!(counts.values().sum::<usize>(), db.len());
+    }
+
+    #[test]
+    fn sort_phosphors_uses_natural_order() {
+        let toml_str = ["P1", "P10", "P2"]
+            .iter()
+            .map(|name| {
+                format!(
+                    r#"
+[{name}]
+description = "Test phosphor."
+category = "general_purpose"
+peak_nm = 525.0
+fwhm_nm = 40.0
+relative_luminance = 50.0
+relative_writing_speed = 50.0
+
+[[{name}.decay_terms]]
+type = "exponential"
+amplitude = 1.0
+tau = 0.001
+"#
+                )
+            })
+            .collect::<String>();
+        let mut phosphors = phosphor_data::load_phosphors(&toml_str).unwrap();
+        sort_phosphors(&mut phosphors);
+        let order: Vec<_> = phosphors.iter().map(|p| p.designation.as_str()).collect();
+        assert_eq!(order, ["P1", "P2", "P10"]);
+    }
+
+    #[test]
+    fn database_is_naturally_ordered() {
+        let db = phosphor_database();
+        for pair in db.windows(2) {
+            assert_ne!(
+                natord::compare(&pair[0].designation, &pair[1].designation),
+                std::cmp::Ordering::Greater
+            );
+        }
+    }
+
     #[test]
     fn dual_layer_parsing_from_toml() {
         let toml_str = r#"
@@ -62,6 +150,20 @@ fwhm_nm = 50.0
         );
     }
 
+    #[test]
+    fn p1_persists_longer_than_short_decay_phosphors() {
+        let db = phosphor_database();
+        let tau = |designation: &str| {
+            db.iter()
+                .find(|p| p.designation == designation)
+                .unwrap()
+                .longest_tau()
+        };
+        assert!((tau("P1") - 0.0151).abs() < 1e-6, "{}", tau("P1"));
+        assert!(tau("P1") > tau("P24"));
+        assert!(tau("P1") > tau("P15"));
+    }
+
     #[test]
     fn single_layer_phosphors_share_emission() {
         let db = phosphor_database();
@@ -1,59 +1,12 @@
 pub use phosphor_data::spectral::{
-    BAND_WIDTH, SPECTRAL_BANDS, WAVELENGTH_MAX, WAVELENGTH_MIN, band_center, band_range,
+    PACKED_BAND_VEC4S, PackedBands, SPECTRAL_BAND_CENTERS, SPECTRAL_BANDS, WAVELENGTH_MAX,
+    WAVELENGTH_MIN, pack_bands, pack_cie_weights,
 };
 
 // Re-exported for tests; not used in non-test code paths within this crate.
 #[cfg(test)]
-pub use phosphor_data::spectral::gaussian_emission_weights;
-
-/// CIE 1931 2-degree standard observer color matching functions.
-/// 1nm steps, 360-830nm. Each entry: (wavelength_nm, x_bar, y_bar, z_bar).
-///
-/// Source: CIE 018:2019 (DOI: 10.25039/CIE.DS.xvudnb9b)
-const CIE_1931_DATA: [(f32, f64, f64, f64); 471] =
-    cie_data::cie_1931_table!("data/CIE_xyz_1931_2deg.csv");
-
-/// CIE 1931 color matching function values (x_bar, y_bar, z_bar),
-/// pre-integrated over each spectral band. Used to convert spectral
-/// energy to XYZ tristimulus values.
-///
-/// Computed at compile time as a [SPECTRAL_BANDS] array of (x, y, z) tuples.
-pub const CIE_INTEGRATION_WEIGHTS: [(f32, f32, f32); SPECTRAL_BANDS] = {
-    let mut weights = [(0.0f32, 0.0f32, 0.0f32); SPECTRAL_BANDS];
-
-    let mut band = 0;
-    while band < SPECTRAL_BANDS {
-        let (band_min, band_max) = band_range(band);
-        let mut sum_x = 0.0f64;
-        let mut sum_y = 0.0f64;
-        let mut sum_z = 0.0f64;
-        let mut count = 0u32;
-
-        let mut i = 0;
-        while i < CIE_1931_DATA.len() {
-            let (wl, x, y, z) = CIE_1931_DATA[i];
-            if wl >= band_min && wl < band_max {
-                sum_x += x;
-                sum_y += y;
-                sum_z += z;
-                count += 1;
-            }
-            i += 1;
-        }
-
-        if count > 0 {
-            let n = count as f64;
-            weights[band] = (
-                (sum_x / n * BAND_WIDTH as f64) as f32,
-                (sum_y / n * BAND_WIDTH as f64) as f32,
-                (sum_z / n * BAND_WIDTH as f64) as f32,
-            );
-        }
-
-        band += 1;
-    }
-
-    weights
+pub use phosphor_data::spectral::{
+    CIE_INTEGRATION_WEIGHTS, band_center, band_range, gaussian_emission_weights,
 };
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use crate::beam::oscilloscope::Waveform;
+use crate::beam::oscilloscope::{OscilloscopeMode, TriggerSlope, TriggerSource, Waveform};
 use crate::types::OscilloscopeState;
 
 use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
@@ -25,6 +25,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: FRAC_PI_2,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -42,6 +56,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: 0.0,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -59,6 +87,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: 0.0,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -76,6 +118,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: 0.0,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -93,6 +149,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: FRAC_PI_2,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -110,6 +180,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: FRAC_PI_2,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -127,6 +211,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: FRAC_PI_2,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -144,6 +242,20 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: 0.0,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
     OscilloscopePreset {
@@ -161,6 +273,46 @@ pub const OSCILLOSCOPE_PRESETS: &[OscilloscopePreset] = &[
             y_phase: 0.0,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         },
     },
 ];
+
+/// A named combination of the engineer panel's glass faceplate controls.
+pub struct GlassPreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub curvature: f32,
+    pub edge_falloff: f32,
+    pub glass_tint: [f32; 3],
+}
+
+pub const GLASS_PRESETS: &[GlassPreset] = &[
+    GlassPreset {
+        name: "CRT look",
+        description: "Curved, slightly green-tinted glass with darkened edges",
+        curvature: 0.15,
+        edge_falloff: 0.35,
+        glass_tint: [0.88, 0.94, 0.9],
+    },
+    GlassPreset {
+        name: "Off (flat)",
+        description: "Flat, clear glass with no edge darkening",
+        curvature: 0.0,
+        edge_falloff: 0.0,
+        glass_tint: [1.0, 1.0, 1.0],
+    },
+];
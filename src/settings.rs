@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::{PanelTab, TimingPalette};
+
+/// Persistent application settings, stored as TOML in the user's config
+/// directory. Missing fields fall back to their defaults so older settings
+/// files keep loading as new fields are added.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Last main-window geometry, in physical pixels.
+    pub window: Option<WindowGeometry>,
+    /// Whether the controls live in a separate window.
+    pub detached: bool,
+    /// Stop rendering and simulating while no window has focus.
+    pub pause_when_unfocused: bool,
+    /// Colors of the engineer panel's GPU timing plots.
+    pub timing_palette: TimingPalette,
+    /// Control panel tab that was open.
+    pub panel_tab: PanelTab,
+    /// Side panel width in points, once the user has shown it.
+    pub panel_width: Option<f32>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            window: None,
+            detached: true,
+            pause_when_unfocused: false,
+            timing_palette: TimingPalette::default(),
+            panel_tab: PanelTab::default(),
+            panel_width: None,
+        }
+    }
+}
+
+/// Outer position and inner size of a window, in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A monitor's desktop-space rectangle, in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonitorRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorRect {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (x as i64, y as i64);
+        let (left, top) = (self.x as i64, self.y as i64);
+        x >= left && y >= top && x < left + self.width as i64 && y < top + self.height as i64
+    }
+}
+
+impl WindowGeometry {
+    /// Whether the window's top-left corner (where the title bar lives) falls
+    /// on one of `monitors`. A saved position failing this check came from a
+    /// monitor that is no longer attached and should not be restored.
+    pub fn is_on_screen(&self, monitors: &[MonitorRect]) -> bool {
+        self.width > 0 && self.height > 0 && monitors.iter().any(|m| m.contains(self.x, self.y))
+    }
+}
+
+impl AppSettings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("phosphor").join("settings.toml"))
+    }
+
+    /// Load settings from disk, falling back to defaults if the file is
+    /// missing or unreadable.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid settings file {}: {e}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path().ok_or_else(|| anyhow::anyhow!("no config directory"))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIMARY: MonitorRect = MonitorRect {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+    };
+    const SECONDARY: MonitorRect = MonitorRect {
+        x: 1920,
+        y: -200,
+        width: 2560,
+        height: 1440,
+    };
+
+    fn geometry(x: i32, y: i32) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width: 800,
+            height: 600,
+        }
+    }
+
+    #[test]
+    fn position_on_primary_is_on_screen() {
+        assert!(geometry(100, 100).is_on_screen(&[PRIMARY]));
+    }
+
+    #[test]
+    fn position_on_secondary_is_on_screen() {
+        assert!(geometry(2500, -100).is_on_screen(&[PRIMARY, SECONDARY]));
+    }
+
+    #[test]
+    fn position_on_detached_monitor_is_rejected() {
+        assert!(!geometry(2500, 300).is_on_screen(&[PRIMARY]));
+    }
+
+    #[test]
+    fn no_monitors_rejects_everything() {
+        assert!(!geometry(0, 0).is_on_screen(&[]));
+    }
+
+    #[test]
+    fn zero_size_is_rejected() {
+        let geom = WindowGeometry {
+            width: 0,
+            ..geometry(100, 100)
+        };
+        assert!(!geom.is_on_screen(&[PRIMARY]));
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let settings = AppSettings {
+            window: Some(geometry(-50, 20)),
+            detached: false,
+            pause_when_unfocused: true,
+            timing_palette: TimingPalette::ColorBlind,
+            panel_tab: PanelTab::Engineer,
+            panel_width: Some(310.0),
+        };
+        let text = toml::to_string(&settings).unwrap();
+        assert_eq!(toml::from_str::<AppSettings>(&text).unwrap(), settings);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let settings: AppSettings = toml::from_str("").unwrap();
+        assert_eq!(settings, AppSettings::default());
+    }
+}
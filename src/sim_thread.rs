@@ -0,0 +1,235 @@
+//! Scheduling for the simulation thread: best-effort priority tweaks and
+//! the hybrid sleep that paces its batches. Raising its priority keeps
+//! sample generation on time when the system is loaded; failures only log a
+//! warning, since the thread runs fine without them.
+
+use std::time::{Duration, Instant};
+
+use thread_priority::ThreadPriority;
+
+/// How to set up the simulation thread's scheduling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimThreadConfig {
+    /// Try to raise the thread above normal priority, within the normal
+    /// time-sharing scheduler.
+    pub elevate_priority: bool,
+    /// Ask for real-time round-robin scheduling instead (Unix only, usually
+    /// needs `CAP_SYS_NICE` or an rtprio limit). Opt-in: a real-time thread
+    /// preempts the render and UI threads, so it never spins while waiting.
+    pub realtime: bool,
+}
+
+impl Default for SimThreadConfig {
+    fn default() -> Self {
+        Self {
+            elevate_priority: true,
+            realtime: false,
+        }
+    }
+}
+
+/// The scheduling the simulation thread ended up with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimScheduling {
+    Normal,
+    /// Raised priority under the normal scheduler.
+    Elevated,
+    /// Real-time round-robin.
+    Realtime,
+}
+
+impl SimScheduling {
+    /// The sleeper to pace batches with. Real-time threads sleep through
+    /// the whole wait, since spinning would starve everything below them.
+    pub fn sleeper(self) -> HybridSleeper {
+        match self {
+            Self::Realtime => HybridSleeper::sleep_only(),
+            Self::Normal | Self::Elevated => HybridSleeper::default(),
+        }
+    }
+}
+
+/// Priority for [`SimThreadConfig::elevate_priority`], on thread-priority's
+/// 0–100 scale: nice -5 on Linux, above normal on Windows.
+const ELEVATED_PRIORITY: u8 = 60;
+
+impl SimThreadConfig {
+    /// Apply the config to the calling thread, logging anything that fails.
+    /// Falls back from real-time to a plain raise when that is refused.
+    pub fn apply_to_current_thread(&self) -> SimScheduling {
+        if self.realtime {
+            match set_current_thread_realtime() {
+                Ok(()) => return SimScheduling::Realtime,
+                Err(e) => tracing::warn!("Couldn't make simulation thread real-time: {e}"),
+            }
+        }
+        if !self.elevate_priority {
+            return SimScheduling::Normal;
+        }
+        let priority = ThreadPriority::try_from(ELEVATED_PRIORITY).unwrap();
+        match thread_priority::set_current_thread_priority(priority) {
+            Ok(()) => SimScheduling::Elevated,
+            Err(e) => {
+                tracing::warn!("Couldn't raise simulation thread priority: {e}");
+                SimScheduling::Normal
+            }
+        }
+    }
+}
+
+/// Switch the calling thread to round-robin real-time scheduling at the
+/// lowest real-time priority.
+#[cfg(unix)]
+fn set_current_thread_realtime() -> Result<(), thread_priority::Error> {
+    use thread_priority::{RealtimeThreadSchedulePolicy, ThreadSchedulePolicy};
+
+    thread_priority::set_thread_priority_and_policy(
+        thread_priority::thread_native_id(),
+        ThreadPriority::Min,
+        ThreadSchedulePolicy::Realtime(RealtimeThreadSchedulePolicy::RoundRobin),
+    )
+}
+
+#[cfg(not(unix))]
+fn set_current_thread_realtime() -> Result<(), thread_priority::Error> {
+    Err(thread_priority::Error::Priority(
+        "real-time scheduling is only supported on Unix",
+    ))
+}
+
+/// Shortest tail to spin through before a deadline, even when the OS
+/// sleeps precisely.
+const MIN_SPIN: Duration = Duration::from_micros(100);
+
+/// Cap on the measured sleep overshoot, so one long stall doesn't turn
+/// every later wait into a spin.
+const MAX_GRANULARITY: Duration = Duration::from_millis(4);
+
+/// How much of `remaining` to hand to the OS sleep: everything except a
+/// tail of `granularity` (at least [`MIN_SPIN`]) that is spun instead, so a
+/// late wakeup still lands before the deadline.
+pub fn coarse_sleep_for(remaining: Duration, granularity: Duration) -> Duration {
+    remaining.saturating_sub(granularity.max(MIN_SPIN))
+}
+
+/// Waits until a deadline by sleeping coarsely, then spinning for the last
+/// stretch. Learns the OS sleep granularity from how far each coarse sleep
+/// overshoots.
+pub struct HybridSleeper {
+    granularity: Duration,
+    /// Spin through the tail; off for [`Self::sleep_only`].
+    spin: bool,
+}
+
+impl Default for HybridSleeper {
+    fn default() -> Self {
+        Self {
+            granularity: Duration::from_millis(1),
+            spin: true,
+        }
+    }
+}
+
+impl HybridSleeper {
+    /// A sleeper that never spins: it hands the whole wait to the OS and
+    /// accepts waking late.
+    pub fn sleep_only() -> Self {
+        Self {
+            spin: false,
+            ..Self::default()
+        }
+    }
+
+    /// Block until `deadline`. Returns the time spent in the OS sleep, i.e.
+    /// with the CPU idle; the remainder of the wait was spun.
+    pub fn sleep_until(&mut self, deadline: Instant) -> Duration {
+        let start = Instant::now();
+        if !self.spin {
+            std::thread::sleep(deadline.saturating_duration_since(start));
+            return start.elapsed();
+        }
+        let coarse = coarse_sleep_for(deadline.saturating_duration_since(start), self.granularity);
+        let mut idle = Duration::ZERO;
+        if !coarse.is_zero() {
+            std::thread::sleep(coarse);
+            idle = start.elapsed();
+            self.record_overshoot(idle.saturating_sub(coarse));
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+        idle
+    }
+
+    /// Track the worst recent overshoot: jump up to a larger one at once,
+    /// relax toward smaller ones slowly.
+    fn record_overshoot(&mut self, overshoot: Duration) {
+        self.granularity = if overshoot > self.granularity {
+            overshoot.min(MAX_GRANULARITY)
+        } else {
+            (self.granularity * 15 + overshoot) / 16
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_elevates_without_realtime() {
+        let config = SimThreadConfig::default();
+        assert!(config.elevate_priority);
+        assert!(!config.realtime);
+    }
+
+    #[test]
+    fn elevated_priority_is_a_valid_value() {
+        assert!(ThreadPriority::try_from(ELEVATED_PRIORITY).is_ok());
+    }
+
+    #[test]
+    fn coarse_sleep_leaves_a_spin_tail() {
+        let ms = Duration::from_millis;
+        // Sleep all but the measured granularity.
+        assert_eq!(coarse_sleep_for(ms(5), ms(1)), ms(4));
+        // Deadlines inside the granularity are spun entirely.
+        assert_eq!(coarse_sleep_for(ms(1), ms(2)), Duration::ZERO);
+        // Precise sleepers still keep a minimal spin tail.
+        assert_eq!(coarse_sleep_for(ms(5), Duration::ZERO), ms(5) - MIN_SPIN);
+    }
+
+    #[test]
+    fn granularity_jumps_up_and_relaxes_down() {
+        let mut sleeper = HybridSleeper::default();
+        sleeper.record_overshoot(Duration::from_millis(3));
+        assert_eq!(sleeper.granularity, Duration::from_millis(3));
+        sleeper.record_overshoot(Duration::from_secs(1));
+        assert_eq!(sleeper.granularity, MAX_GRANULARITY);
+        for _ in 0..200 {
+            sleeper.record_overshoot(Duration::from_micros(50));
+        }
+        assert!(sleeper.granularity < Duration::from_micros(100));
+    }
+
+    #[test]
+    fn sleep_until_reaches_the_deadline() {
+        let mut sleeper = HybridSleeper::default();
+        let deadline = Instant::now() + Duration::from_millis(3);
+        let idle = sleeper.sleep_until(deadline);
+        assert!(Instant::now() >= deadline);
+        assert!(idle <= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn realtime_threads_never_spin() {
+        assert!(!SimScheduling::Realtime.sleeper().spin);
+        assert!(SimScheduling::Elevated.sleeper().spin);
+
+        let mut sleeper = HybridSleeper::sleep_only();
+        let deadline = Instant::now() + Duration::from_millis(2);
+        let idle = sleeper.sleep_until(deadline);
+        assert!(Instant::now() >= deadline);
+        assert!(idle >= Duration::from_millis(2));
+    }
+}
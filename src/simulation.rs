@@ -4,13 +4,16 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::Receiver;
+use crossbeam_channel::{Receiver, TryRecvError};
 
-use crate::beam::audio::AudioSource;
+use crate::beam::audio::{AudioSource, Downmix, LevelMode};
+use crate::beam::envelope::{EnvelopeFollower, EnvelopeParams};
 use crate::beam::oscilloscope::{ChannelConfig, OscilloscopeSource};
-use crate::beam::vector::VectorSegment;
+use crate::beam::spectrum::{DEFAULT_FFT_SIZE, FFT_SIZES, SpectrumSource, SpectrumWindow};
+use crate::beam::vector::{VectorFile, VectorSegment};
 use crate::beam::{BeamSample, BeamSource, BeamState, SampleProducer};
-use crate::simulation_stats::SimStats;
+use crate::sim_thread::{HybridSleeper, SimScheduling, SimThreadConfig};
+use crate::simulation_stats::{SimStats, SourceStatus};
 use crate::types::{ExternalState, InputMode, OscilloscopeState};
 
 /// Calibration constant for beam energy deposition. The beam_write shader
@@ -19,7 +22,13 @@ use crate::types::{ExternalState, InputMode, OscilloscopeState};
 /// order of 1e-5, which is invisible after spectral integration and
 /// tonemapping. This constant represents the beam current / power scale
 /// that makes the phosphor visibly glow at the default settings.
-const BEAM_ENERGY_SCALE: f32 = 5000.0;
+///
+/// Because each sample's energy is weighted by its dwell time, the energy
+/// deposited per second of beam time is `BEAM_ENERGY_SCALE` regardless of
+/// sample rate: doubling the rate doubles the samples but halves their dt.
+/// The scale must stay a flat multiplier; dividing it by the sample rate
+/// would count the rate twice.
+pub(crate) const BEAM_ENERGY_SCALE: f32 = 5000.0;
 
 pub struct AudioState {
     pub file_path: Option<PathBuf>,
@@ -28,6 +37,42 @@ pub struct AudioState {
     pub looping: bool,
     pub speed: f32,
     pub load_error: Option<String>,
+    /// Level to normalize the file to; `None` plays it as recorded.
+    pub normalize: Option<LevelMode>,
+    /// How files with extra channels are folded to X/Y; `None` picks by
+    /// channel count. Applied at load, so changing it reloads the file.
+    pub downmix: Option<Downmix>,
+    /// Peak-hold envelope settings; `None` leaves intensity untouched.
+    pub envelope: Option<EnvelopeParams>,
+    pub envelope_follower: EnvelopeFollower,
+}
+
+impl AudioState {
+    /// The next `count` samples of playback, scaled by the speed, with
+    /// level normalization applied and looping or stopping at the end.
+    /// Empty while paused or with no file.
+    fn next_block(&mut self, count: usize, beam: &BeamState) -> Vec<BeamSample> {
+        if !self.playing {
+            return Vec::new();
+        }
+        let Some(source) = &mut self.source else {
+            return Vec::new();
+        };
+        let adj_count = (count as f32 * self.speed) as usize;
+        if adj_count == 0 {
+            return Vec::new();
+        }
+        source.set_normalization(self.normalize);
+        let samples = source.generate(adj_count, beam);
+        if source.is_finished() {
+            if self.looping {
+                source.seek(0.0);
+            } else {
+                self.playing = false;
+            }
+        }
+        samples
+    }
 }
 
 impl Default for AudioState {
@@ -39,6 +84,10 @@ impl Default for AudioState {
             looping: false,
             speed: 1.0,
             load_error: None,
+            normalize: None,
+            downmix: None,
+            envelope: None,
+            envelope_follower: EnvelopeFollower::default(),
         }
     }
 }
@@ -48,6 +97,8 @@ pub struct VectorState {
     pub segments: Vec<VectorSegment>,
     pub beam_speed: f32,
     pub settling_time: f32,
+    pub ringing: f32,
+    pub retrace_intensity: f32,
     pub looping: bool,
     pub load_error: Option<String>,
 }
@@ -59,6 +110,8 @@ impl Default for VectorState {
             segments: Vec::new(),
             beam_speed: 1.0,
             settling_time: 0.001,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
             looping: true,
             load_error: None,
         }
@@ -72,6 +125,7 @@ pub struct InputState {
     pub vector: VectorState,
     pub external: ExternalState,
     osc_source: OscilloscopeSource,
+    spectrum: SpectrumSource,
 }
 
 impl Default for InputState {
@@ -94,6 +148,8 @@ impl Default for InputState {
             },
             osc.sample_rate,
         );
+        let spectrum =
+            SpectrumSource::new(DEFAULT_FFT_SIZE, SpectrumWindow::default(), osc.sample_rate);
 
         Self {
             mode: InputMode::default(),
@@ -102,19 +158,63 @@ impl Default for InputState {
             vector: VectorState::default(),
             external: ExternalState::default(),
             osc_source,
+            spectrum,
         }
     }
 }
 
 impl InputState {
-    /// Generate a fixed number of samples at the given sample rate.
-    /// Unlike `generate_samples`, this does NOT measure wall-clock time —
-    /// dt is always `1/sample_rate`, making output deterministic.
+    /// Why the active source is or isn't producing samples.
+    pub fn source_status(&self) -> SourceStatus {
+        match self.mode {
+            InputMode::Oscilloscope => SourceStatus::Generating,
+            InputMode::Audio | InputMode::Spectrum if self.audio.source.is_none() => {
+                if self.audio.load_error.is_some() {
+                    SourceStatus::AudioLoadFailed
+                } else {
+                    SourceStatus::AudioNoFile
+                }
+            }
+            InputMode::Audio | InputMode::Spectrum if !self.audio.playing => {
+                SourceStatus::AudioPaused
+            }
+            InputMode::Audio | InputMode::Spectrum => SourceStatus::Generating,
+            InputMode::Vector if self.vector.segments.is_empty() => {
+                if self.vector.load_error.is_some() {
+                    SourceStatus::VectorLoadFailed
+                } else {
+                    SourceStatus::VectorNoFile
+                }
+            }
+            InputMode::Vector => SourceStatus::Generating,
+            InputMode::External if !self.external.connected => SourceStatus::ExternalNotConnected,
+            InputMode::External => SourceStatus::Generating,
+        }
+    }
+
+    /// Generate a fixed number of samples at the given sample rate. This is
+    /// the entry point for driving a source without the simulation thread.
+    /// It does not measure wall-clock time, so output is deterministic.
+    ///
+    /// Returned samples are in normalized viewport coordinates: `x` and `y`
+    /// run from 0 to 1 across the accumulation buffer, with (0, 0) at the
+    /// top-left and the trace centered on (0.5, 0.5). The narrower axis is
+    /// squeezed so the figure keeps its aspect ratio for `aspect`
+    /// (width / height). Samples are arc-length resampled to
+    /// `resample_factor` spot radii (`focus` px over `viewport_width`; see
+    /// [`resample_threshold`]), so `count` is only the number of source
+    /// samples and the result may be longer or shorter. `None` skips the
+    /// resampler and passes the source's samples through. `dt` is the
+    /// sample's dwell time in seconds, and `intensity` is pre-multiplied by
+    /// the beam energy scale, so `intensity * dt` is the energy `beam_write`
+    /// deposits.
+    #[must_use]
     pub fn generate_samples_fixed(
         &mut self,
         focus: f32,
         aspect: f32,
         viewport_width: f32,
+        resample_factor: Option<f32>,
         sample_rate: f32,
         count: usize,
     ) -> Vec<BeamSample> {
@@ -124,33 +224,33 @@ impl InputState {
         let mut samples = match self.mode {
             InputMode::Oscilloscope => {
                 self.sync_oscilloscope_params();
-                self.osc_source.sample_rate = sample_rate;
+                // Oversampling runs the source N times faster for N times
+                // as many samples: the same beam time at finer steps.
+                let oversample = self.oscilloscope.oversample.clamp(1, MAX_OVERSAMPLE);
+                self.osc_source.sample_rate = sample_rate * oversample as f32;
                 if count == 0 {
                     return Vec::new();
                 }
-                self.osc_source.generate(count, &beam)
+                self.osc_source.generate(count * oversample as usize, &beam)
             }
             InputMode::Audio => {
                 let audio = &mut self.audio;
-                if !audio.playing {
-                    return Vec::new();
+                let mut samples = audio.next_block(count, &beam);
+                if let Some(params) = &audio.envelope {
+                    audio.envelope_follower.apply(&mut samples, params);
                 }
-                let Some(source) = &mut audio.source else {
-                    return Vec::new();
-                };
-                let adj_count = (count as f32 * audio.speed) as usize;
-                if adj_count == 0 {
+                samples
+            }
+            InputMode::Spectrum => {
+                // The audio plays through the analyzer in real time; the
+                // sweep takes as much beam time as the audio it consumed.
+                let block = self.audio.next_block(count, &beam);
+                if block.is_empty() {
                     return Vec::new();
                 }
-                let samples = source.generate(adj_count, &beam);
-                if source.is_finished() {
-                    if audio.looping {
-                        source.seek(0.0);
-                    } else {
-                        audio.playing = false;
-                    }
-                }
-                samples
+                self.spectrum.push_beam_audio(&block);
+                self.spectrum.sample_rate = 1.0 / block[0].dt;
+                self.spectrum.generate(block.len(), &beam)
             }
             InputMode::Vector => {
                 if self.vector.segments.is_empty() {
@@ -160,6 +260,8 @@ impl InputState {
                     segments: self.vector.segments.clone(),
                     beam_speed: self.vector.beam_speed,
                     settling_time: self.vector.settling_time,
+                    ringing: self.vector.ringing,
+                    retrace_intensity: self.vector.retrace_intensity,
                 };
                 src.generate(0, &beam)
             }
@@ -178,7 +280,12 @@ impl InputState {
         }
 
         // Arc-length resample
-        let mut samples = crate::beam::resample::arc_length_resample(&samples, spot_radius * 0.5);
+        if let Some(factor) = resample_factor {
+            samples = crate::beam::resample::arc_length_resample(
+                &samples,
+                resample_threshold(spot_radius, factor),
+            );
+        }
 
         // Scale beam energy
         for s in &mut samples {
@@ -201,12 +308,27 @@ impl InputState {
         self.osc_source.y_channel.phase = osc.y_phase;
         self.osc_source.y_channel.dc_offset = osc.y_dc_offset;
         self.osc_source.sample_rate = osc.sample_rate;
+        self.osc_source.clamp_to_screen = osc.clamp_to_screen;
+        self.osc_source.blank_freq = osc.blank_freq;
+        self.osc_source.blank_duty = osc.blank_duty;
+        self.osc_source.mode = osc.mode;
+        self.osc_source.y2_channel.waveform = osc.y2_waveform;
+        self.osc_source.y2_channel.frequency = osc.y2_frequency;
+        self.osc_source.y2_channel.amplitude = osc.y2_amplitude;
+        self.osc_source.y2_channel.phase = osc.y2_phase;
+        self.osc_source.y2_channel.dc_offset = osc.y2_dc_offset;
+        self.osc_source.chop_freq = osc.chop_freq;
+        self.osc_source.trigger_source = osc.trigger_source;
+        self.osc_source.trigger_slope = osc.trigger_slope;
+        self.osc_source.trigger_level = osc.trigger_level;
     }
 
     pub fn load_audio_file(&mut self, path: PathBuf) {
-        match AudioSource::load(&path) {
+        match AudioSource::load_with_downmix(&path, self.audio.downmix) {
             Ok(source) => {
                 self.audio.source = Some(source);
+                self.audio.envelope_follower.reset();
+                self.spectrum.reset();
                 self.audio.file_path = Some(path);
                 self.audio.load_error = None;
                 self.audio.playing = true;
@@ -218,13 +340,42 @@ impl InputState {
         }
     }
 
+    /// Switch the audio downmix, reloading the current file with it. Playback
+    /// keeps its position and play/pause state.
+    pub fn set_audio_downmix(&mut self, downmix: Option<Downmix>) {
+        if downmix == self.audio.downmix {
+            return;
+        }
+        self.audio.downmix = downmix;
+        let (Some(path), Some(source)) = (self.audio.file_path.clone(), &self.audio.source) else {
+            return;
+        };
+        let position = source.position_secs() / source.duration_secs().max(f32::EPSILON);
+        let playing = self.audio.playing;
+        self.load_audio_file(path);
+        if let Some(source) = &mut self.audio.source {
+            source.seek(position);
+            self.audio.playing = playing;
+        }
+    }
+
+    /// Load a vector JSON file (see [`VectorFile`]), applying any beam speed
+    /// or settling time it carries.
     pub fn load_vector_file(&mut self, path: PathBuf) {
         match std::fs::read_to_string(&path) {
-            Ok(contents) => match serde_json::from_str::<Vec<VectorSegment>>(&contents) {
-                Ok(segments) => {
-                    self.vector.segments = segments;
+            Ok(contents) => match VectorFile::parse(&contents) {
+                Ok(file) => {
+                    // Out-of-range segments still load; the warning just
+                    // shows where the error would.
+                    self.vector.load_error = file.range_warning();
+                    self.vector.segments = file.segments;
+                    if let Some(speed) = file.beam_speed {
+                        self.vector.beam_speed = speed;
+                    }
+                    if let Some(settling) = file.settling_time {
+                        self.vector.settling_time = settling;
+                    }
                     self.vector.file_path = Some(path);
-                    self.vector.load_error = None;
                 }
                 Err(e) => {
                     self.vector.load_error = Some(e.to_string());
@@ -239,9 +390,38 @@ impl InputState {
     }
 }
 
-/// Target batch interval bounds.
-const MIN_BATCH_INTERVAL: Duration = Duration::from_millis(1);
-const MAX_BATCH_INTERVAL: Duration = Duration::from_millis(10);
+/// Smallest viewport dimension (pixels) accepted from `SetViewport`. Keeps
+/// spot-radius and aspect computations finite for degenerate sizes.
+const MIN_VIEWPORT_DIM: f32 = 16.0;
+
+/// Default target batch interval bounds, in milliseconds. Adjustable at
+/// runtime with [`SimCommand::SetBatchBounds`].
+pub const DEFAULT_BATCH_BOUNDS_MS: (f32, f32) = (1.0, 10.0);
+/// Range `SetBatchBounds` accepts for either bound, in milliseconds.
+pub const BATCH_BOUND_LIMITS_MS: (f32, f32) = (0.1, 100.0);
+
+fn batch_bound(ms: f32) -> Duration {
+    Duration::from_secs_f64(f64::from(ms) / 1000.0)
+}
+
+/// Largest oscilloscope oversampling factor.
+pub const MAX_OVERSAMPLE: u32 = 16;
+
+/// Default arc-length resample spacing, in spot radii.
+pub const DEFAULT_RESAMPLE_FACTOR: f32 = 0.5;
+/// Range `SetResampleFactor` accepts.
+pub const RESAMPLE_FACTOR_LIMITS: (f32, f32) = (0.1, 2.0);
+
+/// Arc length between resampled depositions for a spot of `spot_radius`
+/// (normalized units). Smaller factors keep more detail in dense figures
+/// at the cost of more samples; larger ones merge more and can smear fine
+/// structure.
+pub fn resample_threshold(spot_radius: f32, resample_factor: f32) -> f32 {
+    spot_radius * resample_factor
+}
+
+/// Beam time covered by one [`SimState::step`].
+const STEP_INTERVAL: Duration = Duration::from_millis(1);
 
 /// Commands sent from the render/UI thread to the simulation thread.
 pub enum SimCommand {
@@ -259,43 +439,132 @@ pub enum SimCommand {
     SetAudioPlaying(bool),
     SetAudioLooping(bool),
     SetAudioSpeed(f32),
+    /// FFT size and window for the spectrum analyzer. Sizes outside
+    /// [`FFT_SIZES`] are ignored.
+    SetSpectrumParams {
+        fft_size: usize,
+        window: SpectrumWindow,
+    },
+    /// Level normalization for audio; `None` turns it off.
+    SetAudioNormalize(Option<LevelMode>),
+    /// Channel fold-down for audio; `None` picks by channel count.
+    SetAudioDownmix(Option<Downmix>),
+    /// Peak-hold envelope for audio; `None` turns it off.
+    SetAudioEnvelope(Option<EnvelopeParams>),
     LoadVectorFile(PathBuf),
+    SetVectorParams {
+        beam_speed: f32,
+        settling_time: f32,
+        ringing: f32,
+        retrace_intensity: f32,
+    },
     /// Sample rate change — carries the new producer from a resized channel.
     /// The render thread creates the new channel and swaps its consumer.
     SetSampleRate {
         rate: f32,
         producer: SampleProducer,
     },
+    /// Bounds for the adaptive batch interval, in milliseconds. Ignored
+    /// unless `min_ms <= max_ms`; both are clamped to
+    /// [`BATCH_BOUND_LIMITS_MS`].
+    SetBatchBounds {
+        min_ms: f32,
+        max_ms: f32,
+    },
+    /// Stop generating samples while no window has focus, to save power.
+    SetInputPaused(bool),
+    /// Arc-length resample spacing in spot radii, clamped to
+    /// [`RESAMPLE_FACTOR_LIMITS`].
+    SetResampleFactor(f32),
+    /// Turn the arc-length resampler off to see a source's raw samples.
+    SetResampleEnabled(bool),
     Shutdown,
 }
 
 /// State tracked by the simulation thread, derived from SimCommands.
-struct SimState {
-    input: InputState,
+///
+/// Also usable without the thread: build one, configure it with
+/// [`apply_command`](Self::apply_command), and pull batches with
+/// [`step`](Self::step).
+///
+/// ```ignore
+/// // phosphor is a binary crate, so rustdoc doesn't run this; the
+/// // `step_generates_oscilloscope_samples` unit test does the same.
+/// let mut sim = SimState::new();
+/// sim.apply_command(SimCommand::SetInputMode(InputMode::Oscilloscope));
+/// let samples = sim.input.generate_samples_fixed(1.5, 1.0, 800.0, Some(0.5), 44_100.0, 100);
+/// assert!(!samples.is_empty());
+/// assert!(samples.iter().all(|s| (0.0..=1.0).contains(&s.x)));
+/// let batch = sim.step();
+/// ```
+pub struct SimState {
+    pub input: InputState,
     focus: f32,
+    /// Arc-length resample spacing in spot radii; see [`resample_threshold`].
+    resample_factor: f32,
+    /// Off passes source samples to the GPU unresampled, for debugging.
+    resample_enabled: bool,
     viewport_width: f32,
     viewport_height: f32,
+    /// The window is minimized (a zero-sized viewport was reported); no
+    /// samples are generated until it is restored.
+    minimized: bool,
+    /// Generation is paused because the app lost focus.
+    input_paused: bool,
     sample_rate: f32,
+    /// Bounds the adaptive batch interval stays within.
+    min_batch_interval: Duration,
+    max_batch_interval: Duration,
+    /// `on_shutdown` has run; resources are released.
+    shut_down: bool,
+}
+
+impl Default for SimState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SimState {
-    fn new() -> Self {
+    pub fn new() -> Self {
         let input = InputState::default();
         let sample_rate = input.oscilloscope.sample_rate;
         Self {
             input,
             focus: 1.5,
+            resample_factor: DEFAULT_RESAMPLE_FACTOR,
+            resample_enabled: true,
             viewport_width: 800.0,
             viewport_height: 600.0,
+            minimized: false,
+            input_paused: false,
             sample_rate,
+            min_batch_interval: batch_bound(DEFAULT_BATCH_BOUNDS_MS.0),
+            max_batch_interval: batch_bound(DEFAULT_BATCH_BOUNDS_MS.1),
+            shut_down: false,
+        }
+    }
+
+    /// Release everything the thread holds open before it exits. Resources
+    /// that need flushing or closing (open files, sockets) are torn down
+    /// here so every exit path cleans up the same way. Safe to call more
+    /// than once.
+    fn on_shutdown(&mut self) {
+        if self.shut_down {
+            return;
         }
+        self.shut_down = true;
+        // Dropping the decoder closes the audio file.
+        self.input.audio.source = None;
+        self.input.audio.playing = false;
+        self.input.vector.segments.clear();
     }
 
     fn aspect(&self) -> f32 {
         self.viewport_width / self.viewport_height.max(1.0)
     }
 
-    fn apply_command(&mut self, cmd: SimCommand) {
+    pub fn apply_command(&mut self, cmd: SimCommand) {
         match cmd {
             SimCommand::SetInputMode(mode) => self.input.mode = mode,
             SimCommand::SetOscilloscopeParams(params) => {
@@ -303,76 +572,181 @@ impl SimState {
             }
             SimCommand::SetFocus(f) => self.focus = f,
             SimCommand::SetViewport { width, height, .. } => {
-                self.viewport_width = width;
-                self.viewport_height = height;
+                // NaN compares false, so it is treated as minimized too
+                self.minimized = !(width >= 1.0 && height >= 1.0);
+                self.viewport_width = width.max(MIN_VIEWPORT_DIM);
+                self.viewport_height = height.max(MIN_VIEWPORT_DIM);
             }
             SimCommand::LoadAudioFile(path) => self.input.load_audio_file(path),
             SimCommand::SetAudioPlaying(p) => self.input.audio.playing = p,
             SimCommand::SetAudioLooping(l) => self.input.audio.looping = l,
             SimCommand::SetAudioSpeed(s) => self.input.audio.speed = s,
+            SimCommand::SetAudioNormalize(mode) => self.input.audio.normalize = mode,
+            SimCommand::SetAudioDownmix(downmix) => self.input.set_audio_downmix(downmix),
+            SimCommand::SetSpectrumParams { fft_size, window } => {
+                if FFT_SIZES.contains(&fft_size) {
+                    self.input.spectrum.set_params(fft_size, window);
+                } else {
+                    tracing::warn!(fft_size, "ignoring unsupported FFT size");
+                }
+            }
+            SimCommand::SetAudioEnvelope(envelope) => self.input.audio.envelope = envelope,
             SimCommand::LoadVectorFile(path) => self.input.load_vector_file(path),
+            SimCommand::SetVectorParams {
+                beam_speed,
+                settling_time,
+                ringing,
+                retrace_intensity,
+            } => {
+                self.input.vector.beam_speed = beam_speed;
+                self.input.vector.settling_time = settling_time;
+                self.input.vector.ringing = ringing;
+                self.input.vector.retrace_intensity = retrace_intensity;
+            }
             SimCommand::SetSampleRate { rate, .. } => self.sample_rate = rate,
+            SimCommand::SetBatchBounds { min_ms, max_ms } => {
+                self.set_batch_bounds(min_ms, max_ms);
+            }
+            SimCommand::SetInputPaused(paused) => self.input_paused = paused,
+            SimCommand::SetResampleFactor(factor) => {
+                let (lo, hi) = RESAMPLE_FACTOR_LIMITS;
+                if factor.is_nan() {
+                    tracing::warn!("ignoring NaN resample factor");
+                } else {
+                    self.resample_factor = factor.clamp(lo, hi);
+                }
+            }
+            SimCommand::SetResampleEnabled(enabled) => self.resample_enabled = enabled,
             SimCommand::Shutdown => {} // handled by caller
         }
     }
+
+    fn set_batch_bounds(&mut self, min_ms: f32, max_ms: f32) {
+        if min_ms.is_nan() || max_ms.is_nan() || min_ms > max_ms {
+            tracing::warn!(min_ms, max_ms, "ignoring batch bounds with min > max");
+            return;
+        }
+        let (lo, hi) = BATCH_BOUND_LIMITS_MS;
+        self.min_batch_interval = batch_bound(min_ms.clamp(lo, hi));
+        self.max_batch_interval = batch_bound(max_ms.clamp(lo, hi));
+    }
+
+    /// Next batch interval after a batch at `interval` took `gen_elapsed`
+    /// to generate: double it when generation used over 80% of the
+    /// interval, halve it under 20%, always within the batch bounds.
+    fn adapt_batch_interval(&self, interval: Duration, gen_elapsed: Duration) -> Duration {
+        let next = if gen_elapsed > interval.mul_f32(0.8) {
+            interval * 2
+        } else if gen_elapsed < interval.mul_f32(0.2) {
+            interval / 2
+        } else {
+            interval
+        };
+        self.clamp_batch_interval(next)
+    }
+
+    fn clamp_batch_interval(&self, interval: Duration) -> Duration {
+        interval.clamp(self.min_batch_interval, self.max_batch_interval)
+    }
+
+    fn source_status(&self) -> SourceStatus {
+        if self.minimized {
+            SourceStatus::Minimized
+        } else if self.input_paused {
+            SourceStatus::Unfocused
+        } else {
+            self.input.source_status()
+        }
+    }
+
+    /// Generate one batch of samples covering `STEP_INTERVAL` of beam time,
+    /// with the conventions of [`InputState::generate_samples_fixed`].
+    #[must_use]
+    pub fn step(&mut self) -> Vec<BeamSample> {
+        let count = (self.sample_rate as f64 * STEP_INTERVAL.as_secs_f64()) as usize;
+        self.generate_batch(count.max(1))
+    }
+
+    /// Generate one batch of `count` samples for the current state. Empty
+    /// while the window is minimized or input is paused.
+    fn generate_batch(&mut self, count: usize) -> Vec<BeamSample> {
+        if self.minimized || self.input_paused {
+            return Vec::new();
+        }
+        let aspect = self.aspect();
+        self.input.generate_samples_fixed(
+            self.focus,
+            aspect,
+            self.viewport_width,
+            self.resample_enabled.then_some(self.resample_factor),
+            self.sample_rate,
+            count,
+        )
+    }
 }
 
-/// Run the simulation loop on the current thread. Blocks until Shutdown
-/// is received or the command channel is disconnected.
+/// Run the simulation loop on the current thread, pacing batches with
+/// `sleeper`. Blocks until Shutdown is received or the command channel is
+/// disconnected.
 pub fn run_simulation(
     mut producer: SampleProducer,
     commands: Receiver<SimCommand>,
     stats: Arc<SimStats>,
+    mut sleeper: HybridSleeper,
 ) {
     let _span = tracing::info_span!("sim").entered();
     let mut state = SimState::new();
 
     tracing::info!(sample_rate = state.sample_rate, "thread started");
 
-    let mut batch_interval = MIN_BATCH_INTERVAL;
+    let mut batch_interval = state.min_batch_interval;
     let mut next_tick = Instant::now();
 
     // Throughput tracking: count samples over a 1-second window
     let mut samples_this_second: usize = 0;
     let mut generated_this_second: usize = 0;
+    let mut idle_this_second = Duration::ZERO;
     let mut second_timer = Instant::now();
 
     loop {
         // Process all pending commands
-        while let Ok(cmd) = commands.try_recv() {
-            if matches!(cmd, SimCommand::Shutdown) {
-                tracing::info!("thread shutting down");
-                return;
-            }
-            // SetSampleRate carries a new producer — swap it before
-            // apply_command, which only updates the rate field.
-            if let SimCommand::SetSampleRate {
-                rate,
-                producer: new_prod,
-            } = cmd
-            {
-                producer = new_prod;
-                state.sample_rate = rate;
-                tracing::info!(sample_rate = rate, "sample rate changed");
-                continue;
+        loop {
+            match commands.try_recv() {
+                Ok(SimCommand::Shutdown) | Err(TryRecvError::Disconnected) => {
+                    tracing::info!(
+                        throughput = stats.throughput.load(Ordering::Relaxed),
+                        samples_dropped = stats.samples_dropped.load(Ordering::Relaxed),
+                        "thread shutting down"
+                    );
+                    state.on_shutdown();
+                    return;
+                }
+                // SetSampleRate carries a new producer — swap it before
+                // apply_command, which only updates the rate field.
+                Ok(SimCommand::SetSampleRate {
+                    rate,
+                    producer: new_prod,
+                }) => {
+                    producer = new_prod;
+                    state.sample_rate = rate;
+                    tracing::info!(sample_rate = rate, "sample rate changed");
+                }
+                Ok(cmd) => state.apply_command(cmd),
+                Err(TryRecvError::Empty) => break,
             }
-            state.apply_command(cmd);
         }
 
-        // Compute batch size from current sample rate and batch interval
+        // Compute batch size from current sample rate and batch interval,
+        // which may be out of bounds after a SetBatchBounds
+        batch_interval = state.clamp_batch_interval(batch_interval);
         let batch_size = (state.sample_rate as f64 * batch_interval.as_secs_f64()) as usize;
         let batch_size = batch_size.max(1);
 
         let gen_start = Instant::now();
 
         // Generate a batch of samples
-        let samples = state.input.generate_samples_fixed(
-            state.focus,
-            state.aspect(),
-            state.viewport_width,
-            state.sample_rate,
-            batch_size,
-        );
+        let samples = state.generate_batch(batch_size);
+        stats.set_source_status(state.source_status());
 
         // Push into ring buffer (partial write if buffer is near-full)
         let pushed = if !samples.is_empty() {
@@ -402,34 +776,33 @@ pub fn run_simulation(
             stats
                 .samples_generated
                 .store(generated_this_second as f32, Ordering::Relaxed);
+            let idle_fraction =
+                idle_this_second.as_secs_f32() / second_timer.elapsed().as_secs_f32();
+            stats
+                .idle_fraction
+                .store(idle_fraction.min(1.0), Ordering::Relaxed);
 
             // If throughput fell below 90% of target, grow the batch interval
             // so each iteration produces more samples, amortizing loop overhead.
             if throughput < state.sample_rate * 0.9 {
-                batch_interval = (batch_interval * 2).min(MAX_BATCH_INTERVAL);
+                batch_interval = state.clamp_batch_interval(batch_interval * 2);
             }
 
             samples_this_second = 0;
             generated_this_second = 0;
+            idle_this_second = Duration::ZERO;
             second_timer = Instant::now();
         }
 
         let gen_elapsed = gen_start.elapsed();
 
-        // Adaptive batch interval:
-        // If generation took >80% of the batch interval, double it (up to cap).
-        // If generation took <20% of the batch interval, halve it (down to floor).
-        if gen_elapsed > batch_interval.mul_f32(0.8) {
-            batch_interval = (batch_interval * 2).min(MAX_BATCH_INTERVAL);
-        } else if gen_elapsed < batch_interval.mul_f32(0.2) {
-            batch_interval = (batch_interval / 2).max(MIN_BATCH_INTERVAL);
-        }
+        batch_interval = state.adapt_batch_interval(batch_interval, gen_elapsed);
 
         // Pace to target interval
         next_tick += batch_interval;
         let now = Instant::now();
         if next_tick > now {
-            spin_sleep::sleep(next_tick - now);
+            idle_this_second += sleeper.sleep_until(next_tick);
         } else {
             // Fell behind — reset to avoid burst catch-up
             next_tick = now;
@@ -437,10 +810,12 @@ pub fn run_simulation(
     }
 }
 
-/// Spawn the simulation thread. Returns a join handle and command sender.
+/// Spawn the simulation thread, applying `thread_config` to it first.
+/// Returns a join handle and command sender.
 pub fn spawn_simulation(
     producer: SampleProducer,
     stats: Arc<SimStats>,
+    thread_config: SimThreadConfig,
 ) -> (
     thread::JoinHandle<()>,
     crossbeam_channel::Sender<SimCommand>,
@@ -449,8 +824,295 @@ pub fn spawn_simulation(
     let handle = thread::Builder::new()
         .name("phosphor-sim".into())
         .spawn(move || {
-            run_simulation(producer, rx, stats);
+            let scheduling = thread_config.apply_to_current_thread();
+            stats
+                .priority_elevated
+                .store(scheduling != SimScheduling::Normal, Ordering::Relaxed);
+            run_simulation(producer, rx, stats, scheduling.sleeper());
         })
         .expect("failed to spawn simulation thread");
     (handle, tx)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_vector_json(name: &str, json: &str) -> InputState {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, json).unwrap();
+        let mut input = InputState::default();
+        input.load_vector_file(path.clone());
+        let _ = std::fs::remove_file(&path);
+        input
+    }
+
+    const VECTOR_SEGMENT: &str =
+        r#"{"x0": 0.0, "y0": 0.0, "x1": 1.0, "y1": 1.0, "intensity": 1.0}"#;
+
+    #[test]
+    fn bare_vector_file_keeps_current_settings() {
+        let input = load_vector_json("phosphor_test_bare.json", &format!("[{VECTOR_SEGMENT}]"));
+        assert_eq!(input.vector.load_error, None);
+        assert_eq!(input.vector.segments.len(), 1);
+        assert_eq!(input.vector.beam_speed, VectorState::default().beam_speed);
+        assert_eq!(
+            input.vector.settling_time,
+            VectorState::default().settling_time
+        );
+    }
+
+    #[test]
+    fn wrapped_vector_file_applies_embedded_settings() {
+        let json = format!(
+            r#"{{"version": 1, "beam_speed": 2.5, "settling_time": 0.0004,
+                "segments": [{VECTOR_SEGMENT}]}}"#
+        );
+        let input = load_vector_json("phosphor_test_wrapped.json", &json);
+        assert_eq!(input.vector.load_error, None);
+        assert_eq!(input.vector.segments.len(), 1);
+        assert_eq!(input.vector.beam_speed, 2.5);
+        assert_eq!(input.vector.settling_time, 0.0004);
+    }
+
+    #[test]
+    fn resample_threshold_scales_with_factor() {
+        assert_eq!(resample_threshold(0.002, DEFAULT_RESAMPLE_FACTOR), 0.001);
+        assert_eq!(resample_threshold(0.002, 0.25), 0.0005);
+        assert_eq!(resample_threshold(0.002, 2.0), 0.004);
+
+        // A finer spacing keeps more of the same trace. Vector segments are
+        // subdivided about a spot radius apart, so a coarse spacing merges.
+        let batch_len = |factor: f32| {
+            let mut sim = SimState::new();
+            sim.apply_command(SimCommand::SetInputMode(InputMode::Vector));
+            sim.input.vector.segments = vec![VectorSegment {
+                x0: 0.1,
+                y0: 0.5,
+                x1: 0.9,
+                y1: 0.5,
+                intensity: 1.0,
+            }];
+            sim.apply_command(SimCommand::SetResampleFactor(factor));
+            sim.generate_batch(1).len()
+        };
+        assert!(batch_len(0.1) > batch_len(2.0));
+
+        let mut sim = SimState::new();
+        sim.apply_command(SimCommand::SetResampleFactor(50.0));
+        assert_eq!(sim.resample_factor, RESAMPLE_FACTOR_LIMITS.1);
+        sim.apply_command(SimCommand::SetResampleFactor(f32::NAN));
+        assert_eq!(sim.resample_factor, RESAMPLE_FACTOR_LIMITS.1);
+    }
+
+    #[test]
+    fn disabled_resampler_passes_every_sample_through() {
+        let mut sim = SimState::new();
+        sim.apply_command(SimCommand::SetResampleEnabled(false));
+        let samples = sim.generate_batch(500);
+        assert_eq!(samples.len(), 500);
+        // Energy is still scaled for the GPU.
+        let raw = sim
+            .input
+            .osc_source
+            .generate(1, &BeamState { spot_radius: 0.01 });
+        assert_eq!(samples[0].intensity, raw[0].intensity * BEAM_ENERGY_SCALE);
+    }
+
+    #[test]
+    fn oversampling_multiplies_raw_samples_over_the_same_time() {
+        let raw = |oversample: u32| {
+            let mut input = InputState::default();
+            input.oscilloscope.oversample = oversample;
+            input.generate_samples_fixed(1.5, 1.0, 800.0, None, 44_100.0, 100)
+        };
+        let (plain, oversampled) = (raw(1), raw(4));
+        assert_eq!(plain.len(), 100);
+        assert_eq!(oversampled.len(), 400);
+        let duration = |samples: &[BeamSample]| samples.iter().map(|s| s.dt).sum::<f32>();
+        assert!((duration(&plain) - duration(&oversampled)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn step_generates_oscilloscope_samples() {
+        let mut sim = SimState::new();
+        sim.apply_command(SimCommand::SetInputMode(InputMode::Oscilloscope));
+        let samples = sim
+            .input
+            .generate_samples_fixed(1.5, 1.0, 800.0, Some(0.5), 44_100.0, 100);
+        assert!(!samples.is_empty());
+        for s in &samples {
+            assert!((0.0..=1.0).contains(&s.x) && (0.0..=1.0).contains(&s.y));
+            assert!(s.dt > 0.0);
+        }
+
+        assert!(!sim.step().is_empty());
+    }
+
+    #[test]
+    fn energy_per_second_is_independent_of_sample_rate() {
+        let energy_per_second = |sample_rate: f32| {
+            let mut input = InputState::default();
+            input.oscilloscope.sample_rate = sample_rate;
+            let samples = input.generate_samples_fixed(
+                1.5,
+                1.0,
+                800.0,
+                Some(0.5),
+                sample_rate,
+                sample_rate as usize,
+            );
+            samples.iter().map(|s| s.intensity * s.dt).sum::<f32>()
+        };
+        let slow = energy_per_second(22_050.0);
+        let fast = energy_per_second(96_000.0);
+        assert!(
+            ((slow - fast) / slow).abs() < 1e-3,
+            "22.05 kHz: {slow}, 96 kHz: {fast}"
+        );
+        assert!((slow - BEAM_ENERGY_SCALE).abs() / BEAM_ENERGY_SCALE < 1e-3);
+    }
+
+    #[test]
+    fn adaptive_batch_interval_stays_within_user_bounds() {
+        let ms = Duration::from_millis;
+        let mut state = SimState::new();
+        state.apply_command(SimCommand::SetBatchBounds {
+            min_ms: 2.0,
+            max_ms: 4.0,
+        });
+
+        // Slow generation doubles the interval, up to the max.
+        assert_eq!(state.adapt_batch_interval(ms(3), ms(3)), ms(4));
+        // Fast generation halves it, down to the min.
+        assert_eq!(state.adapt_batch_interval(ms(3), Duration::ZERO), ms(2));
+        // In between it holds.
+        assert_eq!(state.adapt_batch_interval(ms(3), ms(1)), ms(3));
+
+        // Inverted bounds are rejected.
+        state.apply_command(SimCommand::SetBatchBounds {
+            min_ms: 8.0,
+            max_ms: 5.0,
+        });
+        assert_eq!(state.clamp_batch_interval(ms(10)), ms(4));
+    }
+
+    #[test]
+    fn zero_width_viewport_is_clamped() {
+        let mut state = SimState::new();
+        state.apply_command(SimCommand::SetViewport {
+            width: 0.0,
+            height: 600.0,
+            x_offset: 0.0,
+        });
+        assert_eq!(state.viewport_width, MIN_VIEWPORT_DIM);
+        assert!(state.minimized);
+        assert!(state.aspect().is_finite());
+    }
+
+    #[test]
+    fn minimized_viewport_generates_nothing() {
+        let mut state = SimState::new();
+        state.apply_command(SimCommand::SetViewport {
+            width: 0.0,
+            height: 0.0,
+            x_offset: 0.0,
+        });
+        assert!(state.generate_batch(1000).is_empty());
+        assert_eq!(state.source_status(), SourceStatus::Minimized);
+    }
+
+    #[test]
+    fn paused_input_generates_nothing_until_resumed() {
+        let mut state = SimState::new();
+        state.apply_command(SimCommand::SetInputPaused(true));
+        assert!(state.generate_batch(1000).is_empty());
+        assert_eq!(state.source_status(), SourceStatus::Unfocused);
+        state.apply_command(SimCommand::SetInputPaused(false));
+        assert!(!state.generate_batch(1000).is_empty());
+    }
+
+    #[test]
+    fn on_shutdown_is_idempotent() {
+        let mut state = SimState::new();
+        state.input.vector.segments.push(VectorSegment {
+            x0: 0.0,
+            y0: 0.0,
+            x1: 1.0,
+            y1: 1.0,
+            intensity: 1.0,
+        });
+        state.on_shutdown();
+        assert!(state.shut_down);
+        assert!(state.input.vector.segments.is_empty());
+        assert!(state.input.audio.source.is_none());
+
+        state.on_shutdown();
+        assert!(state.shut_down);
+        assert!(state.input.vector.segments.is_empty());
+    }
+
+    #[test]
+    fn disconnected_channel_stops_the_thread() {
+        let (producer, _consumer) = crate::beam::sample_channel(1024);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        drop(tx);
+        // Returns instead of looping forever
+        run_simulation(producer, rx, SimStats::new(1024), HybridSleeper::default());
+    }
+
+    #[test]
+    fn source_status_explains_empty_input() {
+        let mut state = SimState::new();
+        assert_eq!(state.source_status(), SourceStatus::Generating);
+
+        state.apply_command(SimCommand::SetInputMode(InputMode::Vector));
+        assert_eq!(state.source_status(), SourceStatus::VectorNoFile);
+
+        state.apply_command(SimCommand::SetInputMode(InputMode::Audio));
+        assert_eq!(state.source_status(), SourceStatus::AudioNoFile);
+        state.input.audio.load_error = Some("bad file".into());
+        assert_eq!(state.source_status(), SourceStatus::AudioLoadFailed);
+    }
+
+    #[test]
+    fn clamped_viewport_produces_finite_samples() {
+        let mut state = SimState::new();
+        state.apply_command(SimCommand::SetViewport {
+            width: 0.0,
+            height: 600.0,
+            x_offset: 0.0,
+        });
+        // Generate directly, bypassing the minimized gate, to check the
+        // clamped dimensions themselves are safe.
+        let samples = state.input.generate_samples_fixed(
+            state.focus,
+            state.aspect(),
+            state.viewport_width,
+            Some(state.resample_factor),
+            state.sample_rate,
+            1000,
+        );
+        assert!(!samples.is_empty());
+        for s in &samples {
+            assert!(s.x.is_finite() && s.y.is_finite() && s.intensity.is_finite());
+        }
+    }
+
+    #[test]
+    fn restored_viewport_resumes_generation() {
+        let mut state = SimState::new();
+        state.apply_command(SimCommand::SetViewport {
+            width: 0.0,
+            height: 0.0,
+            x_offset: 0.0,
+        });
+        state.apply_command(SimCommand::SetViewport {
+            width: 1024.0,
+            height: 768.0,
+            x_offset: 0.0,
+        });
+        assert!(!state.minimized);
+        assert!(!state.generate_batch(1000).is_empty());
+    }
+}
@@ -1,8 +1,36 @@
 use std::sync::Arc;
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, Ordering};
 
 use atomic_float::AtomicF32;
 
+/// What the active input source is doing, so an empty screen can be told
+/// apart from a broken one. Stored in [`SimStats`] as its `u8` discriminant.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumIter, strum::FromRepr,
+)]
+#[repr(u8)]
+pub enum SourceStatus {
+    #[default]
+    #[strum(serialize = "Generating")]
+    Generating,
+    #[strum(serialize = "Paused: window minimized")]
+    Minimized,
+    #[strum(serialize = "Paused: window unfocused")]
+    Unfocused,
+    #[strum(serialize = "Audio: no file loaded")]
+    AudioNoFile,
+    #[strum(serialize = "Audio: load failed")]
+    AudioLoadFailed,
+    #[strum(serialize = "Audio: paused")]
+    AudioPaused,
+    #[strum(serialize = "Vector: no file loaded")]
+    VectorNoFile,
+    #[strum(serialize = "Vector: load failed")]
+    VectorLoadFailed,
+    #[strum(serialize = "External: not connected")]
+    ExternalNotConnected,
+}
+
 /// Statistics shared between the simulation thread (writer) and
 /// the render/UI thread (reader). All fields use relaxed atomics —
 /// individual reads may be slightly stale but that's fine for display.
@@ -17,6 +45,13 @@ pub struct SimStats {
     pub samples_dropped: AtomicU32,
     /// Ring buffer capacity.
     pub buffer_capacity: AtomicU32,
+    /// Active source's [`SourceStatus`], updated every iteration.
+    pub source_status: AtomicU8,
+    /// Fraction of wall time the thread spent asleep rather than generating
+    /// or spinning (updated ~once per second).
+    pub idle_fraction: AtomicF32,
+    /// Whether the simulation thread runs above normal priority.
+    pub priority_elevated: AtomicBool,
 }
 
 impl SimStats {
@@ -27,6 +62,41 @@ impl SimStats {
             samples_generated: AtomicF32::new(0.0),
             samples_dropped: AtomicU32::new(0),
             buffer_capacity: AtomicU32::new(buffer_capacity),
+            source_status: AtomicU8::new(SourceStatus::default() as u8),
+            idle_fraction: AtomicF32::new(0.0),
+            priority_elevated: AtomicBool::new(false),
         })
     }
+
+    pub fn set_source_status(&self, status: SourceStatus) {
+        self.source_status.store(status as u8, Ordering::Relaxed);
+    }
+
+    pub fn source_status(&self) -> SourceStatus {
+        SourceStatus::from_repr(self.source_status.load(Ordering::Relaxed)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn source_status_round_trips_through_u8() {
+        let stats = SimStats::new(0);
+        for status in SourceStatus::iter() {
+            assert_eq!(SourceStatus::from_repr(status as u8), Some(status));
+            stats.set_source_status(status);
+            assert_eq!(stats.source_status(), status);
+        }
+    }
+
+    #[test]
+    fn unknown_status_byte_reads_as_default() {
+        let stats = SimStats::new(0);
+        stats.source_status.store(u8::MAX, Ordering::Relaxed);
+        assert_eq!(stats.source_status(), SourceStatus::default());
+    }
 }
@@ -1,10 +1,12 @@
-use crate::beam::oscilloscope::Waveform;
+use crate::beam::oscilloscope::{OscilloscopeMode, TriggerSlope, TriggerSource, Waveform};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum InputMode {
     #[default]
     Oscilloscope,
     Audio,
+    /// Spectrum analyzer over the loaded audio file.
+    Spectrum,
     Vector,
     External,
 }
@@ -22,6 +24,31 @@ pub struct OscilloscopeState {
     pub y_phase: f32,
     pub y_dc_offset: f32,
     pub sample_rate: f32,
+    /// Internal samples generated per output sample, at that multiple of
+    /// `sample_rate`. Keeps fast signals continuous; the arc-length
+    /// resampler merges the extra samples before they reach the GPU.
+    pub oversample: u32,
+    /// Pin the beam to the screen edge instead of letting large deflections
+    /// run off it.
+    pub clamp_to_screen: bool,
+    /// Blanking gate frequency in Hz.
+    pub blank_freq: f32,
+    /// Fraction of each blanking period the beam is on; 1.0 never blanks.
+    pub blank_duty: f32,
+    pub mode: OscilloscopeMode,
+    /// Second Y channel, drawn against the X sweep in dual-trace mode.
+    pub y2_waveform: Waveform,
+    pub y2_frequency: f32,
+    pub y2_amplitude: f32,
+    pub y2_phase: f32,
+    pub y2_dc_offset: f32,
+    /// Rate in Hz at which dual-trace mode switches between the Y channels.
+    pub chop_freq: f32,
+    /// Dual-trace sweep trigger; free run ignores the slope and level.
+    pub trigger_source: TriggerSource,
+    pub trigger_slope: TriggerSlope,
+    /// Trigger threshold as a deflection from screen center.
+    pub trigger_level: f32,
 }
 
 impl Default for OscilloscopeState {
@@ -40,6 +67,20 @@ impl Default for OscilloscopeState {
             y_phase: std::f32::consts::FRAC_PI_2,
             y_dc_offset: 0.0,
             sample_rate: 44100.0,
+            oversample: 1,
+            clamp_to_screen: true,
+            blank_freq: 1000.0,
+            blank_duty: 1.0,
+            mode: OscilloscopeMode::XY,
+            y2_waveform: Waveform::Sine,
+            y2_frequency: 200.0,
+            y2_amplitude: 0.2,
+            y2_phase: 0.0,
+            y2_dc_offset: -0.25,
+            chop_freq: 10_000.0,
+            trigger_source: TriggerSource::FreeRun,
+            trigger_slope: TriggerSlope::Rising,
+            trigger_level: 0.0,
         }
     }
 }
@@ -1,59 +1,210 @@
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 
-use crate::phosphor::spectral::{SPECTRAL_BANDS, band_center};
+use egui::emath::Numeric;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::phosphor::spectral::SPECTRAL_BAND_CENTERS;
 
 use crate::gpu::TAU_CUTOFF;
-use crate::gpu::composite::TonemapMode;
+use crate::gpu::accumulation::{AccumFormat, F16_ACCUM_CEILING};
+use crate::gpu::composite::{NEUTRAL_WHITE_K, TonemapMode, cct_to_tint};
+use crate::gpu::decay::{DEFAULT_ACCUM_CEILING, MAX_DECAY_TERMS};
+use crate::gpu::faceplate_scatter::MAX_SCATTER_LEVELS;
+use crate::gpu::histogram::{
+    CLIP_EV, HISTOGRAM_BINS, HISTOGRAM_MAX_EV, HISTOGRAM_MIN_EV, LuminanceHistogram, bin_ev,
+};
 use crate::gpu::profiler::{HISTORY_CAP, NUM_SEGMENTS, SEGMENT_NAMES, TimingHistory};
+use crate::gpu::spectral_resolve::SpectralDebugMode;
 use crate::phosphor::PhosphorType;
+use crate::presets::{GLASS_PRESETS, GlassPreset};
+use crate::simulation::{
+    BATCH_BOUND_LIMITS_MS, DEFAULT_BATCH_BOUNDS_MS, DEFAULT_RESAMPLE_FACTOR, RESAMPLE_FACTOR_LIMITS,
+};
 use crate::simulation_stats::SimStats;
 use crate::types::Resolution;
 
+use super::phosphor_swatch::{PhosphorListing, phosphor_combo};
+use super::widgets::{labeled_value, labeled_value_with};
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct EngineerState {
+    // Phosphor
+    /// Plot the emission spectrum on a log scale.
+    pub spectrum_log_y: bool,
     // Beam
     pub sigma_core: f32,
     pub sigma_halo: f32,
     pub halo_fraction: f32,
     pub space_charge: f32,
     pub accel_voltage: f32,
+    /// Arc-length resample spacing, in spot radii.
+    pub resample_factor: f32,
+    /// Debug: pass source samples to the GPU without arc-length resampling.
+    pub resample_enabled: bool,
     // Faceplate scatter
     pub scatter_threshold: f32,
     pub scatter_sigma: f32,
     pub scatter_intensity: f32,
+    pub scatter_levels: u32,
+    pub scatter_temporal: bool,
+    pub scatter_persistence: f32,
     // Composite / display
     pub tonemap_mode: TonemapMode,
+    pub spectral_debug: SpectralDebugMode,
     pub exposure: f32,
     pub white_point: f32,
+    pub white_balance_k: f32,
     pub glass_tint: [f32; 3],
+    /// Last color temperature picked with the tint-by-temperature slider.
+    /// Only drives `glass_tint` when the slider moves; the RGB picker can
+    /// still set any tint.
+    pub glass_tint_k: f32,
     pub curvature: f32,
     pub edge_falloff: f32,
+    pub grid_enabled: bool,
+    pub grid_divisions: u32,
+    pub grid_intensity: f32,
+    pub grid_color: [f32; 3],
     // Resolution
     pub accum_resolution_scale: f32,
+    pub accum_ceiling: f32,
+    pub accum_format: AccumFormat,
+    // Compare
+    pub compare_split: f32,
+    // Simulation tuning
+    pub batch_min_ms: f32,
+    pub batch_max_ms: f32,
+    // App preferences: persisted in the settings file rather than reset
+    // with the panel.
+    /// Stop rendering and simulating while no window has focus.
+    pub pause_when_unfocused: bool,
+    pub timing_palette: TimingPalette,
 }
 
 impl Default for EngineerState {
     fn default() -> Self {
         Self {
+            spectrum_log_y: false,
             sigma_core: 1.5,
             sigma_halo: 6.0,
             halo_fraction: 0.03,
             space_charge: 0.0,
             accel_voltage: 10.0,
+            resample_factor: DEFAULT_RESAMPLE_FACTOR,
+            resample_enabled: true,
             scatter_threshold: 0.5,
             scatter_sigma: 4.0,
             scatter_intensity: 0.15,
+            scatter_levels: 1,
+            scatter_temporal: false,
+            scatter_persistence: 0.5,
             tonemap_mode: TonemapMode::default(),
+            spectral_debug: SpectralDebugMode::default(),
             exposure: 1.0,
             white_point: 1.0,
+            white_balance_k: NEUTRAL_WHITE_K,
             glass_tint: [0.92, 0.95, 0.92],
+            glass_tint_k: NEUTRAL_WHITE_K,
             curvature: 0.0,
             edge_falloff: 0.0,
+            grid_enabled: false,
+            grid_divisions: 10,
+            grid_intensity: 0.35,
+            grid_color: [0.7, 0.7, 0.7],
             accum_resolution_scale: 1.0,
+            accum_ceiling: DEFAULT_ACCUM_CEILING,
+            accum_format: AccumFormat::default(),
+            compare_split: 0.5,
+            batch_min_ms: DEFAULT_BATCH_BOUNDS_MS.0,
+            batch_max_ms: DEFAULT_BATCH_BOUNDS_MS.1,
+            pause_when_unfocused: false,
+            timing_palette: TimingPalette::default(),
+        }
+    }
+}
+
+impl EngineerState {
+    /// Restore every control to its default. GPU-side params follow on the
+    /// next frame, since `sync_gpu_params` reads this state every frame.
+    pub fn reset(&mut self) {
+        *self = Self {
+            pause_when_unfocused: self.pause_when_unfocused,
+            timing_palette: self.timing_palette,
+            ..Self::default()
+        };
+    }
+
+    /// Set the glass faceplate controls from a preset.
+    pub fn apply_glass_preset(&mut self, preset: &GlassPreset) {
+        self.curvature = preset.curvature;
+        self.edge_falloff = preset.edge_falloff;
+        self.glass_tint = preset.glass_tint;
+    }
+
+    /// Restore only the controls under one panel heading.
+    pub fn reset_section(&mut self, section: EngineerSection) {
+        let d = Self::default();
+        match section {
+            EngineerSection::Beam => {
+                self.sigma_core = d.sigma_core;
+                self.sigma_halo = d.sigma_halo;
+                self.halo_fraction = d.halo_fraction;
+                self.space_charge = d.space_charge;
+                self.accel_voltage = d.accel_voltage;
+                self.resample_factor = d.resample_factor;
+                self.resample_enabled = d.resample_enabled;
+            }
+            EngineerSection::FaceplateScatter => {
+                self.scatter_threshold = d.scatter_threshold;
+                self.scatter_sigma = d.scatter_sigma;
+                self.scatter_intensity = d.scatter_intensity;
+                self.scatter_levels = d.scatter_levels;
+                self.scatter_temporal = d.scatter_temporal;
+                self.scatter_persistence = d.scatter_persistence;
+            }
+            EngineerSection::Display => {
+                self.tonemap_mode = d.tonemap_mode;
+                self.spectral_debug = d.spectral_debug;
+                self.exposure = d.exposure;
+                self.white_point = d.white_point;
+                self.white_balance_k = d.white_balance_k;
+            }
+            EngineerSection::Glass => {
+                self.glass_tint = d.glass_tint;
+                self.glass_tint_k = d.glass_tint_k;
+                self.curvature = d.curvature;
+                self.edge_falloff = d.edge_falloff;
+                self.grid_enabled = d.grid_enabled;
+                self.grid_divisions = d.grid_divisions;
+                self.grid_intensity = d.grid_intensity;
+                self.grid_color = d.grid_color;
+            }
+            EngineerSection::Resolution => {
+                self.accum_resolution_scale = d.accum_resolution_scale;
+                self.accum_ceiling = d.accum_ceiling;
+                self.accum_format = d.accum_format;
+            }
+            EngineerSection::Compare => {
+                self.compare_split = d.compare_split;
+            }
         }
     }
 }
 
+/// A resettable group of controls in the engineer panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineerSection {
+    Beam,
+    FaceplateScatter,
+    Display,
+    Glass,
+    Resolution,
+    Compare,
+}
+
 const TONEMAP_MODES: &[(TonemapMode, &str)] = &[
     (TonemapMode::Reinhard, "Reinhard"),
     (TonemapMode::Aces, "ACES"),
@@ -61,18 +212,316 @@ const TONEMAP_MODES: &[(TonemapMode, &str)] = &[
     (TonemapMode::None, "None (HDR)"),
 ];
 
-const SEGMENT_COLORS: &[egui::Color32] = &[
+/// Hover help for engineer-panel sliders, keyed by label: what the control
+/// means physically, and its units.
+const CONTROL_HELP: &[(&str, &str)] = &[
+    (
+        "Core sigma",
+        "Gaussian radius of the beam's bright core, in buffer pixels. \
+         Not yet affecting output: the scope panel's Focus sets the core.",
+    ),
+    (
+        "Halo sigma",
+        "Radius of the dim halo from electrons scattered in the gun and phosphor, in buffer pixels.",
+    ),
+    (
+        "Halo fraction",
+        "Share of beam energy landing in the halo rather than the core (0–1).",
+    ),
+    (
+        "Space charge",
+        "Beam spreading from mutual repulsion of electrons at high current (0–1). \
+         Not yet affecting output.",
+    ),
+    (
+        "Accel voltage",
+        "Anode accelerating voltage in kV; higher voltage gives a brighter, tighter spot. \
+         Not yet affecting output.",
+    ),
+    (
+        "Resample spacing",
+        "Distance between merged beam samples along the trace, in spot radii. \
+         Smaller keeps more detail in dense figures but costs more samples.",
+    ),
+    (
+        "Resample",
+        "Merge closely spaced beam samples before they reach the GPU. Turn off \
+         to check whether an artifact comes from the input source or the resampler.",
+    ),
+    (
+        "Threshold",
+        "HDR luminance (CIE Y) above which light scatters inside the faceplate glass.",
+    ),
+    (
+        "Sigma",
+        "Spread of light scattered inside the faceplate glass, in half-resolution pixels.",
+    ),
+    (
+        "Intensity",
+        "Fraction of the scattered light added back over the image (0–1).",
+    ),
+    (
+        "Glow spread (levels)",
+        "Extra levels blur at successively halved resolution and add back in, \
+         widening the glow. Each level costs a quarter of the memory of the one above.",
+    ),
+    (
+        "Glow persistence",
+        "Fraction of the previous frame's glow kept each frame. \
+         A still image's glow settles at 1 / (1 − persistence) times its usual strength.",
+    ),
+    (
+        "Exposure",
+        "Linear gain applied before tonemapping. \
+         Not yet affecting output: the scope panel's Intensity sets exposure.",
+    ),
+    (
+        "White point",
+        "HDR luminance mapped to display white by the tonemapper. Not yet affecting output.",
+    ),
+    (
+        "White balance",
+        "Target white color temperature in kelvin; 6500 K leaves colors unchanged.",
+    ),
+    (
+        "Tint temperature",
+        "Set the glass tint to the color of daylight at this temperature in kelvin: \
+         6500 K is clear, lower is amber, higher is blue.",
+    ),
+    (
+        "Curvature",
+        "Barrel distortion of the curved faceplate (0 = flat).",
+    ),
+    (
+        "Edge Falloff",
+        "Darkening toward the screen edges as the glass is viewed at a steeper angle (0–1).",
+    ),
+    (
+        "Graticule",
+        "Measurement grid on the faceplate, bent along with the curvature.",
+    ),
+    (
+        "Divisions",
+        "Graticule squares across each axis of the display.",
+    ),
+    (
+        "Grid intensity",
+        "How strongly the graticule lines cover the image (0–1).",
+    ),
+    (
+        "Internal simulation scale",
+        "Accumulation buffer resolution relative to the window. \
+         Higher is sharper but costs VRAM and GPU time.",
+    ),
+    (
+        "Energy ceiling",
+        "Cap on the energy a texel can store per decay layer. Values above half \
+         the ceiling are softly compressed, so a beam parked for a long session \
+         can't lose float precision. The default is well above normal use.",
+    ),
+    (
+        "Min batch interval",
+        "Shortest time between simulation batches. Lower tracks input changes sooner \
+         but wakes the CPU more often.",
+    ),
+    (
+        "Max batch interval",
+        "Longest time between simulation batches the adaptive pacing may reach when \
+         generation falls behind. Raise it on slow machines that drop samples.",
+    ),
+    (
+        "Pause when unfocused",
+        "Stop drawing and generating samples while no Phosphor window has focus, \
+         to save power. The display freezes, decay included, until focus returns.",
+    ),
+    (
+        "Spectral view",
+        "Debug view of the spectral pipeline. Dominant band colors each pixel by the \
+         band carrying the most energy, violet for the shortest wavelength through \
+         red for the longest, in place of its real color.",
+    ),
+    (
+        "Timing colors",
+        "Palette for the GPU timing readouts and plots. The color-blind safe set \
+         stays distinguishable with red-green color blindness.",
+    ),
+    (
+        "Storage",
+        "Accumulation buffer value format. F16 packs two values per word to halve its \
+         VRAM, at about three significant digits of precision. Switching clears the \
+         buffer.",
+    ),
+    (
+        "Split position",
+        "Where the compare split sits, as a fraction of the screen width.",
+    ),
+];
+
+fn control_help(label: &str) -> &'static str {
+    CONTROL_HELP
+        .iter()
+        .find(|(name, _)| *name == label)
+        .map_or("", |(_, help)| help)
+}
+
+/// A section heading with a small button that resets just that section.
+fn section_heading(
+    ui: &mut egui::Ui,
+    state: &mut EngineerState,
+    title: &str,
+    section: EngineerSection,
+) {
+    ui.horizontal(|ui| {
+        ui.heading(title);
+        if ui
+            .small_button("↺")
+            .on_hover_text(format!("Reset {title} to defaults"))
+            .clicked()
+        {
+            state.reset_section(section);
+        }
+    });
+}
+
+/// [`labeled_value`] with the label's `CONTROL_HELP` entry as hover text.
+fn help_value<N: Numeric>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut N,
+    range: RangeInclusive<N>,
+    suffix: &str,
+) -> egui::Response {
+    labeled_value(ui, label, value, range, suffix).on_hover_text(control_help(label))
+}
+
+/// [`labeled_value_with`] with the label's `CONTROL_HELP` entry as hover text.
+fn help_value_with<N: Numeric>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut N,
+    range: RangeInclusive<N>,
+    suffix: &str,
+    configure: impl FnOnce(egui::Slider<'_>) -> egui::Slider<'_>,
+) -> egui::Response {
+    labeled_value_with(ui, label, value, range, suffix, configure)
+        .on_hover_text(control_help(label))
+}
+
+/// Colors for the GPU timing readouts and plots.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumIter,
+)]
+pub enum TimingPalette {
+    #[default]
+    Standard,
+    /// Okabe–Ito colors, distinguishable with red-green color blindness.
+    #[strum(serialize = "Color-blind safe")]
+    ColorBlind,
+}
+
+/// Legend entries after the per-pass segments: GPU total, then CPU total.
+const TOTAL_COLORS: usize = 2;
+
+/// Per-pass segment colors in `SEGMENT_NAMES` order, then the totals.
+const STANDARD_COLORS: &[egui::Color32] = &[
     egui::Color32::from_rgb(255, 100, 100), // Beam Write - red
     egui::Color32::from_rgb(100, 200, 100), // Decay - green
     egui::Color32::from_rgb(100, 150, 255), // Spectral Resolve - blue
     egui::Color32::from_rgb(255, 200, 80),  // Faceplate Scatter - yellow
     egui::Color32::from_rgb(200, 130, 255), // Composite - purple
+    egui::Color32::from_rgb(100, 220, 220), // GPU total - cyan
+    egui::Color32::from_rgb(240, 240, 240), // CPU total - white
 ];
 
+const COLOR_BLIND_COLORS: &[egui::Color32] = &[
+    egui::Color32::from_rgb(230, 159, 0),   // Beam Write - orange
+    egui::Color32::from_rgb(86, 180, 233),  // Decay - sky blue
+    egui::Color32::from_rgb(0, 158, 115),   // Spectral Resolve - bluish green
+    egui::Color32::from_rgb(240, 228, 66),  // Faceplate Scatter - yellow
+    egui::Color32::from_rgb(213, 94, 0),    // Composite - vermillion
+    egui::Color32::from_rgb(204, 121, 167), // GPU total - reddish purple
+    egui::Color32::from_rgb(240, 240, 240), // CPU total - white
+];
+
+impl TimingPalette {
+    fn colors(self) -> &'static [egui::Color32] {
+        match self {
+            Self::Standard => STANDARD_COLORS,
+            Self::ColorBlind => COLOR_BLIND_COLORS,
+        }
+    }
+
+    fn segment(self, index: usize) -> egui::Color32 {
+        self.colors()[index]
+    }
+
+    fn gpu_total(self) -> egui::Color32 {
+        self.colors()[NUM_SEGMENTS]
+    }
+
+    fn cpu_total(self) -> egui::Color32 {
+        self.colors()[NUM_SEGMENTS + 1]
+    }
+}
+
+/// Internal buffer size info passed from the render loop.
+#[derive(Clone, Copy)]
+pub struct BufferInfo {
+    /// Allocated buffer resolution.
+    pub resolution: Resolution,
+    /// Resolution asked for by window size × scale; larger than
+    /// `resolution` when clamped to the device's buffer limit.
+    pub requested: Resolution,
+    /// Total VRAM of the internal buffers, in bytes.
+    pub bytes: u64,
+}
+
+/// Adapter and capability info captured once at GPU setup, for bug reports.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuInfo {
+    pub adapter: String,
+    pub backend: String,
+    /// Driver name and version, or "unknown" when the backend reports none.
+    pub driver: String,
+    pub hdr_output: bool,
+    pub timestamps: bool,
+}
+
+impl GpuInfo {
+    pub fn new(info: &wgpu::AdapterInfo, hdr_output: bool, timestamps: bool) -> Self {
+        let driver = match (info.driver.is_empty(), info.driver_info.is_empty()) {
+            (true, true) => "unknown".to_string(),
+            (false, true) => info.driver.clone(),
+            (true, false) => info.driver_info.clone(),
+            (false, false) => format!("{} {}", info.driver, info.driver_info),
+        };
+        Self {
+            adapter: format!("{} ({:?})", info.name, info.device_type),
+            backend: info.backend.to_string(),
+            driver,
+            hdr_output,
+            timestamps,
+        }
+    }
+}
+
 /// Per-frame simulation info passed from the render loop.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SimFrameInfo {
     pub samples_this_frame: usize,
     pub sim_dt: f32,
+    /// Energy the drained samples deposit, from
+    /// [`deposited_energy`](crate::beam::deposited_energy).
+    pub energy_this_frame: f32,
     pub buffer_pending: usize,
 }
 
@@ -81,68 +530,134 @@ pub fn engineer_panel(
     ui: &mut egui::Ui,
     state: &mut EngineerState,
     phosphors: &[PhosphorType],
+    swatches: &[egui::Color32],
     phosphor_index: &mut usize,
+    phosphor_listing: &mut PhosphorListing,
+    compare_phosphor: &mut Option<usize>,
     fps: f32,
     timings: Option<&TimingHistory>,
-    accum_size: Option<Resolution>,
+    buffer_info: Option<BufferInfo>,
+    histogram: Option<&LuminanceHistogram>,
     sim_stats: Option<&Arc<SimStats>>,
     sim_frame: Option<&SimFrameInfo>,
+    gpu_info: Option<&GpuInfo>,
 ) {
     egui::ScrollArea::vertical().show(ui, |ui| {
+        if ui
+            .button("Reset")
+            .on_hover_text("Reset all engineer controls to defaults")
+            .clicked()
+        {
+            state.reset();
+        }
+
         // -- Phosphor selector (mirrored from scope panel) --
         ui.heading("Phosphor");
-        let current = &phosphors[*phosphor_index];
-        egui::ComboBox::from_id_salt("eng_phosphor_type")
-            .selected_text(&current.designation)
-            .show_ui(ui, |ui| {
-                for (i, p) in phosphors.iter().enumerate() {
-                    ui.selectable_value(
-                        phosphor_index,
-                        i,
-                        format!("{} — {}", p.designation, p.description),
-                    );
-                }
-            });
+        phosphor_combo(
+            ui,
+            "eng_phosphor_type",
+            phosphors,
+            swatches,
+            phosphor_index,
+            phosphor_listing,
+        );
 
         let phosphor = &phosphors[*phosphor_index];
-        emission_spectrum_plot(ui, phosphor);
+        emission_spectrum_plot(ui, phosphor, state.spectrum_log_y);
+        if phosphor.truncated_terms {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "\u{26a0} Decay truncated to the first {MAX_DECAY_TERMS} terms; \
+                     the afterglow won't match the data"
+                ),
+            );
+        }
+        ui.checkbox(&mut state.spectrum_log_y, "Log scale")
+            .on_hover_text("Show weak secondary emission peaks on a logarithmic axis");
+
+        ui.collapsing("Database info", |ui| {
+            ui.label(format!("{} phosphors", phosphors.len()));
+            for (category, count) in phosphor_data::category_counts(phosphors) {
+                ui.label(format!("{}: {count}", category.label()));
+            }
+        });
 
         ui.separator();
 
         // -- Decay terms --
         ui.heading("Decay");
+        decay_curve_plot(ui, phosphor);
+        ui.label(format!(
+            "Persistence — 10%: {}, 1%: {}",
+            format_persistence(phosphor.persistence_time(0.1)),
+            format_persistence(phosphor.persistence_time(0.01)),
+        ));
         decay_term_display(ui, phosphor);
 
         ui.separator();
 
         // -- Beam --
-        ui.heading("Beam");
-        ui.label("Core sigma");
-        ui.add(egui::Slider::new(&mut state.sigma_core, 0.5..=5.0).text("px"));
-        ui.label("Halo sigma");
-        ui.add(egui::Slider::new(&mut state.sigma_halo, 2.0..=20.0).text("px"));
-        ui.label("Halo fraction");
-        ui.add(egui::Slider::new(&mut state.halo_fraction, 0.0..=0.5));
-        ui.label("Space charge");
-        ui.add(egui::Slider::new(&mut state.space_charge, 0.0..=1.0));
-        ui.label("Accel voltage");
-        ui.add(egui::Slider::new(&mut state.accel_voltage, 1.0..=25.0).text("kV"));
+        section_heading(ui, state, "Beam", EngineerSection::Beam);
+        help_value(ui, "Core sigma", &mut state.sigma_core, 0.5..=5.0, "px");
+        help_value(ui, "Halo sigma", &mut state.sigma_halo, 2.0..=20.0, "px");
+        help_value(ui, "Halo fraction", &mut state.halo_fraction, 0.0..=0.5, "");
+        help_value(ui, "Space charge", &mut state.space_charge, 0.0..=1.0, "");
+        help_value(
+            ui,
+            "Accel voltage",
+            &mut state.accel_voltage,
+            1.0..=25.0,
+            "kV",
+        );
+        help_value(
+            ui,
+            "Resample spacing",
+            &mut state.resample_factor,
+            RESAMPLE_FACTOR_LIMITS.0..=RESAMPLE_FACTOR_LIMITS.1,
+            "",
+        );
+        ui.checkbox(&mut state.resample_enabled, "Resample")
+            .on_hover_text(control_help("Resample"));
 
         ui.separator();
 
         // -- Faceplate Scatter --
-        ui.heading("Faceplate Scatter");
-        ui.label("Threshold");
-        ui.add(egui::Slider::new(&mut state.scatter_threshold, 0.0..=2.0));
-        ui.label("Sigma");
-        ui.add(egui::Slider::new(&mut state.scatter_sigma, 1.0..=16.0).text("px"));
-        ui.label("Intensity");
-        ui.add(egui::Slider::new(&mut state.scatter_intensity, 0.0..=1.0));
+        section_heading(
+            ui,
+            state,
+            "Faceplate Scatter",
+            EngineerSection::FaceplateScatter,
+        );
+        help_value(ui, "Threshold", &mut state.scatter_threshold, 0.0..=2.0, "");
+        help_value(ui, "Sigma", &mut state.scatter_sigma, 1.0..=16.0, "px");
+        help_value(ui, "Intensity", &mut state.scatter_intensity, 0.0..=1.0, "");
+        help_value(
+            ui,
+            "Glow spread (levels)",
+            &mut state.scatter_levels,
+            1..=MAX_SCATTER_LEVELS,
+            "",
+        );
+        ui.checkbox(&mut state.scatter_temporal, "Sustained glow")
+            .on_hover_text(
+                "Carry the scatter over from frame to frame instead of redrawing it, \
+                 so glow lingers and builds up. Costs one extra half-resolution buffer.",
+            );
+        if state.scatter_temporal {
+            help_value(
+                ui,
+                "Glow persistence",
+                &mut state.scatter_persistence,
+                0.0..=0.95,
+                "",
+            );
+        }
 
         ui.separator();
 
         // -- Display --
-        ui.heading("Display");
+        section_heading(ui, state, "Display", EngineerSection::Display);
         ui.label("Tonemap");
         egui::ComboBox::from_id_salt("tonemap_mode")
             .selected_text(
@@ -156,35 +671,181 @@ pub fn engineer_panel(
                     ui.selectable_value(&mut state.tonemap_mode, mode, name);
                 }
             });
+        ui.label("Spectral view")
+            .on_hover_text(control_help("Spectral view"));
+        egui::ComboBox::from_id_salt("spectral_debug")
+            .selected_text(state.spectral_debug.to_string())
+            .show_ui(ui, |ui| {
+                for mode in SpectralDebugMode::iter() {
+                    ui.selectable_value(&mut state.spectral_debug, mode, mode.to_string());
+                }
+            });
 
-        ui.label("Exposure");
-        ui.add(egui::Slider::new(&mut state.exposure, 0.1..=10.0).logarithmic(true));
-        ui.label("White point");
-        ui.add(egui::Slider::new(&mut state.white_point, 0.1..=10.0).logarithmic(true));
+        help_value_with(ui, "Exposure", &mut state.exposure, 0.1..=10.0, "", |s| {
+            s.logarithmic(true)
+        });
+        help_value_with(
+            ui,
+            "White point",
+            &mut state.white_point,
+            0.1..=10.0,
+            "",
+            |s| s.logarithmic(true),
+        );
+        help_value_with(
+            ui,
+            "White balance",
+            &mut state.white_balance_k,
+            4000.0..=10000.0,
+            "K",
+            |s| s.step_by(100.0),
+        );
+
+        ui.label("Luminance histogram");
+        match histogram {
+            Some(histogram) => {
+                luminance_histogram_plot(ui, histogram);
+                ui.label(format!(
+                    "Clipped: {:.1}% of lit pixels",
+                    histogram.clipped_fraction() * 100.0
+                ));
+            }
+            None => {
+                ui.weak("Waiting for GPU readback…");
+            }
+        }
 
         ui.separator();
 
         // -- Glass --
-        ui.heading("Glass Faceplate");
+        section_heading(ui, state, "Glass Faceplate", EngineerSection::Glass);
+        ui.horizontal(|ui| {
+            for preset in GLASS_PRESETS {
+                if ui
+                    .button(preset.name)
+                    .on_hover_text(preset.description)
+                    .clicked()
+                {
+                    state.apply_glass_preset(preset);
+                }
+            }
+        });
         ui.label("Tint");
         ui.color_edit_button_rgb(&mut state.glass_tint);
-        ui.label("Curvature");
-        ui.add(egui::Slider::new(&mut state.curvature, 0.0..=0.5));
-        ui.label("Edge Falloff");
-        ui.add(egui::Slider::new(&mut state.edge_falloff, 0.0..=1.0));
+        if help_value_with(
+            ui,
+            "Tint temperature",
+            &mut state.glass_tint_k,
+            4000.0..=10000.0,
+            "K",
+            |s| s.step_by(100.0),
+        )
+        .changed()
+        {
+            state.glass_tint = cct_to_tint(state.glass_tint_k);
+        }
+        help_value(ui, "Curvature", &mut state.curvature, 0.0..=0.5, "");
+        help_value(ui, "Edge Falloff", &mut state.edge_falloff, 0.0..=1.0, "");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.grid_enabled, "Graticule")
+                .on_hover_text(control_help("Graticule"));
+            ui.color_edit_button_rgb(&mut state.grid_color);
+        });
+        if state.grid_enabled {
+            help_value(ui, "Divisions", &mut state.grid_divisions, 2..=20, "");
+            help_value(
+                ui,
+                "Grid intensity",
+                &mut state.grid_intensity,
+                0.0..=1.0,
+                "",
+            );
+        }
 
         ui.separator();
 
         // -- Resolution --
-        ui.heading("Resolution");
-        ui.label("Internal simulation scale");
-        ui.add(
-            egui::Slider::new(&mut state.accum_resolution_scale, 0.25..=4.0)
-                .step_by(0.25)
-                .text("x"),
+        section_heading(ui, state, "Resolution", EngineerSection::Resolution);
+        help_value_with(
+            ui,
+            "Internal simulation scale",
+            &mut state.accum_resolution_scale,
+            0.25..=4.0,
+            "x",
+            |s| s.step_by(0.25),
+        );
+        help_value_with(
+            ui,
+            "Energy ceiling",
+            &mut state.accum_ceiling,
+            1e3..=1e8,
+            "",
+            |s| s.logarithmic(true),
         );
-        if let Some(res) = accum_size {
-            ui.label(res.to_string());
+        ui.label("Storage").on_hover_text(control_help("Storage"));
+        egui::ComboBox::from_id_salt("accum_format")
+            .selected_text(state.accum_format.to_string())
+            .show_ui(ui, |ui| {
+                for format in AccumFormat::iter() {
+                    ui.selectable_value(&mut state.accum_format, format, format.to_string());
+                }
+            });
+        if state.accum_format == AccumFormat::F16 {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "Reduced precision: faint traces over bright ones are lost, \
+                     energy ceiling capped at {F16_ACCUM_CEILING:.0}"
+                ),
+            );
+        }
+        if let Some(info) = buffer_info {
+            ui.label(format!(
+                "{} — {:.1} MB VRAM",
+                info.resolution,
+                info.bytes as f64 / (1024.0 * 1024.0)
+            ))
+            .on_hover_text("Accumulation, HDR, and scatter buffers (including compare)");
+            if info.resolution != info.requested {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("Clamped from {} by GPU max buffer size", info.requested),
+                );
+            }
+        }
+
+        ui.separator();
+
+        // -- Compare --
+        section_heading(ui, state, "Compare", EngineerSection::Compare);
+        let mut comparing = compare_phosphor.is_some();
+        if ui
+            .checkbox(&mut comparing, "Split-screen compare")
+            .on_hover_text(
+                "Render a second phosphor on the right of the split. \
+                 Allocates a second accumulation, HDR, and scatter buffer set \
+                 (roughly doubles VRAM use).",
+            )
+            .changed()
+        {
+            *compare_phosphor = comparing.then_some(*phosphor_index);
+        }
+        if let Some(index) = compare_phosphor {
+            phosphor_combo(
+                ui,
+                "compare_phosphor_type",
+                phosphors,
+                swatches,
+                index,
+                phosphor_listing,
+            );
+            help_value(
+                ui,
+                "Split position",
+                &mut state.compare_split,
+                0.0..=1.0,
+                "",
+            );
         }
 
         ui.separator();
@@ -192,6 +853,16 @@ pub fn engineer_panel(
         // -- Render Information --
         ui.heading("Render Information");
         ui.label(format!("FPS: {fps:.0}"));
+        if let Some(info) = gpu_info {
+            ui.collapsing("About GPU", |ui| {
+                ui.label(format!("Adapter: {}", info.adapter));
+                ui.label(format!("Backend: {}", info.backend));
+                ui.label(format!("Driver: {}", info.driver));
+                let on_off = |on| if on { "on" } else { "off" };
+                ui.label(format!("HDR output: {}", on_off(info.hdr_output)));
+                ui.label(format!("Timestamp queries: {}", on_off(info.timestamps)));
+            });
+        }
 
         if let Some(history) = timings {
             const AVG_WINDOW: usize = 32;
@@ -202,19 +873,36 @@ pub fn engineer_panel(
                 fmt_ms(history.avg_total(AVG_WINDOW)),
                 beam_avg,
             ));
+            ui.label(format!("CPU: {} ms", fmt_ms(history.avg_cpu(AVG_WINDOW))));
 
             if let Some(segs) = history.avg_segments(AVG_WINDOW) {
                 for (i, (name, us)) in segs.iter().enumerate() {
                     ui.label(
                         egui::RichText::new(format!("{name}: {} ms", fmt_ms(*us)))
-                            .color(SEGMENT_COLORS[i]),
+                            .color(state.timing_palette.segment(i)),
                     );
                 }
             }
 
             if history.len() > 1 {
-                gpu_timing_plot(ui, history);
+                gpu_timing_plot(ui, history, state.timing_palette);
+                frame_total_plot(ui, history, state.timing_palette);
             }
+            ui.horizontal(|ui| {
+                ui.label("Timing colors")
+                    .on_hover_text(control_help("Timing colors"));
+                egui::ComboBox::from_id_salt("timing_palette")
+                    .selected_text(state.timing_palette.to_string())
+                    .show_ui(ui, |ui| {
+                        for palette in TimingPalette::iter() {
+                            ui.selectable_value(
+                                &mut state.timing_palette,
+                                palette,
+                                palette.to_string(),
+                            );
+                        }
+                    });
+            });
         }
 
         // -- Simulation thread stats --
@@ -228,9 +916,48 @@ pub fn engineer_panel(
             let dropped = stats.samples_dropped.load(Ordering::Relaxed);
             let capacity = stats.buffer_capacity.load(Ordering::Relaxed);
 
+            ui.label(format!("Source: {}", stats.source_status()));
             ui.label(format!("Generated: {generated:.0} samples/s"));
             ui.label(format!("After resample: {throughput:.0} samples/s"));
             ui.label(format!("Batch interval: {batch_ms:.2} ms"));
+            ui.collapsing("Simulation tuning", |ui| {
+                let limits = BATCH_BOUND_LIMITS_MS.0..=BATCH_BOUND_LIMITS_MS.1;
+                let min_changed = help_value_with(
+                    ui,
+                    "Min batch interval",
+                    &mut state.batch_min_ms,
+                    limits.clone(),
+                    "ms",
+                    |s| s.logarithmic(true),
+                )
+                .changed();
+                let max_changed = help_value_with(
+                    ui,
+                    "Max batch interval",
+                    &mut state.batch_max_ms,
+                    limits,
+                    "ms",
+                    |s| s.logarithmic(true),
+                )
+                .changed();
+                // Keep min <= max by dragging the other bound along.
+                if min_changed && state.batch_min_ms > state.batch_max_ms {
+                    state.batch_max_ms = state.batch_min_ms;
+                } else if max_changed && state.batch_max_ms < state.batch_min_ms {
+                    state.batch_min_ms = state.batch_max_ms;
+                }
+                ui.checkbox(&mut state.pause_when_unfocused, "Pause when unfocused")
+                    .on_hover_text(control_help("Pause when unfocused"));
+            });
+            ui.label(format!(
+                "Idle: {:.0}%",
+                stats.idle_fraction.load(Ordering::Relaxed) * 100.0
+            ));
+            ui.label(if stats.priority_elevated.load(Ordering::Relaxed) {
+                "Thread priority: raised"
+            } else {
+                "Thread priority: normal"
+            });
 
             if let Some(frame) = sim_frame {
                 let fill_pct = if capacity > 0 {
@@ -247,6 +974,11 @@ pub fn engineer_panel(
                     frame.samples_this_frame,
                     frame.sim_dt * 1000.0,
                 ));
+                ui.label(format!("Energy/frame: {:.3}", frame.energy_this_frame))
+                    .on_hover_text(
+                        "Sum of intensity × dt over this frame's samples: the energy \
+                         deposited before phosphor efficiency and exposure.",
+                    );
             }
 
             if dropped > 0 {
@@ -268,7 +1000,21 @@ fn fmt_ms(us: f32) -> String {
     }
 }
 
-fn emission_spectrum_plot(ui: &mut egui::Ui, phosphor: &PhosphorType) {
+/// Lowest emission weight the log-scale spectrum shows; zero weights sit
+/// on this floor instead of going to negative infinity.
+const SPECTRUM_LOG_FLOOR: f64 = 1e-4;
+
+/// Plot height of an emission weight: the weight itself, or its log10
+/// clamped to [`SPECTRUM_LOG_FLOOR`] on a log scale.
+fn spectrum_y(weight: f64, log_y: bool) -> f64 {
+    if log_y {
+        weight.max(SPECTRUM_LOG_FLOOR).log10()
+    } else {
+        weight
+    }
+}
+
+fn emission_spectrum_plot(ui: &mut egui::Ui, phosphor: &PhosphorType, log_y: bool) {
     use egui_plot::{Line, Plot, PlotPoints};
 
     use crate::phosphor::spectral::{WAVELENGTH_MAX, WAVELENGTH_MIN};
@@ -276,7 +1022,7 @@ fn emission_spectrum_plot(ui: &mut egui::Ui, phosphor: &PhosphorType) {
         .height(100.0)
         .include_x(WAVELENGTH_MIN as f64)
         .include_x(WAVELENGTH_MAX as f64)
-        .include_y(0.0)
+        .include_y(spectrum_y(0.0, log_y))
         .set_margin_fraction(egui::Vec2::new(0.0, 0.05))
         .x_axis_label("nm")
         .show_axes([true, false])
@@ -286,13 +1032,10 @@ fn emission_spectrum_plot(ui: &mut egui::Ui, phosphor: &PhosphorType) {
         .allow_boxed_zoom(false);
 
     plot.show(ui, |plot_ui| {
-        let fluor_points: Vec<[f64; 2]> = (0..SPECTRAL_BANDS)
-            .map(|i| {
-                [
-                    band_center(i) as f64,
-                    phosphor.fluorescence.emission_weights[i] as f64,
-                ]
-            })
+        let fluor_points: Vec<[f64; 2]> = SPECTRAL_BAND_CENTERS
+            .iter()
+            .zip(&phosphor.fluorescence.emission_weights)
+            .map(|(&nm, &w)| [nm as f64, spectrum_y(w as f64, log_y)])
             .collect();
         plot_ui.line(
             Line::new("Fluorescence", PlotPoints::new(fluor_points))
@@ -301,13 +1044,10 @@ fn emission_spectrum_plot(ui: &mut egui::Ui, phosphor: &PhosphorType) {
         );
 
         if phosphor.is_dual_layer {
-            let phos_points: Vec<[f64; 2]> = (0..SPECTRAL_BANDS)
-                .map(|i| {
-                    [
-                        band_center(i) as f64,
-                        phosphor.phosphorescence.emission_weights[i] as f64,
-                    ]
-                })
+            let phos_points: Vec<[f64; 2]> = SPECTRAL_BAND_CENTERS
+                .iter()
+                .zip(&phosphor.phosphorescence.emission_weights)
+                .map(|(&nm, &w)| [nm as f64, spectrum_y(w as f64, log_y)])
                 .collect();
             plot_ui.line(
                 Line::new("Phosphorescence", PlotPoints::new(phos_points))
@@ -329,7 +1069,7 @@ fn emission_spectrum_plot(ui: &mut egui::Ui, phosphor: &PhosphorType) {
     }
 }
 
-fn gpu_timing_plot(ui: &mut egui::Ui, history: &TimingHistory) {
+fn gpu_timing_plot(ui: &mut egui::Ui, history: &TimingHistory, palette: TimingPalette) {
     use egui_plot::{Line, Plot, PlotPoints};
 
     let cap = HISTORY_CAP as f64;
@@ -348,17 +1088,147 @@ fn gpu_timing_plot(ui: &mut egui::Ui, history: &TimingHistory) {
         .allow_boxed_zoom(false);
 
     plot.show(ui, |plot_ui| {
-        for seg in 0..NUM_SEGMENTS {
+        for (seg, name) in SEGMENT_NAMES.iter().enumerate() {
             let points =
                 PlotPoints::from_iter(history.segment_iter(seg).map(|[x, y]| [x + x_offset, y]));
-            let line = Line::new(SEGMENT_NAMES[seg], points)
-                .color(SEGMENT_COLORS[seg])
+            let line = Line::new(*name, points)
+                .color(palette.segment(seg))
                 .allow_hover(false);
             plot_ui.line(line);
         }
     });
 }
 
+/// GPU total against CPU frame time, to tell GPU-bound frames from CPU-bound
+/// ones. CPU time excludes the vsync wait.
+fn frame_total_plot(ui: &mut egui::Ui, history: &TimingHistory, palette: TimingPalette) {
+    use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+    let cap = HISTORY_CAP as f64;
+    let x_offset = (HISTORY_CAP - history.len()) as f64;
+    let shift = |[x, y]: [f64; 2]| [x + x_offset, y];
+
+    let plot = Plot::new("frame_totals")
+        .height(100.0)
+        .include_y(0.0)
+        .include_x(0.0)
+        .include_x(cap - 1.0)
+        .show_axes(false)
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false)
+        .legend(Legend::default());
+
+    plot.show(ui, |plot_ui| {
+        let gpu = PlotPoints::from_iter(history.total_iter().map(shift));
+        plot_ui.line(
+            Line::new("GPU", gpu)
+                .color(palette.gpu_total())
+                .allow_hover(false),
+        );
+        let cpu = PlotPoints::from_iter(history.cpu_iter().map(shift));
+        plot_ui.line(
+            Line::new("CPU", cpu)
+                .color(palette.cpu_total())
+                .allow_hover(false),
+        );
+    });
+}
+
+/// Combined decay curve on a log-time axis (1 µs – 10 s), normalized to the
+/// intensity at excitation.
+fn decay_curve_plot(ui: &mut egui::Ui, phosphor: &PhosphorType) {
+    use egui_plot::{Line, Plot, PlotPoints};
+
+    const LOG_T_MIN: f64 = -6.0;
+    const LOG_T_MAX: f64 = 1.0;
+    const POINTS: usize = 200;
+
+    let initial = phosphor.decay_intensity(0.0);
+    if initial <= 0.0 {
+        return;
+    }
+
+    let points: Vec<[f64; 2]> = (0..POINTS)
+        .map(|i| {
+            let log_t = LOG_T_MIN + (LOG_T_MAX - LOG_T_MIN) * i as f64 / (POINTS - 1) as f64;
+            let t = 10f64.powf(log_t) as f32;
+            [log_t, (phosphor.decay_intensity(t) / initial) as f64]
+        })
+        .collect();
+
+    let plot = Plot::new("decay_curve")
+        .height(100.0)
+        .include_x(LOG_T_MIN)
+        .include_x(LOG_T_MAX)
+        .include_y(0.0)
+        .include_y(1.0)
+        .set_margin_fraction(egui::Vec2::new(0.0, 0.05))
+        .x_axis_formatter(|mark, _range| format_time(10f64.powf(mark.value) as f32))
+        .show_axes([true, false])
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false);
+
+    plot.show(ui, |plot_ui| {
+        plot_ui.line(
+            Line::new("Decay", PlotPoints::new(points))
+                .color(egui::Color32::from_rgb(100, 255, 100))
+                .allow_hover(false),
+        );
+    });
+}
+
+/// Bar chart of exposed luminance in stops, with the display-white clip
+/// point marked. Counts are normalized to the tallest bin.
+fn luminance_histogram_plot(ui: &mut egui::Ui, histogram: &LuminanceHistogram) {
+    use egui_plot::{Bar, BarChart, Plot, VLine};
+
+    let peak = histogram.counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let bin_width = ((HISTOGRAM_MAX_EV - HISTOGRAM_MIN_EV) / HISTOGRAM_BINS as f32) as f64;
+    let bars: Vec<Bar> = histogram
+        .counts
+        .iter()
+        .enumerate()
+        .map(|(bin, &count)| {
+            let center = bin_ev(bin) as f64 + bin_width / 2.0;
+            let color = if bin_ev(bin) >= CLIP_EV {
+                egui::Color32::from_rgb(255, 100, 100)
+            } else {
+                egui::Color32::from_rgb(180, 180, 180)
+            };
+            Bar::new(center, count as f64 / peak)
+                .width(bin_width)
+                .fill(color)
+        })
+        .collect();
+
+    let plot = Plot::new("luminance_histogram")
+        .height(80.0)
+        .include_x(HISTOGRAM_MIN_EV as f64)
+        .include_x(HISTOGRAM_MAX_EV as f64)
+        .include_y(0.0)
+        .include_y(1.0)
+        .set_margin_fraction(egui::Vec2::new(0.0, 0.05))
+        .x_axis_formatter(|mark, _range| format!("{:+.0} EV", mark.value))
+        .show_axes([true, false])
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .allow_boxed_zoom(false);
+
+    plot.show(ui, |plot_ui| {
+        plot_ui.bar_chart(BarChart::new("Luminance", bars).allow_hover(false));
+        plot_ui.vline(
+            VLine::new("Clip", CLIP_EV as f64)
+                .color(egui::Color32::from_rgb(255, 60, 60))
+                .allow_hover(false),
+        );
+    });
+}
+
 fn decay_term_display(ui: &mut egui::Ui, phosphor: &PhosphorType) {
     let terms = &phosphor.fluorescence.decay_terms;
     let class = phosphor_data::classify_decay_terms(terms, TAU_CUTOFF);
@@ -399,6 +1269,14 @@ fn decay_term_display(ui: &mut egui::Ui, phosphor: &PhosphorType) {
     }
 }
 
+fn format_persistence(seconds: f32) -> String {
+    if seconds.is_finite() {
+        format_time(seconds)
+    } else {
+        format!(">{}", format_time(phosphor_data::PERSISTENCE_HORIZON_SECS))
+    }
+}
+
 fn format_time(seconds: f32) -> String {
     if seconds >= 1.0 {
         format!("{seconds:.3} s")
@@ -410,3 +1288,144 @@ fn format_time(seconds: f32) -> String {
         format!("{:.1} ns", seconds * 1e9)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpu_info_is_captured_from_the_adapter() {
+        let adapter = wgpu::AdapterInfo {
+            name: "Test GPU".to_string(),
+            vendor: 0x10de,
+            device: 0x2684,
+            device_type: wgpu::DeviceType::DiscreteGpu,
+            driver: "NVIDIA".to_string(),
+            driver_info: "550.54".to_string(),
+            backend: wgpu::Backend::Vulkan,
+        };
+        let info = GpuInfo::new(&adapter, true, false);
+        assert_eq!(
+            info,
+            GpuInfo {
+                adapter: "Test GPU (DiscreteGpu)".to_string(),
+                backend: "vulkan".to_string(),
+                driver: "NVIDIA 550.54".to_string(),
+                hdr_output: true,
+                timestamps: false,
+            }
+        );
+
+        let bare = wgpu::AdapterInfo {
+            driver: String::new(),
+            driver_info: String::new(),
+            ..adapter
+        };
+        assert_eq!(GpuInfo::new(&bare, false, false).driver, "unknown");
+    }
+
+    #[test]
+    fn control_help_labels_are_unique_and_described() {
+        for (i, (label, help)) in CONTROL_HELP.iter().enumerate() {
+            assert!(!help.is_empty(), "{label} has no help text");
+            assert!(
+                CONTROL_HELP[i + 1..]
+                    .iter()
+                    .all(|(other, _)| other != label),
+                "{label} is listed twice"
+            );
+        }
+    }
+
+    fn tweaked() -> EngineerState {
+        EngineerState {
+            sigma_core: 3.0,
+            scatter_levels: 4,
+            tonemap_mode: TonemapMode::Aces,
+            exposure: 2.5,
+            glass_tint: [1.0, 0.0, 0.0],
+            accum_resolution_scale: 2.0,
+            compare_split: 0.25,
+            ..EngineerState::default()
+        }
+    }
+
+    #[test]
+    fn every_palette_colors_each_segment_and_total() {
+        for palette in TimingPalette::iter() {
+            assert_eq!(
+                palette.colors().len(),
+                NUM_SEGMENTS + TOTAL_COLORS,
+                "{palette}"
+            );
+        }
+    }
+
+    #[test]
+    fn log_spectrum_floors_zero_weights() {
+        assert_eq!(spectrum_y(0.25, false), 0.25);
+        assert_eq!(spectrum_y(0.0, false), 0.0);
+
+        assert_eq!(spectrum_y(1.0, true), 0.0);
+        assert!((spectrum_y(0.01, true) + 2.0).abs() < 1e-12);
+        // Zero, and anything below the floor, sits on the floor.
+        let floor = SPECTRUM_LOG_FLOOR.log10();
+        assert_eq!(spectrum_y(0.0, true), floor);
+        assert_eq!(spectrum_y(1e-9, true), floor);
+        assert!(spectrum_y(0.0, true).is_finite());
+    }
+
+    #[test]
+    fn reset_restores_defaults() {
+        let mut state = tweaked();
+        state.reset();
+        assert_eq!(state, EngineerState::default());
+
+        state.reset();
+        assert_eq!(state, EngineerState::default());
+
+        // App preferences are settings, not tuning knobs.
+        state.pause_when_unfocused = true;
+        state.timing_palette = TimingPalette::ColorBlind;
+        state.reset();
+        assert!(state.pause_when_unfocused);
+        assert_eq!(state.timing_palette, TimingPalette::ColorBlind);
+    }
+
+    #[test]
+    fn glass_preset_sets_glass_fields_only() {
+        let mut state = tweaked();
+        let flat = GLASS_PRESETS
+            .iter()
+            .find(|p| p.name == "Off (flat)")
+            .unwrap();
+        state.apply_glass_preset(flat);
+
+        let expected = EngineerState {
+            curvature: 0.0,
+            edge_falloff: 0.0,
+            glass_tint: [1.0, 1.0, 1.0],
+            ..tweaked()
+        };
+        assert_eq!(state, expected);
+
+        let crt = &GLASS_PRESETS[0];
+        state.apply_glass_preset(crt);
+        assert_eq!(state.curvature, crt.curvature);
+        assert_eq!(state.edge_falloff, crt.edge_falloff);
+        assert_eq!(state.glass_tint, crt.glass_tint);
+    }
+
+    #[test]
+    fn reset_section_only_touches_its_fields() {
+        let mut state = tweaked();
+        state.reset_section(EngineerSection::Display);
+
+        let expected = EngineerState {
+            tonemap_mode: TonemapMode::default(),
+            exposure: 1.0,
+            ..tweaked()
+        };
+        assert_eq!(state, expected);
+    }
+}
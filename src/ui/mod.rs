@@ -1,27 +1,99 @@
 pub mod engineer_panel;
+pub mod phosphor_swatch;
 pub mod scope_panel;
+pub mod widgets;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use winit::window::Window;
 
+use crate::beam::audio::{Downmix, LevelMode};
+use crate::beam::spectrum::{DEFAULT_FFT_SIZE, SpectrumWindow};
+use crate::beam::vector::VectorFile;
+use crate::cli::AppConfig;
+use crate::gpu::histogram::LuminanceHistogram;
 use crate::gpu::profiler::TimingHistory;
 use crate::phosphor::{PhosphorType, phosphor_database};
 use crate::simulation_stats::SimStats;
-use crate::types::Resolution;
 use crate::types::{ExternalState, InputMode, OscilloscopeState};
+use phosphor_swatch::PhosphorListing;
 
 pub use engineer_panel::EngineerState;
-pub use engineer_panel::SimFrameInfo;
+pub use engineer_panel::{BufferInfo, GpuInfo, SimFrameInfo, TimingPalette};
 
-#[derive(Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PanelTab {
     #[default]
     Scope,
     Engineer,
 }
 
+/// Side panel width, in points, before the user resizes it.
+pub const DEFAULT_PANEL_WIDTH: f32 = 220.0;
+
+/// Narrowest the side panel is restored at, in points.
+const MIN_PANEL_WIDTH: f32 = 160.0;
+
+/// Widest the side panel is restored at, as a fraction of the window, so the
+/// display stays visible.
+const MAX_PANEL_FRACTION: f32 = 0.6;
+
+/// Clamp a saved panel width to what fits a window `window_width` points
+/// wide. The minimum wins on windows too narrow for both.
+pub fn clamp_panel_width(width: f32, window_width: f32) -> f32 {
+    width
+        .min(window_width * MAX_PANEL_FRACTION)
+        .max(MIN_PANEL_WIDTH)
+}
+
+/// Text of the heads-up display: frame rate and the active phosphor.
+pub fn hud_text(fps: f32, designation: &str) -> String {
+    if fps.is_finite() {
+        format!("{fps:.0} FPS \u{00b7} {designation}")
+    } else {
+        format!("-- FPS \u{00b7} {designation}")
+    }
+}
+
+/// A window-management action asked for from UI code, which can't create or
+/// destroy windows itself. The app takes it after running the UI.
+#[derive(Default)]
+pub struct DetachRequest {
+    pending: bool,
+}
+
+impl DetachRequest {
+    pub fn request(&mut self) {
+        self.pending = true;
+    }
+
+    /// Whether a toggle was requested since the last call.
+    pub fn take(&mut self) -> bool {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// The newest [`SimFrameInfo`] from the render loop. The detached controls
+/// window redraws on its own schedule, outside the viewport redraw that
+/// computes the info, so both windows read it from here.
+#[derive(Default)]
+pub struct SimFrameCache {
+    latest: Option<SimFrameInfo>,
+}
+
+impl SimFrameCache {
+    pub fn record(&mut self, info: SimFrameInfo) {
+        self.latest = Some(info);
+    }
+
+    /// The most recently recorded info, or `None` before the first frame.
+    pub fn latest(&self) -> Option<&SimFrameInfo> {
+        self.latest.as_ref()
+    }
+}
+
 pub struct EguiRenderOutput {
     pub primitives: Vec<egui::ClippedPrimitive>,
     pub textures_delta: egui::TexturesDelta,
@@ -34,6 +106,18 @@ pub struct AudioUiState {
     pub playing: bool,
     pub looping: bool,
     pub speed: f32,
+    /// Scale the file to fill the screen; off by default.
+    pub normalize: bool,
+    pub normalize_mode: LevelMode,
+    /// Channel fold-down for multichannel files; `None` picks by channel count.
+    pub downmix: Option<Downmix>,
+    /// Spectrum analyzer settings, used in spectrum input mode.
+    pub fft_size: usize,
+    pub fft_window: SpectrumWindow,
+    /// Hold peaks with an envelope follower; off by default.
+    pub peak_hold: bool,
+    pub attack_ms: f32,
+    pub release_ms: f32,
     pub has_file: bool,
     pub load_error: Option<String>,
     /// Set by the UI when a file is picked; consumed by the render thread
@@ -48,6 +132,14 @@ impl Default for AudioUiState {
             playing: false,
             looping: false,
             speed: 1.0,
+            normalize: false,
+            normalize_mode: LevelMode::default(),
+            downmix: None,
+            fft_size: DEFAULT_FFT_SIZE,
+            fft_window: SpectrumWindow::default(),
+            peak_hold: false,
+            attack_ms: 1.0,
+            release_ms: 200.0,
             has_file: false,
             load_error: None,
             pending_file: None,
@@ -61,6 +153,10 @@ pub struct VectorUiState {
     pub segment_count: usize,
     pub beam_speed: f32,
     pub settling_time: f32,
+    /// Corner overshoot of the deflection amplifiers; 0.0 is a clean corner.
+    pub ringing: f32,
+    /// Beam intensity during retrace between figures; 0.0 is fully blanked.
+    pub retrace_intensity: f32,
     pub looping: bool,
     pub load_error: Option<String>,
     /// Set by the UI when a file is picked; consumed by the render thread.
@@ -74,6 +170,8 @@ impl Default for VectorUiState {
             segment_count: 0,
             beam_speed: 1.0,
             settling_time: 0.001,
+            ringing: 0.0,
+            retrace_intensity: 0.0,
             looping: true,
             load_error: None,
             pending_file: None,
@@ -81,13 +179,51 @@ impl Default for VectorUiState {
     }
 }
 
+impl VectorUiState {
+    /// Read `path` the way the simulation thread will, to show its segment
+    /// count or error and take on any beam speed or settling time it
+    /// carries. Without this the controls would send their old values on
+    /// the next frame and override the file's.
+    pub fn preview_file(&mut self, path: &Path) {
+        let file = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| VectorFile::parse(&text));
+        match file {
+            Ok(file) => {
+                self.segment_count = file.segments.len();
+                self.load_error = file.range_warning();
+                if let Some(speed) = file.beam_speed {
+                    self.beam_speed = speed;
+                }
+                if let Some(settling) = file.settling_time {
+                    self.settling_time = settling;
+                }
+            }
+            Err(e) => {
+                self.segment_count = 0;
+                self.load_error = Some(e);
+            }
+        }
+    }
+}
+
 pub struct UiState {
     pub ctx: egui::Context,
     winit_state: egui_winit::State,
     pub phosphors: Vec<PhosphorType>,
+    /// Emission color of each entry in `phosphors`, computed once.
+    phosphor_swatches: Vec<egui::Color32>,
     pub phosphor_index: usize,
     prev_phosphor_index: usize,
+    /// Order and filter of the phosphor selectors.
+    phosphor_listing: PhosphorListing,
+    /// Second phosphor shown on the right of the split in compare mode.
+    pub compare_phosphor: Option<usize>,
+    prev_compare_phosphor: Option<usize>,
     pub intensity: f32,
+    /// The user has moved the intensity control, so phosphor switches no
+    /// longer apply the phosphor's default exposure.
+    intensity_touched: bool,
     pub focus: f32,
     pub engineer: EngineerState,
     // UI-local copies of input state (sim thread owns the real InputState)
@@ -99,8 +235,23 @@ pub struct UiState {
     pub preset_index: Option<usize>,
     tab: PanelTab,
     pub panel_visible: bool,
+    /// Width the side panel opens at; egui tracks it after the user resizes.
+    panel_default_width: f32,
+    /// The side panel's width this frame, or 0 while it is hidden.
     pub panel_width: f32,
-    pub accum_size: Option<Resolution>,
+    /// Show the FPS/phosphor overlay in the main window, panel or not.
+    pub hud_visible: bool,
+    /// Set by the header's Detach/Attach button; the app toggles the layout.
+    pub detach_request: DetachRequest,
+    pub buffer_info: Option<BufferInfo>,
+    /// Adapter details for the engineer panel, set once the GPU is up.
+    pub gpu_info: Option<GpuInfo>,
+    /// Latest luminance histogram read back from the GPU, shown in the
+    /// engineer panel.
+    pub histogram: Option<LuminanceHistogram>,
+    /// Simulation stats for the latest viewport frame, shown in the
+    /// engineer panel in both window modes.
+    pub sim_frame: SimFrameCache,
 }
 
 impl UiState {
@@ -116,15 +267,24 @@ impl UiState {
         );
 
         let phosphors = phosphor_database();
+        let phosphor_swatches = phosphors
+            .iter()
+            .map(phosphor_swatch::swatch_color)
+            .collect();
         let engineer = EngineerState::default();
 
-        Self {
+        let mut state = Self {
             ctx,
             winit_state,
             phosphors,
+            phosphor_swatches,
             phosphor_index: 0,
             prev_phosphor_index: 0,
+            phosphor_listing: PhosphorListing::default(),
+            compare_phosphor: None,
+            prev_compare_phosphor: None,
             intensity: 1.0,
+            intensity_touched: false,
             focus: 1.5,
             engineer,
             input_mode: InputMode::default(),
@@ -135,9 +295,17 @@ impl UiState {
             preset_index: Some(0),
             tab: PanelTab::default(),
             panel_visible: true,
+            panel_default_width: DEFAULT_PANEL_WIDTH,
             panel_width: 0.0,
-            accum_size: None,
-        }
+            hud_visible: false,
+            detach_request: DetachRequest::default(),
+            buffer_info: None,
+            gpu_info: None,
+            histogram: None,
+            sim_frame: SimFrameCache::default(),
+        };
+        state.apply_default_exposure();
+        state
     }
 
     pub fn on_event(
@@ -153,7 +321,6 @@ impl UiState {
         window: &Window,
         timings: Option<&TimingHistory>,
         sim_stats: Option<&Arc<SimStats>>,
-        sim_frame: Option<&SimFrameInfo>,
     ) -> EguiRenderOutput {
         let raw_input = self.winit_state.take_egui_input(window);
         let ctx = self.ctx.clone();
@@ -162,7 +329,8 @@ impl UiState {
         let full_output = ctx.run(raw_input, |egui_ctx| {
             if self.panel_visible {
                 let panel_response = egui::SidePanel::left("control_panel")
-                    .default_width(220.0)
+                    .resizable(true)
+                    .default_width(self.panel_default_width)
                     .show(egui_ctx, |ui| {
                         ui.horizontal(|ui| {
                             ui.selectable_value(&mut self.tab, PanelTab::Scope, "Scope");
@@ -173,11 +341,20 @@ impl UiState {
                                     if ui.small_button("\u{00d7}").clicked() {
                                         self.panel_visible = false;
                                     }
+                                    if ui
+                                        .small_button("\u{29c9} Detach")
+                                        .on_hover_text(
+                                            "Move the controls to their own window (Ctrl+D)",
+                                        )
+                                        .clicked()
+                                    {
+                                        self.detach_request.request();
+                                    }
                                 },
                             );
                         });
                         ui.separator();
-                        self.draw_panels(ui, fps, timings, sim_stats, sim_frame);
+                        self.draw_panels(ui, fps, timings, sim_stats);
                     });
                 self.panel_width = panel_response.response.rect.width();
             } else {
@@ -190,6 +367,17 @@ impl UiState {
                         }
                     });
             }
+            if self.hud_visible {
+                let text = hud_text(fps, &self.selected_phosphor().designation);
+                egui::Area::new(egui::Id::new("hud"))
+                    .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+                    .interactable(false)
+                    .show(egui_ctx, |ui| {
+                        egui::Frame::popup(ui.style())
+                            .fill(egui::Color32::from_black_alpha(160))
+                            .show(ui, |ui| ui.monospace(text));
+                    });
+            }
         });
 
         let egui::FullOutput {
@@ -210,7 +398,6 @@ impl UiState {
         egui_winit: &mut egui_winit::State,
         timings: Option<&TimingHistory>,
         sim_stats: Option<&Arc<SimStats>>,
-        sim_frame: Option<&SimFrameInfo>,
     ) -> EguiRenderOutput {
         let raw_input = egui_winit.take_egui_input(window);
         let ctx = self.ctx.clone();
@@ -221,9 +408,18 @@ impl UiState {
                 ui.horizontal(|ui| {
                     ui.selectable_value(&mut self.tab, PanelTab::Scope, "Scope");
                     ui.selectable_value(&mut self.tab, PanelTab::Engineer, "Engineer");
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .small_button("\u{29c9} Attach")
+                            .on_hover_text("Move the controls back into the main window (Ctrl+D)")
+                            .clicked()
+                        {
+                            self.detach_request.request();
+                        }
+                    });
                 });
                 ui.separator();
-                self.draw_panels(ui, fps, timings, sim_stats, sim_frame);
+                self.draw_panels(ui, fps, timings, sim_stats);
             });
         });
 
@@ -244,14 +440,16 @@ impl UiState {
         fps: f32,
         timings: Option<&TimingHistory>,
         sim_stats: Option<&Arc<SimStats>>,
-        sim_frame: Option<&SimFrameInfo>,
     ) {
+        let intensity_before = self.intensity;
         match self.tab {
             PanelTab::Scope => {
                 scope_panel::scope_panel(
                     ui,
                     &self.phosphors,
+                    &self.phosphor_swatches,
                     &mut self.phosphor_index,
+                    &mut self.phosphor_listing,
                     &mut self.intensity,
                     &mut self.focus,
                     &mut self.input_mode,
@@ -267,21 +465,92 @@ impl UiState {
                     ui,
                     &mut self.engineer,
                     &self.phosphors,
+                    &self.phosphor_swatches,
                     &mut self.phosphor_index,
+                    &mut self.phosphor_listing,
+                    &mut self.compare_phosphor,
                     fps,
                     timings,
-                    self.accum_size,
+                    self.buffer_info,
+                    self.histogram.as_ref(),
                     sim_stats,
-                    sim_frame,
+                    self.sim_frame.latest(),
+                    self.gpu_info.as_ref(),
                 );
             }
         }
+        if self.intensity != intensity_before {
+            self.intensity_touched = true;
+        }
+    }
+
+    pub fn tab(&self) -> PanelTab {
+        self.tab
+    }
+
+    /// Restore the saved tab and side panel width. `window_width` is the
+    /// main window's width in points, used to keep the panel on screen.
+    pub fn restore_panel(&mut self, tab: PanelTab, width: Option<f32>, window_width: f32) {
+        self.tab = tab;
+        if let Some(width) = width {
+            self.panel_default_width = clamp_panel_width(width, window_width);
+        }
+    }
+
+    /// Whether the engineer tab is the one selected. Combined with whether
+    /// the panel itself is showing, this gates engineer-only GPU work.
+    pub fn engineer_tab_selected(&self) -> bool {
+        self.tab == PanelTab::Engineer
+    }
+
+    /// Apply command-line startup selections. A file is queued as pending,
+    /// so the first frame's `dispatch_sim_commands` sends the load.
+    pub fn apply_config(&mut self, config: &AppConfig) {
+        if let Some(designation) = &config.phosphor {
+            match self
+                .phosphors
+                .iter()
+                .position(|p| p.designation.eq_ignore_ascii_case(designation))
+            {
+                Some(index) => {
+                    self.phosphor_index = index;
+                    self.prev_phosphor_index = index;
+                    self.apply_default_exposure();
+                }
+                None => tracing::warn!("Unknown phosphor '{designation}', using default"),
+            }
+        }
+        if let Some(mode) = config.input {
+            self.input_mode = mode;
+        }
+        if let Some(path) = &config.file {
+            match self.input_mode {
+                InputMode::Audio | InputMode::Spectrum => {
+                    self.audio_ui.pending_file = Some(path.clone());
+                    self.audio_ui.playing = true;
+                }
+                InputMode::Vector => self.vector_ui.pending_file = Some(path.clone()),
+                InputMode::External => tracing::warn!(
+                    "External input can't load {} yet; ignoring the file",
+                    path.display()
+                ),
+                InputMode::Oscilloscope => {}
+            }
+        }
     }
 
     pub fn selected_phosphor(&self) -> &PhosphorType {
         &self.phosphors[self.phosphor_index]
     }
 
+    /// Set the intensity to the selected phosphor's default exposure, unless
+    /// the user has adjusted it by hand.
+    pub fn apply_default_exposure(&mut self) {
+        if !self.intensity_touched {
+            self.intensity = self.selected_phosphor().default_exposure();
+        }
+    }
+
     /// Returns true once per phosphor selection change, consuming the event.
     pub fn phosphor_changed(&mut self) -> bool {
         if self.phosphor_index != self.prev_phosphor_index {
@@ -291,6 +560,21 @@ impl UiState {
             false
         }
     }
+
+    pub fn compare_phosphor(&self) -> Option<&PhosphorType> {
+        self.compare_phosphor.map(|i| &self.phosphors[i])
+    }
+
+    /// Returns true once per compare-mode selection change (including
+    /// toggling compare mode on or off), consuming the event.
+    pub fn compare_changed(&mut self) -> bool {
+        if self.compare_phosphor != self.prev_compare_phosphor {
+            self.prev_compare_phosphor = self.compare_phosphor;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 fn tessellate_output(
@@ -311,3 +595,59 @@ fn tessellate_output(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(samples_this_frame: usize) -> SimFrameInfo {
+        SimFrameInfo {
+            samples_this_frame,
+            sim_dt: samples_this_frame as f32 / 44_100.0,
+            energy_this_frame: 0.0,
+            buffer_pending: 0,
+        }
+    }
+
+    #[test]
+    fn sim_frame_cache_returns_latest_record() {
+        let mut cache = SimFrameCache::default();
+        assert_eq!(cache.latest(), None);
+
+        cache.record(info(100));
+        cache.record(info(735));
+        assert_eq!(cache.latest(), Some(&info(735)));
+        // Reading doesn't consume, so a second window sees the same info.
+        assert_eq!(cache.latest(), Some(&info(735)));
+    }
+
+    #[test]
+    fn restored_panel_width_fits_the_window() {
+        // Wider than the window: cut to leave the display visible.
+        assert_eq!(clamp_panel_width(1500.0, 1000.0), 600.0);
+        assert_eq!(clamp_panel_width(300.0, 1000.0), 300.0);
+        // Never narrower than usable, even on a tiny window.
+        assert_eq!(clamp_panel_width(50.0, 1000.0), MIN_PANEL_WIDTH);
+        assert_eq!(clamp_panel_width(300.0, 100.0), MIN_PANEL_WIDTH);
+    }
+
+    #[test]
+    fn hud_shows_rounded_fps_and_designation() {
+        assert_eq!(hud_text(59.94, "P31"), "60 FPS \u{00b7} P31");
+        // Before egui has a frame time, 1/dt is infinite.
+        assert_eq!(hud_text(f32::INFINITY, "P7"), "-- FPS \u{00b7} P7");
+        assert_eq!(hud_text(f32::NAN, "P7"), "-- FPS \u{00b7} P7");
+    }
+
+    #[test]
+    fn detach_request_is_consumed_once() {
+        let mut request = DetachRequest::default();
+        assert!(!request.take());
+
+        request.request();
+        // Repeated clicks before the app looks still make one toggle.
+        request.request();
+        assert!(request.take());
+        assert!(!request.take());
+    }
+}
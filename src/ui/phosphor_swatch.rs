@@ -0,0 +1,186 @@
+use phosphor_data::cmp_by_peak;
+use phosphor_data::spectral::{spectrum_to_xyz, xyz_to_srgb};
+
+use crate::phosphor::PhosphorType;
+
+/// Side length of the color square drawn before a phosphor's name.
+const SWATCH_SIZE: f32 = 12.0;
+
+/// Linear sRGB for an XYZ color, gamut-mapped the same way as the spectral
+/// resolve shader (desaturate toward gray at constant luminance) and scaled
+/// so the brightest channel is 1. Black for zero luminance.
+fn swatch_rgb(xyz: [f32; 3]) -> [f32; 3] {
+    let luminance = xyz[1];
+    if luminance <= 0.0 {
+        return [0.0; 3];
+    }
+    let mut rgb = xyz_to_srgb(xyz);
+    let min_c = rgb.iter().copied().fold(f32::INFINITY, f32::min);
+    if min_c < 0.0 {
+        let t = luminance / (luminance - min_c);
+        rgb = rgb.map(|c| luminance + (c - luminance) * t);
+    }
+    let max_c = rgb.iter().copied().fold(0.0, f32::max);
+    rgb.map(|c| (c / max_c).clamp(0.0, 1.0))
+}
+
+/// A phosphor's fluorescence color at full brightness, for identifying it
+/// at a glance in selectors.
+pub fn swatch_color(phosphor: &PhosphorType) -> egui::Color32 {
+    let [r, g, b] = swatch_rgb(spectrum_to_xyz(&phosphor.fluorescence.emission_weights));
+    egui::Rgba::from_rgb(r, g, b).into()
+}
+
+/// Selector label for a phosphor, with a warning sign when it was loaded
+/// with some decay terms dropped.
+fn combo_label(phosphor: &PhosphorType) -> String {
+    if phosphor.truncated_terms {
+        format!("{} \u{26a0}", phosphor.designation)
+    } else {
+        phosphor.designation.clone()
+    }
+}
+
+fn swatch(ui: &mut egui::Ui, color: egui::Color32) {
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(SWATCH_SIZE), egui::Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, color);
+}
+
+/// Order phosphor selectors list their entries in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhosphorSort {
+    /// Natural designation order, as loaded.
+    #[default]
+    Designation,
+    /// Peak wavelength, blue to red.
+    Peak,
+}
+
+/// How phosphor selectors list their entries: the order, and optionally
+/// only phosphors that persist long enough.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhosphorListing {
+    pub sort: PhosphorSort,
+    /// List only phosphors whose [`PhosphorType::longest_tau`] exceeds
+    /// `min_persistence_ms`.
+    pub persistence_filter: bool,
+    pub min_persistence_ms: f32,
+}
+
+impl Default for PhosphorListing {
+    fn default() -> Self {
+        Self {
+            sort: PhosphorSort::default(),
+            persistence_filter: false,
+            min_persistence_ms: 10.0,
+        }
+    }
+}
+
+impl PhosphorListing {
+    /// Whether `phosphor` passes the persistence filter.
+    pub fn includes(&self, phosphor: &PhosphorType) -> bool {
+        !self.persistence_filter || phosphor.longest_tau() * 1000.0 > self.min_persistence_ms
+    }
+}
+
+/// Indices into `phosphors` that `listing` shows, in its order.
+pub fn display_order(phosphors: &[PhosphorType], listing: &PhosphorListing) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..phosphors.len())
+        .filter(|&i| listing.includes(&phosphors[i]))
+        .collect();
+    if listing.sort == PhosphorSort::Peak {
+        order.sort_by(|&a, &b| cmp_by_peak(&phosphors[a], &phosphors[b]));
+    }
+    order
+}
+
+/// Phosphor selector with each entry's emission color shown as a swatch,
+/// followed by a toggle between designation and peak-wavelength order.
+/// Lists only the phosphors `listing` includes, though the current
+/// selection stays selected if filtered out. `swatches` holds
+/// [`swatch_color`] for each of `phosphors`.
+pub fn phosphor_combo(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    phosphors: &[PhosphorType],
+    swatches: &[egui::Color32],
+    index: &mut usize,
+    listing: &mut PhosphorListing,
+) {
+    ui.horizontal(|ui| {
+        swatch(ui, swatches[*index]);
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(combo_label(&phosphors[*index]))
+            .show_ui(ui, |ui| {
+                for i in display_order(phosphors, listing) {
+                    let p = &phosphors[i];
+                    ui.horizontal(|ui| {
+                        swatch(ui, swatches[i]);
+                        ui.selectable_value(
+                            index,
+                            i,
+                            format!("{} — {}", combo_label(p), p.description),
+                        );
+                    });
+                }
+            });
+        let sort = &mut listing.sort;
+        let (label, hover) = match sort {
+            PhosphorSort::Designation => ("A–Z", "Listed by designation; click to sort by peak"),
+            PhosphorSort::Peak => (
+                "λ",
+                "Listed by peak wavelength; click to sort by designation",
+            ),
+        };
+        if ui.small_button(label).on_hover_text(hover).clicked() {
+            *sort = match sort {
+                PhosphorSort::Designation => PhosphorSort::Peak,
+                PhosphorSort::Peak => PhosphorSort::Designation,
+            };
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phosphor::spectral::gaussian_emission_weights;
+
+    #[test]
+    fn d65_white_is_neutral() {
+        let rgb = swatch_rgb([0.9505, 1.0, 1.089]);
+        for c in rgb {
+            assert!((c - 1.0).abs() < 0.01, "{rgb:?}");
+        }
+    }
+
+    #[test]
+    fn green_emission_is_green_and_in_gamut() {
+        let rgb = swatch_rgb(spectrum_to_xyz(&gaussian_emission_weights(525.0, 30.0)));
+        assert_eq!(rgb[1], 1.0, "{rgb:?}");
+        assert!(rgb[0] < 0.5 && rgb[2] < 0.5, "{rgb:?}");
+        assert!(rgb.iter().all(|c| (0.0..=1.0).contains(c)), "{rgb:?}");
+    }
+
+    #[test]
+    fn persistence_filter_hides_short_phosphors() {
+        let db = crate::phosphor::phosphor_database();
+        let mut listing = PhosphorListing::default();
+        assert_eq!(display_order(&db, &listing).len(), db.len());
+
+        listing.persistence_filter = true;
+        listing.min_persistence_ms = 10.0;
+        let shown: Vec<&str> = display_order(&db, &listing)
+            .into_iter()
+            .map(|i| db[i].designation.as_str())
+            .collect();
+        assert!(shown.contains(&"P1"), "{shown:?}");
+        assert!(!shown.contains(&"P24"), "{shown:?}");
+    }
+
+    #[test]
+    fn zero_luminance_is_black() {
+        assert_eq!(swatch_rgb([0.0; 3]), [0.0; 3]);
+    }
+}
@@ -1,16 +1,26 @@
+use std::f32::consts::TAU;
+
 use strum::IntoEnumIterator;
 
+use crate::beam::audio::{Downmix, LevelMode};
+use crate::beam::oscilloscope::{OscilloscopeMode, TriggerSlope, TriggerSource, Waveform};
+use crate::beam::spectrum::{FFT_SIZES, SpectrumWindow};
 use crate::phosphor::PhosphorType;
 use crate::presets::OSCILLOSCOPE_PRESETS;
+use crate::simulation::MAX_OVERSAMPLE;
 use crate::types::{ExternalMode, ExternalState, InputMode, OscilloscopeState};
 
+use super::phosphor_swatch::{PhosphorListing, phosphor_combo};
+use super::widgets::{labeled_value, labeled_value_with};
 use super::{AudioUiState, VectorUiState};
 
 #[allow(clippy::too_many_arguments)]
 pub fn scope_panel(
     ui: &mut egui::Ui,
     phosphors: &[PhosphorType],
+    swatches: &[egui::Color32],
     phosphor_index: &mut usize,
+    phosphor_listing: &mut PhosphorListing,
     intensity: &mut f32,
     focus: &mut f32,
     input_mode: &mut InputMode,
@@ -22,26 +32,36 @@ pub fn scope_panel(
 ) {
     ui.heading("Phosphor");
 
-    let current = &phosphors[*phosphor_index];
-    egui::ComboBox::from_id_salt("phosphor_type")
-        .selected_text(&current.designation)
-        .show_ui(ui, |ui| {
-            for (i, p) in phosphors.iter().enumerate() {
-                ui.selectable_value(
-                    phosphor_index,
-                    i,
-                    format!("{} — {}", p.designation, p.description),
-                );
-            }
-        });
+    phosphor_combo(
+        ui,
+        "phosphor_type",
+        phosphors,
+        swatches,
+        phosphor_index,
+        phosphor_listing,
+    );
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut phosphor_listing.persistence_filter, "Persistence >");
+        ui.add_enabled(
+            phosphor_listing.persistence_filter,
+            egui::DragValue::new(&mut phosphor_listing.min_persistence_ms)
+                .range(0.0..=10_000.0)
+                .speed(1.0)
+                .suffix(" ms"),
+        );
+    })
+    .response
+    .on_hover_text(
+        "List only phosphors with a decay component slower than this. \
+         Power-law tails always pass.",
+    );
 
     ui.separator();
 
-    ui.label("Intensity");
-    ui.add(egui::Slider::new(intensity, 0.1..=10.0).logarithmic(true));
-
-    ui.label("Focus");
-    ui.add(egui::Slider::new(focus, 0.5..=5.0).text("px"));
+    labeled_value_with(ui, "Intensity", intensity, 0.1..=10.0, "", |s| {
+        s.logarithmic(true)
+    });
+    labeled_value(ui, "Focus", focus, 0.5..=5.0, "px");
 
     ui.separator();
     ui.heading("Input");
@@ -49,6 +69,7 @@ pub fn scope_panel(
     ui.horizontal(|ui| {
         ui.selectable_value(input_mode, InputMode::Oscilloscope, "Scope");
         ui.selectable_value(input_mode, InputMode::Audio, "Audio");
+        ui.selectable_value(input_mode, InputMode::Spectrum, "FFT");
         ui.selectable_value(input_mode, InputMode::Vector, "Vector");
         ui.selectable_value(input_mode, InputMode::External, "Extern");
     });
@@ -58,6 +79,7 @@ pub fn scope_panel(
     egui::ScrollArea::vertical().show(ui, |ui| match input_mode {
         InputMode::Oscilloscope => oscilloscope_controls(ui, oscilloscope, preset_index),
         InputMode::Audio => audio_controls(ui, audio_ui),
+        InputMode::Spectrum => spectrum_controls(ui, audio_ui),
         InputMode::Vector => vector_controls(ui, vector_ui),
         InputMode::External => external_controls(ui, external),
     });
@@ -91,23 +113,40 @@ fn oscilloscope_controls(
     // Track the state before rendering controls to detect manual changes
     let osc_before = osc.clone();
 
-    ui.label("X Channel");
+    egui::ComboBox::from_label("Mode")
+        .selected_text(osc.mode.to_string())
+        .show_ui(ui, |ui| {
+            for m in OscilloscopeMode::iter() {
+                ui.selectable_value(&mut osc.mode, m, m.to_string());
+            }
+        });
+    let dual_trace = osc.mode == OscilloscopeMode::DualTrace;
+
+    ui.separator();
+
+    ui.label(if dual_trace { "X Sweep" } else { "X Channel" });
     ui.indent("x_ch", |ui| {
-        egui::ComboBox::from_id_salt("x_waveform")
-            .selected_text(osc.x_waveform.to_string())
-            .show_ui(ui, |ui| {
-                for w in crate::beam::oscilloscope::Waveform::iter() {
-                    ui.selectable_value(&mut osc.x_waveform, w, w.to_string());
-                }
-            });
-        ui.add(
-            egui::Slider::new(&mut osc.x_frequency, 1.0..=10_000.0)
-                .logarithmic(true)
-                .text("Hz"),
+        // Dual trace always sweeps with a sawtooth.
+        if !dual_trace {
+            egui::ComboBox::from_id_salt("x_waveform")
+                .selected_text(osc.x_waveform.to_string())
+                .show_ui(ui, |ui| {
+                    for w in Waveform::iter() {
+                        ui.selectable_value(&mut osc.x_waveform, w, w.to_string());
+                    }
+                });
+        }
+        labeled_value_with(
+            ui,
+            "Frequency",
+            &mut osc.x_frequency,
+            1.0..=10_000.0,
+            "Hz",
+            |s| s.logarithmic(true),
         );
-        ui.add(egui::Slider::new(&mut osc.x_amplitude, 0.0..=1.0).text("Amp"));
-        ui.add(egui::Slider::new(&mut osc.x_phase, 0.0..=std::f32::consts::TAU).text("Phase"));
-        ui.add(egui::Slider::new(&mut osc.x_dc_offset, -1.0..=1.0).text("DC"));
+        labeled_value(ui, "Amplitude", &mut osc.x_amplitude, 0.0..=1.0, "");
+        labeled_value(ui, "Phase", &mut osc.x_phase, 0.0..=TAU, "rad");
+        labeled_value(ui, "DC offset", &mut osc.x_dc_offset, -1.0..=1.0, "");
     });
 
     ui.separator();
@@ -117,27 +156,121 @@ fn oscilloscope_controls(
         egui::ComboBox::from_id_salt("y_waveform")
             .selected_text(osc.y_waveform.to_string())
             .show_ui(ui, |ui| {
-                for w in crate::beam::oscilloscope::Waveform::iter() {
+                for w in Waveform::iter() {
                     ui.selectable_value(&mut osc.y_waveform, w, w.to_string());
                 }
             });
-        ui.add(
-            egui::Slider::new(&mut osc.y_frequency, 1.0..=10_000.0)
-                .logarithmic(true)
-                .text("Hz"),
+        labeled_value_with(
+            ui,
+            "Frequency",
+            &mut osc.y_frequency,
+            1.0..=10_000.0,
+            "Hz",
+            |s| s.logarithmic(true),
         );
-        ui.add(egui::Slider::new(&mut osc.y_amplitude, 0.0..=1.0).text("Amp"));
-        ui.add(egui::Slider::new(&mut osc.y_phase, 0.0..=std::f32::consts::TAU).text("Phase"));
-        ui.add(egui::Slider::new(&mut osc.y_dc_offset, -1.0..=1.0).text("DC"));
+        labeled_value(ui, "Amplitude", &mut osc.y_amplitude, 0.0..=1.0, "");
+        labeled_value(ui, "Phase", &mut osc.y_phase, 0.0..=TAU, "rad");
+        labeled_value(ui, "DC offset", &mut osc.y_dc_offset, -1.0..=1.0, "");
     });
 
+    if dual_trace {
+        ui.separator();
+
+        ui.label("Y2 Channel");
+        ui.indent("y2_ch", |ui| {
+            egui::ComboBox::from_id_salt("y2_waveform")
+                .selected_text(osc.y2_waveform.to_string())
+                .show_ui(ui, |ui| {
+                    for w in Waveform::iter() {
+                        ui.selectable_value(&mut osc.y2_waveform, w, w.to_string());
+                    }
+                });
+            labeled_value_with(
+                ui,
+                "Frequency",
+                &mut osc.y2_frequency,
+                1.0..=10_000.0,
+                "Hz",
+                |s| s.logarithmic(true),
+            );
+            labeled_value(ui, "Amplitude", &mut osc.y2_amplitude, 0.0..=1.0, "");
+            labeled_value(ui, "Phase", &mut osc.y2_phase, 0.0..=TAU, "rad");
+            labeled_value(ui, "DC offset", &mut osc.y2_dc_offset, -1.0..=1.0, "");
+        });
+
+        labeled_value_with(
+            ui,
+            "Chop frequency",
+            &mut osc.chop_freq,
+            100.0..=96_000.0,
+            "Hz",
+            |s| s.logarithmic(true),
+        )
+        .on_hover_text("How often the beam switches between the two Y traces.");
+
+        ui.separator();
+
+        ui.label("Trigger");
+        ui.indent("trigger", |ui| {
+            egui::ComboBox::from_label("Source")
+                .selected_text(osc.trigger_source.to_string())
+                .show_ui(ui, |ui| {
+                    for t in TriggerSource::iter() {
+                        ui.selectable_value(&mut osc.trigger_source, t, t.to_string());
+                    }
+                });
+            if osc.trigger_source != TriggerSource::FreeRun {
+                ui.horizontal(|ui| {
+                    for slope in TriggerSlope::iter() {
+                        ui.radio_value(&mut osc.trigger_slope, slope, slope.to_string());
+                    }
+                });
+                labeled_value(ui, "Level", &mut osc.trigger_level, -1.0..=1.0, "");
+            }
+        });
+    }
+
     ui.separator();
 
-    ui.add(
-        egui::Slider::new(&mut osc.sample_rate, 1_000.0..=192_000.0)
-            .logarithmic(true)
-            .text("Sample Rate"),
+    labeled_value_with(
+        ui,
+        "Sample rate",
+        &mut osc.sample_rate,
+        1_000.0..=192_000.0,
+        "Hz",
+        |s| s.logarithmic(true),
+    );
+    ui.horizontal(|ui| {
+        ui.label("Oversample ×");
+        ui.add(egui::DragValue::new(&mut osc.oversample).range(1..=MAX_OVERSAMPLE));
+    })
+    .response
+    .on_hover_text(
+        "Generate this many samples per output sample at the same beam speed. \
+         Fixes gaps in fast traces; the extra samples are merged before drawing.",
     );
+    ui.separator();
+
+    ui.label("Blanking");
+    ui.indent("blanking", |ui| {
+        labeled_value_with(
+            ui,
+            "Frequency",
+            &mut osc.blank_freq,
+            1.0..=10_000.0,
+            "Hz",
+            |s| s.logarithmic(true),
+        );
+        labeled_value(ui, "Duty", &mut osc.blank_duty, 0.0..=1.0, "")
+            .on_hover_text("Fraction of each blanking period the beam is on. 1.0 never blanks.");
+    });
+
+    ui.separator();
+
+    ui.checkbox(&mut osc.clamp_to_screen, "Clamp to screen")
+        .on_hover_text(
+            "Pin the trace to the screen edges. Off lets large DC offsets push it off-screen.",
+        );
 
     // Clear preset selection if user manually changed any parameter
     if *osc != osc_before {
@@ -174,14 +307,74 @@ fn audio_controls(ui: &mut egui::Ui, audio: &mut AudioUiState) {
             ui.checkbox(&mut audio.looping, "Loop");
         });
 
-        ui.add(
-            egui::Slider::new(&mut audio.speed, 0.25..=4.0)
-                .logarithmic(true)
-                .text("Speed"),
-        );
+        labeled_value_with(ui, "Speed", &mut audio.speed, 0.25..=4.0, "x", |s| {
+            s.logarithmic(true)
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut audio.normalize, "Normalize")
+                .on_hover_text("Scale the file so its level fills the screen.");
+            ui.add_enabled_ui(audio.normalize, |ui| {
+                for mode in LevelMode::iter() {
+                    ui.radio_value(&mut audio.normalize_mode, mode, mode.to_string());
+                }
+            });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Downmix")
+                .on_hover_text("How files with more than two channels become X and Y.");
+            ui.radio_value(&mut audio.downmix, None, "Auto")
+                .on_hover_text("5.1 matrix for six-channel files, first two otherwise.");
+            for mix in Downmix::iter() {
+                ui.radio_value(&mut audio.downmix, Some(mix), mix.to_string());
+            }
+        });
+
+        ui.separator();
+        ui.checkbox(&mut audio.peak_hold, "Peak hold")
+            .on_hover_text(
+                "Brighten the trace with the signal's envelope so transients linger \
+             and fade over the release time.",
+            );
+        if audio.peak_hold {
+            labeled_value_with(ui, "Attack", &mut audio.attack_ms, 0.1..=100.0, "ms", |s| {
+                s.logarithmic(true)
+            });
+            labeled_value_with(
+                ui,
+                "Release",
+                &mut audio.release_ms,
+                10.0..=2000.0,
+                "ms",
+                |s| s.logarithmic(true),
+            );
+        }
     }
 }
 
+/// The audio file controls plus the analyzer's FFT settings.
+fn spectrum_controls(ui: &mut egui::Ui, audio: &mut AudioUiState) {
+    audio_controls(ui, audio);
+
+    ui.separator();
+    egui::ComboBox::from_label("FFT size")
+        .selected_text(audio.fft_size.to_string())
+        .show_ui(ui, |ui| {
+            for size in FFT_SIZES {
+                ui.selectable_value(&mut audio.fft_size, size, size.to_string());
+            }
+        })
+        .response
+        .on_hover_text("Larger sizes resolve finer frequencies but sweep and update more slowly.");
+    ui.horizontal(|ui| {
+        ui.label("Window");
+        for window in SpectrumWindow::iter() {
+            ui.radio_value(&mut audio.fft_window, window, window.to_string());
+        }
+    });
+}
+
 fn vector_controls(ui: &mut egui::Ui, vector: &mut VectorUiState) {
     if ui.button("Open File...").clicked()
         && let Some(path) = rfd::FileDialog::new()
@@ -206,16 +399,24 @@ fn vector_controls(ui: &mut egui::Ui, vector: &mut VectorUiState) {
 
         ui.separator();
 
-        ui.add(
-            egui::Slider::new(&mut vector.beam_speed, 0.1..=10.0)
-                .logarithmic(true)
-                .text("Beam Speed"),
+        labeled_value_with(
+            ui,
+            "Beam speed",
+            &mut vector.beam_speed,
+            0.1..=10.0,
+            "",
+            |s| s.logarithmic(true),
         );
-        ui.add(
-            egui::Slider::new(&mut vector.settling_time, 0.0001..=0.01)
-                .logarithmic(true)
-                .text("Settling"),
+        labeled_value_with(
+            ui,
+            "Settling",
+            &mut vector.settling_time,
+            0.0001..=0.01,
+            "s",
+            |s| s.logarithmic(true),
         );
+        labeled_value(ui, "Ringing", &mut vector.ringing, 0.0..=1.0, "");
+        labeled_value(ui, "Retrace", &mut vector.retrace_intensity, 0.0..=0.2, "");
         ui.checkbox(&mut vector.looping, "Loop");
     }
 }
@@ -0,0 +1,50 @@
+use std::ops::RangeInclusive;
+
+use egui::emath::Numeric;
+
+/// Drag distance in points to sweep a `DragValue` across its whole range.
+const DRAG_SPAN: f64 = 200.0;
+
+/// A label above a slider and a `DragValue` for typing an exact number. Both
+/// edit the same `value`, so they stay in sync. `suffix` is the unit shown
+/// after the number (empty for unitless values).
+pub fn labeled_value<N: Numeric>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut N,
+    range: RangeInclusive<N>,
+    suffix: &str,
+) -> egui::Response {
+    labeled_value_with(ui, label, value, range, suffix, |slider| slider)
+}
+
+/// [`labeled_value`] with the slider adjusted by `configure`, e.g. to make
+/// it logarithmic or snap to steps. Typed values are not snapped.
+pub fn labeled_value_with<N: Numeric>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut N,
+    range: RangeInclusive<N>,
+    suffix: &str,
+    configure: impl FnOnce(egui::Slider<'_>) -> egui::Slider<'_>,
+) -> egui::Response {
+    let label_response = ui.label(label);
+    let speed = (range.end().to_f64() - range.start().to_f64()) / DRAG_SPAN;
+    let suffix = if suffix.is_empty() {
+        String::new()
+    } else {
+        format!(" {suffix}")
+    };
+    let row = ui.horizontal(|ui| {
+        let slider = configure(egui::Slider::new(value, range.clone()).show_value(false));
+        let slider_response = ui.add(slider);
+        let drag_response = ui.add(
+            egui::DragValue::new(value)
+                .range(range)
+                .speed(speed)
+                .suffix(suffix),
+        );
+        slider_response | drag_response
+    });
+    label_response | row.inner
+}